@@ -0,0 +1,238 @@
+//! Vault snapshots for recovering from bulk changes
+//! 一括変更から復旧するためのボルトのスナップショット
+//!
+//! A backup is a plain directory copy of the vault, named after the time it was
+//! taken and the operation that requested it, inside `storage.backup.path`.
+//! Only the newest `storage.backup.retain_count` snapshots are kept.
+//! バックアップは`storage.backup.path`内に作成されるボルトの単純なディレクトリコピーで、
+//! 作成時刻と要求した操作にちなんで命名されます。最新の
+//! `storage.backup.retain_count`個のスナップショットのみが保持されます。
+
+use crate::config::BackupConfig;
+use crate::storage::NoteStore;
+use crate::{Result, ZynapseError};
+use std::path::{Path, PathBuf};
+
+/// Timestamp format used as the prefix of snapshot directory names
+/// スナップショットのディレクトリ名の接頭辞に使うタイムスタンプ形式
+///
+/// Milliseconds keep snapshots taken in quick succession in creation order.
+/// ミリ秒により、連続して作成されたスナップショットも作成順に並びます。
+const TIMESTAMP_FORMAT: &str = "%Y%m%d_%H%M%S%3f";
+
+/// Directory names skipped when copying the vault
+/// ボルトのコピー時にスキップするディレクトリ名
+const SKIPPED_DIRECTORIES: &[&str] = &[".git"];
+
+/// A snapshot taken by [`Backups::create`]
+/// [`Backups::create`]で作成されたスナップショット
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Backup {
+    /// Directory holding the copied vault
+    /// コピーされたボルトを保持するディレクトリ
+    pub path: PathBuf,
+
+    /// Number of files copied
+    /// コピーされたファイル数
+    pub file_count: usize,
+}
+
+/// Manager for the backup directory
+/// バックアップディレクトリの管理
+#[derive(Debug, Clone)]
+pub struct Backups {
+    /// Directory containing all snapshots
+    /// すべてのスナップショットを含むディレクトリ
+    dir: PathBuf,
+
+    /// Number of snapshots to keep
+    /// 保持するスナップショット数
+    retain_count: usize,
+}
+
+impl Backups {
+    /// Create a manager from the backup configuration
+    /// バックアップ設定から管理オブジェクトを作成
+    #[must_use]
+    pub fn new(config: &BackupConfig) -> Self {
+        Self {
+            dir: config.path.clone(),
+            retain_count: usize::try_from(config.retain_count).unwrap_or(usize::MAX),
+        }
+    }
+
+    /// Directory containing all snapshots
+    /// すべてのスナップショットを含むディレクトリ
+    #[must_use]
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Copy the whole vault into a new snapshot, then prune old snapshots
+    /// ボルト全体を新しいスナップショットにコピーし、古いスナップショットを削除
+    ///
+    /// `label` names the operation that requested the backup, e.g. `bulk`.
+    /// `label`はバックアップを要求した操作の名前です（例: `bulk`）。
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the vault cannot be read or the snapshot cannot be written.
+    /// ボルトを読み込めないか、スナップショットを書き込めない場合にエラーを返します。
+    pub fn create(&self, store: &NoteStore, label: &str) -> Result<Backup> {
+        let timestamp = chrono::Local::now().format(TIMESTAMP_FORMAT).to_string();
+        let label = crate::utils::sanitize_filename(label);
+        let mut target = self.dir.join(format!("{timestamp}_{label}"));
+        let mut attempt = 1;
+        while target.exists() {
+            attempt += 1;
+            target = self.dir.join(format!("{timestamp}_{label}_{attempt}"));
+        }
+
+        let file_count = copy_tree(store.root(), &target, &self.dir)?;
+        log::info!(
+            "Backed up {file_count} files from {} to {}",
+            store.root().display(),
+            target.display()
+        );
+
+        self.prune()?;
+        Ok(Backup {
+            path: target,
+            file_count,
+        })
+    }
+
+    /// List snapshot directories, oldest first
+    /// スナップショットのディレクトリを古い順に一覧表示
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the backup directory cannot be read.
+    /// バックアップディレクトリを読み込めない場合にエラーを返します。
+    pub fn list(&self) -> Result<Vec<PathBuf>> {
+        if !self.dir.is_dir() {
+            return Ok(Vec::new());
+        }
+
+        let entries = std::fs::read_dir(&self.dir).map_err(|e| {
+            ZynapseError::io_error(e, format!("Failed to read {}", self.dir.display()))
+        })?;
+
+        let mut snapshots = Vec::new();
+        for entry in entries {
+            let entry = entry.map_err(|e| ZynapseError::storage_error("list backups", e))?;
+            let name = entry.file_name();
+            if entry.path().is_dir() && is_snapshot_name(&name.to_string_lossy()) {
+                snapshots.push(entry.path());
+            }
+        }
+        snapshots.sort();
+        Ok(snapshots)
+    }
+
+    /// Remove the oldest snapshots beyond the retention count
+    /// 保持数を超えた古いスナップショットを削除
+    fn prune(&self) -> Result<()> {
+        let snapshots = self.list()?;
+        let excess = snapshots.len().saturating_sub(self.retain_count);
+        for old in &snapshots[..excess] {
+            std::fs::remove_dir_all(old).map_err(|e| {
+                ZynapseError::storage_error(format!("remove backup {}", old.display()), e)
+            })?;
+            log::debug!("Pruned backup {}", old.display());
+        }
+        Ok(())
+    }
+}
+
+/// Whether a directory name was produced by [`Backups::create`]
+/// ディレクトリ名が[`Backups::create`]によって生成されたものか
+fn is_snapshot_name(name: &str) -> bool {
+    let bytes = name.as_bytes();
+    bytes.len() > 19
+        && bytes[..8].iter().all(u8::is_ascii_digit)
+        && bytes[8] == b'_'
+        && bytes[9..18].iter().all(u8::is_ascii_digit)
+        && bytes[18] == b'_'
+}
+
+/// Recursively copy `source` into `target`, skipping `exclude`
+/// `exclude`をスキップしながら`source`を`target`に再帰的にコピー
+fn copy_tree(source: &Path, target: &Path, exclude: &Path) -> Result<usize> {
+    let walker = walkdir::WalkDir::new(source)
+        .into_iter()
+        .filter_entry(|entry| {
+            entry.path() != exclude
+                && !(entry.file_type().is_dir()
+                    && SKIPPED_DIRECTORIES
+                        .iter()
+                        .any(|skipped| entry.file_name() == *skipped))
+        });
+
+    let mut count = 0;
+    for entry in walker {
+        let entry = entry.map_err(|e| ZynapseError::storage_error("walk vault", e))?;
+        let relative = entry
+            .path()
+            .strip_prefix(source)
+            .map_err(|e| ZynapseError::internal(format!("Path outside vault: {e}")))?;
+        let destination = target.join(relative);
+
+        if entry.file_type().is_dir() {
+            crate::utils::ensure_directory_exists(&destination)?;
+        } else if entry.file_type().is_file() {
+            std::fs::copy(entry.path(), &destination).map_err(|e| {
+                ZynapseError::io_error(e, format!("Failed to back up {}", entry.path().display()))
+            })?;
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::note::Note;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_create_and_prune() {
+        let vault = TempDir::new().unwrap();
+        let store = NoteStore::new(vault.path().to_path_buf(), 1024 * 1024);
+        store.create(&Note::new("One", "first")).unwrap();
+        store.create(&Note::new("Two", "second")).unwrap();
+
+        // Backups nested inside the vault must not copy themselves
+        // ボルト内に置かれたバックアップは自身をコピーしてはならない
+        let config = BackupConfig {
+            enabled: true,
+            path: vault.path().join(".backups"),
+            retain_count: 2,
+        };
+        let backups = Backups::new(&config);
+
+        let first = backups.create(&store, "bulk edit").unwrap();
+        assert_eq!(first.file_count, 2);
+        assert!(first.path.join("one.md").is_file());
+        assert!(first
+            .path
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .ends_with("_bulk-edit"));
+
+        backups.create(&store, "bulk edit").unwrap();
+        backups.create(&store, "bulk edit").unwrap();
+        let snapshots = backups.list().unwrap();
+        assert_eq!(snapshots.len(), 2);
+        assert!(!snapshots.contains(&first.path));
+    }
+
+    #[test]
+    fn test_snapshot_names() {
+        assert!(is_snapshot_name("20240516_093000123_bulk"));
+        assert!(!is_snapshot_name("notes"));
+        assert!(!is_snapshot_name("2024-05-16_093000123_bulk"));
+    }
+}
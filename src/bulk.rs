@@ -0,0 +1,581 @@
+//! Bulk edits applied to every note matching a query
+//! クエリに一致するすべてのノートに適用する一括編集
+//!
+//! Bulk operations are planned first and applied second: [`plan`] computes the
+//! edited notes and a human-readable change list without touching the disk, so a
+//! dry run and the real run share exactly the same logic.
+//! 一括操作は計画と適用の2段階で行われます。[`plan`]はディスクに触れずに編集後の
+//! ノートと人間が読める変更一覧を計算するため、ドライランと実際の実行は
+//! 完全に同じロジックを共有します。
+
+use crate::note::{normalize_tag, tag_is_within, Note};
+use crate::query::Query;
+use crate::storage::NoteStore;
+use crate::{Result, ZynapseError};
+use std::collections::BTreeSet;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Frontmatter keys that bulk field edits cannot touch
+/// 一括フィールド編集で変更できないフロントマターキー
+const PROTECTED_FIELDS: &[&str] = &["id", "title", "created", "updated", "tags", "aliases"];
+
+/// A single edit applied to each matching note
+/// 一致した各ノートに適用される単一の編集
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Edit {
+    /// Add a tag, unless the note has it in any letter case
+    /// タグを追加（大文字小文字を問わず既にある場合を除く）
+    AddTag(String),
+
+    /// Remove a tag together with the tags below it, as matched by `tag:`
+    /// タグを下位のタグとともに削除（`tag:`と同じ一致方法）
+    RemoveTag(String),
+
+    /// Rename a tag together with the tags below it
+    /// タグの名前を下位のタグとともに変更
+    RenameTag {
+        /// Current tag name
+        /// 現在のタグ名
+        from: String,
+        /// New tag name
+        /// 新しいタグ名
+        to: String,
+    },
+
+    /// Set a custom frontmatter field
+    /// カスタムフロントマターフィールドを設定
+    SetField {
+        /// Field name
+        /// フィールド名
+        key: String,
+        /// New value
+        /// 新しい値
+        value: serde_yaml::Value,
+    },
+
+    /// Remove a custom frontmatter field
+    /// カスタムフロントマターフィールドを削除
+    UnsetField(String),
+
+    /// Move the note file into a vault-relative folder
+    /// ノートファイルをボルト相対のフォルダに移動
+    MoveTo(PathBuf),
+}
+
+impl Edit {
+    /// Parse a `key=value` field assignment
+    /// `key=value`形式のフィールド代入を解析
+    ///
+    /// The value is read as a YAML scalar, so `3` and `true` keep their types.
+    /// 値はYAMLスカラーとして読み込まれるため、`3`や`true`は型を保持します。
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the assignment has no `=`, or names a protected field.
+    /// 代入に`=`がない場合、または保護されたフィールドを指定した場合にエラーを返します。
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use zynapse::bulk::Edit;
+    ///
+    /// let edit = Edit::set_field("priority=2").unwrap();
+    /// assert_eq!(edit.to_string(), "set priority = 2");
+    /// assert!(Edit::set_field("title=Other").is_err());
+    /// ```
+    pub fn set_field(assignment: &str) -> Result<Self> {
+        let (key, value) = assignment.split_once('=').ok_or_else(|| {
+            ZynapseError::invalid_content(format!(
+                "Invalid field assignment `{assignment}`: expected KEY=VALUE"
+            ))
+        })?;
+        let key = check_field(key.trim())?;
+        let value = serde_yaml::from_str(value)
+            .ok()
+            .filter(|value: &serde_yaml::Value| {
+                !matches!(
+                    value,
+                    serde_yaml::Value::Mapping(_) | serde_yaml::Value::Null
+                )
+            })
+            .unwrap_or_else(|| serde_yaml::Value::String(value.to_string()));
+        Ok(Self::SetField { key, value })
+    }
+
+    /// Build a field removal
+    /// フィールドの削除を構築
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key names a protected field.
+    /// キーが保護されたフィールドを指定した場合にエラーを返します。
+    pub fn unset_field(key: &str) -> Result<Self> {
+        Ok(Self::UnsetField(check_field(key.trim())?))
+    }
+
+    /// Parse an `old=new` tag rename
+    /// `old=new`形式のタグ名変更を解析
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if either side of the rename is empty.
+    /// 名前変更のどちらかが空の場合にエラーを返します。
+    pub fn rename_tag(rename: &str) -> Result<Self> {
+        match rename
+            .split_once('=')
+            .map(|(from, to)| (tag(from), tag(to)))
+        {
+            Some((from, to)) if !from.is_empty() && !to.is_empty() => {
+                Ok(Self::RenameTag { from, to })
+            }
+            _ => Err(ZynapseError::invalid_content(format!(
+                "Invalid tag rename `{rename}`: expected OLD=NEW"
+            ))),
+        }
+    }
+
+    /// Build a move into a vault-relative folder
+    /// ボルト相対のフォルダへの移動を構築
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the folder is absolute or escapes the vault.
+    /// フォルダが絶対パスであるか、ボルト外を指す場合にエラーを返します。
+    pub fn move_to(folder: impl Into<PathBuf>) -> Result<Self> {
+        let folder = folder.into();
+        if folder.is_absolute() {
+            return Err(ZynapseError::invalid_content(format!(
+                "Move target must be relative to the vault: {}",
+                folder.display()
+            )));
+        }
+        crate::utils::validate_safe_path(&folder)?;
+        Ok(Self::MoveTo(folder))
+    }
+
+    /// Apply the edit to a note, describing what changed
+    /// ノートに編集を適用し、変更内容を記述
+    fn apply(&self, note: &mut Note, changes: &mut Vec<String>) {
+        let frontmatter = &mut note.frontmatter;
+        match self {
+            Self::AddTag(name) => {
+                if !contains_tag(&frontmatter.tags, name) {
+                    frontmatter.tags.push(name.clone());
+                    changes.push(format!("+ tag {name}"));
+                }
+            }
+            Self::RemoveTag(name) => {
+                frontmatter.tags.retain(|existing| {
+                    let removed = tag_is_within(existing, name);
+                    if removed {
+                        changes.push(format!("- tag {existing}"));
+                    }
+                    !removed
+                });
+            }
+            Self::RenameTag { from, to } => {
+                let mut tags: Vec<String> = Vec::with_capacity(frontmatter.tags.len());
+                for tag in frontmatter.tags.drain(..) {
+                    // `a/b` renamed to `c` turns `a/b/x` into `c/x`
+                    // `a/b`を`c`に変更すると`a/b/x`は`c/x`になる
+                    let tag = if tag_is_within(&tag, from) {
+                        let renamed = format!("{to}{}", &tag[from.len()..]);
+                        changes.push(format!("~ tag {tag} → {renamed}"));
+                        renamed
+                    } else {
+                        tag
+                    };
+                    if !contains_tag(&tags, &tag) {
+                        tags.push(tag);
+                    }
+                }
+                frontmatter.tags = tags;
+            }
+            Self::SetField { key, value } => {
+                let previous = frontmatter.extra.insert(key.clone(), value.clone());
+                match previous {
+                    Some(previous) if previous == *value => {}
+                    Some(previous) => changes.push(format!(
+                        "~ {key}: {} → {}",
+                        yaml_inline(&previous),
+                        yaml_inline(value)
+                    )),
+                    None => changes.push(format!("+ {key}: {}", yaml_inline(value))),
+                }
+            }
+            Self::UnsetField(key) => {
+                if let Some(previous) = frontmatter.extra.remove(key) {
+                    changes.push(format!("- {key}: {}", yaml_inline(&previous)));
+                }
+            }
+            Self::MoveTo(folder) => {
+                let target = folder.join(note.path.file_name().unwrap_or_default());
+                if target != note.path {
+                    changes.push(format!("→ {}", target.display()));
+                    note.path = target;
+                }
+            }
+        }
+    }
+
+    /// Order in which edits are applied, regardless of command-line order
+    /// コマンドラインの順序に関係なく編集が適用される順序
+    const fn rank(&self) -> u8 {
+        match self {
+            Self::RenameTag { .. } => 0,
+            Self::RemoveTag(_) => 1,
+            Self::AddTag(_) => 2,
+            Self::UnsetField(_) => 3,
+            Self::SetField { .. } => 4,
+            Self::MoveTo(_) => 5,
+        }
+    }
+}
+
+impl fmt::Display for Edit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AddTag(name) => write!(f, "add tag {name}"),
+            Self::RemoveTag(name) => write!(f, "remove tag {name}"),
+            Self::RenameTag { from, to } => write!(f, "rename tag {from} to {to}"),
+            Self::SetField { key, value } => write!(f, "set {key} = {}", yaml_inline(value)),
+            Self::UnsetField(key) => write!(f, "unset {key}"),
+            Self::MoveTo(folder) => write!(f, "move to {}", folder.display()),
+        }
+    }
+}
+
+/// Planned change to a single note
+/// 単一ノートへの計画された変更
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+    /// Path of the note before the edits
+    /// 編集前のノートのパス
+    pub original_path: PathBuf,
+
+    /// The note after the edits
+    /// 編集後のノート
+    pub note: Note,
+
+    /// Human-readable description of each modification
+    /// 各変更の人間が読める説明
+    pub changes: Vec<String>,
+}
+
+impl Change {
+    /// Whether the edits move the note to a different file
+    /// 編集によりノートが別のファイルに移動するか
+    #[must_use]
+    pub fn moves(&self) -> bool {
+        self.note.path != self.original_path
+    }
+}
+
+/// Result of planning a bulk operation
+/// 一括操作の計画結果
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Plan {
+    /// Notes that would change
+    /// 変更されるノート
+    pub changes: Vec<Change>,
+
+    /// Number of matching notes the edits leave untouched
+    /// 編集によって変更されない一致ノートの数
+    pub unchanged: usize,
+}
+
+/// Plan edits for every note matching a query
+/// クエリに一致するすべてのノートに対する編集を計画
+///
+/// # Errors
+///
+/// Returns an error if a move would overwrite an existing file or another moved note.
+/// 移動により既存のファイルまたは他の移動ノートが上書きされる場合にエラーを返します。
+pub fn plan(store: &NoteStore, query: &Query, edits: &[Edit]) -> Result<Plan> {
+    let mut edits: Vec<&Edit> = edits.iter().collect();
+    edits.sort_by_key(|edit| edit.rank());
+
+    let mut plan = Plan::default();
+    for mut note in store.list()?.into_iter().filter(|note| query.matches(note)) {
+        let original_path = note.path.clone();
+        let mut changes = Vec::new();
+        for edit in &edits {
+            edit.apply(&mut note, &mut changes);
+        }
+
+        if changes.is_empty() {
+            plan.unchanged += 1;
+        } else {
+            plan.changes.push(Change {
+                original_path,
+                note,
+                changes,
+            });
+        }
+    }
+
+    check_move_conflicts(store, &plan)?;
+    Ok(plan)
+}
+
+/// Write every planned change to the store
+/// 計画されたすべての変更をストアに書き込み
+///
+/// # Errors
+///
+/// Returns an error on the first note that cannot be written or moved; notes
+/// before it have already been updated.
+/// 書き込みまたは移動できない最初のノートでエラーを返します。
+/// それより前のノートは既に更新されています。
+pub fn apply(store: &NoteStore, plan: Plan) -> Result<usize> {
+    let mut applied = 0;
+    for change in plan.changes {
+        let Change {
+            original_path,
+            mut note,
+            ..
+        } = change;
+
+        if note.path == original_path {
+            store.save(&mut note)?;
+        } else {
+            let target = std::mem::replace(&mut note.path, original_path);
+            store.relocate(&mut note, target)?;
+        }
+        applied += 1;
+    }
+    Ok(applied)
+}
+
+/// Reject moves onto existing files or onto each other
+/// 既存ファイルへの移動や移動先同士の衝突を拒否
+fn check_move_conflicts(store: &NoteStore, plan: &Plan) -> Result<()> {
+    let leaving: BTreeSet<&Path> = plan
+        .changes
+        .iter()
+        .filter(|change| change.moves())
+        .map(|change| change.original_path.as_path())
+        .collect();
+
+    let mut targets = BTreeSet::new();
+    for change in plan.changes.iter().filter(|change| change.moves()) {
+        let target = change.note.path.as_path();
+        let occupied = store.exists(target) && !leaving.contains(target);
+        if occupied || !targets.insert(target) {
+            return Err(ZynapseError::storage_error(
+                "plan bulk move",
+                std::io::Error::new(
+                    std::io::ErrorKind::AlreadyExists,
+                    format!(
+                        "moving {} would overwrite {}",
+                        change.original_path.display(),
+                        target.display()
+                    ),
+                ),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Normalize a tag name from the command line
+/// コマンドラインからのタグ名を正規化
+#[must_use]
+pub fn tag(name: &str) -> String {
+    normalize_tag(name)
+}
+
+/// Whether `tags` holds `tag`, ignoring ASCII case as `tag:` queries do
+/// `tags`が`tag`を含むか（`tag:`クエリと同様にASCIIの大文字小文字は無視）
+fn contains_tag(tags: &[String], tag: &str) -> bool {
+    tags.iter()
+        .any(|existing| existing.eq_ignore_ascii_case(tag))
+}
+
+/// Reject protected frontmatter fields
+/// 保護されたフロントマターフィールドを拒否
+fn check_field(key: &str) -> Result<String> {
+    if key.is_empty() {
+        return Err(ZynapseError::invalid_content("Field name cannot be empty"));
+    }
+    if PROTECTED_FIELDS.contains(&key) {
+        return Err(ZynapseError::invalid_content(format!(
+            "Field `{key}` cannot be changed by bulk field edits"
+        )));
+    }
+    Ok(key.to_string())
+}
+
+/// Render a YAML value on a single line for change summaries
+/// 変更の要約用にYAMLの値を1行で表示
+fn yaml_inline(value: &serde_yaml::Value) -> String {
+    serde_json::to_string(value).unwrap_or_else(|_| "?".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn store_with_notes() -> (TempDir, NoteStore) {
+        let dir = TempDir::new().unwrap();
+        let store = NoteStore::new(dir.path().to_path_buf(), 1024 * 1024);
+
+        let mut draft = Note::new("Draft idea", "body");
+        draft.frontmatter.tags = vec!["idea".to_string(), "wip".to_string()];
+        draft
+            .frontmatter
+            .extra
+            .insert("status".to_string(), "draft".into());
+        store.create(&draft).unwrap();
+
+        let mut done = Note::new("Finished idea", "body");
+        done.frontmatter.tags = vec!["idea".to_string()];
+        store.create(&done).unwrap();
+
+        store.create(&Note::new("Unrelated", "body")).unwrap();
+        (dir, store)
+    }
+
+    #[test]
+    fn test_plan_describes_changes_without_writing() {
+        let (_dir, store) = store_with_notes();
+        let query = Query::parse("tag:idea").unwrap();
+        let edits = [
+            Edit::AddTag("project".to_string()),
+            Edit::rename_tag("wip=in-progress").unwrap(),
+            Edit::set_field("status=done").unwrap(),
+            Edit::unset_field("missing").unwrap(),
+        ];
+
+        let plan = plan(&store, &query, &edits).unwrap();
+        assert_eq!(plan.changes.len(), 2);
+        assert_eq!(
+            plan.changes[0].changes,
+            vec![
+                "~ tag wip → in-progress",
+                "+ tag project",
+                r#"~ status: "draft" → "done""#,
+            ]
+        );
+        assert_eq!(
+            plan.changes[1].changes,
+            vec!["+ tag project", r#"+ status: "done""#]
+        );
+
+        // Nothing is written while planning
+        // 計画中は何も書き込まれない
+        let draft = store.find("Draft idea").unwrap();
+        assert_eq!(draft.frontmatter.tags, vec!["idea", "wip"]);
+    }
+
+    #[test]
+    fn test_apply_edits_and_moves() {
+        let (_dir, store) = store_with_notes();
+        let query = Query::parse("tag:idea").unwrap();
+        let edits = [
+            Edit::RemoveTag("wip".to_string()),
+            Edit::set_field("priority=2").unwrap(),
+            Edit::move_to("archive").unwrap(),
+        ];
+
+        let plan = plan(&store, &query, &edits).unwrap();
+        assert!(plan.changes.iter().all(Change::moves));
+        assert_eq!(apply(&store, plan).unwrap(), 2);
+
+        let draft = store.find("Draft idea").unwrap();
+        assert_eq!(draft.path, Path::new("archive/draft-idea.md"));
+        assert_eq!(draft.frontmatter.tags, vec!["idea"]);
+        assert_eq!(
+            draft.frontmatter.extra["priority"],
+            serde_yaml::Value::from(2)
+        );
+        assert!(!store.exists(Path::new("draft-idea.md")));
+        assert_eq!(store.list().unwrap().len(), 3);
+
+        // Running the same edits again changes nothing
+        // 同じ編集を再実行しても何も変わらない
+        let again = super::plan(&store, &query, &edits).unwrap();
+        assert!(again.changes.is_empty());
+        assert_eq!(again.unchanged, 2);
+    }
+
+    #[test]
+    fn test_rename_moves_nested_tags() {
+        let (_dir, store) = store_with_notes();
+        let mut nested = Note::new("Nested", "body");
+        nested.frontmatter.tags = vec![
+            "project/zynapse".to_string(),
+            "project/zynapse/search".to_string(),
+            "projects".to_string(),
+            "work/zynapse".to_string(),
+        ];
+        store.create(&nested).unwrap();
+
+        let query = Query::parse("tag:project").unwrap();
+        let edits = [Edit::rename_tag("#project/zynapse/=work/zynapse").unwrap()];
+        let plan = plan(&store, &query, &edits).unwrap();
+        assert_eq!(
+            plan.changes[0].changes,
+            vec![
+                "~ tag project/zynapse → work/zynapse",
+                "~ tag project/zynapse/search → work/zynapse/search",
+            ]
+        );
+        apply(&store, plan).unwrap();
+        assert_eq!(
+            store.find("Nested").unwrap().frontmatter.tags,
+            vec!["work/zynapse", "work/zynapse/search", "projects"]
+        );
+    }
+
+    #[test]
+    fn test_tag_edits_match_like_queries() {
+        let (_dir, store) = store_with_notes();
+        let mut nested = Note::new("Nested", "body");
+        nested.frontmatter.tags = vec![
+            "Project".to_string(),
+            "project/zynapse".to_string(),
+            "projects".to_string(),
+        ];
+        store.create(&nested).unwrap();
+
+        let query = Query::parse("tag:project").unwrap();
+        let edits = [
+            Edit::RemoveTag("PROJECT".to_string()),
+            Edit::AddTag("Projects".to_string()),
+        ];
+        let plan = plan(&store, &query, &edits).unwrap();
+        assert_eq!(
+            plan.changes[0].changes,
+            vec!["- tag Project", "- tag project/zynapse"]
+        );
+        apply(&store, plan).unwrap();
+        assert_eq!(
+            store.find("Nested").unwrap().frontmatter.tags,
+            vec!["projects"]
+        );
+        assert!(super::plan(&store, &query, &edits)
+            .unwrap()
+            .changes
+            .is_empty());
+    }
+
+    #[test]
+    fn test_invalid_edits_and_conflicts() {
+        let (_dir, store) = store_with_notes();
+        assert!(Edit::set_field("id=1").is_err());
+        assert!(Edit::set_field("status").is_err());
+        assert!(Edit::rename_tag("wip=").is_err());
+        assert!(Edit::move_to("../outside").is_err());
+
+        let mut clash = Note::new("Draft idea", "other");
+        clash.path = PathBuf::from("archive/draft-idea.md");
+        store.create(&clash).unwrap();
+
+        let query = Query::parse("title:draft -path:archive").unwrap();
+        let edits = [Edit::move_to("archive").unwrap()];
+        assert!(plan(&store, &query, &edits).is_err());
+    }
+}
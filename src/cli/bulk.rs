@@ -0,0 +1,203 @@
+//! `bulk` command for editing many notes at once
+//! 多数のノートを一度に編集する`bulk`コマンド
+
+use super::Context;
+use crate::backup::{Backup, Backups};
+use crate::bulk::{self, Edit, Plan};
+use crate::query::Query;
+use crate::Result;
+use clap::{ArgGroup, Args};
+use std::path::PathBuf;
+
+/// Arguments for `zynapse bulk`
+/// `zynapse bulk`の引数
+#[derive(Debug, Clone, Args)]
+#[command(group(
+    ArgGroup::new("edits")
+        .required(true)
+        .multiple(true)
+        .args(["add_tag", "remove_tag", "rename_tag", "set", "unset", "move_to"])
+))]
+pub struct BulkArgs {
+    /// Query selecting the notes to edit, e.g. `tag:inbox -status:done` (`*` for all)
+    ///
+    /// 編集するノートを選択するクエリ（例: `tag:inbox -status:done`、すべては`*`）
+    #[arg(value_name = "QUERY")]
+    pub query: Query,
+
+    /// Add a tag (repeatable)
+    ///
+    /// タグを追加（複数指定可）
+    #[arg(long, value_name = "TAG", value_parser = parse_tag)]
+    pub add_tag: Vec<String>,
+
+    /// Remove a tag and the tags below it (repeatable)
+    ///
+    /// タグを下位のタグとともに削除（複数指定可）
+    #[arg(long, value_name = "TAG", value_parser = parse_tag)]
+    pub remove_tag: Vec<String>,
+
+    /// Rename a tag (repeatable)
+    ///
+    /// タグの名前を変更（複数指定可）
+    #[arg(long, value_name = "OLD=NEW", value_parser = Edit::rename_tag)]
+    pub rename_tag: Vec<Edit>,
+
+    /// Set a frontmatter field; the value is parsed as YAML (repeatable)
+    ///
+    /// フロントマターのフィールドを設定。値はYAMLとして解析（複数指定可）
+    #[arg(long, value_name = "KEY=VALUE", value_parser = Edit::set_field)]
+    pub set: Vec<Edit>,
+
+    /// Remove a frontmatter field (repeatable)
+    ///
+    /// フロントマターのフィールドを削除（複数指定可）
+    #[arg(long, value_name = "KEY", value_parser = Edit::unset_field)]
+    pub unset: Vec<Edit>,
+
+    /// Move the notes into a folder of the vault
+    ///
+    /// ノートをボルト内のフォルダに移動
+    #[arg(long, value_name = "DIR")]
+    pub move_to: Option<PathBuf>,
+
+    /// Only show what would change
+    ///
+    /// 変更内容の表示のみ行う
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Skip the backup taken before applying the changes (also skipped when
+    /// `storage.backup.enabled` is false)
+    ///
+    /// 変更の適用前に作成されるバックアップを省略（`storage.backup.enabled`が
+    /// falseの場合も省略）
+    #[arg(long)]
+    pub no_backup: bool,
+}
+
+impl BulkArgs {
+    /// Collect all requested edits
+    /// 要求されたすべての編集を収集
+    fn edits(&self) -> Result<Vec<Edit>> {
+        let mut edits: Vec<Edit> = self.add_tag.iter().cloned().map(Edit::AddTag).collect();
+        edits.extend(self.remove_tag.iter().cloned().map(Edit::RemoveTag));
+        edits.extend(self.rename_tag.iter().cloned());
+        edits.extend(self.set.iter().cloned());
+        edits.extend(self.unset.iter().cloned());
+        if let Some(folder) = &self.move_to {
+            edits.push(Edit::move_to(folder)?);
+        }
+        Ok(edits)
+    }
+}
+
+/// Plan the bulk edit, then print it or apply it after taking a backup
+/// 一括編集を計画し、表示するかバックアップ後に適用
+pub(super) fn run(context: &Context, args: &BulkArgs) -> Result<()> {
+    let edits = args.edits()?;
+    let plan = bulk::plan(&context.store, &args.query, &edits)?;
+    print_summary(&plan);
+
+    if plan.changes.is_empty() {
+        eprintln!("Nothing to change");
+        return Ok(());
+    }
+    if args.dry_run {
+        eprintln!("Dry run: no files were modified");
+        return Ok(());
+    }
+
+    if let Some(backup) = backup(context, args)? {
+        eprintln!("Backed up vault to {}", backup.path.display());
+    }
+
+    let applied = bulk::apply(&context.store, plan)?;
+    eprintln!("Updated {applied} note(s)");
+    Ok(())
+}
+
+/// Back the vault up unless backups are disabled or `--no-backup` was given
+/// バックアップが無効か`--no-backup`が指定されていなければボルトをバックアップ
+fn backup(context: &Context, args: &BulkArgs) -> Result<Option<Backup>> {
+    let config = &context.config.storage.backup;
+    if args.no_backup || !config.enabled {
+        return Ok(None);
+    }
+    Backups::new(config)
+        .create(&context.store, "bulk")
+        .map(Some)
+}
+
+/// Print the planned changes per note and a summary line
+/// ノートごとの計画された変更と要約行を表示
+fn print_summary(plan: &Plan) {
+    for change in &plan.changes {
+        println!("{}", change.original_path.display());
+        for line in &change.changes {
+            println!("  {line}");
+        }
+    }
+
+    let moved = plan.changes.iter().filter(|change| change.moves()).count();
+    println!(
+        "{} note(s) to change ({moved} moved), {} matching note(s) unchanged",
+        plan.changes.len(),
+        plan.unchanged
+    );
+}
+
+/// Parse a tag argument, rejecting empty names
+/// タグ引数を解析し、空の名前を拒否
+fn parse_tag(name: &str) -> std::result::Result<String, String> {
+    let tag = bulk::tag(name);
+    if tag.is_empty() {
+        Err("tag cannot be empty".to_string())
+    } else {
+        Ok(tag)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::storage::NoteStore;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_backup_follows_config_and_flag() {
+        let dir = TempDir::new().unwrap();
+        let mut config = Config::default();
+        config.storage.root_path = dir.path().join("vault");
+        config.storage.backup.path = dir.path().join("backups");
+        std::fs::create_dir_all(&config.storage.root_path).unwrap();
+        let mut context = Context {
+            store: NoteStore::open(&config.storage).unwrap(),
+            config,
+        };
+        let mut args = BulkArgs {
+            query: "*".parse().unwrap(),
+            add_tag: vec!["done".to_string()],
+            remove_tag: Vec::new(),
+            rename_tag: Vec::new(),
+            set: Vec::new(),
+            unset: Vec::new(),
+            move_to: None,
+            dry_run: false,
+            no_backup: false,
+        };
+
+        assert!(backup(&context, &args).unwrap().is_some());
+        let backups = Backups::new(&context.config.storage.backup);
+        assert_eq!(backups.list().unwrap().len(), 1);
+
+        args.no_backup = true;
+        assert!(backup(&context, &args).unwrap().is_none());
+
+        args.no_backup = false;
+        context.config.storage.backup.enabled = false;
+        assert!(backup(&context, &args).unwrap().is_none());
+        assert_eq!(backups.list().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,255 @@
+//! Shell completion and man page generation
+//! シェル補完とmanページの生成
+//!
+//! Completions are dynamic: the registration script printed by
+//! `zynapse completions <shell>` calls back into the binary on every `<TAB>`, so
+//! note titles, identifiers and tags are read from the current vault.
+//! 補完は動的です。`zynapse completions <shell>`が出力する登録スクリプトは
+//! `<TAB>`のたびにバイナリを呼び出すため、ノートのタイトル・識別子・タグは
+//! 現在のボルトから読み込まれます。
+
+use super::Cli;
+use crate::config::{Layered, Sources};
+use crate::storage::NoteStore;
+use crate::{Result, ZynapseError};
+use clap::{Args, CommandFactory, ValueEnum};
+use clap_complete::env::{Bash, Elvish, EnvCompleter, Fish, Powershell, Zsh};
+use clap_complete::CompletionCandidate;
+use std::collections::BTreeSet;
+use std::io::Write;
+use std::path::PathBuf;
+
+/// Environment variable used by the completion scripts to call back into zynapse
+/// 補完スクリプトがzynapseを呼び出す際に使用する環境変数
+pub const COMPLETE_VAR: &str = "COMPLETE";
+
+/// Shells supported by `zynapse completions`
+/// `zynapse completions`が対応するシェル
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Shell {
+    /// Bourne Again `SHell`
+    Bash,
+    /// Z shell
+    Zsh,
+    /// Friendly interactive shell
+    Fish,
+    /// Elvish shell
+    Elvish,
+    /// `PowerShell`
+    Powershell,
+}
+
+impl Shell {
+    /// Completion integration for this shell
+    /// このシェル用の補完統合
+    fn completer(self) -> &'static dyn EnvCompleter {
+        match self {
+            Self::Bash => &Bash,
+            Self::Zsh => &Zsh,
+            Self::Fish => &Fish,
+            Self::Elvish => &Elvish,
+            Self::Powershell => &Powershell,
+        }
+    }
+}
+
+/// Arguments for `zynapse completions`
+/// `zynapse completions`の引数
+#[derive(Debug, Clone, Copy, Args)]
+pub struct CompletionsArgs {
+    /// Shell to generate the completion script for
+    ///
+    /// 補完スクリプトを生成するシェル
+    #[arg(value_enum)]
+    pub shell: Shell,
+}
+
+/// Arguments for `zynapse man`
+/// `zynapse man`の引数
+#[derive(Debug, Clone, Args)]
+pub struct ManArgs {
+    /// Write one page per subcommand into this directory instead of printing
+    ///
+    /// 表示する代わりにサブコマンドごとのページをこのディレクトリに書き込む
+    #[arg(long, value_name = "DIR")]
+    pub out_dir: Option<PathBuf>,
+}
+
+/// Print the completion registration script for a shell
+/// シェル用の補完登録スクリプトを表示
+pub(super) fn completions(args: CompletionsArgs) -> Result<()> {
+    let command = Cli::command();
+    let name = command.get_name();
+
+    // Call back into this exact binary so completions match its version
+    // バージョンを一致させるため、この実行ファイル自体を呼び出す
+    let completer = std::env::current_exe()
+        .map_or_else(|_| name.to_string(), |path| path.display().to_string());
+
+    let mut script = Vec::new();
+    args.shell
+        .completer()
+        .write_registration(COMPLETE_VAR, name, name, &completer, &mut script)
+        .map_err(|e| ZynapseError::io_error(e, "Failed to generate completion script"))?;
+
+    std::io::stdout()
+        .write_all(&script)
+        .map_err(|e| ZynapseError::io_error(e, "Failed to write completion script"))
+}
+
+/// Print the man page, or write all pages into a directory
+/// manページを表示、またはすべてのページをディレクトリに書き込み
+pub(super) fn man(args: &ManArgs) -> Result<()> {
+    let command = Cli::command();
+
+    if let Some(dir) = &args.out_dir {
+        crate::utils::ensure_directory_exists(dir)?;
+        clap_mangen::generate_to(command, dir).map_err(|e| {
+            ZynapseError::io_error(e, format!("Failed to write man pages to {}", dir.display()))
+        })?;
+        eprintln!("Wrote man pages to {}", dir.display());
+        return Ok(());
+    }
+
+    let mut page = Vec::new();
+    clap_mangen::Man::new(command)
+        .render(&mut page)
+        .map_err(|e| ZynapseError::io_error(e, "Failed to render man page"))?;
+    std::io::stdout()
+        .write_all(&page)
+        .map_err(|e| ZynapseError::io_error(e, "Failed to write man page"))
+}
+
+/// Complete note titles, falling back to identifiers
+/// ノートタイトルを補完し、識別子にフォールバック
+///
+/// Identifiers are hidden candidates, offered only once the typed prefix no
+/// longer matches any title.
+/// 識別子は非表示の候補で、入力中の接頭辞がどのタイトルにも一致しない場合にのみ提示されます。
+pub(super) fn note_candidates() -> Vec<CompletionCandidate> {
+    let Some(store) = completion_store() else {
+        return Vec::new();
+    };
+    let Ok(notes) = store.list() else {
+        return Vec::new();
+    };
+
+    let mut candidates = Vec::with_capacity(notes.len() * 2);
+    for note in &notes {
+        candidates
+            .push(CompletionCandidate::new(note.title()).help(Some(note.id().to_owned().into())));
+    }
+    for note in &notes {
+        candidates.push(
+            CompletionCandidate::new(note.id())
+                .help(Some(note.title().to_owned().into()))
+                .hide(true),
+        );
+    }
+    candidates
+}
+
+/// Complete the tags used across the vault
+/// ボルト全体で使われているタグを補完
+pub(super) fn tag_candidates() -> Vec<CompletionCandidate> {
+    let Some(store) = completion_store() else {
+        return Vec::new();
+    };
+    let Ok(notes) = store.list() else {
+        return Vec::new();
+    };
+
+    notes
+        .iter()
+        .flat_map(|note| note.frontmatter.tags.iter().cloned())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .map(CompletionCandidate::new)
+        .collect()
+}
+
+/// Complete the names of the configured vaults
+/// 設定されたボルトの名前を補完
+pub(super) fn vault_candidates() -> Vec<CompletionCandidate> {
+    let Some(sources) = completion_sources() else {
+        return Vec::new();
+    };
+    let Ok(layered) = Layered::load(&Sources {
+        profile: None,
+        ..sources
+    }) else {
+        return Vec::new();
+    };
+    layered
+        .config
+        .vaults
+        .iter()
+        .map(|(name, profile)| {
+            let root = profile
+                .root_path()
+                .map(|root| root.display().to_string().into());
+            CompletionCandidate::new(name).help(root)
+        })
+        .collect()
+}
+
+/// Open the vault for a completion request
+/// 補完リクエスト用にボルトを開く
+///
+/// Completion must never print errors into the shell or touch the disk, so
+/// failures and a missing vault yield `None`. The configuration file and vault
+/// are taken from `ZYNAPSE_CONFIG` and `ZYNAPSE_VAULT` only, as the partially
+/// typed command line is not parsed at this point.
+/// 補完はシェルにエラーを出力したりディスクを変更したりしてはならないため、失敗時や
+/// ボルトが存在しない場合は`None`を返します。入力途中のコマンドラインはここでは
+/// 解析されないため、設定ファイルとボルトは`ZYNAPSE_CONFIG`と`ZYNAPSE_VAULT`から
+/// のみ取得します。
+fn completion_store() -> Option<NoteStore> {
+    let sources = completion_sources()?;
+    let storage = Layered::load(&sources).ok()?.config.storage;
+    storage
+        .root_path
+        .is_dir()
+        .then(|| NoteStore::new(&storage.root_path, storage.max_file_size))
+}
+
+/// Configuration sources for a completion request
+/// 補完リクエスト用の設定ソース
+fn completion_sources() -> Option<Sources> {
+    let config_path = std::env::var_os("ZYNAPSE_CONFIG").map(PathBuf::from);
+    let mut sources = Sources::standard(config_path.as_deref(), Vec::new()).ok()?;
+    // Completing a word must not rewrite configuration files
+    // 単語の補完で設定ファイルを書き換えてはならない
+    sources.migrate = false;
+    sources.profile = std::env::var(super::VAULT_VAR).ok();
+    Some(sources)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registration_scripts() {
+        for shell in Shell::value_variants() {
+            let mut script = Vec::new();
+            shell
+                .completer()
+                .write_registration(COMPLETE_VAR, "zynapse", "zynapse", "zynapse", &mut script)
+                .unwrap();
+            let script = String::from_utf8(script).unwrap();
+            assert!(script.contains(COMPLETE_VAR), "{shell:?}");
+        }
+    }
+
+    #[test]
+    fn test_man_page_lists_subcommands() {
+        let mut page = Vec::new();
+        clap_mangen::Man::new(Cli::command())
+            .render(&mut page)
+            .unwrap();
+        let page = String::from_utf8(page).unwrap();
+        assert!(page.contains("zynapse"));
+        assert!(page.contains("completions"));
+    }
+}
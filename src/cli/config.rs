@@ -0,0 +1,225 @@
+//! `config` command for inspecting the effective configuration
+//! 有効な設定を確認する`config`コマンド
+
+use crate::config::{diagnostics, schema, Layered, Severity, Sources};
+use crate::{Result, ZynapseError};
+use clap::{Args, Subcommand};
+use std::fmt::Write as _;
+
+/// Arguments for `zynapse config`
+/// `zynapse config`の引数
+#[derive(Debug, Clone, Copy, Args)]
+pub struct ConfigArgs {
+    /// Configuration subcommand
+    /// 設定のサブコマンド
+    #[command(subcommand)]
+    pub command: ConfigCommand,
+}
+
+/// Subcommands of `zynapse config`
+/// `zynapse config`のサブコマンド
+#[derive(Debug, Clone, Copy, Subcommand)]
+pub enum ConfigCommand {
+    /// Print the effective configuration after merging every layer
+    ///
+    /// すべての層をマージした後の有効な設定を表示
+    Show(ShowArgs),
+
+    /// Report every problem of every configuration layer with its location
+    ///
+    /// すべての設定の層のすべての問題を位置とともに報告
+    Check,
+
+    /// Print the JSON Schema of the configuration file for editor completion
+    ///
+    /// エディタ補完用に設定ファイルのJSON Schemaを表示
+    Schema,
+}
+
+/// Arguments for `zynapse config show`
+/// `zynapse config show`の引数
+#[derive(Debug, Clone, Copy, Args)]
+pub struct ShowArgs {
+    /// Print one setting per line with the layer it came from
+    ///
+    /// 設定を1行に1つずつ、由来の層とともに表示
+    #[arg(long)]
+    pub origin: bool,
+}
+
+/// Parse a `--set key=value` override
+/// `--set key=value`の上書きを解析
+pub(super) fn parse_override(input: &str) -> std::result::Result<(String, String), String> {
+    match input.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() => {
+            Ok((key.trim().to_string(), value.trim().to_string()))
+        }
+        _ => Err(format!("expected KEY=VALUE, got '{input}'")),
+    }
+}
+
+/// Run a `zynapse config` subcommand
+/// `zynapse config`のサブコマンドを実行
+pub(super) fn run(sources: &Sources, args: ConfigArgs) -> Result<()> {
+    match args.command {
+        ConfigCommand::Show(args) => show(sources, args),
+        ConfigCommand::Check => check(sources),
+        ConfigCommand::Schema => schema(),
+    }
+}
+
+/// Print the JSON Schema of the configuration
+/// 設定のJSON Schemaを表示
+fn schema() -> Result<()> {
+    let schema = serde_json::to_string_pretty(&schema::generate()).map_err(|e| {
+        ZynapseError::internal(format!("Failed to serialize the configuration schema: {e}"))
+    })?;
+    println!("{schema}");
+    Ok(())
+}
+
+/// Print the diagnostics of every layer, failing if any is an error
+/// すべての層の診断を表示し、エラーがあれば失敗
+fn check(sources: &Sources) -> Result<()> {
+    let diagnostics = diagnostics::check(sources);
+    print!("{}", diagnostics::render(&diagnostics));
+    if diagnostics
+        .iter()
+        .any(|diagnostic| diagnostic.severity == Severity::Error)
+    {
+        return Err(ZynapseError::config_error("The configuration has errors"));
+    }
+    Ok(())
+}
+
+/// List the configured vaults with their roots, marking the selected one
+/// 設定されたボルトをルートとともに一覧表示し、選択中のボルトに印を付ける
+pub(super) fn vaults(sources: &Sources) -> Result<()> {
+    let layered = super::load_layered(sources)?;
+    print!("{}", vault_listing(&layered));
+    Ok(())
+}
+
+/// One `* name  root` line per vault, `*` marking the selected vault
+/// ボルトごとに1行の`* name  root`（`*`は選択中のボルト）
+fn vault_listing(layered: &Layered) -> String {
+    let vaults = &layered.config.vaults;
+    if vaults.is_empty() {
+        return format!(
+            "No vaults configured; using {}\n",
+            layered.config.storage.root_path.display()
+        );
+    }
+    let width = vaults
+        .keys()
+        .map(|name| name.chars().count())
+        .max()
+        .unwrap_or(0);
+    let mut listing = String::new();
+    for (name, profile) in vaults {
+        let marker = if layered.vault.as_deref() == Some(name) {
+            '*'
+        } else {
+            ' '
+        };
+        let root = profile
+            .root_path()
+            .map(|root| root.display().to_string())
+            .unwrap_or_default();
+        let _ = writeln!(listing, "{marker} {name:width$}  {root}");
+    }
+    listing
+}
+
+/// Print the effective configuration, optionally with origins
+/// 有効な設定を表示（任意で由来付き）
+fn show(sources: &Sources, args: ShowArgs) -> Result<()> {
+    let layered = super::load_layered(sources)?;
+    if args.origin {
+        print!("{}", origin_listing(&layered));
+    } else {
+        let content = toml::to_string(&layered.config)
+            .map_err(|e| ZynapseError::config_error(format!("Failed to serialize config: {e}")))?;
+        print!("{content}");
+    }
+    Ok(())
+}
+
+/// One `key = value  # origin` line per setting, with the comments aligned
+/// 設定ごとに1行の`key = value  # origin`（コメントは揃える）
+fn origin_listing(layered: &Layered) -> String {
+    let lines: Vec<(String, String)> = layered
+        .entries()
+        .into_iter()
+        .map(|(key, value, origin)| (format!("{key} = {value}"), origin.to_string()))
+        .collect();
+    let width = lines
+        .iter()
+        .map(|(setting, _)| setting.chars().count())
+        .max()
+        .unwrap_or(0);
+    let mut listing = String::new();
+    for (setting, origin) in lines {
+        let padding = width - setting.chars().count();
+        listing.push_str(&setting);
+        listing.push_str(&" ".repeat(padding));
+        listing.push_str("  # ");
+        listing.push_str(&origin);
+        listing.push('\n');
+    }
+    listing
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_origin_listing() {
+        let sources = Sources {
+            env: vec![("ZYNAPSE_LOGGING_LEVEL".to_string(), "warn".to_string())],
+            ..Sources::default()
+        };
+        let listing = origin_listing(&Layered::load(&sources).unwrap());
+        let level = listing
+            .lines()
+            .find(|line| line.starts_with("logging.level = "))
+            .unwrap();
+        assert!(level.starts_with("logging.level = \"warn\" "));
+        assert!(level.ends_with("  # environment ZYNAPSE_LOGGING_LEVEL"));
+        assert!(
+            listing
+                .lines()
+                .any(|line| line.starts_with("logging.colored = true ")
+                    && line.ends_with("# default"))
+        );
+
+        assert_eq!(
+            parse_override("tui.frame_rate = 30"),
+            Ok(("tui.frame_rate".to_string(), "30".to_string()))
+        );
+        assert!(parse_override("=30").is_err());
+        assert!(parse_override("tui.frame_rate").is_err());
+    }
+
+    #[test]
+    fn test_vault_listing() {
+        let overrides = [
+            ("vaults.work.storage.root_path", "/notes/work"),
+            ("vaults.home.storage.root_path", "/notes/home"),
+            ("default_vault", "work"),
+        ];
+        let sources = Sources {
+            overrides: overrides
+                .iter()
+                .map(|(key, value)| ((*key).to_string(), (*value).to_string()))
+                .collect(),
+            ..Sources::default()
+        };
+        let listing = vault_listing(&Layered::load(&sources).unwrap());
+        assert_eq!(listing, "  home  /notes/home\n* work  /notes/work\n");
+
+        let listing = vault_listing(&Layered::load(&Sources::default()).unwrap());
+        assert!(listing.starts_with("No vaults configured"));
+    }
+}
@@ -0,0 +1,18 @@
+//! `doctor` command for checking the health of an installation
+//! インストールの健全性を確認する`doctor`コマンド
+
+use crate::config::Sources;
+use crate::{doctor, Result, ZynapseError};
+
+/// Print every check with its suggested fix, failing if any check failed
+/// すべてのチェックを修正方法とともに表示し、失敗したチェックがあれば失敗
+pub(super) fn run(sources: &Sources) -> Result<()> {
+    let report = doctor::run(sources);
+    print!("{report}");
+    if report.has_failures() {
+        return Err(ZynapseError::Cli {
+            message: "Some checks failed".to_string(),
+        });
+    }
+    Ok(())
+}
@@ -0,0 +1,335 @@
+//! `init` command for setting Zynapse up on first run
+//! 初回実行時にZynapseをセットアップする`init`コマンド
+//!
+//! The wizard asks for the few settings worth choosing up front, writes them
+//! as the user configuration file, creates the directories of the resulting
+//! configuration, optionally imports an existing folder of Markdown files and
+//! reports the notes and links found in the vault. Every question has a default, so pressing Enter (or
+//! piping no input) accepts the built-in configuration.
+//! ウィザードは最初に選ぶ価値のある少数の設定を尋ね、ユーザー設定ファイルとして
+//! 書き込み、得られた設定のディレクトリを作成し、任意で既存のMarkdownフォルダを
+//! インポートしてボルト内で見つかったノートとリンクを報告します。すべての質問にデフォルトがあるため、
+//! Enterを押す（または入力を与えない）と組み込みの設定を受け入れます。
+
+#[cfg(feature = "search")]
+use crate::config::Language;
+use crate::config::{Config, IdScheme, Layered, Sources, CONFIG_VERSION};
+use crate::links::LinkIndex;
+use crate::storage::NoteStore;
+use crate::template::Prompt;
+use crate::utils::plural;
+use crate::{Result, ZynapseError};
+use clap::Args;
+use serde::de::DeserializeOwned;
+use std::path::{Path, PathBuf};
+use toml::{Table, Value};
+
+/// Arguments for `zynapse init`
+/// `zynapse init`の引数
+#[derive(Debug, Clone, Copy, Args)]
+pub struct InitArgs {
+    /// Replace an existing configuration file
+    ///
+    /// 既存の設定ファイルを置き換える
+    #[arg(long, short)]
+    pub force: bool,
+}
+
+/// Settings chosen in the wizard
+/// ウィザードで選択された設定
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Answers {
+    /// Vault directory
+    /// ボルトディレクトリ
+    root_path: PathBuf,
+
+    /// Editor command
+    /// エディタコマンド
+    editor: String,
+
+    /// Identifier scheme of new notes
+    /// 新しいノートの識別子の形式
+    id_scheme: IdScheme,
+
+    /// Language of the notes
+    /// ノートの言語
+    #[cfg(feature = "search")]
+    language: Language,
+
+    /// Folder of Markdown files to copy into the vault
+    /// ボルトにコピーするMarkdownファイルのフォルダ
+    import: Option<PathBuf>,
+}
+
+/// Ask the setup questions, write the configuration and prepare the vault
+/// セットアップの質問をし、設定を書き込み、ボルトを準備
+pub(super) fn run(sources: &Sources, args: InitArgs) -> Result<()> {
+    let path = sources
+        .user
+        .clone()
+        .ok_or_else(|| ZynapseError::internal("No user configuration file to write"))?;
+    if path.exists() && !args.force {
+        return Err(ZynapseError::Cli {
+            message: format!(
+                "{} already exists; pass --force to replace it",
+                path.display()
+            ),
+        });
+    }
+
+    let answers = ask(&mut super::prompt)?;
+    for line in setup(sources, &path, &answers)? {
+        println!("{line}");
+    }
+    Ok(())
+}
+
+/// Ask every question, repeating the ones with invalid answers
+/// すべての質問をし、無効な回答の質問は繰り返す
+fn ask(prompt: &mut Prompt<'_>) -> Result<Answers> {
+    let defaults = Config::default();
+    let root_path = directory(
+        prompt(
+            "Vault location",
+            Some(&defaults.storage.root_path.display().to_string()),
+        )?
+        .as_str(),
+    );
+    let editor = prompt("Editor command", Some(&defaults.cli.editor))?;
+    let id_scheme = choose(prompt, "Note ID scheme", &["uuid", "timestamp"])?;
+    #[cfg(feature = "search")]
+    let language = choose(prompt, "Language of the notes", &["en", "ja"])?;
+    let import = loop {
+        let answer = prompt("Import an existing Markdown folder (empty to skip)", None)?;
+        if answer.is_empty() {
+            break None;
+        }
+        let folder = directory(&answer);
+        if folder.is_dir() {
+            break Some(folder);
+        }
+        eprintln!("{} is not a directory", folder.display());
+    };
+
+    Ok(Answers {
+        root_path,
+        editor,
+        id_scheme,
+        #[cfg(feature = "search")]
+        language,
+        import,
+    })
+}
+
+/// Ask for one of `options` until the answer names one, the first being the
+/// default
+/// 回答が`options`のいずれかになるまで尋ねる（最初の選択肢がデフォルト）
+fn choose<T: DeserializeOwned>(
+    prompt: &mut Prompt<'_>,
+    question: &str,
+    options: &[&str],
+) -> Result<T> {
+    let question = format!("{question} ({})", options.join(", "));
+    loop {
+        let answer = prompt(&question, options.first().copied())?;
+        if options.contains(&answer.as_str()) {
+            if let Ok(value) = Value::String(answer).try_into() {
+                return Ok(value);
+            }
+        }
+        eprintln!("Please answer {}", options.join(" or "));
+    }
+}
+
+/// An answered directory as an absolute path, expanding a leading `~`
+/// 回答されたディレクトリの絶対パス（先頭の`~`を展開）
+fn directory(answer: &str) -> PathBuf {
+    let path = match (answer.strip_prefix('~'), dirs::home_dir()) {
+        (Some(rest), Some(home)) if rest.is_empty() || rest.starts_with('/') => {
+            home.join(rest.trim_start_matches('/'))
+        }
+        _ => PathBuf::from(answer),
+    };
+    if path.is_relative() {
+        if let Ok(current) = std::env::current_dir() {
+            return current.join(path);
+        }
+    }
+    path
+}
+
+/// Write the answers to `path`, create the directories, import notes and
+/// count the vault's notes and links, returning a summary
+/// 回答を`path`に書き込み、ディレクトリを作成し、ノートをインポートし、ボルトの
+/// ノートとリンクを数え、概要を返す
+fn setup(sources: &Sources, path: &Path, answers: &Answers) -> Result<Vec<String>> {
+    let content = toml::to_string(&document(answers))
+        .map_err(|e| ZynapseError::config_error(format!("Failed to serialize config: {e}")))?;
+    if let Some(parent) = path.parent() {
+        crate::utils::ensure_directory_exists(parent)?;
+    }
+    std::fs::write(path, content).map_err(|e| {
+        ZynapseError::io_error(
+            e,
+            format!("Failed to write config file: {}", path.display()),
+        )
+    })?;
+    let mut summary = vec![format!("Wrote {}", path.display())];
+
+    let config = Layered::load(&Sources {
+        migrate: false,
+        ..sources.clone()
+    })?
+    .config;
+    config.create_directories()?;
+    let store = NoteStore::open(&config.storage)?;
+    summary.push(format!("Vault ready at {}", store.root().display()));
+
+    if let Some(folder) = &answers.import {
+        let imported = store.import(folder)?;
+        summary.push(format!(
+            "Imported {} from {}",
+            plural(imported.len(), "note"),
+            folder.display()
+        ));
+    }
+
+    let notes = store.list()?;
+    let links = LinkIndex::build(&notes);
+    let resolved: usize = (0..notes.len()).map(|i| links.outgoing(i).len()).sum();
+    let dangling: usize = (0..notes.len()).map(|i| links.unresolved(i).len()).sum();
+    summary.push(format!(
+        "Found {} with {} ({dangling} to missing notes)",
+        plural(notes.len(), "note"),
+        plural(resolved, "link")
+    ));
+    Ok(summary)
+}
+
+/// The configuration file holding only the answered settings
+/// 回答された設定のみを保持する設定ファイル
+fn document(answers: &Answers) -> Table {
+    let mut storage = Table::new();
+    storage.insert(
+        "root_path".to_string(),
+        Value::String(answers.root_path.display().to_string()),
+    );
+    storage.insert(
+        "id_scheme".to_string(),
+        Value::try_from(answers.id_scheme).expect("enum serializes to a string"),
+    );
+    let mut cli = Table::new();
+    cli.insert("editor".to_string(), Value::String(answers.editor.clone()));
+
+    let mut document = Table::new();
+    document.insert(
+        "config_version".to_string(),
+        Value::Integer(i64::from(CONFIG_VERSION)),
+    );
+    document.insert("storage".to_string(), Value::Table(storage));
+    #[cfg(feature = "search")]
+    {
+        let mut search = Table::new();
+        search.insert(
+            "language".to_string(),
+            Value::try_from(answers.language).expect("enum serializes to a string"),
+        );
+        document.insert("search".to_string(), Value::Table(search));
+    }
+    document.insert("cli".to_string(), Value::Table(cli));
+    document
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// Answer the questions in order, then fall back to the defaults
+    /// 質問に順に回答し、その後はデフォルトにフォールバック
+    fn answering(answers: &[&str]) -> impl FnMut(&str, Option<&str>) -> Result<String> {
+        let mut answers: Vec<String> = answers.iter().rev().map(ToString::to_string).collect();
+        move |_, default| {
+            Ok(answers
+                .pop()
+                .filter(|answer| !answer.is_empty())
+                .unwrap_or_else(|| default.unwrap_or_default().to_string()))
+        }
+    }
+
+    #[test]
+    fn test_ask_repeats_invalid_answers() {
+        let dir = TempDir::new().unwrap();
+        let vault = dir.path().join("vault");
+        let answers = ask(&mut answering(&[
+            vault.to_str().unwrap(),
+            "hx",
+            "sequential",
+            "timestamp",
+            "ja",
+            "/nowhere/at/all",
+            dir.path().to_str().unwrap(),
+        ]))
+        .unwrap();
+        assert_eq!(answers.root_path, vault);
+        assert_eq!(answers.editor, "hx");
+        assert_eq!(answers.id_scheme, IdScheme::Timestamp);
+        #[cfg(feature = "search")]
+        assert_eq!(answers.language, Language::Japanese);
+        assert_eq!(answers.import.as_deref(), Some(dir.path()));
+
+        let defaults = ask(&mut answering(&[])).unwrap();
+        assert_eq!(defaults.id_scheme, IdScheme::Uuid);
+        assert_eq!(defaults.import, None);
+    }
+
+    #[test]
+    fn test_setup_writes_config_and_imports() {
+        let dir = TempDir::new().unwrap();
+        let source = dir.path().join("old");
+        std::fs::create_dir_all(&source).unwrap();
+        std::fs::write(source.join("one.md"), "Links to [[Two]] and [[Three]]\n").unwrap();
+        std::fs::write(source.join("two.md"), "# Two\n").unwrap();
+        let answers = Answers {
+            root_path: dir.path().join("vault"),
+            editor: "vim".to_string(),
+            id_scheme: IdScheme::Timestamp,
+            #[cfg(feature = "search")]
+            language: Language::Japanese,
+            import: Some(source),
+        };
+        let path = dir.path().join("config/config.toml");
+        // Keep the backups and the index out of the real data directories
+        // バックアップとインデックスを実際のデータディレクトリの外に置く
+        let overrides = [
+            ("storage.backup.path", "backups"),
+            #[cfg(feature = "search")]
+            ("search.index_path", "index"),
+        ];
+        let sources = Sources {
+            user: Some(path.clone()),
+            vault: true,
+            overrides: overrides
+                .into_iter()
+                .map(|(key, name)| (key.to_string(), dir.path().join(name).display().to_string()))
+                .collect(),
+            ..Sources::default()
+        };
+
+        let summary = setup(&sources, &path, &answers).unwrap();
+        assert_eq!(
+            summary[2],
+            format!("Imported 2 notes from {}", dir.path().join("old").display())
+        );
+        assert_eq!(summary[3], "Found 2 notes with 1 link (1 to missing notes)");
+
+        let config = Layered::load(&sources).unwrap().config;
+        assert_eq!(config.storage.root_path, answers.root_path);
+        assert_eq!(config.storage.id_scheme, IdScheme::Timestamp);
+        assert_eq!(config.cli.editor, "vim");
+        #[cfg(feature = "search")]
+        assert_eq!(config.search.language, Language::Japanese);
+        assert!(answers.root_path.join("two.md").is_file());
+        assert!(dir.path().join("backups").is_dir());
+    }
+}
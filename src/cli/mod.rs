@@ -0,0 +1,410 @@
+//! Command-line interface for Zynapse
+//! Zynapseのコマンドラインインターフェース
+//!
+//! This module defines the clap command tree and dispatches each subcommand to
+//! its handler. Handlers live in submodules grouped by functional area.
+//! このモジュールはclapのコマンドツリーを定義し、各サブコマンドをハンドラに
+//! ディスパッチします。ハンドラは機能領域ごとにサブモジュールに分かれています。
+//!
+//! Doc comments on clap items keep the English summary as the first paragraph so
+//! that `-h` stays compact, while `--help` also shows the Japanese translation.
+//! clap項目のドキュメントコメントは英語の要約を最初の段落に置き、`-h`を簡潔に保ちつつ
+//! `--help`では日本語訳も表示します。
+
+mod bulk;
+mod completion;
+mod config;
+mod doctor;
+mod init;
+mod note;
+mod periodic;
+mod picker;
+
+#[cfg(feature = "tui")]
+use crate::config::Watcher;
+use crate::config::{Config, Layered, Sources};
+use crate::periodic::Period;
+use crate::storage::NoteStore;
+use crate::template::Templates;
+use crate::{Result, ZynapseError};
+use clap::{CommandFactory, Parser, Subcommand};
+use clap_complete::{ArgValueCandidates, CompleteEnv};
+use std::path::{Path, PathBuf};
+
+pub use bulk::BulkArgs;
+pub use completion::{CompletionsArgs, ManArgs, Shell};
+pub use config::{ConfigArgs, ConfigCommand, ShowArgs};
+pub use init::InitArgs;
+pub use note::{LinkArgs, NewArgs, NoteArgs, RmArgs};
+pub use periodic::PeriodicArgs;
+
+/// Environment variable selecting the vault, like `--vault`
+/// `--vault`と同様にボルトを選択する環境変数
+pub const VAULT_VAR: &str = "ZYNAPSE_VAULT";
+
+/// Top-level command-line arguments
+/// トップレベルのコマンドライン引数
+#[derive(Debug, Parser)]
+#[command(name = "zynapse", version, about, long_about = None)]
+pub struct Cli {
+    /// Use an alternative configuration file
+    ///
+    /// 別の設定ファイルを使用
+    #[arg(long, global = true, value_name = "FILE", env = "ZYNAPSE_CONFIG")]
+    pub config: Option<PathBuf>,
+
+    /// Use the named vault instead of `default_vault`
+    ///
+    /// `default_vault`の代わりに指定した名前のボルトを使用
+    #[arg(
+        long,
+        global = true,
+        value_name = "NAME",
+        env = VAULT_VAR,
+        add = ArgValueCandidates::new(completion::vault_candidates)
+    )]
+    pub vault: Option<String>,
+
+    /// Override a setting, e.g. `--set tui.theme=light` (repeatable, before the subcommand)
+    ///
+    /// 設定を上書き（例: `--set tui.theme=light`、複数指定可、サブコマンドの前に指定）
+    #[arg(long = "set", value_name = "KEY=VALUE", value_parser = config::parse_override)]
+    pub overrides: Vec<(String, String)>,
+
+    /// Subcommand to execute
+    /// 実行するサブコマンド
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// Available subcommands
+/// 利用可能なサブコマンド
+#[derive(Debug, Clone, Subcommand)]
+pub enum Command {
+    /// Create a new note
+    ///
+    /// 新しいノートを作成
+    New(NewArgs),
+
+    /// Print a note
+    ///
+    /// ノートを表示
+    Show(NoteArgs),
+
+    /// Open an existing note in the editor
+    ///
+    /// 既存のノートをエディタで開く
+    Edit(NoteArgs),
+
+    /// Add a wiki link from one note to another
+    ///
+    /// あるノートから別のノートへウィキリンクを追加
+    Link(LinkArgs),
+
+    /// Delete a note
+    ///
+    /// ノートを削除
+    Rm(RmArgs),
+
+    /// Edit tags, fields or folders of all notes matching a query
+    ///
+    /// クエリに一致するすべてのノートのタグ・フィールド・フォルダを編集
+    Bulk(BulkArgs),
+
+    /// List the available note templates
+    ///
+    /// 利用可能なノートテンプレートを一覧表示
+    Templates,
+
+    /// Create or open the daily note
+    ///
+    /// 日次ノートを作成または開く
+    Daily(PeriodicArgs),
+
+    /// Create or open the weekly note
+    ///
+    /// 週次ノートを作成または開く
+    Weekly(PeriodicArgs),
+
+    /// Create or open the monthly note
+    ///
+    /// 月次ノートを作成または開く
+    Monthly(PeriodicArgs),
+
+    /// Print the shell completion script
+    ///
+    /// シェル補完スクリプトを表示
+    #[command(after_help = "Load completions in the current shell:\n  \
+        bash:  source <(zynapse completions bash)\n  \
+        zsh:   source <(zynapse completions zsh)\n  \
+        fish:  zynapse completions fish | source")]
+    Completions(CompletionsArgs),
+
+    /// Inspect the effective configuration
+    ///
+    /// 有効な設定を確認
+    Config(ConfigArgs),
+
+    /// List the configured vaults, marking the selected one
+    ///
+    /// 設定されたボルトを一覧表示し、選択中のボルトに印を付ける
+    Vaults,
+
+    /// Set up the configuration and vault interactively
+    ///
+    /// 設定とボルトを対話的にセットアップ
+    Init(InitArgs),
+
+    /// Check the environment, configuration and vault, suggesting fixes
+    ///
+    /// 環境・設定・ボルトを確認し、修正方法を提案
+    Doctor,
+
+    /// Generate man pages from the command definitions
+    ///
+    /// コマンド定義からmanページを生成
+    Man(ManArgs),
+
+    /// Browse notes in the interactive terminal UI
+    ///
+    /// インタラクティブなターミナルUIでノートを閲覧
+    #[cfg(feature = "tui")]
+    Tui,
+}
+
+/// Parse the process arguments and run the selected command
+/// プロセス引数を解析して選択されたコマンドを実行
+///
+/// # Errors
+///
+/// Returns an error if the configuration cannot be loaded or the command fails.
+/// 設定を読み込めないか、コマンドが失敗した場合にエラーを返します。
+pub fn run() -> Result<()> {
+    // Answer shell completion requests before normal argument parsing
+    // 通常の引数解析の前にシェル補完リクエストに応答
+    CompleteEnv::with_factory(Cli::command)
+        .var(completion::COMPLETE_VAR)
+        .complete();
+
+    execute(&Cli::parse())
+}
+
+/// Run an already parsed command line
+/// 解析済みのコマンドラインを実行
+///
+/// # Errors
+///
+/// Returns an error if the configuration cannot be loaded or the command fails.
+/// 設定を読み込めないか、コマンドが失敗した場合にエラーを返します。
+pub fn execute(cli: &Cli) -> Result<()> {
+    let Some(command) = &cli.command else {
+        Cli::command()
+            .print_help()
+            .map_err(|e| ZynapseError::io_error(e, "Failed to print help"))?;
+        return Ok(());
+    };
+
+    // Generators only need the command definitions, not a valid configuration
+    // 生成コマンドはコマンド定義のみを必要とし、有効な設定は不要
+    let sources = || {
+        Sources::standard(cli.config.as_deref(), cli.overrides.clone()).map(|sources| Sources {
+            profile: cli.vault.clone(),
+            ..sources
+        })
+    };
+    let context = || {
+        let config = load_layered(&sources()?)?.config;
+        // Logs must not be drawn over by the TUI, nor stop a command
+        // ログはTUIに上書きされてはならず、コマンドを止めてもならない
+        #[cfg(feature = "tui")]
+        let terminal = !matches!(command, Command::Tui);
+        #[cfg(not(feature = "tui"))]
+        let terminal = true;
+        if let Err(error) = crate::logging::init(&config.logging, terminal) {
+            eprintln!("Logging disabled: {error}");
+        }
+        log::debug!("Starting {}", crate::version_info());
+        Context::open(config)
+    };
+
+    match command {
+        Command::New(args) => note::new(&context()?, args),
+        Command::Show(args) => note::show(&context()?, args),
+        Command::Edit(args) => note::edit(&context()?, args),
+        Command::Link(args) => note::link(&context()?, args),
+        Command::Rm(args) => note::rm(&context()?, args),
+        Command::Bulk(args) => bulk::run(&context()?, args),
+        Command::Templates => note::templates(&context()?),
+        Command::Daily(args) => periodic::run(&context()?, Period::Daily, *args),
+        Command::Weekly(args) => periodic::run(&context()?, Period::Weekly, *args),
+        Command::Monthly(args) => periodic::run(&context()?, Period::Monthly, *args),
+        Command::Completions(args) => completion::completions(*args),
+        Command::Config(args) => config::run(&sources()?, *args),
+        Command::Vaults => config::vaults(&sources()?),
+        Command::Init(args) => init::run(&sources()?, *args),
+        Command::Doctor => doctor::run(&sources()?),
+        Command::Man(args) => completion::man(args),
+        #[cfg(feature = "tui")]
+        Command::Tui => {
+            let context = context()?;
+            let watcher = Watcher::new(sources()?, &context.config);
+            crate::tui::run(&context.config, Some(watcher))
+        }
+    }
+}
+
+/// Shared state for command handlers
+/// コマンドハンドラ共有の状態
+#[derive(Debug)]
+pub(crate) struct Context {
+    /// Loaded configuration
+    /// 読み込まれた設定
+    pub(crate) config: Config,
+
+    /// Note store for the configured vault
+    /// 設定されたボルトのノートストア
+    pub(crate) store: NoteStore,
+}
+
+impl Context {
+    /// Open the note store of a loaded configuration
+    /// 読み込まれた設定のノートストアを開く
+    fn open(config: Config) -> Result<Self> {
+        let store = NoteStore::open(&config.storage)?;
+        Ok(Self { config, store })
+    }
+
+    /// Templates directory of the vault
+    /// ボルトのテンプレートディレクトリ
+    pub(crate) fn templates(&self) -> Templates {
+        Templates::new(self.store.root().join(&self.config.storage.templates_dir))
+    }
+
+    /// Open a note file in the configured editor and wait for it to exit
+    /// 設定されたエディタでノートファイルを開き、終了を待つ
+    pub(crate) fn open_in_editor(&self, path: &Path) -> Result<()> {
+        crate::utils::launch_editor(&self.config.cli.editor, path)
+    }
+}
+
+/// Ask the user for a template prompt value on the terminal
+/// テンプレートのプロンプト値をターミナルでユーザーに尋ねる
+///
+/// An empty answer (or end of input) selects the default value.
+/// 空の回答（または入力の終端）はデフォルト値を選択します。
+fn prompt(question: &str, default: Option<&str>) -> Result<String> {
+    use std::io::{BufRead, Write};
+
+    match default {
+        Some(default) => eprint!("{question} [{default}]: "),
+        None => eprint!("{question}: "),
+    }
+    std::io::stderr()
+        .flush()
+        .map_err(|e| ZynapseError::io_error(e, "Failed to write prompt"))?;
+
+    let mut answer = String::new();
+    std::io::stdin()
+        .lock()
+        .read_line(&mut answer)
+        .map_err(|e| ZynapseError::io_error(e, "Failed to read answer"))?;
+
+    let answer = answer.trim();
+    if answer.is_empty() {
+        Ok(default.unwrap_or_default().to_string())
+    } else {
+        Ok(answer.to_string())
+    }
+}
+
+/// Merge the configuration layers, reporting any file that was upgraded
+/// 設定の層をマージし、更新されたファイルを報告
+fn load_layered(sources: &Sources) -> Result<Layered> {
+    let layered = Layered::load(sources)?;
+    for migration in &layered.migrations {
+        eprintln!("{migration}");
+    }
+    Ok(layered)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cli_definition() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn test_parse_periodic_commands() {
+        let cli =
+            Cli::try_parse_from(["zynapse", "daily", "--date", "2024-05-16", "--prev"]).unwrap();
+        assert!(matches!(cli.command, Some(Command::Daily(_))));
+
+        assert!(Cli::try_parse_from(["zynapse", "weekly", "--prev", "--next"]).is_err());
+        assert!(Cli::try_parse_from(["zynapse", "monthly", "--date", "May"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_new_command() {
+        let cli = Cli::try_parse_from([
+            "zynapse",
+            "new",
+            "Reading",
+            "--template",
+            "literature",
+            "-T",
+            "books",
+            "--tag",
+            "#toread",
+        ])
+        .unwrap();
+        let Some(Command::New(args)) = cli.command else {
+            panic!("Expected new command");
+        };
+        assert_eq!(args.title, "Reading");
+        assert_eq!(args.template.as_deref(), Some("literature"));
+        assert_eq!(args.tags, vec!["books", "#toread"]);
+    }
+
+    #[test]
+    fn test_parse_config_overrides() {
+        let cli = Cli::try_parse_from([
+            "zynapse",
+            "--set",
+            "tui.theme=light",
+            "config",
+            "show",
+            "--origin",
+        ])
+        .unwrap();
+        assert_eq!(
+            cli.overrides,
+            vec![("tui.theme".to_string(), "light".to_string())]
+        );
+        let Some(Command::Config(args)) = cli.command else {
+            panic!("Expected config command");
+        };
+        assert!(matches!(
+            args.command,
+            ConfigCommand::Show(ShowArgs { origin: true })
+        ));
+
+        // `bulk --set` still edits front matter rather than the configuration
+        // `bulk --set`は設定ではなくフロントマターを編集する
+        let cli =
+            Cli::try_parse_from(["zynapse", "bulk", "tag:x", "--set", "status=done"]).unwrap();
+        assert!(cli.overrides.is_empty());
+        assert!(Cli::try_parse_from(["zynapse", "--set", "theme", "config", "show"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_vault_flag() {
+        let cli = Cli::try_parse_from(["zynapse", "daily", "--vault", "work"]).unwrap();
+        assert_eq!(cli.vault.as_deref(), Some("work"));
+        let cli = Cli::try_parse_from(["zynapse", "--vault", "personal", "vaults"]).unwrap();
+        assert_eq!(cli.vault.as_deref(), Some("personal"));
+        assert!(matches!(cli.command, Some(Command::Vaults)));
+    }
+}
@@ -0,0 +1,218 @@
+//! Note management commands
+//! ノート管理コマンド
+
+use super::{completion, picker, Context};
+use crate::note::{normalize_tag, Note};
+use crate::template::TemplateContext;
+use crate::{Result, ZynapseError};
+use clap::Args;
+use clap_complete::ArgValueCandidates;
+use std::fmt::Write as _;
+
+/// Arguments for `zynapse new`
+/// `zynapse new`の引数
+#[derive(Debug, Clone, Args)]
+pub struct NewArgs {
+    /// Title of the new note
+    ///
+    /// 新しいノートのタイトル
+    pub title: String,
+
+    /// Template to render the note from (name inside the templates directory)
+    ///
+    /// ノートのレンダリング元テンプレート（テンプレートディレクトリ内の名前）
+    #[arg(long, short)]
+    pub template: Option<String>,
+
+    /// Additional tag to attach (repeatable)
+    ///
+    /// 追加で付与するタグ（複数指定可）
+    #[arg(
+        long = "tag",
+        short = 'T',
+        value_name = "TAG",
+        add = ArgValueCandidates::new(completion::tag_candidates)
+    )]
+    pub tags: Vec<String>,
+
+    /// Only print the note path instead of opening the editor
+    ///
+    /// エディタを開かずにノートパスのみを表示
+    #[arg(long)]
+    pub no_edit: bool,
+}
+
+/// Arguments for commands operating on a single existing note
+/// 既存の単一ノートを操作するコマンドの引数
+#[derive(Debug, Clone, Args)]
+pub struct NoteArgs {
+    /// Title, alias, identifier or file name of the note (picked interactively if omitted)
+    ///
+    /// ノートのタイトル・エイリアス・識別子・ファイル名（省略時は対話的に選択）
+    #[arg(value_name = "NOTE", add = ArgValueCandidates::new(completion::note_candidates))]
+    pub note: Option<String>,
+}
+
+/// Arguments for `zynapse link`
+/// `zynapse link`の引数
+#[derive(Debug, Clone, Args)]
+pub struct LinkArgs {
+    /// Note that receives the link (picked interactively if omitted)
+    ///
+    /// リンクを追加するノート（省略時は対話的に選択）
+    #[arg(value_name = "FROM", add = ArgValueCandidates::new(completion::note_candidates))]
+    pub source: Option<String>,
+
+    /// Note to link to (picked interactively if omitted)
+    ///
+    /// リンク先のノート（省略時は対話的に選択）
+    #[arg(value_name = "TO", add = ArgValueCandidates::new(completion::note_candidates))]
+    pub target: Option<String>,
+}
+
+/// Arguments for `zynapse rm`
+/// `zynapse rm`の引数
+#[derive(Debug, Clone, Args)]
+pub struct RmArgs {
+    /// Note to delete (picked interactively if omitted)
+    ///
+    /// 削除するノート（省略時は対話的に選択）
+    #[arg(value_name = "NOTE", add = ArgValueCandidates::new(completion::note_candidates))]
+    pub note: Option<String>,
+
+    /// Delete without asking for confirmation
+    ///
+    /// 確認せずに削除
+    #[arg(long, short)]
+    pub force: bool,
+}
+
+/// Create a new note, optionally from a template
+/// 新しいノートを作成（任意でテンプレートから）
+pub(super) fn new(context: &Context, args: &NewArgs) -> Result<()> {
+    let mut note = match &args.template {
+        Some(name) => {
+            let template = context.templates().load(name)?;
+            let id = context.store.new_id();
+            let template_context = TemplateContext::new(&args.title, id);
+            template.instantiate(&template_context, &mut super::prompt)?
+        }
+        None => {
+            Note::new(&args.title, format!("# {}\n", args.title)).with_id(context.store.new_id())
+        }
+    };
+
+    for tag in args.tags.iter().map(|tag| normalize_tag(tag)) {
+        if !tag.is_empty() && !note.frontmatter.tags.contains(&tag) {
+            note.frontmatter.tags.push(tag);
+        }
+    }
+
+    context.store.create(&note)?;
+    let path = context.store.absolute_path(&note);
+    eprintln!("Created note {}", note.title());
+    println!("{}", path.display());
+
+    if args.no_edit {
+        Ok(())
+    } else {
+        context.open_in_editor(&path)
+    }
+}
+
+/// List the available note templates
+/// 利用可能なノートテンプレートを一覧表示
+pub(super) fn templates(context: &Context) -> Result<()> {
+    let templates = context.templates();
+    let names = templates.list()?;
+    if names.is_empty() {
+        eprintln!("No templates found in {}", templates.dir().display());
+    }
+    for name in names {
+        println!("{name}");
+    }
+    Ok(())
+}
+
+/// Print a note to standard output
+/// ノートを標準出力に表示
+pub(super) fn show(context: &Context, args: &NoteArgs) -> Result<()> {
+    let note = select(context, args.note.as_deref(), "show")?;
+    print!("{}", note.to_markdown()?);
+    Ok(())
+}
+
+/// Open an existing note in the editor
+/// 既存のノートをエディタで開く
+pub(super) fn edit(context: &Context, args: &NoteArgs) -> Result<()> {
+    let note = select(context, args.note.as_deref(), "edit")?;
+    context.open_in_editor(&context.store.absolute_path(&note))
+}
+
+/// Append a wiki link to another note
+/// 別のノートへのウィキリンクを追加
+pub(super) fn link(context: &Context, args: &LinkArgs) -> Result<()> {
+    let mut source = select(context, args.source.as_deref(), "link from")?;
+    let target = select(context, args.target.as_deref(), "link to")?;
+
+    if source.path == target.path {
+        return Err(ZynapseError::Cli {
+            message: format!("Cannot link {} to itself", source.title()),
+        });
+    }
+    if source.links().iter().any(|link| target.is_named(link)) {
+        eprintln!("{} already links to {}", source.title(), target.title());
+        return Ok(());
+    }
+
+    if !source.body.is_empty() && !source.body.ends_with('\n') {
+        source.body.push('\n');
+    }
+    let _ = writeln!(source.body, "[[{}]]", target.title());
+    context.store.save(&mut source)?;
+    eprintln!("Linked {} → {}", source.title(), target.title());
+    Ok(())
+}
+
+/// Delete a note after confirmation
+/// 確認後にノートを削除
+pub(super) fn rm(context: &Context, args: &RmArgs) -> Result<()> {
+    let notes = context.store.list()?;
+    let backlinks = |note: &Note| {
+        notes
+            .iter()
+            .filter(|other| other.path != note.path)
+            .filter(|other| other.links().iter().any(|link| note.is_named(link)))
+            .count()
+    };
+    let note = picker::select_note(notes.clone(), args.note.as_deref(), "rm")?;
+
+    let linked_from = backlinks(&note);
+    if linked_from > 0 {
+        eprintln!(
+            "Warning: {linked_from} note(s) link to {} and will have dangling links",
+            note.title()
+        );
+    }
+
+    if !args.force {
+        let answer = super::prompt(
+            &format!("Delete {} ({})? (y/n)", note.title(), note.path.display()),
+            Some("n"),
+        )?;
+        if !matches!(answer.to_lowercase().as_str(), "y" | "yes") {
+            eprintln!("Aborted");
+            return Ok(());
+        }
+    }
+
+    context.store.delete(&note)?;
+    eprintln!("Deleted {}", note.path.display());
+    Ok(())
+}
+
+/// Resolve a note argument, opening the picker when needed
+/// ノート引数を解決し、必要に応じてピッカーを開く
+fn select(context: &Context, query: Option<&str>, label: &str) -> Result<Note> {
+    picker::select_note(context.store.list()?, query, label)
+}
@@ -0,0 +1,105 @@
+//! `daily`, `weekly` and `monthly` commands
+//! `daily`・`weekly`・`monthly`コマンド
+
+use super::Context;
+use crate::periodic::{Period, PeriodicNotes};
+use crate::Result;
+use chrono::NaiveDate;
+use clap::Args;
+
+/// Arguments shared by the periodic note commands
+/// 定期ノートコマンド共通の引数
+#[derive(Debug, Clone, Copy, Args)]
+pub struct PeriodicArgs {
+    /// Date inside the period to open (defaults to today)
+    ///
+    /// 開く期間内の日付（デフォルトは今日）
+    #[arg(long, value_name = "YYYY-MM-DD")]
+    pub date: Option<NaiveDate>,
+
+    /// Open the period before the selected one
+    ///
+    /// 選択された期間の前の期間を開く
+    #[arg(long, conflicts_with = "next")]
+    pub prev: bool,
+
+    /// Open the period after the selected one
+    ///
+    /// 選択された期間の次の期間を開く
+    #[arg(long)]
+    pub next: bool,
+
+    /// Only print the note path instead of opening the editor
+    ///
+    /// エディタを開かずにノートパスのみを表示
+    #[arg(long)]
+    pub no_edit: bool,
+}
+
+impl PeriodicArgs {
+    /// Resolve the target date from the arguments
+    /// 引数から対象日付を解決
+    fn target_date(self, period: Period, today: NaiveDate) -> NaiveDate {
+        let date = self.date.unwrap_or(today);
+        if self.prev {
+            period.previous(date)
+        } else if self.next {
+            period.next(date)
+        } else {
+            date
+        }
+    }
+}
+
+/// Create or open the periodic note selected by the arguments
+/// 引数で選択された定期ノートを作成または開く
+pub(super) fn run(context: &Context, period: Period, args: PeriodicArgs) -> Result<()> {
+    let today = chrono::Local::now().date_naive();
+    let date = args.target_date(period, today);
+
+    let notes = PeriodicNotes::new(&context.store, &context.config.periodic);
+    let opened = notes.open_or_create(period, date, &mut super::prompt)?;
+    let path = context.store.absolute_path(&opened.note);
+
+    if opened.created {
+        eprintln!("Created {} note {}", period.name(), opened.note.title());
+    }
+    println!("{}", path.display());
+
+    if args.no_edit {
+        Ok(())
+    } else {
+        context.open_in_editor(&path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_target_date() {
+        let today = NaiveDate::from_ymd_opt(2024, 5, 16).unwrap();
+        let mut args = PeriodicArgs {
+            date: None,
+            prev: false,
+            next: false,
+            no_edit: true,
+        };
+        assert_eq!(args.target_date(Period::Daily, today), today);
+
+        args.prev = true;
+        assert_eq!(
+            args.target_date(Period::Monthly, today),
+            NaiveDate::from_ymd_opt(2024, 4, 1).unwrap()
+        );
+
+        args.prev = false;
+        args.next = true;
+        args.date = NaiveDate::from_ymd_opt(2024, 12, 31);
+        assert_eq!(
+            args.target_date(Period::Daily, today),
+            NaiveDate::from_ymd_opt(2025, 1, 1).unwrap()
+        );
+    }
+}
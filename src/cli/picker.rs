@@ -0,0 +1,495 @@
+//! Inline fuzzy picker for selecting notes
+//! ノート選択用のインラインファジーピッカー
+//!
+//! The picker is drawn below the cursor on standard error, so command output on
+//! standard output stays clean. It is only used when both standard input and
+//! standard error are terminals; otherwise note resolution fails as before.
+//! ピッカーは標準エラー上のカーソル下に描画されるため、標準出力のコマンド出力は
+//! 汚れません。標準入力と標準エラーの両方がターミナルの場合にのみ使用され、
+//! それ以外ではノートの解決は従来通り失敗します。
+
+use crate::note::Note;
+use crate::{Result, ZynapseError};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::{Attribute, Print, SetAttribute};
+use crossterm::terminal::{self, Clear, ClearType};
+use crossterm::{cursor, queue};
+use fuzzy_matcher::skim::SkimMatcherV2;
+use fuzzy_matcher::FuzzyMatcher;
+use std::io::{IsTerminal, Write};
+use unicode_width::UnicodeWidthChar;
+
+/// Maximum number of candidates shown at once
+/// 一度に表示する候補の最大数
+const MAX_VISIBLE: usize = 10;
+
+/// Resolve a note argument, asking the user to pick when it is missing or ambiguous
+/// ノート引数を解決し、省略または曖昧な場合はユーザーに選択を求める
+///
+/// A query naming exactly one note (by identifier, title, alias or file name)
+/// is returned directly. Otherwise the picker opens, pre-filtered with the query.
+/// 1つのノートのみを（識別子・タイトル・エイリアス・ファイル名で）指すクエリは
+/// そのまま返されます。それ以外の場合はクエリで絞り込まれたピッカーが開きます。
+pub(super) fn select_note(notes: Vec<Note>, query: Option<&str>, label: &str) -> Result<Note> {
+    let named: Vec<usize> = query.map_or_else(Vec::new, |query| {
+        (0..notes.len())
+            .filter(|&index| notes[index].is_named(query))
+            .collect()
+    });
+
+    if let [index] = named[..] {
+        return Ok(notes.into_iter().nth(index).expect("index from enumerate"));
+    }
+
+    if !std::io::stdin().is_terminal() || !std::io::stderr().is_terminal() {
+        return Err(match query {
+            None => ZynapseError::Cli {
+                message: "No note given (the picker needs a terminal)".to_string(),
+            },
+            Some(query) if named.is_empty() => ZynapseError::note_not_found(query),
+            Some(query) => ZynapseError::Cli {
+                message: format!(
+                    "`{query}` matches several notes: {}",
+                    named
+                        .iter()
+                        .map(|&index| notes[index].path.display().to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            },
+        });
+    }
+
+    if notes.is_empty() {
+        return Err(ZynapseError::Cli {
+            message: "The vault has no notes to pick from".to_string(),
+        });
+    }
+
+    let mut picker = Picker::new(&notes, query.unwrap_or_default());
+    let index = picker.run(label)?.ok_or_else(|| ZynapseError::Cli {
+        message: "No note selected".to_string(),
+    })?;
+    Ok(notes.into_iter().nth(index).expect("index from picker"))
+}
+
+/// Result of handling a key press
+/// キー入力処理の結果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Outcome {
+    /// Keep the picker open
+    /// ピッカーを開いたままにする
+    Continue,
+
+    /// The candidate with this note index was chosen
+    /// このノートインデックスの候補が選択された
+    Accept(usize),
+
+    /// The user aborted the selection
+    /// ユーザーが選択を中止した
+    Cancel,
+}
+
+/// Filtering and selection state of the picker
+/// ピッカーの絞り込みと選択の状態
+struct Picker<'a> {
+    /// All notes that can be picked
+    /// 選択可能なすべてのノート
+    notes: &'a [Note],
+
+    /// Current filter text
+    /// 現在の絞り込みテキスト
+    query: String,
+
+    /// Indices of matching notes, best match first
+    /// 一致したノートのインデックス（最良の一致が先頭）
+    matches: Vec<usize>,
+
+    /// Position of the highlighted candidate in `matches`
+    /// `matches`内で強調表示中の候補の位置
+    selected: usize,
+
+    /// Fuzzy matcher shared across filter runs
+    /// 絞り込み間で共有されるファジーマッチャー
+    matcher: SkimMatcherV2,
+}
+
+impl<'a> Picker<'a> {
+    /// Create a picker filtered with an initial query
+    /// 初期クエリで絞り込まれたピッカーを作成
+    fn new(notes: &'a [Note], query: &str) -> Self {
+        let mut picker = Self {
+            notes,
+            query: query.to_string(),
+            matches: Vec::new(),
+            selected: 0,
+            matcher: SkimMatcherV2::default().ignore_case(),
+        };
+        picker.refilter();
+        picker
+    }
+
+    /// Recompute the matches for the current query
+    /// 現在のクエリで一致を再計算
+    ///
+    /// Titles and aliases are scored; the best scoring name counts for the note.
+    /// タイトルとエイリアスを採点し、最も高いスコアの名前をノートのスコアとします。
+    fn refilter(&mut self) {
+        let query = self.query.trim();
+        let mut scored: Vec<(i64, usize)> = self
+            .notes
+            .iter()
+            .enumerate()
+            .filter_map(|(index, note)| {
+                if query.is_empty() {
+                    return Some((0, index));
+                }
+                std::iter::once(note.title())
+                    .chain(note.frontmatter.aliases.iter().map(String::as_str))
+                    .filter_map(|name| self.matcher.fuzzy_match(name, query))
+                    .max()
+                    .map(|score| (score, index))
+            })
+            .collect();
+
+        // Stable sort keeps the store order among equal scores
+        // 安定ソートにより同スコア内ではストアの順序を維持
+        scored.sort_by_key(|&(score, _)| std::cmp::Reverse(score));
+        self.matches = scored.into_iter().map(|(_, index)| index).collect();
+        self.selected = 0;
+    }
+
+    /// Note index of the highlighted candidate
+    /// 強調表示中の候補のノートインデックス
+    fn current(&self) -> Option<usize> {
+        self.matches.get(self.selected).copied()
+    }
+
+    /// Update the state for a key press
+    /// キー入力に応じて状態を更新
+    fn handle(&mut self, key: KeyEvent) -> Outcome {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc => return Outcome::Cancel,
+            KeyCode::Char('c' | 'g') if ctrl => return Outcome::Cancel,
+            KeyCode::Enter => {
+                if let Some(index) = self.current() {
+                    return Outcome::Accept(index);
+                }
+            }
+            KeyCode::Up | KeyCode::BackTab => self.move_selection(-1),
+            KeyCode::Char('p' | 'k') if ctrl => self.move_selection(-1),
+            KeyCode::Down | KeyCode::Tab => self.move_selection(1),
+            KeyCode::Char('n' | 'j') if ctrl => self.move_selection(1),
+            KeyCode::Backspace => {
+                self.query.pop();
+                self.refilter();
+            }
+            KeyCode::Char('u') if ctrl => {
+                self.query.clear();
+                self.refilter();
+            }
+            KeyCode::Char(c) if !ctrl => {
+                self.query.push(c);
+                self.refilter();
+            }
+            _ => {}
+        }
+        Outcome::Continue
+    }
+
+    /// Move the highlight, wrapping around at both ends
+    /// 強調表示を移動（両端で折り返し）
+    fn move_selection(&mut self, delta: isize) {
+        let len = self.matches.len();
+        if len > 0 {
+            self.selected = (self.selected + len).wrapping_add_signed(delta) % len;
+        }
+    }
+
+    /// Number of terminal lines the picker occupies
+    /// ピッカーが占有するターミナルの行数
+    fn height(&self) -> usize {
+        // Prompt, candidates and preview
+        // プロンプト・候補・プレビュー
+        self.notes.len().min(MAX_VISIBLE) + 2
+    }
+
+    /// Run the interactive loop on the terminal
+    /// ターミナル上で対話ループを実行
+    fn run(&mut self, label: &str) -> Result<Option<usize>> {
+        let height = self.height();
+
+        let mut guard = InlineGuard::enter().map_err(|e| terminal_error(&e))?;
+        let outcome = self.event_loop(&mut std::io::stderr(), label, height);
+        let released = guard.release();
+
+        let outcome = outcome
+            .and_then(|outcome| released.map(|()| outcome))
+            .map_err(|e| terminal_error(&e))?;
+        match outcome {
+            Outcome::Accept(index) => Ok(Some(index)),
+            Outcome::Continue | Outcome::Cancel => Ok(None),
+        }
+    }
+
+    /// Read keys and redraw until the user accepts or cancels
+    /// ユーザーが確定または中止するまでキーを読み取り再描画
+    fn event_loop(
+        &mut self,
+        out: &mut impl Write,
+        label: &str,
+        height: usize,
+    ) -> std::io::Result<Outcome> {
+        // Reserve room below the cursor so redraws never scroll
+        // 再描画でスクロールしないようカーソル下に領域を確保
+        queue!(
+            out,
+            Print("\n".repeat(height - 1)),
+            cursor::MoveUp(lines(height - 1))
+        )?;
+        loop {
+            self.draw(out, label)?;
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Release {
+                    continue;
+                }
+                match self.handle(key) {
+                    Outcome::Continue => {}
+                    outcome => return Ok(outcome),
+                }
+            }
+        }
+    }
+
+    /// Draw the prompt, candidates and preview, leaving the cursor on the prompt
+    /// プロンプト・候補・プレビューを描画し、カーソルをプロンプト上に残す
+    fn draw(&self, out: &mut impl Write, label: &str) -> std::io::Result<()> {
+        // Pseudo terminals may report a zero size
+        // 疑似ターミナルはサイズ0を報告することがある
+        let width = terminal::size()
+            .ok()
+            .map(|(columns, _)| usize::from(columns))
+            .filter(|&columns| columns > 0)
+            .unwrap_or(80);
+        let visible = self.height() - 2;
+        let first = self.selected.saturating_sub(visible - 1);
+
+        let prompt = format!("{label}> {}", self.query);
+        let counter = format!("  {}/{}", self.matches.len(), self.notes.len());
+        queue!(
+            out,
+            cursor::MoveToColumn(0),
+            Clear(ClearType::FromCursorDown),
+            Print(fit(&prompt, width)),
+            SetAttribute(Attribute::Dim),
+            Print(fit(&counter, width.saturating_sub(display_width(&prompt)))),
+            SetAttribute(Attribute::Reset),
+        )?;
+
+        for row in 0..visible {
+            queue!(out, Print("\r\n"))?;
+            let position = first + row;
+            let Some(&index) = self.matches.get(position) else {
+                continue;
+            };
+            let note = &self.notes[index];
+            let mut line = note.title().to_string();
+            if !note.frontmatter.aliases.is_empty() {
+                line = format!("{line} ({})", note.frontmatter.aliases.join(", "));
+            }
+            if position == self.selected {
+                queue!(
+                    out,
+                    SetAttribute(Attribute::Reverse),
+                    Print(fit(&format!("> {line}"), width)),
+                    SetAttribute(Attribute::Reset),
+                )?;
+            } else {
+                queue!(out, Print(fit(&format!("  {line}"), width)))?;
+            }
+        }
+
+        let preview = self
+            .current()
+            .map(|index| preview_line(&self.notes[index]))
+            .unwrap_or_default();
+        queue!(
+            out,
+            Print("\r\n"),
+            SetAttribute(Attribute::Dim),
+            Print(fit(&preview, width)),
+            SetAttribute(Attribute::Reset),
+            cursor::MoveUp(lines(visible + 1)),
+            cursor::MoveToColumn(lines(display_width(&prompt).min(width.saturating_sub(1)))),
+        )?;
+        out.flush()
+    }
+}
+
+/// Raw mode and the picker area while the picker runs, restored when dropped
+/// so an error or panic in the loop still hands a usable shell back
+/// ピッカー実行中のrawモードとピッカー領域（ドロップ時に復元されるため、ループ内の
+/// エラーやパニックでも使えるシェルが戻る）
+#[derive(Debug)]
+struct InlineGuard {
+    /// Whether the terminal still needs restoring
+    /// ターミナルの復元がまだ必要かどうか
+    active: bool,
+}
+
+impl InlineGuard {
+    /// Switch the terminal into raw mode
+    /// ターミナルをrawモードに切り替え
+    fn enter() -> std::io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        Ok(Self { active: true })
+    }
+
+    /// Clear the picker area and leave raw mode; does nothing when already
+    /// released
+    /// ピッカー領域を消去してrawモードを終了（解放済みなら何もしない）
+    fn release(&mut self) -> std::io::Result<()> {
+        if !std::mem::replace(&mut self.active, false) {
+            return Ok(());
+        }
+        let mut err = std::io::stderr();
+        let cleanup = queue!(
+            err,
+            cursor::MoveToColumn(0),
+            Clear(ClearType::FromCursorDown)
+        )
+        .and_then(|()| err.flush());
+        let restored = terminal::disable_raw_mode();
+        cleanup.and(restored)
+    }
+}
+
+impl Drop for InlineGuard {
+    fn drop(&mut self) {
+        // Errors cannot be reported from here; the terminal is restored on a
+        // best-effort basis
+        // ここからはエラーを報告できないため、ベストエフォートで復元する
+        let _ = self.release();
+    }
+}
+
+/// First meaningful line of a note body, shown below the candidates
+/// 候補の下に表示するノート本文の最初の意味のある行
+fn preview_line(note: &Note) -> String {
+    let line = note
+        .body
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty() && !line.starts_with('#'))
+        .unwrap_or_default();
+    format!("{}: {line}", note.path.display())
+}
+
+/// Truncate text to a display width, accounting for wide characters
+/// 全角文字を考慮して表示幅に収まるようテキストを切り詰め
+fn fit(text: &str, width: usize) -> String {
+    let mut used = 0;
+    text.chars()
+        .take_while(|c| {
+            used += c.width().unwrap_or(0);
+            used <= width
+        })
+        .collect()
+}
+
+/// Display width of text in terminal columns
+/// ターミナルの列数でのテキストの表示幅
+fn display_width(text: &str) -> usize {
+    text.chars().map(|c| c.width().unwrap_or(0)).sum()
+}
+
+/// Convert a line count for cursor movement, saturating on overflow
+/// カーソル移動用に行数を変換（オーバーフロー時は飽和）
+fn lines(count: usize) -> u16 {
+    u16::try_from(count).unwrap_or(u16::MAX)
+}
+
+/// Wrap a terminal I/O failure into a CLI error
+/// ターミナルI/Oの失敗をCLIエラーに変換
+fn terminal_error(error: &std::io::Error) -> ZynapseError {
+    ZynapseError::Cli {
+        message: format!("Picker terminal error: {error}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn notes() -> Vec<Note> {
+        let mut rust = Note::new("Rust ownership", "# Rust ownership\nBorrowing rules\n");
+        rust.frontmatter.aliases.push("borrowck".to_string());
+        vec![
+            Note::new("Daily 2024-05-16", "Notes of the day\n"),
+            rust,
+            Note::new("Reading list", "Books\n"),
+        ]
+    }
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_filtering_and_selection() {
+        let notes = notes();
+        let mut picker = Picker::new(&notes, "");
+        assert_eq!(picker.matches, vec![0, 1, 2]);
+
+        for c in "rdng".chars() {
+            picker.handle(key(KeyCode::Char(c)));
+        }
+        assert_eq!(picker.matches, vec![2]);
+
+        picker.handle(key(KeyCode::Backspace));
+        picker.handle(key(KeyCode::Backspace));
+        picker.handle(key(KeyCode::Backspace));
+        assert_eq!(picker.query, "r");
+
+        // Up from the first candidate wraps to the last one
+        // 先頭の候補から上に移動すると末尾に折り返す
+        picker.handle(key(KeyCode::Up));
+        assert_eq!(picker.selected, picker.matches.len() - 1);
+        assert_eq!(
+            picker.handle(key(KeyCode::Esc)),
+            Outcome::Cancel,
+            "escape cancels"
+        );
+    }
+
+    #[test]
+    fn test_aliases_are_matched() {
+        let notes = notes();
+        let mut picker = Picker::new(&notes, "borrowck");
+        assert_eq!(picker.current(), Some(1));
+        assert_eq!(picker.handle(key(KeyCode::Enter)), Outcome::Accept(1));
+
+        picker = Picker::new(&notes, "zzz");
+        assert_eq!(picker.handle(key(KeyCode::Enter)), Outcome::Continue);
+    }
+
+    #[test]
+    fn test_select_note_without_terminal() {
+        // Exact names resolve without any interaction
+        // 完全一致する名前は対話なしで解決される
+        let note = select_note(notes(), Some("reading list"), "show").unwrap();
+        assert_eq!(note.title(), "Reading list");
+    }
+
+    #[test]
+    fn test_fit_wide_characters() {
+        assert_eq!(fit("日本語のノート", 6), "日本語");
+        assert_eq!(fit("abc", 10), "abc");
+        assert_eq!(display_width("日本a"), 5);
+        assert_eq!(
+            preview_line(&notes()[1]),
+            "rust-ownership.md: Borrowing rules"
+        );
+    }
+}
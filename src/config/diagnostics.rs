@@ -0,0 +1,442 @@
+//! Configuration problems with their locations
+//! 位置情報付きの設定の問題
+//!
+//! [`Layered::load`] stops at the first error, while [`check`] reports every
+//! problem of every layer at once: invalid TOML, unknown keys, values of the
+//! wrong type, invalid values and paths, and conflicting key bindings. Each
+//! diagnostic points at the file, line and column it came from.
+//! [`Layered::load`]は最初のエラーで停止しますが、[`check`]はすべての層の
+//! すべての問題（不正なTOML、未知のキー、型の誤った値、無効な値とパス、衝突する
+//! キーバインド）を一度に報告します。各診断は由来するファイル・行・列を指します。
+//!
+//! ```text
+//! error: tui.frame_rate must be between 1 and 120
+//!  --> /home/me/.zynapse/config.toml:7:14
+//!   |
+//! 7 | frame_rate = 500
+//!   |              ^^^
+//! ```
+
+use super::{Config, Layered, Origin, Sources};
+use std::fmt;
+use std::fmt::Write as _;
+use std::ops::Range;
+use std::path::Path;
+
+/// Problem of a configuration value
+/// 設定値の問題
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Problem {
+    /// Dotted key of the offending setting
+    /// 問題のある設定のドット区切りのキー
+    pub key: String,
+
+    /// Description of the problem
+    /// 問題の説明
+    pub message: String,
+}
+
+impl Problem {
+    /// Create a problem of the setting at `key`
+    /// `key`の設定の問題を作成
+    pub fn new(key: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            key: key.into(),
+            message: message.into(),
+        }
+    }
+}
+
+/// How serious a diagnostic is
+/// 診断の重大度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// The configuration cannot be used
+    /// 設定を使用できない
+    Error,
+
+    /// The configuration works, but probably not as intended
+    /// 設定は動作するが、おそらく意図どおりではない
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Error => "error",
+            Self::Warning => "warning",
+        })
+    }
+}
+
+/// Position of a diagnostic inside a file
+/// ファイル内の診断の位置
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Span {
+    /// Line number, starting at 1
+    /// 行番号（1始まり）
+    pub line: usize,
+
+    /// Column in characters, starting at 1
+    /// 文字単位の列（1始まり）
+    pub column: usize,
+
+    /// Number of characters marked on the line
+    /// 行内でマークする文字数
+    pub width: usize,
+
+    /// Text of the line
+    /// 行のテキスト
+    pub source: String,
+}
+
+impl Span {
+    /// Span of a byte range of `content`
+    /// `content`のバイト範囲のスパン
+    #[must_use]
+    pub fn new(content: &str, range: Range<usize>) -> Self {
+        let start = floor_char_boundary(content, range.start);
+        let line_start = content[..start].rfind('\n').map_or(0, |index| index + 1);
+        let line_end = content[start..]
+            .find('\n')
+            .map_or(content.len(), |index| start + index);
+        let end = floor_char_boundary(content, range.end.clamp(start, line_end));
+        Self {
+            line: content[..start].matches('\n').count() + 1,
+            column: content[line_start..start].chars().count() + 1,
+            width: content[start..end].chars().count().max(1),
+            source: content[line_start..line_end]
+                .trim_end_matches('\r')
+                .to_string(),
+        }
+    }
+}
+
+/// A problem found in the configuration, with where it came from
+/// 設定で見つかった問題とその由来
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// How serious the problem is
+    /// 問題の重大度
+    pub severity: Severity,
+
+    /// Description of the problem
+    /// 問題の説明
+    pub message: String,
+
+    /// Layer the offending value came from, if known
+    /// 問題のある値の由来となった層（判明している場合）
+    pub origin: Option<Origin>,
+
+    /// Position inside the file of the layer, if known
+    /// 層のファイル内の位置（判明している場合）
+    pub span: Option<Span>,
+}
+
+impl Diagnostic {
+    /// Diagnostic without a location
+    /// 位置情報のない診断
+    pub fn new(severity: Severity, message: impl Into<String>) -> Self {
+        Self {
+            severity,
+            message: message.into(),
+            origin: None,
+            span: None,
+        }
+    }
+
+    /// Where the diagnostic points, as `file:line:column` or the layer
+    /// 診断の指す場所（`file:line:column`または層）
+    #[must_use]
+    pub fn location(&self) -> Option<String> {
+        let origin = self.origin.as_ref()?;
+        Some(match (origin.path(), &self.span) {
+            (Some(path), Some(span)) => {
+                format!("{}:{}:{}", path.display(), span.line, span.column)
+            }
+            (Some(path), None) => path.display().to_string(),
+            (None, _) => origin.to_string(),
+        })
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.severity, self.message)?;
+        let Some(location) = self.location() else {
+            return Ok(());
+        };
+        let Some(span) = &self.span else {
+            return write!(f, "\n --> {location}");
+        };
+        let gutter = span.line.to_string().len();
+        let blank = " ".repeat(gutter);
+        write!(
+            f,
+            "\n{blank}--> {location}\n{blank} |\n{} | {}\n{blank} | {}{}",
+            span.line,
+            span.source,
+            " ".repeat(span.column - 1),
+            "^".repeat(span.width)
+        )
+    }
+}
+
+/// Check every layer of `sources`, collecting all problems
+/// `sources`のすべての層を検査し、すべての問題を収集
+///
+/// Besides the problems that make loading fail, this reports unknown keys and
+/// paths that exist but cannot be used. Files are never rewritten.
+/// 読み込みを失敗させる問題に加え、未知のキーと、存在するが使用できないパスを
+/// 報告します。ファイルを書き換えることはありません。
+///
+/// # Examples
+///
+/// ```rust
+/// use zynapse::config::{diagnostics, Severity, Sources};
+///
+/// let sources = Sources {
+///     overrides: vec![
+///         ("logging.level".to_string(), "loud".to_string()),
+///         ("storage.max_file_size".to_string(), "0".to_string()),
+///     ],
+///     ..Sources::default()
+/// };
+/// let diagnostics = diagnostics::check(&sources);
+/// assert_eq!(diagnostics.len(), 2);
+/// assert!(diagnostics.iter().all(|d| d.severity == Severity::Error));
+/// ```
+#[must_use]
+pub fn check(sources: &Sources) -> Vec<Diagnostic> {
+    let sources = Sources {
+        migrate: false,
+        ..sources.clone()
+    };
+    let (layered, mut diagnostics) = Layered::inspect(&sources);
+    if let Some(layered) = layered {
+        for problem in path_problems(&layered.config) {
+            diagnostics.push(layered.diagnostic(Severity::Error, &problem.key, problem.message));
+        }
+    }
+    diagnostics
+}
+
+/// Render diagnostics as a list followed by a summary line
+/// 診断を一覧と要約行として描画
+#[must_use]
+pub fn render(diagnostics: &[Diagnostic]) -> String {
+    let count = |severity: Severity| {
+        diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.severity == severity)
+            .count()
+    };
+    let plural = |count: usize, noun: &str| {
+        if count == 1 {
+            format!("1 {noun}")
+        } else {
+            format!("{count} {noun}s")
+        }
+    };
+
+    let mut output = String::new();
+    for diagnostic in diagnostics {
+        output.push_str(&diagnostic.to_string());
+        output.push_str("\n\n");
+    }
+    if diagnostics.is_empty() {
+        output.push_str("No problems found in the configuration\n");
+    } else {
+        let _ = writeln!(
+            output,
+            "Found {} and {} in the configuration",
+            plural(count(Severity::Error), "error"),
+            plural(count(Severity::Warning), "warning")
+        );
+    }
+    output
+}
+
+/// Paths that exist but cannot serve their purpose
+/// 存在するが目的を果たせないパス
+fn path_problems(config: &Config) -> Vec<Problem> {
+    let mut problems = Vec::new();
+    let mut directory = |key: &str, path: &Path| {
+        if path.exists() && !path.is_dir() {
+            problems.push(Problem::new(
+                key,
+                format!("{key} is not a directory: {}", path.display()),
+            ));
+        }
+    };
+    directory("storage.root_path", &config.storage.root_path);
+    directory("storage.backup.path", &config.storage.backup.path);
+    directory(
+        "storage.templates_dir",
+        &config.storage.root_path.join(&config.storage.templates_dir),
+    );
+    #[cfg(feature = "search")]
+    directory("search.index_path", &config.search.index_path);
+
+    if let Some(path) = &config.logging.file_path {
+        if path.is_dir() {
+            problems.push(Problem::new(
+                "logging.file_path",
+                format!("logging.file_path is a directory: {}", path.display()),
+            ));
+        }
+    }
+
+    #[cfg(feature = "basic-storage")]
+    for (name, settings) in [
+        ("daily", &config.periodic.daily),
+        ("weekly", &config.periodic.weekly),
+        ("monthly", &config.periodic.monthly),
+    ] {
+        if let Some(template) = &settings.template {
+            let path = config.storage.root_path.join(template);
+            if !path.is_file() {
+                problems.push(Problem::new(
+                    format!("periodic.{name}.template"),
+                    format!(
+                        "periodic.{name}.template does not exist: {}",
+                        path.display()
+                    ),
+                ));
+            }
+        }
+    }
+    problems
+}
+
+/// Largest char boundary of `text` not after `index`
+/// `index`以前で最大の`text`の文字境界
+fn floor_char_boundary(text: &str, index: usize) -> usize {
+    let mut index = index.min(text.len());
+    while !text.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::layers::VAULT_CONFIG_FILE;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_check_reports_every_problem_with_spans() {
+        let dir = TempDir::new().unwrap();
+        let vault = dir.path().join("vault");
+        std::fs::create_dir_all(&vault).unwrap();
+        std::fs::write(dir.path().join("backups"), "not a directory").unwrap();
+        let user = dir.path().join("config.toml");
+        std::fs::write(
+            &user,
+            format!(
+                "config_version = 1\n\n[storage]\nroot_path = {:?}\nmax_file_size = \"big\"\n\n[storage.backup]\npath = {:?}\nretain_count = 0\n\n[logging]\nlevel = \"loud\"\ncolour = true\n",
+                vault.display().to_string(),
+                dir.path().join("backups").display().to_string()
+            ),
+        )
+        .unwrap();
+        std::fs::write(
+            vault.join(VAULT_CONFIG_FILE),
+            "[storage]\nroot_path = \"/x\"\n",
+        )
+        .unwrap();
+        let sources = Sources {
+            user: Some(user.clone()),
+            vault: true,
+            env: vec![("ZYNAPSE_LOGGING_TIMESTAMP".to_string(), "[1]".to_string())],
+            ..Sources::default()
+        };
+
+        let diagnostics = check(&sources);
+        let find = |needle: &str| {
+            diagnostics
+                .iter()
+                .find(|diagnostic| diagnostic.message.contains(needle))
+                .unwrap_or_else(|| panic!("no diagnostic for {needle}: {diagnostics:#?}"))
+        };
+
+        let size = find("storage.max_file_size must be an integer");
+        assert_eq!(size.severity, Severity::Error);
+        assert_eq!(size.origin, Some(Origin::User(user.clone())));
+        let span = size.span.as_ref().unwrap();
+        assert_eq!((span.line, span.column, span.width), (5, 17, 5));
+        assert_eq!(span.source, "max_file_size = \"big\"");
+
+        let unknown = find("unknown key `logging.colour`");
+        assert_eq!(unknown.severity, Severity::Warning);
+        assert_eq!(unknown.span.as_ref().unwrap().line, 13);
+
+        let retain = find("backup.retain_count must be greater than 0");
+        assert_eq!(retain.span.as_ref().unwrap().line, 9);
+        assert_eq!(
+            find("logging.level must be one of")
+                .span
+                .as_ref()
+                .unwrap()
+                .line,
+            12
+        );
+        assert_eq!(
+            find("storage.backup.path is not a directory")
+                .span
+                .as_ref()
+                .unwrap()
+                .line,
+            8
+        );
+        assert_eq!(
+            find("logging.timestamp must be a boolean").location(),
+            Some("environment ZYNAPSE_LOGGING_TIMESTAMP".to_string())
+        );
+        let vault_root = find("storage.root_path cannot be set in the vault file");
+        assert_eq!(
+            vault_root.location(),
+            Some(format!("{}:2:13", vault.join(VAULT_CONFIG_FILE).display()))
+        );
+        assert_eq!(diagnostics.len(), 7);
+
+        let rendered = render(&diagnostics);
+        assert!(rendered.contains("\n --> "));
+        assert!(rendered.contains("\n5 | max_file_size = \"big\"\n  |                 ^^^^^\n"));
+        assert!(rendered.ends_with("Found 6 errors and 1 warning in the configuration\n"));
+
+        // Broken TOML is located too
+        // 壊れたTOMLも位置が示される
+        std::fs::write(&user, "[logging]\nlevel = \n").unwrap();
+        let diagnostics = check(&sources);
+        assert_eq!(diagnostics.len(), 2, "{diagnostics:#?}");
+        assert_eq!(
+            diagnostics[0].message,
+            "Invalid TOML: invalid string, expected `\"`, `'`"
+        );
+        assert_eq!(diagnostics[0].span.as_ref().unwrap().line, 2);
+    }
+
+    #[cfg(feature = "tui")]
+    #[test]
+    fn test_check_reports_every_keybinding_conflict() {
+        let dir = TempDir::new().unwrap();
+        let user = dir.path().join("config.toml");
+        std::fs::write(
+            &user,
+            "[tui.keybindings]\nsearch = \"q\"\nedit = \"n\"\nreload = \"ctrl+nope\"\n",
+        )
+        .unwrap();
+        let sources = Sources {
+            user: Some(user),
+            ..Sources::default()
+        };
+        let diagnostics = check(&sources);
+        let lines: Vec<usize> = diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.span.as_ref().map_or(0, |span| span.line))
+            .collect();
+        assert_eq!(lines, [2, 3, 4], "{diagnostics:#?}");
+    }
+}
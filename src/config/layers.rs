@@ -0,0 +1,1083 @@
+//! Layered configuration sources
+//! 階層化された設定ソース
+//!
+//! The effective configuration is merged from these layers, each overriding
+//! the ones before it:
+//! 有効な設定は次の層をマージしたもので、後の層が前の層を上書きします。
+//!
+//! 1. built-in defaults / 組み込みのデフォルト
+//! 2. the system file `/etc/zynapse/config.toml` (Unix) / システムファイル
+//! 3. the user file `config.toml` in the configuration directory (see
+//!    [`super::paths`]), or the file given with `--config` /
+//!    ユーザーファイル（または`--config`で指定したファイル）
+//! 4. the vault file `.zynapse.toml` in the vault root / ボルトルートのボルトファイル
+//! 5. `ZYNAPSE_*` environment variables / 環境変数
+//! 6. `--set key=value` flags / フラグ
+//!
+//! Tables are merged key by key, so a layer only needs to mention the values
+//! it changes. An environment variable is named after the dotted key it sets:
+//! `ZYNAPSE_TUI_FRAME_RATE` sets `tui.frame_rate`.
+//! テーブルはキーごとにマージされるため、各層は変更する値だけを記述すれば
+//! 十分です。環境変数は設定するドット区切りのキーにちなんで命名されます：
+//! `ZYNAPSE_TUI_FRAME_RATE`は`tui.frame_rate`を設定します。
+//!
+//! A vault is selected with `--vault` or `default_vault`. The `storage` and
+//! `search` tables of its `[vaults.<name>]` profile are merged over the system
+//! and user files, keeping the origin of the file they were written in, and
+//! its search index and backups default to directories of their own.
+//! ボルトは`--vault`または`default_vault`で選択されます。その`[vaults.<name>]`
+//! プロファイルの`storage`と`search`テーブルは、書かれたファイルの由来を保った
+//! ままシステムファイルとユーザーファイルの上にマージされ、検索インデックスと
+//! バックアップはデフォルトでそのボルト専用のディレクトリになります。
+//!
+//! Files written for an older format are upgraded through [`super::migrate`]
+//! before they are merged.
+//! 古い形式で書かれたファイルはマージの前に[`super::migrate`]で更新されます。
+
+use super::diagnostics::{Diagnostic, Severity, Span};
+use super::{migrate, Config, Migration, Paths};
+use crate::{Result, ZynapseError};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fmt::Write as _;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use toml::{Table, Value};
+use toml_edit::{ImDocument, TableLike};
+
+/// Name of the configuration file inside a vault
+/// ボルト内の設定ファイル名
+pub const VAULT_CONFIG_FILE: &str = ".zynapse.toml";
+
+/// Prefix of the environment variables that override settings
+/// 設定を上書きする環境変数の接頭辞
+pub const ENV_PREFIX: &str = "ZYNAPSE_";
+
+/// System-wide configuration file
+/// システム全体の設定ファイル
+#[cfg(unix)]
+pub const SYSTEM_CONFIG_FILE: &str = "/etc/zynapse/config.toml";
+
+/// Keys without a default value that can still be set
+/// デフォルト値はないが設定可能なキー
+#[cfg(feature = "basic-storage")]
+const OPTIONAL_KEYS: &[&str] = &[
+    "default_vault",
+    "logging.file_path",
+    "periodic.daily.template",
+    "periodic.weekly.template",
+    "periodic.monthly.template",
+];
+#[cfg(not(feature = "basic-storage"))]
+const OPTIONAL_KEYS: &[&str] = &["default_vault", "logging.file_path"];
+
+/// Sections a vault profile can set
+/// ボルトのプロファイルが設定できるセクション
+const PROFILE_SECTIONS: &[&str] = &["storage", "search"];
+
+/// Tables whose keys are chosen by the user
+/// キーをユーザーが決めるテーブル
+#[cfg(feature = "tui")]
+const OPEN_TABLES: &[&str] = &["tui.keybindings", "tui.saved_searches"];
+#[cfg(not(feature = "tui"))]
+const OPEN_TABLES: &[&str] = &[];
+
+/// Layer an effective value came from
+/// 有効な値の由来となった層
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Origin {
+    /// Built-in default
+    /// 組み込みのデフォルト
+    Default,
+
+    /// System-wide file
+    /// システム全体のファイル
+    System(PathBuf),
+
+    /// User file
+    /// ユーザーファイル
+    User(PathBuf),
+
+    /// Vault file
+    /// ボルトファイル
+    Vault(PathBuf),
+
+    /// Environment variable
+    /// 環境変数
+    Env(String),
+
+    /// `--set` flag
+    /// `--set`フラグ
+    Flag,
+}
+
+impl Origin {
+    /// File of the layer, if it is a file
+    /// 層のファイル（ファイルの場合）
+    #[must_use]
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            Self::System(path) | Self::User(path) | Self::Vault(path) => Some(path),
+            Self::Default | Self::Env(_) | Self::Flag => None,
+        }
+    }
+}
+
+impl fmt::Display for Origin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Default => f.write_str("default"),
+            Self::System(path) => write!(f, "system file {}", path.display()),
+            Self::User(path) => write!(f, "user file {}", path.display()),
+            Self::Vault(path) => write!(f, "vault file {}", path.display()),
+            Self::Env(name) => write!(f, "environment {name}"),
+            Self::Flag => f.write_str("--set"),
+        }
+    }
+}
+
+/// Where the layers are read from
+/// 各層の読み込み元
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Sources {
+    /// System-wide file, skipped when missing
+    /// システム全体のファイル（存在しなければ省略）
+    pub system: Option<PathBuf>,
+
+    /// User file, skipped when missing unless `require_user` is set
+    /// ユーザーファイル（`require_user`が設定されていなければ、存在しない場合は省略）
+    pub user: Option<PathBuf>,
+
+    /// Fail when the user file is missing, as for a file named with `--config`
+    /// `--config`で指定したファイルのように、ユーザーファイルがなければ失敗する
+    pub require_user: bool,
+
+    /// Read `.zynapse.toml` from the vault root
+    /// ボルトルートから`.zynapse.toml`を読み込む
+    pub vault: bool,
+
+    /// Vault profile chosen with `--vault`, instead of `default_vault`
+    /// `default_vault`の代わりに`--vault`で選択したボルトのプロファイル
+    pub profile: Option<String>,
+
+    /// Rewrite outdated user and vault files in place instead of only
+    /// upgrading them in memory
+    /// 古いユーザーファイルとボルトファイルを、メモリ上で更新するだけでなく
+    /// その場で書き換える
+    pub migrate: bool,
+
+    /// Environment variables; those without the `ZYNAPSE_` prefix or not naming
+    /// a setting are ignored
+    /// 環境変数（`ZYNAPSE_`接頭辞がないもの、設定を指さないものは無視）
+    pub env: Vec<(String, String)>,
+
+    /// `--set` overrides as dotted key and value
+    /// ドット区切りのキーと値による`--set`の上書き
+    pub overrides: Vec<(String, String)>,
+}
+
+impl Sources {
+    /// The standard layers of the process: the system file, the user file or
+    /// `config_file` instead, the vault file and the environment
+    /// プロセスの標準の層：システムファイル、ユーザーファイル（または代わりに
+    /// `config_file`）、ボルトファイル、環境変数
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no config file is given and the home directory
+    /// cannot be determined.
+    /// 設定ファイルが指定されず、ホームディレクトリを特定できない場合に
+    /// エラーを返します。
+    pub fn standard(config_file: Option<&Path>, overrides: Vec<(String, String)>) -> Result<Self> {
+        let user = match config_file {
+            Some(path) => path.to_path_buf(),
+            None => Config::config_file_path()?,
+        };
+        Ok(Self {
+            #[cfg(unix)]
+            system: Some(PathBuf::from(SYSTEM_CONFIG_FILE)),
+            #[cfg(not(unix))]
+            system: None,
+            user: Some(user),
+            require_user: config_file.is_some(),
+            vault: true,
+            profile: None,
+            migrate: true,
+            env: std::env::vars().collect(),
+            overrides,
+        })
+    }
+}
+
+/// Effective configuration together with the origin of every value
+/// すべての値の由来を伴う有効な設定
+#[derive(Debug, Clone)]
+pub struct Layered {
+    /// The merged and validated configuration
+    /// マージおよび検証済みの設定
+    pub config: Config,
+
+    /// Files that contributed a layer
+    /// 層を提供したファイル
+    pub files: Vec<PathBuf>,
+
+    /// Files that were written for an older format
+    /// 古い形式で書かれていたファイル
+    pub migrations: Vec<Migration>,
+
+    /// Name of the selected vault profile
+    /// 選択されたボルトのプロファイル名
+    pub vault: Option<String>,
+
+    table: Table,
+    origins: BTreeMap<String, Origin>,
+    documents: Vec<(Origin, ImDocument<String>)>,
+}
+
+impl Layered {
+    /// Merge the layers of `sources` into a configuration
+    /// `sources`の層をマージして設定を構築
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use zynapse::config::{Layered, Origin, Sources};
+    ///
+    /// let sources = Sources {
+    ///     env: vec![("ZYNAPSE_LOGGING_LEVEL".to_string(), "debug".to_string())],
+    ///     overrides: vec![("logging.timestamp".to_string(), "false".to_string())],
+    ///     ..Sources::default()
+    /// };
+    /// let layered = Layered::load(&sources)?;
+    /// assert_eq!(layered.config.logging.level, "debug");
+    /// assert!(!layered.config.logging.timestamp);
+    /// assert_eq!(
+    ///     layered.origin("logging.level"),
+    ///     Some(&Origin::Env("ZYNAPSE_LOGGING_LEVEL".to_string()))
+    /// );
+    /// assert_eq!(layered.origin("logging.colored"), Some(&Origin::Default));
+    /// # Ok::<(), zynapse::ZynapseError>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a file cannot be read or parsed, an override names
+    /// an unknown key, or the merged configuration is invalid.
+    /// ファイルを読み込めないか解析できない場合、上書きが未知のキーを指す場合、
+    /// またはマージ後の設定が無効な場合にエラーを返します。
+    pub fn load(sources: &Sources) -> Result<Self> {
+        let (layered, diagnostics) = Self::inspect(sources);
+        let errors: Vec<Diagnostic> = diagnostics
+            .into_iter()
+            .filter(|diagnostic| diagnostic.severity == Severity::Error)
+            .collect();
+        let Some(first) = errors.first() else {
+            return layered.ok_or_else(|| ZynapseError::internal("Configuration was not loaded"));
+        };
+        let mut message = first.location().map_or_else(
+            || first.message.clone(),
+            |location| format!("{location}: {}", first.message),
+        );
+        if errors.len() > 1 {
+            let _ = write!(
+                message,
+                " (and {} more; run `zynapse config check` to list them)",
+                errors.len() - 1
+            );
+        }
+        Err(ZynapseError::config_error(message))
+    }
+
+    /// Merge the layers of `sources`, collecting every problem on the way
+    /// 途中のすべての問題を収集しながら`sources`の層をマージ
+    ///
+    /// Values that are unknown or of the wrong type are reported and left out,
+    /// so that the remaining layers can still be checked. The configuration is
+    /// returned whenever it could be deserialized, even if it is invalid.
+    /// 未知の値や型の誤った値は報告して除外し、残りの層を引き続き検査できる
+    /// ようにします。設定はデシリアライズできた場合、無効であっても返されます。
+    pub(super) fn inspect(sources: &Sources) -> (Option<Self>, Vec<Diagnostic>) {
+        let mut diagnostics = Vec::new();
+        let Ok(Value::Table(defaults)) = Value::try_from(Config::default()) else {
+            diagnostics.push(Diagnostic::new(
+                Severity::Error,
+                "Failed to serialize the default configuration",
+            ));
+            return (None, diagnostics);
+        };
+        let known = known_keys(&defaults);
+
+        let mut layered = Self {
+            config: Config::default(),
+            files: Vec::new(),
+            migrations: Vec::new(),
+            vault: None,
+            table: Table::new(),
+            origins: BTreeMap::new(),
+            documents: Vec::new(),
+        };
+        layered.merge(defaults, &Origin::Default);
+
+        if let Some(path) = sources.system.as_deref().filter(|path| path.exists()) {
+            // The system file belongs to the administrator and is never rewritten
+            // システムファイルは管理者のものであり、書き換えない
+            let origin = Origin::System(path.to_path_buf());
+            if let Some(table) = layered.read_file(&origin, false, &known, &mut diagnostics) {
+                layered.merge(table, &origin);
+            }
+        }
+        if let Some(path) = &sources.user {
+            if path.exists() || sources.require_user {
+                let origin = Origin::User(path.clone());
+                let write = sources.migrate;
+                if let Some(table) = layered.read_file(&origin, write, &known, &mut diagnostics) {
+                    layered.merge(table, &origin);
+                }
+            }
+        }
+
+        let mut values = Vec::new();
+        for (name, raw) in &sources.env {
+            let Some(key) = name.strip_prefix(ENV_PREFIX).and_then(|rest| {
+                known
+                    .keys()
+                    .find(|key| key.replace('.', "_").eq_ignore_ascii_case(rest))
+            }) else {
+                continue;
+            };
+            values.push((
+                key.clone(),
+                parse_value(raw, known[key].as_ref()),
+                Origin::Env(name.clone()),
+            ));
+        }
+        for (key, raw) in &sources.overrides {
+            let default = match known.get(setting_key(key)) {
+                Some(default) => default.as_ref(),
+                None if is_open(key) => None,
+                None => {
+                    diagnostics.push(Diagnostic {
+                        origin: Some(Origin::Flag),
+                        ..Diagnostic::new(
+                            Severity::Error,
+                            format!("unknown configuration key `{key}` in --set"),
+                        )
+                    });
+                    continue;
+                }
+            };
+            values.push((key.clone(), parse_value(raw, default), Origin::Flag));
+        }
+        values.retain(|(key, value, origin)| {
+            let Some(message) = type_error(key, value, &known) else {
+                return true;
+            };
+            diagnostics.push(Diagnostic {
+                origin: Some(origin.clone()),
+                ..Diagnostic::new(Severity::Error, message)
+            });
+            false
+        });
+
+        let mut probe = layered.clone();
+        probe.apply(&values);
+        let profile = sources.profile.clone().or_else(|| {
+            probe
+                .get("default_vault")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+        });
+        if let Some(name) = profile {
+            layered.select_vault(&probe, &name, sources.profile.is_some(), &mut diagnostics);
+        }
+
+        // The vault is found through every layer but its own file
+        // ボルトは自身のファイル以外のすべての層から求める
+        if sources.vault {
+            let mut probe = layered.clone();
+            probe.apply(&values);
+            if let Some(root) = probe.get("storage.root_path").and_then(Value::as_str) {
+                let path = Path::new(root).join(VAULT_CONFIG_FILE);
+                if path.exists() {
+                    let origin = Origin::Vault(path.clone());
+                    let write = sources.migrate;
+                    if let Some(mut table) =
+                        layered.read_file(&origin, write, &known, &mut diagnostics)
+                    {
+                        if remove(&mut table, "storage.root_path") {
+                            diagnostics.push(layered.diagnostic_in(
+                                &origin,
+                                "storage.root_path",
+                                true,
+                                Severity::Error,
+                                format!(
+                                    "storage.root_path cannot be set in the vault file {}",
+                                    path.display()
+                                ),
+                            ));
+                        }
+                        layered.merge(table, &origin);
+                    }
+                }
+            }
+        }
+        layered.apply(&values);
+
+        match Value::Table(layered.table.clone()).try_into() {
+            Ok(config) => layered.config = config,
+            Err(e) => {
+                diagnostics.push(Diagnostic::new(
+                    Severity::Error,
+                    format!("Invalid configuration: {e}"),
+                ));
+                return (None, diagnostics);
+            }
+        }
+        for problem in layered.config.problems() {
+            diagnostics.push(layered.diagnostic(Severity::Error, &problem.key, problem.message));
+        }
+        (Some(layered), diagnostics)
+    }
+
+    /// Layer a value came from, by dotted key
+    /// ドット区切りのキーで指定した値の由来となった層
+    #[must_use]
+    pub fn origin(&self, key: &str) -> Option<&Origin> {
+        self.origins.get(key)
+    }
+
+    /// Every effective value with its dotted key and origin, sorted by key
+    /// キー順に並べた、ドット区切りのキーと由来を伴うすべての有効な値
+    #[must_use]
+    pub fn entries(&self) -> Vec<(String, Value, &Origin)> {
+        let mut leaves = Vec::new();
+        flatten(&self.table, "", &mut leaves);
+        leaves
+            .into_iter()
+            .filter_map(|(key, value)| {
+                let origin = self.origins.get(&key)?;
+                Some((key, value, origin))
+            })
+            .collect()
+    }
+
+    /// Diagnostic pointing at the layer that set the value of a dotted key
+    /// ドット区切りのキーの値を設定した層を指す診断
+    ///
+    /// A key without a value of its own, such as a table or a key binding
+    /// taken from the preset, points at the first value set below it.
+    /// テーブルやプリセット由来のキーバインドなど、自身の値を持たないキーは、
+    /// その下で最初に設定された値を指します。
+    pub(super) fn diagnostic(
+        &self,
+        severity: Severity,
+        key: &str,
+        message: impl Into<String>,
+    ) -> Diagnostic {
+        let nested = format!("{key}.");
+        let origin = self.origins.get(key).or_else(|| {
+            self.origins
+                .iter()
+                .find(|(other, origin)| other.starts_with(&nested) && **origin != Origin::Default)
+                .map(|(_, origin)| origin)
+        });
+        match origin {
+            Some(origin) if *origin != Origin::Default => {
+                self.diagnostic_in(origin, key, true, severity, message)
+            }
+            _ => Diagnostic::new(severity, message),
+        }
+    }
+
+    /// Diagnostic pointing at a key, or at its value, in the file of a layer
+    /// 層のファイル内のキーまたはその値を指す診断
+    fn diagnostic_in(
+        &self,
+        origin: &Origin,
+        key: &str,
+        value: bool,
+        severity: Severity,
+        message: impl Into<String>,
+    ) -> Diagnostic {
+        let span = self
+            .documents
+            .iter()
+            .find(|(layer, _)| layer == origin)
+            .and_then(|(_, document)| {
+                locate(document, key, value).map(|range| Span::new(document.raw(), range))
+            });
+        Diagnostic {
+            origin: Some(origin.clone()),
+            span,
+            ..Diagnostic::new(severity, message)
+        }
+    }
+
+    /// Effective value of a dotted key
+    /// ドット区切りのキーの有効な値
+    fn get(&self, key: &str) -> Option<&Value> {
+        get(&self.table, key)
+    }
+
+    /// Read the file of a layer, leaving out and reporting the values that
+    /// cannot be used
+    /// 層のファイルを読み込み、使用できない値を除外して報告
+    fn read_file(
+        &mut self,
+        origin: &Origin,
+        write: bool,
+        known: &BTreeMap<String, Option<Value>>,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) -> Option<Table> {
+        let path = origin.path()?;
+        let failure = |message: String, span: Option<Span>| Diagnostic {
+            origin: Some(origin.clone()),
+            span,
+            ..Diagnostic::new(Severity::Error, message)
+        };
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) => {
+                diagnostics.push(failure(format!("Failed to read config file: {e}"), None));
+                return None;
+            }
+        };
+        let document = match ImDocument::parse(content.clone()) {
+            Ok(document) => document,
+            Err(e) => {
+                let span = e.span().map(|range| Span::new(&content, range));
+                diagnostics.push(failure(
+                    format!("Invalid TOML: {}", single_line(e.message())),
+                    span,
+                ));
+                return None;
+            }
+        };
+        self.files.push(path.to_path_buf());
+        self.documents.push((origin.clone(), document.clone()));
+        if let Err(e) = migrate::version(&document.into_mut()) {
+            let message = match e {
+                ZynapseError::Configuration { message } => message,
+                e => e.to_string(),
+            };
+            diagnostics.push(self.diagnostic_in(
+                origin,
+                "config_version",
+                true,
+                Severity::Error,
+                message,
+            ));
+            return None;
+        }
+        let (content, migration) = match migrate::migrate_file(path, write) {
+            Ok(upgraded) => upgraded,
+            Err(e) => {
+                diagnostics.push(failure(e.to_string(), None));
+                return None;
+            }
+        };
+        self.migrations.extend(migration);
+        let mut table: Table = content.parse().ok()?;
+
+        let mut leaves = Vec::new();
+        flatten(&table, "", &mut leaves);
+        for (key, value) in leaves {
+            let nested = format!("{key}.");
+            let setting = setting_key(&key);
+            let (severity, message) = if known.contains_key(setting) || is_open(setting) {
+                match type_error(&key, &value, known) {
+                    Some(message) => (Severity::Error, message),
+                    None => continue,
+                }
+            } else if known.keys().any(|known| known.starts_with(&nested)) {
+                (Severity::Error, format!("{key} must be a table"))
+            } else {
+                (Severity::Warning, format!("unknown key `{key}`"))
+            };
+            let value = severity == Severity::Error;
+            diagnostics.push(self.diagnostic_in(origin, &key, value, severity, message));
+            remove(&mut table, &key);
+        }
+        Some(table)
+    }
+
+    /// Merge the profile of the named vault over `[storage]` and `[search]`
+    /// 指定されたボルトのプロファイルを`[storage]`と`[search]`の上にマージ
+    ///
+    /// `probe` holds every layer, so that profiles can also be set from the
+    /// environment and with `--set`. An unknown `default_vault` is left to
+    /// [`Config::problems`] to report.
+    /// `probe`はすべての層を保持するため、プロファイルは環境変数や`--set`からも
+    /// 設定できます。未知の`default_vault`の報告は[`Config::problems`]に任せます。
+    fn select_vault(
+        &mut self,
+        probe: &Self,
+        name: &str,
+        requested: bool,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) {
+        let prefix = format!("vaults.{name}");
+        let Some(profile) = probe.get(&prefix).and_then(Value::as_table) else {
+            if requested {
+                let known = probe
+                    .get("vaults")
+                    .and_then(Value::as_table)
+                    .map(|vaults| vaults.keys().cloned().collect::<Vec<_>>().join(", "))
+                    .filter(|known| !known.is_empty())
+                    .unwrap_or_else(|| "none".to_string());
+                diagnostics.push(Diagnostic::new(
+                    Severity::Error,
+                    format!("unknown vault `{name}` (configured vaults: {known})"),
+                ));
+            }
+            return;
+        };
+
+        let mut leaves = Vec::new();
+        flatten(profile, "", &mut leaves);
+        for (key, value) in leaves {
+            let origin = probe
+                .origins
+                .get(&format!("{prefix}.{key}"))
+                .cloned()
+                .unwrap_or(Origin::Default);
+            self.merge(single(&key, value), &origin);
+        }
+
+        // Each vault keeps its own index and backups unless told otherwise
+        // 指定がなければ各ボルトは専用のインデックスとバックアップを持つ
+        let paths = Paths::current();
+        let dir = Path::new("vaults").join(name);
+        for (key, path) in [
+            (
+                "search.index_path",
+                paths.cache_dir.join(&dir).join("index"),
+            ),
+            (
+                "storage.backup.path",
+                paths.data_dir.join(&dir).join("backups"),
+            ),
+        ] {
+            if self.get(key).is_some() && self.origins.get(key) == Some(&Origin::Default) {
+                let value = Value::String(path.display().to_string());
+                self.merge(single(key, value), &Origin::Default);
+            }
+        }
+        self.vault = Some(name.to_string());
+    }
+
+    /// Merge the environment and `--set` layers
+    /// 環境変数と`--set`の層をマージ
+    fn apply(&mut self, values: &[(String, Value, Origin)]) {
+        for (key, value, origin) in values {
+            self.merge(single(key, value.clone()), origin);
+        }
+    }
+
+    /// Merge a table over the current values
+    /// 現在の値の上にテーブルをマージ
+    fn merge(&mut self, table: Table, origin: &Origin) {
+        let mut leaves = Vec::new();
+        flatten(&table, "", &mut leaves);
+        for (key, _) in leaves {
+            self.origins.insert(key, origin.clone());
+        }
+        merge_into(&mut self.table, table);
+    }
+}
+
+/// Every dotted key that can be set, with its default value if it has one
+/// 設定可能なすべてのドット区切りのキーと、あればそのデフォルト値
+pub(super) fn known_keys(defaults: &Table) -> BTreeMap<String, Option<Value>> {
+    let mut leaves = Vec::new();
+    flatten(defaults, "", &mut leaves);
+    let mut known: BTreeMap<String, Option<Value>> = leaves
+        .into_iter()
+        .map(|(key, value)| (key, Some(value)))
+        .collect();
+    for key in OPTIONAL_KEYS {
+        known.entry((*key).to_string()).or_insert(None);
+    }
+    #[cfg(feature = "tui")]
+    for action in crate::tui::Action::ALL {
+        known
+            .entry(format!("tui.keybindings.{}", action.name()))
+            .or_insert(None);
+    }
+    known
+}
+
+/// Setting a dotted key stands for
+/// ドット区切りのキーが表す設定
+///
+/// A key inside a vault profile, such as `vaults.work.storage.root_path`,
+/// stands for the same key outside it; every other key stands for itself.
+/// `vaults.work.storage.root_path`のようなボルトのプロファイル内のキーは、
+/// その外側の同じキーを表します。その他のキーはそれ自身を表します。
+fn setting_key(key: &str) -> &str {
+    key.strip_prefix("vaults.")
+        .and_then(|rest| rest.split_once('.'))
+        .map(|(_, setting)| setting)
+        .filter(|setting| {
+            PROFILE_SECTIONS.iter().any(|section| {
+                setting
+                    .strip_prefix(section)
+                    .is_some_and(|rest| rest.starts_with('.'))
+            })
+        })
+        .unwrap_or(key)
+}
+
+/// Whether a key lies in a table whose keys are chosen by the user
+/// キーをユーザーが決めるテーブル内のキーかどうか
+pub(super) fn is_open(key: &str) -> bool {
+    OPEN_TABLES.iter().any(|table| {
+        key.strip_prefix(table)
+            .and_then(|rest| rest.strip_prefix('.'))
+            .is_some_and(|rest| !rest.is_empty())
+    })
+}
+
+/// Interpret a raw string from the environment or the command line
+/// 環境変数またはコマンドラインからの生の文字列を解釈
+///
+/// Settings that default to a string take the text as is; others are read as
+/// a TOML value, falling back to a string.
+/// デフォルトが文字列の設定はテキストをそのまま使い、それ以外はTOMLの値として
+/// 読み込みます（読めなければ文字列）。
+fn parse_value(raw: &str, default: Option<&Value>) -> Value {
+    if matches!(default, Some(Value::String(_))) {
+        return Value::String(raw.to_string());
+    }
+    format!("value = {raw}")
+        .parse::<Table>()
+        .ok()
+        .and_then(|mut table| table.remove("value"))
+        .unwrap_or_else(|| Value::String(raw.to_string()))
+}
+
+/// Why a value does not fit the type of its setting, if it does not
+/// 値が設定の型に合わない理由（合わない場合）
+fn type_error(key: &str, value: &Value, known: &BTreeMap<String, Option<Value>>) -> Option<String> {
+    let expected = match known.get(setting_key(key)) {
+        Some(Some(default)) if std::mem::discriminant(default) == std::mem::discriminant(value) => {
+            return None
+        }
+        Some(Some(default)) => type_name(default),
+        _ if key.starts_with("tui.keybindings.") => match value {
+            Value::String(_) => return None,
+            Value::Array(chords) if chords.iter().all(Value::is_str) => return None,
+            _ => "a chord or a list of chords",
+        },
+        _ if value.is_str() => return None,
+        _ => "a string",
+    };
+    Some(format!(
+        "{key} must be {expected}, found {}",
+        type_name(value)
+    ))
+}
+
+/// Type of a value with its article, as in "an integer"
+/// 冠詞付きの値の型（"an integer"など）
+const fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::String(_) => "a string",
+        Value::Integer(_) => "an integer",
+        Value::Float(_) => "a float",
+        Value::Boolean(_) => "a boolean",
+        Value::Datetime(_) => "a datetime",
+        Value::Array(_) => "an array",
+        Value::Table(_) => "a table",
+    }
+}
+
+/// Byte range of a dotted key, or of its value, in a document
+/// ドキュメント内のドット区切りのキーまたはその値のバイト範囲
+///
+/// A key missing from the document falls back to its closest parent.
+/// ドキュメントにないキーは最も近い親にフォールバックします。
+fn locate(document: &ImDocument<String>, key: &str, value: bool) -> Option<Range<usize>> {
+    let mut table: &dyn TableLike = document.as_table();
+    let mut range = None;
+    let mut parts = key.split('.').peekable();
+    while let Some(part) = parts.next() {
+        let Some((found, item)) = table.get_key_value(part) else {
+            break;
+        };
+        let last = parts.peek().is_none();
+        range = if last && value {
+            item.span().or_else(|| found.span())
+        } else {
+            found.span().or_else(|| item.span())
+        }
+        .or(range);
+        match item.as_table_like() {
+            Some(nested) if !last => table = nested,
+            _ => break,
+        }
+    }
+    range
+}
+
+/// Lines of a parser message joined into one
+/// パーサーのメッセージの行を1行に結合
+fn single_line(message: &str) -> String {
+    let lines: Vec<&str> = message
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect();
+    lines.join(", ")
+}
+
+/// Remove the value at a dotted key, returning whether there was one
+/// ドット区切りのキーの値を削除し、存在したかどうかを返す
+pub(super) fn remove(table: &mut Table, key: &str) -> bool {
+    let (path, last) = key.rsplit_once('.').map_or(("", key), |split| split);
+    let mut table = table;
+    for part in path.split('.').filter(|part| !part.is_empty()) {
+        match table.get_mut(part).and_then(Value::as_table_mut) {
+            Some(nested) => table = nested,
+            None => return false,
+        }
+    }
+    table.remove(last).is_some()
+}
+
+/// Table holding a single value at a dotted key
+/// ドット区切りのキーに1つの値を持つテーブル
+pub(super) fn single(key: &str, value: Value) -> Table {
+    key.rsplit('.')
+        .fold(value, |value, part| {
+            Value::Table(Table::from_iter([(part.to_string(), value)]))
+        })
+        .as_table()
+        .cloned()
+        .unwrap_or_default()
+}
+
+/// Value at a dotted key
+/// ドット区切りのキーの値
+pub(super) fn get<'a>(table: &'a Table, key: &str) -> Option<&'a Value> {
+    let (path, last) = key.rsplit_once('.').map_or(("", key), |split| split);
+    let mut table = table;
+    for part in path.split('.').filter(|part| !part.is_empty()) {
+        table = table.get(part)?.as_table()?;
+    }
+    table.get(last)
+}
+
+/// Recursively merge `overlay` into `base`, replacing everything but tables
+/// `overlay`を`base`に再帰的にマージ（テーブル以外は置き換え）
+pub(super) fn merge_into(base: &mut Table, overlay: Table) {
+    for (key, value) in overlay {
+        match (base.get_mut(&key), value) {
+            (Some(Value::Table(base)), Value::Table(overlay)) => merge_into(base, overlay),
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+/// Collect the non-table values of a table with their dotted keys
+/// テーブルのテーブル以外の値をドット区切りのキーとともに収集
+pub(super) fn flatten(table: &Table, prefix: &str, leaves: &mut Vec<(String, Value)>) {
+    for (key, value) in table {
+        let key = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{prefix}.{key}")
+        };
+        match value {
+            Value::Table(table) => flatten(table, &key, leaves),
+            value => leaves.push((key, value.clone())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write(path: &Path, content: &str) -> PathBuf {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+        path.to_path_buf()
+    }
+
+    #[test]
+    fn test_layers_override_in_order() {
+        let dir = TempDir::new().unwrap();
+        let vault = dir.path().join("vault");
+        let system = write(
+            &dir.path().join("system.toml"),
+            "[logging]\nlevel = \"warn\"\ntimestamp = false\n",
+        );
+        let user = write(
+            &dir.path().join("user.toml"),
+            &format!(
+                "[storage]\nroot_path = {:?}\n[logging]\nlevel = \"info\"\n",
+                vault.display().to_string()
+            ),
+        );
+        let vault_file = write(
+            &vault.join(VAULT_CONFIG_FILE),
+            "[storage]\nauto_save_interval = 30\n[logging]\ncolored = false\n",
+        );
+        let sources = Sources {
+            system: Some(system.clone()),
+            user: Some(user.clone()),
+            require_user: true,
+            vault: true,
+            profile: None,
+            migrate: false,
+            env: vec![
+                ("ZYNAPSE_LOGGING_LEVEL".to_string(), "debug".to_string()),
+                (
+                    "ZYNAPSE_STORAGE_MAX_FILE_SIZE".to_string(),
+                    "2048".to_string(),
+                ),
+                ("ZYNAPSE_UNRELATED".to_string(), "ignored".to_string()),
+            ],
+            overrides: vec![("storage.max_file_size".to_string(), "4096".to_string())],
+        };
+
+        let layered = Layered::load(&sources).unwrap();
+        let config = &layered.config;
+        assert_eq!(config.storage.root_path, vault);
+        assert_eq!(config.storage.auto_save_interval, 30);
+        assert_eq!(config.storage.max_file_size, 4096);
+        assert_eq!(config.logging.level, "debug");
+        assert!(!config.logging.timestamp);
+        assert!(!config.logging.colored);
+        assert_eq!(
+            layered.files,
+            [system.clone(), user.clone(), vault_file.clone()]
+        );
+
+        let origin = |key: &str| layered.origin(key).cloned();
+        assert_eq!(origin("logging.timestamp"), Some(Origin::System(system)));
+        assert_eq!(origin("storage.root_path"), Some(Origin::User(user)));
+        assert_eq!(
+            origin("storage.auto_save_interval"),
+            Some(Origin::Vault(vault_file.clone()))
+        );
+        assert_eq!(
+            origin("logging.level"),
+            Some(Origin::Env("ZYNAPSE_LOGGING_LEVEL".to_string()))
+        );
+        assert_eq!(origin("storage.max_file_size"), Some(Origin::Flag));
+        assert_eq!(origin("storage.backup.retain_count"), Some(Origin::Default));
+        assert!(layered
+            .entries()
+            .iter()
+            .any(|(key, value, _)| key == "logging.level" && value.as_str() == Some("debug")));
+
+        // The vault cannot move itself
+        // ボルトは自身を移動できない
+        write(&vault_file, "[storage]\nroot_path = \"/elsewhere\"\n");
+        assert!(Layered::load(&sources).is_err());
+    }
+
+    #[test]
+    fn test_missing_and_unknown_sources() {
+        let dir = TempDir::new().unwrap();
+        let missing = dir.path().join("missing.toml");
+        let mut sources = Sources {
+            user: Some(missing),
+            ..Sources::default()
+        };
+        assert!(Layered::load(&sources).is_ok());
+        sources.require_user = true;
+        assert!(Layered::load(&sources).is_err());
+
+        sources.require_user = false;
+        sources.overrides = vec![("storage.no_such_key".to_string(), "1".to_string())];
+        assert!(Layered::load(&sources).is_err());
+        sources.overrides = vec![("logging.level".to_string(), "loud".to_string())];
+        assert!(
+            Layered::load(&sources).is_err(),
+            "merged config is validated"
+        );
+
+        assert_eq!(parse_value("30", None), Value::Integer(30));
+        assert_eq!(parse_value("[\"a\"]", None), Value::Array(vec!["a".into()]));
+        assert_eq!(parse_value("ctrl+q", None), Value::String("ctrl+q".into()));
+        assert_eq!(
+            parse_value("42", Some(&Value::String(String::new()))),
+            Value::String("42".into())
+        );
+    }
+
+    #[test]
+    fn test_vault_profiles() {
+        let dir = TempDir::new().unwrap();
+        let work = dir.path().join("work");
+        let personal = dir.path().join("personal");
+        let user = write(
+            &dir.path().join("user.toml"),
+            &format!(
+                "default_vault = \"work\"\n\n[storage]\nmax_file_size = 2048\n\n\
+                 [vaults.work.storage]\nroot_path = {:?}\n\n\
+                 [vaults.personal.storage]\nroot_path = {:?}\nbackup.retain_count = 3\n",
+                work.display().to_string(),
+                personal.display().to_string()
+            ),
+        );
+        write(
+            &personal.join(VAULT_CONFIG_FILE),
+            "[storage]\nauto_save_interval = 30\n",
+        );
+        let mut sources = Sources {
+            user: Some(user.clone()),
+            vault: true,
+            ..Sources::default()
+        };
+
+        let layered = Layered::load(&sources).unwrap();
+        assert_eq!(layered.vault.as_deref(), Some("work"));
+        assert_eq!(layered.config.storage.root_path, work);
+        assert_eq!(layered.config.storage.max_file_size, 2048);
+        assert_eq!(
+            layered.origin("storage.root_path"),
+            Some(&Origin::User(user))
+        );
+        let work_backups = layered.config.storage.backup.path;
+        assert!(work_backups.ends_with("vaults/work/backups"));
+
+        sources.profile = Some("personal".to_string());
+        let layered = Layered::load(&sources).unwrap();
+        let config = &layered.config;
+        assert_eq!(config.storage.root_path, personal);
+        assert_eq!(config.storage.backup.retain_count, 3);
+        assert_eq!(config.storage.auto_save_interval, 30, "vault file is read");
+        assert_ne!(config.storage.backup.path, work_backups);
+        #[cfg(feature = "search")]
+        assert!(config.search.index_path.ends_with("vaults/personal/index"));
+
+        sources.profile = Some("nope".to_string());
+        assert!(Layered::load(&sources).is_err());
+
+        // Profile values are checked against the settings they stand for
+        // プロファイルの値は対応する設定に対して検査される
+        sources.profile = None;
+        sources.overrides = vec![(
+            "vaults.work.storage.max_file_size".to_string(),
+            "big".to_string(),
+        )];
+        assert!(Layered::load(&sources).is_err());
+        sources.overrides = vec![("default_vault".to_string(), "nope".to_string())];
+        assert!(Layered::load(&sources).is_err());
+        assert_eq!(
+            setting_key("vaults.work.search.max_results"),
+            "search.max_results"
+        );
+        assert_eq!(
+            setting_key("vaults.work.tui.theme"),
+            "vaults.work.tui.theme"
+        );
+    }
+}
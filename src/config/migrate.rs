@@ -0,0 +1,324 @@
+//! Versioned configuration format and migrations
+//! バージョン付きの設定形式とマイグレーション
+//!
+//! Every configuration file records the format it was written for in
+//! `config_version`; files without it predate versioning and count as
+//! version 0. Older files are upgraded one step at a time up to
+//! [`CONFIG_VERSION`]. Edits keep the file's comments and layout, and the
+//! original is kept as a backup next to it.
+//! 各設定ファイルは`config_version`に書かれた形式を記録します。これを持たない
+//! ファイルはバージョン管理以前のものとしてバージョン0とみなします。古いファイルは
+//! 1段階ずつ[`CONFIG_VERSION`]まで更新されます。編集はファイルのコメントと
+//! レイアウトを保ち、元のファイルは隣にバックアップとして残します。
+
+use crate::{Result, ZynapseError};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use toml_edit::{DocumentMut, Item, Value};
+
+/// Current version of the configuration format
+/// 設定形式の現在のバージョン
+pub const CONFIG_VERSION: u32 = 1;
+
+/// Key holding the version of a configuration file
+/// 設定ファイルのバージョンを保持するキー
+const VERSION_KEY: &str = "config_version";
+
+/// Upgrade from one version to the next
+/// あるバージョンから次のバージョンへの更新
+#[derive(Debug, Clone, Copy)]
+struct Step {
+    /// Version the step upgrades from
+    /// 更新元のバージョン
+    from: u32,
+
+    /// Edit the document, describing each change
+    /// ドキュメントを編集し、各変更を記述
+    apply: fn(&mut DocumentMut, &mut Vec<String>),
+}
+
+/// Upgrade steps in order
+/// 順序どおりの更新手順
+const STEPS: &[Step] = &[Step {
+    from: 0,
+    apply: unpin_legacy_keybindings,
+}];
+
+/// Report of a migrated configuration file
+/// マイグレーションされた設定ファイルの報告
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Migration {
+    /// Migrated file
+    /// マイグレーションされたファイル
+    pub path: PathBuf,
+
+    /// Version the file was written for
+    /// ファイルが書かれた時のバージョン
+    pub from: u32,
+
+    /// Version the file was upgraded to
+    /// 更新後のバージョン
+    pub to: u32,
+
+    /// What changed, one entry per edit
+    /// 変更内容（編集ごとに1項目）
+    pub changes: Vec<String>,
+
+    /// Copy of the original file, if the file was rewritten in place
+    /// ファイルをその場で書き換えた場合の元のファイルのコピー
+    pub backup: Option<PathBuf>,
+}
+
+impl fmt::Display for Migration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.backup {
+            Some(backup) => write!(
+                f,
+                "Upgraded {} from config version {} to {} (backup: {})",
+                self.path.display(),
+                self.from,
+                self.to,
+                backup.display()
+            )?,
+            None => write!(
+                f,
+                "Read {} as config version {} (written for version {}, file left unchanged)",
+                self.path.display(),
+                self.to,
+                self.from
+            )?,
+        }
+        for change in &self.changes {
+            write!(f, "\n  - {change}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Version a document was written for
+/// ドキュメントが書かれた時のバージョン
+///
+/// # Errors
+///
+/// Returns an error if `config_version` is not a non-negative integer or is
+/// newer than this build supports.
+/// `config_version`が非負の整数でない場合、またはこのビルドが対応するより
+/// 新しい場合にエラーを返します。
+pub fn version(document: &DocumentMut) -> Result<u32> {
+    let Some(item) = document.get(VERSION_KEY) else {
+        return Ok(0);
+    };
+    let version = item
+        .as_integer()
+        .and_then(|version| u32::try_from(version).ok())
+        .ok_or_else(|| {
+            ZynapseError::config_error(format!("{VERSION_KEY} must be a non-negative integer"))
+        })?;
+    if version > CONFIG_VERSION {
+        return Err(ZynapseError::config_error(format!(
+            "{VERSION_KEY} {version} is newer than this version of zynapse supports ({CONFIG_VERSION})"
+        )));
+    }
+    Ok(version)
+}
+
+/// Upgrade a document to [`CONFIG_VERSION`] in memory
+/// ドキュメントをメモリ上で[`CONFIG_VERSION`]に更新
+///
+/// Returns the version the document was written for and the changes made,
+/// which are empty when it was already current.
+/// ドキュメントが書かれた時のバージョンと変更内容を返します（既に最新なら空）。
+///
+/// # Examples
+///
+/// ```rust
+/// use zynapse::config::migrate::{migrate, CONFIG_VERSION};
+///
+/// let mut document = "[logging]\nlevel = \"debug\"\n".parse()?;
+/// let (from, changes) = migrate(&mut document)?;
+/// assert_eq!(from, 0);
+/// assert!(!changes.is_empty());
+/// assert_eq!(document["config_version"].as_integer(), Some(i64::from(CONFIG_VERSION)));
+///
+/// let (from, changes) = migrate(&mut document)?;
+/// assert_eq!(from, CONFIG_VERSION);
+/// assert!(changes.is_empty());
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if the version of the document is invalid.
+/// ドキュメントのバージョンが無効な場合にエラーを返します。
+pub fn migrate(document: &mut DocumentMut) -> Result<(u32, Vec<String>)> {
+    let from = version(document)?;
+    let mut changes = Vec::new();
+    if from == CONFIG_VERSION {
+        return Ok((from, changes));
+    }
+    for step in STEPS.iter().filter(|step| step.from >= from) {
+        (step.apply)(document, &mut changes);
+    }
+    document.insert(VERSION_KEY, toml_edit::value(i64::from(CONFIG_VERSION)));
+    changes.push(format!("Set {VERSION_KEY} = {CONFIG_VERSION}"));
+    Ok((from, changes))
+}
+
+/// Read a configuration file, upgrading it to [`CONFIG_VERSION`]
+/// 設定ファイルを読み込み、[`CONFIG_VERSION`]に更新
+///
+/// Returns the upgraded content, and a report if the file was outdated. With
+/// `write` the file is rewritten in place after copying the original to
+/// `<name>.v<version>.bak`; otherwise it is only upgraded in memory.
+/// 更新後の内容と、ファイルが古かった場合はその報告を返します。`write`を指定すると
+/// 元のファイルを`<name>.v<version>.bak`にコピーしてからその場で書き換え、
+/// そうでなければメモリ上でのみ更新します。
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read or parsed, its version is
+/// invalid, or the backup or the upgraded file cannot be written.
+/// ファイルを読み込めないか解析できない場合、バージョンが無効な場合、または
+/// バックアップや更新後のファイルを書き込めない場合にエラーを返します。
+pub fn migrate_file(path: &Path, write: bool) -> Result<(String, Option<Migration>)> {
+    let content = std::fs::read_to_string(path).map_err(|e| {
+        ZynapseError::io_error(e, format!("Failed to read config file: {}", path.display()))
+    })?;
+    let mut document: DocumentMut = content.parse().map_err(|e| {
+        ZynapseError::config_error(format!("Invalid TOML in {}: {e}", path.display()))
+    })?;
+    let (from, changes) = migrate(&mut document).map_err(|e| match e {
+        ZynapseError::Configuration { message } => {
+            ZynapseError::config_error(format!("{}: {message}", path.display()))
+        }
+        e => e,
+    })?;
+    if changes.is_empty() {
+        return Ok((content, None));
+    }
+
+    let upgraded = document.to_string();
+    let backup = if write {
+        let backup = backup_path(path, from);
+        std::fs::copy(path, &backup).map_err(|e| {
+            ZynapseError::io_error(
+                e,
+                format!("Failed to back up config file: {}", path.display()),
+            )
+        })?;
+        std::fs::write(path, &upgraded).map_err(|e| {
+            ZynapseError::io_error(
+                e,
+                format!("Failed to write config file: {}", path.display()),
+            )
+        })?;
+        Some(backup)
+    } else {
+        None
+    };
+    let migration = Migration {
+        path: path.to_path_buf(),
+        from,
+        to: CONFIG_VERSION,
+        changes,
+        backup,
+    };
+    Ok((upgraded, Some(migration)))
+}
+
+/// Unused backup path for a file written for `version`
+/// `version`向けに書かれたファイルの未使用のバックアップパス
+fn backup_path(path: &Path, version: u32) -> PathBuf {
+    let name = path
+        .file_name()
+        .map_or_else(|| "config.toml".into(), |name| name.to_string_lossy());
+    let mut backup = path.with_file_name(format!("{name}.v{version}.bak"));
+    let mut counter = 1;
+    while backup.exists() {
+        backup = path.with_file_name(format!("{name}.v{version}.{counter}.bak"));
+        counter += 1;
+    }
+    backup
+}
+
+/// Version 0 to 1: drop the key bindings that older releases wrote out with
+/// their defaults, so that the keymap preset applies to those actions
+/// バージョン0から1：以前のリリースがデフォルト値で書き出したキーバインドを削除し、
+/// それらのアクションにキーマップのプリセットが適用されるようにする
+fn unpin_legacy_keybindings(document: &mut DocumentMut, changes: &mut Vec<String>) {
+    const LEGACY: &[(&str, &str)] = &[
+        ("quit", "q"),
+        ("search", "/"),
+        ("new_note", "n"),
+        ("edit", "e"),
+    ];
+
+    let Some(bindings) = document
+        .get_mut("tui")
+        .and_then(|tui| tui.get_mut("keybindings"))
+        .and_then(Item::as_table_like_mut)
+    else {
+        return;
+    };
+    for (action, chord) in LEGACY {
+        let pinned = bindings
+            .get(action)
+            .and_then(Item::as_value)
+            .and_then(Value::as_str)
+            .is_some_and(|value| value == *chord);
+        if pinned {
+            bindings.remove(action);
+            changes.push(format!(
+                "Removed tui.keybindings.{action} = \"{chord}\", an old default, so the keymap preset applies"
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_migrate_file_in_place() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("config.toml");
+        let legacy = "# My settings\n[tui]\ntheme = \"light\"\n\n[tui.keybindings]\nquit = \"q\"\nsearch = \"/\"\nnew_note = \"a\"\nedit = \"e\"\n";
+        std::fs::write(&path, legacy).unwrap();
+
+        let (content, migration) = migrate_file(&path, false).unwrap();
+        let migration = migration.unwrap();
+        assert_eq!((migration.from, migration.to), (0, CONFIG_VERSION));
+        assert_eq!(migration.backup, None);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), legacy);
+        assert!(!content.contains("quit"));
+
+        let (_, migration) = migrate_file(&path, true).unwrap();
+        let migration = migration.unwrap();
+        let backup = migration.backup.clone().unwrap();
+        assert_eq!(backup, dir.path().join("config.toml.v0.bak"));
+        assert_eq!(std::fs::read_to_string(&backup).unwrap(), legacy);
+        assert_eq!(migration.changes.len(), 4);
+        assert!(migration
+            .to_string()
+            .contains("Removed tui.keybindings.quit"));
+
+        // Comments and user choices survive, the old defaults are gone
+        // コメントとユーザーの選択は残り、古いデフォルト値は消える
+        let upgraded = std::fs::read_to_string(&path).unwrap();
+        assert!(upgraded.contains("# My settings"));
+        assert!(upgraded.contains("new_note = \"a\""));
+        assert!(!upgraded.contains("quit"));
+        assert!(upgraded.contains(&format!("config_version = {CONFIG_VERSION}")));
+
+        let (content, migration) = migrate_file(&path, true).unwrap();
+        assert_eq!(content, upgraded);
+        assert_eq!(migration, None);
+
+        std::fs::write(&path, "config_version = 99\n").unwrap();
+        assert!(migrate_file(&path, true).is_err());
+        std::fs::write(&path, "config_version = \"1\"\n").unwrap();
+        assert!(migrate_file(&path, true).is_err());
+    }
+}
@@ -0,0 +1,1289 @@
+//! Configuration management for Zynapse
+//! Zynapseの設定管理
+//!
+//! This module handles loading, validating, and managing configuration settings
+//! for the Zynapse application across different environments and use cases.
+//! このモジュールは異なる環境と使用ケースにわたってZynapseアプリケーションの
+//! 設定の読み込み、検証、管理を処理します。
+
+pub mod diagnostics;
+pub mod layers;
+pub mod migrate;
+pub mod paths;
+pub mod schema;
+pub mod watch;
+
+pub use diagnostics::{Diagnostic, Problem, Severity};
+pub use layers::{Layered, Origin, Sources};
+pub use migrate::{Migration, CONFIG_VERSION};
+pub use paths::Paths;
+pub use watch::{Reload, Watcher};
+
+use crate::{Result, ZynapseError};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Accepted values of `logging.level`
+/// `logging.level`に指定できる値
+pub const LOG_LEVELS: &[&str] = &["error", "warn", "info", "debug", "trace"];
+
+/// Regular expression a vault name has to match
+/// ボルト名が一致すべき正規表現
+pub const VAULT_NAME_PATTERN: &str = "^[A-Za-z0-9_-]+$";
+
+/// Main configuration structure for Zynapse
+/// Zynapseのメイン設定構造体
+///
+/// This structure contains all configuration options for Zynapse,
+/// organized by functional areas.
+/// この構造体は機能領域別に整理されたZynapseのすべての設定オプションを含みます。
+///
+/// Missing sections and fields take their default values, so a file only needs
+/// to mention what it changes.
+/// 存在しないセクションやフィールドはデフォルト値になるため、ファイルには
+/// 変更する項目だけを記述すれば十分です。
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+#[schemars(deny_unknown_fields)]
+pub struct Config {
+    /// Version of the configuration format, see [`migrate`]
+    /// 設定形式のバージョン（[`migrate`]を参照）
+    #[schemars(extend("const" = CONFIG_VERSION))]
+    pub config_version: u32,
+
+    /// Storage configuration
+    /// ストレージ設定
+    pub storage: StorageConfig,
+
+    /// Search configuration
+    /// 検索設定
+    #[cfg(feature = "search")]
+    pub search: SearchConfig,
+
+    /// CLI configuration
+    /// CLI設定
+    #[cfg(feature = "cli")]
+    pub cli: CliConfig,
+
+    /// TUI configuration
+    /// TUI設定
+    #[cfg(feature = "tui")]
+    pub tui: TuiConfig,
+
+    /// Periodic (daily/weekly/monthly) notes configuration
+    /// 定期（日次・週次・月次）ノート設定
+    #[cfg(feature = "basic-storage")]
+    pub periodic: PeriodicConfig,
+
+    /// Logging configuration
+    /// ログ設定
+    pub logging: LoggingConfig,
+
+    /// Vault used when `--vault` is not given
+    /// `--vault`が指定されない場合に使用するボルト
+    pub default_vault: Option<String>,
+
+    /// Named vaults, each with its own storage and search settings
+    /// それぞれ独自のストレージ・検索設定を持つ名前付きのボルト
+    ///
+    /// ```toml
+    /// default_vault = "work"
+    ///
+    /// [vaults.work.storage]
+    /// root_path = "/home/me/work-notes"
+    ///
+    /// [vaults.personal.storage]
+    /// root_path = "/home/me/notes"
+    /// backup.retain_count = 3
+    /// ```
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    #[schemars(extend("propertyNames" = { "pattern": VAULT_NAME_PATTERN }))]
+    pub vaults: BTreeMap<String, VaultProfile>,
+}
+
+/// Settings of a named vault
+/// 名前付きのボルトの設定
+///
+/// The tables have the shape of `[storage]` and `[search]`; the selected vault's
+/// values replace those sections' values, see [`layers`]. A vault that sets no
+/// `search.index_path` or `storage.backup.path` gets directories of its own.
+/// テーブルは`[storage]`と`[search]`と同じ形で、選択されたボルトの値がそれらの
+/// セクションの値を置き換えます（[`layers`]を参照）。`search.index_path`や
+/// `storage.backup.path`を設定しないボルトには専用のディレクトリが割り当てられます。
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+#[schemars(deny_unknown_fields)]
+pub struct VaultProfile {
+    /// Values replacing those of `[storage]`
+    /// `[storage]`の値を置き換える値
+    #[serde(skip_serializing_if = "toml::Table::is_empty")]
+    #[schemars(with = "StorageConfig")]
+    pub storage: toml::Table,
+
+    /// Values replacing those of `[search]`
+    /// `[search]`の値を置き換える値
+    #[cfg(feature = "search")]
+    #[serde(skip_serializing_if = "toml::Table::is_empty")]
+    #[schemars(with = "SearchConfig")]
+    pub search: toml::Table,
+}
+
+impl VaultProfile {
+    /// Root directory of the vault's notes
+    /// ボルトのノートのルートディレクトリ
+    #[must_use]
+    pub fn root_path(&self) -> Option<PathBuf> {
+        self.storage
+            .get("root_path")
+            .and_then(toml::Value::as_str)
+            .map(PathBuf::from)
+    }
+}
+
+/// Storage-related configuration
+/// ストレージ関連設定
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+#[schemars(deny_unknown_fields)]
+pub struct StorageConfig {
+    /// Root directory for storing notes
+    /// ノート保存用ルートディレクトリ
+    pub root_path: PathBuf,
+
+    /// Maximum file size in bytes (default: 10MB)
+    /// 最大ファイルサイズ（バイト単位、デフォルト：10MB）
+    #[schemars(range(min = 1))]
+    pub max_file_size: u64,
+
+    /// Backup configuration
+    /// バックアップ設定
+    pub backup: BackupConfig,
+
+    /// Auto-save interval in seconds (0 = disabled)
+    /// 自動保存間隔（秒単位、0 = 無効）
+    pub auto_save_interval: u64,
+
+    /// Note templates directory, relative to `root_path`
+    /// `root_path`からの相対パスで指定するノートテンプレートディレクトリ
+    #[serde(default = "default_templates_dir")]
+    pub templates_dir: PathBuf,
+
+    /// How identifiers of new notes are generated
+    /// 新しいノートの識別子の生成方法
+    pub id_scheme: IdScheme,
+}
+
+/// Identifier scheme of new notes
+/// 新しいノートの識別子の形式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum IdScheme {
+    /// Random UUID without hyphens, such as `0f8e4c2a9b7d4e1f8a6c3b5d2e9f7a1c`
+    /// ハイフンなしのランダムなUUID
+    #[default]
+    Uuid,
+
+    /// Local creation time in the Zettelkasten style, such as `20240516093000`;
+    /// unique as long as at most one note is created per second
+    /// Zettelkasten形式のローカル作成日時（1秒に1つまでのノート作成なら一意）
+    Timestamp,
+}
+
+impl IdScheme {
+    /// Generate an identifier for a new note
+    /// 新しいノートの識別子を生成
+    #[must_use]
+    pub fn generate(self) -> String {
+        match self {
+            Self::Uuid => uuid::Uuid::new_v4().simple().to_string(),
+            Self::Timestamp => chrono::Local::now().format("%Y%m%d%H%M%S").to_string(),
+        }
+    }
+}
+
+/// Backup configuration
+/// バックアップ設定
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+#[schemars(deny_unknown_fields)]
+pub struct BackupConfig {
+    /// Enable automatic backups
+    /// 自動バックアップを有効にする
+    pub enabled: bool,
+
+    /// Backup directory path
+    /// バックアップディレクトリパス
+    pub path: PathBuf,
+
+    /// Number of backups to retain
+    /// 保持するバックアップ数
+    #[schemars(range(min = 1))]
+    pub retain_count: u32,
+}
+
+/// Search engine configuration
+/// 検索エンジン設定
+#[cfg(feature = "search")]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+#[schemars(deny_unknown_fields)]
+pub struct SearchConfig {
+    /// Index directory path
+    /// インデックスディレクトリパス
+    pub index_path: PathBuf,
+
+    /// Maximum search results to return
+    /// 返す最大検索結果数
+    #[schemars(range(min = 1))]
+    pub max_results: usize,
+
+    /// Enable fuzzy search
+    /// ファジー検索を有効にする
+    pub fuzzy_search: bool,
+
+    /// Search timeout in milliseconds
+    /// 検索タイムアウト（ミリ秒）
+    #[schemars(range(min = 1))]
+    pub timeout_ms: u64,
+
+    /// Language the notes are written in, which decides how text is split
+    /// into words
+    /// ノートの記述言語（テキストを単語に分割する方法を決める）
+    pub language: Language,
+}
+
+/// Language of the notes
+/// ノートの言語
+#[cfg(feature = "search")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum Language {
+    /// English, split on whitespace and stemmed
+    /// 英語（空白で分割し語幹化）
+    #[default]
+    #[serde(rename = "en")]
+    English,
+
+    /// Japanese, split into character n-grams
+    /// 日本語（文字n-gramに分割）
+    #[serde(rename = "ja")]
+    Japanese,
+}
+
+/// CLI-specific configuration
+/// CLI固有設定
+#[cfg(feature = "cli")]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+#[schemars(deny_unknown_fields)]
+pub struct CliConfig {
+    /// Default editor command
+    /// デフォルトエディタコマンド
+    #[schemars(length(min = 1))]
+    pub editor: String,
+
+    /// Enable colored output
+    /// カラー出力を有効にする
+    pub colored_output: bool,
+
+    /// Maximum items to display in lists
+    /// リストで表示する最大項目数
+    #[schemars(range(min = 1))]
+    pub max_list_items: usize,
+}
+
+/// TUI-specific configuration
+/// TUI固有設定
+#[cfg(feature = "tui")]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+#[schemars(deny_unknown_fields)]
+pub struct TuiConfig {
+    /// Theme name
+    /// テーマ名
+    pub theme: String,
+
+    /// Frame rate (FPS) for TUI updates
+    /// TUI更新用フレームレート（FPS）
+    #[schemars(range(min = 1, max = 120))]
+    pub frame_rate: u32,
+
+    /// Enable mouse support
+    /// マウスサポートを有効にする
+    pub mouse_support: bool,
+
+    /// Key bindings configuration
+    /// キーバインド設定
+    pub keybindings: KeyBindings,
+
+    /// Number of link hops shown around a note in the graph view
+    /// グラフビューでノートの周囲に表示するリンクのホップ数
+    #[serde(default = "default_graph_depth")]
+    #[schemars(range(min = 1, max = 5))]
+    pub graph_depth: usize,
+
+    /// Named search queries offered by the command palette
+    /// コマンドパレットで提供される名前付きの検索クエリ
+    ///
+    /// ```toml
+    /// [tui.saved_searches]
+    /// inbox = "tag:inbox -status:done"
+    /// ```
+    #[serde(default)]
+    pub saved_searches: BTreeMap<String, String>,
+}
+
+/// Key binding configuration for TUI
+/// TUI用キーバインド設定
+///
+/// Bindings start from a preset; every other key of the table maps an action
+/// name to a chord or a list of chords that replace the preset's chords for
+/// that action. An empty list unbinds the action.
+/// キーバインドはプリセットから始まります。テーブルのその他のキーはアクション名を
+/// キーコードまたはその一覧に対応付け、そのアクションのプリセットのキーを置き換えます。
+/// 空の一覧はアクションのバインドを解除します。
+///
+/// ```toml
+/// [tui.keybindings]
+/// preset = "vim"
+/// leader = "space"
+/// quit = ["q", "ctrl+c"]
+/// search = "<leader>f"
+/// top = "g g"
+/// ```
+#[cfg(feature = "tui")]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct KeyBindings {
+    /// Preset the bindings start from
+    /// キーバインドの基になるプリセット
+    #[serde(default)]
+    pub preset: KeymapPreset,
+
+    /// Key that `<leader>` stands for in chords
+    /// キーコード内の`<leader>`が表すキー
+    #[serde(default = "default_leader")]
+    pub leader: String,
+
+    /// Chords per action name overriding the preset
+    /// プリセットを上書きするアクション名ごとのキーコード
+    #[serde(flatten)]
+    pub bindings: BTreeMap<String, KeyChords>,
+}
+
+/// Built-in set of key bindings
+/// 組み込みのキーバインドセット
+#[cfg(feature = "tui")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum KeymapPreset {
+    /// Single-key bindings with arrow key navigation
+    /// 矢印キー操作を含む単一キーのバインド
+    #[default]
+    Default,
+
+    /// Vim-style bindings (`g g`, `ctrl+d`, `ctrl+w w`, ...)
+    /// Vim風のバインド（`g g`、`ctrl+d`、`ctrl+w w`など）
+    Vim,
+
+    /// Emacs-style bindings (`ctrl+n`, `ctrl+x ctrl+c`, ...)
+    /// Emacs風のバインド（`ctrl+n`、`ctrl+x ctrl+c`など）
+    Emacs,
+}
+
+/// One chord or a list of chords bound to an action
+/// アクションにバインドされた1つまたは複数のキーコード
+#[cfg(feature = "tui")]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum KeyChords {
+    /// A single chord such as `"ctrl+k"`
+    /// `"ctrl+k"`のような単一のキーコード
+    One(String),
+
+    /// Several alternative chords
+    /// 複数の代替キーコード
+    Many(Vec<String>),
+}
+
+#[cfg(feature = "tui")]
+impl KeyChords {
+    /// The chords as a slice, treating an empty string as no chord
+    /// キーコードのスライス（空文字列はキーコードなしとして扱う）
+    #[must_use]
+    pub fn as_slice(&self) -> &[String] {
+        match self {
+            Self::One(chord) if chord.trim().is_empty() => &[],
+            Self::One(chord) => std::slice::from_ref(chord),
+            Self::Many(chords) => chords,
+        }
+    }
+}
+
+/// Logging configuration
+/// ログ設定
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+#[schemars(deny_unknown_fields)]
+pub struct LoggingConfig {
+    /// Log level (error, warn, info, debug, trace)
+    /// ログレベル（error, warn, info, debug, trace）
+    #[schemars(extend("enum" = LOG_LEVELS))]
+    pub level: String,
+
+    /// Log file path (None = standard error only, or a file in the data
+    /// directory while the TUI is running)
+    /// ログファイルパス（None = 標準エラー出力のみ。TUI実行中はデータディレクトリ内の
+    /// ファイル）
+    pub file_path: Option<PathBuf>,
+
+    /// Enable timestamp in logs
+    /// ログにタイムスタンプを有効にする
+    pub timestamp: bool,
+
+    /// Enable colored logs (for terminal output)
+    /// カラーログを有効にする（ターミナル出力用）
+    pub colored: bool,
+
+    /// Format of each log line
+    /// 各ログ行の形式
+    pub format: LogFormat,
+
+    /// Size in bytes at which the log file is rotated (0 = never)
+    /// ログファイルをローテーションするサイズ（バイト単位、0 = しない）
+    pub max_file_size: u64,
+
+    /// Number of rotated log files kept next to the log file
+    /// ログファイルの隣に保持するローテーション済みファイルの数
+    pub max_files: u32,
+}
+
+/// Format of log lines
+/// ログ行の形式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// `2024-05-16T09:30:00.000+09:00 INFO zynapse::cli: message`
+    #[default]
+    Text,
+
+    /// One JSON object per line with `timestamp`, `level`, `target` and
+    /// `message` fields
+    /// `timestamp`・`level`・`target`・`message`フィールドを持つ1行1つのJSONオブジェクト
+    Json,
+}
+
+/// Periodic notes configuration
+/// 定期ノート設定
+#[cfg(feature = "basic-storage")]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+#[schemars(deny_unknown_fields)]
+pub struct PeriodicConfig {
+    /// Daily note settings
+    /// 日次ノート設定
+    pub daily: PeriodSettings,
+
+    /// Weekly note settings
+    /// 週次ノート設定
+    pub weekly: PeriodSettings,
+
+    /// Monthly note settings
+    /// 月次ノート設定
+    pub monthly: PeriodSettings,
+}
+
+/// Settings for one kind of periodic note
+/// 1種類の定期ノートの設定
+#[cfg(feature = "basic-storage")]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct PeriodSettings {
+    /// Folder inside the vault where the notes are stored
+    /// ノートを保存するボルト内のフォルダ
+    pub folder: PathBuf,
+
+    /// `strftime` format used for the note title and file name
+    /// ノートタイトルとファイル名に使用する`strftime`形式
+    pub format: String,
+
+    /// Optional template file (relative paths resolve against the vault root)
+    /// 任意のテンプレートファイル（相対パスはボルトルート基準で解決）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<PathBuf>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            config_version: CONFIG_VERSION,
+            storage: StorageConfig::default(),
+            #[cfg(feature = "search")]
+            search: SearchConfig::default(),
+            #[cfg(feature = "cli")]
+            cli: CliConfig::default(),
+            #[cfg(feature = "tui")]
+            tui: TuiConfig::default(),
+            #[cfg(feature = "basic-storage")]
+            periodic: PeriodicConfig::default(),
+            logging: LoggingConfig::default(),
+            default_vault: None,
+            vaults: BTreeMap::new(),
+        }
+    }
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        Self {
+            root_path: Paths::current().data_dir.join("notes"),
+            max_file_size: 10 * 1024 * 1024, // 10MB
+            backup: BackupConfig::default(),
+            auto_save_interval: 300, // 5 minutes
+            templates_dir: default_templates_dir(),
+            id_scheme: IdScheme::default(),
+        }
+    }
+}
+
+/// Default templates directory (hidden so it is not listed as notes)
+/// デフォルトのテンプレートディレクトリ（ノートとして一覧表示されないよう隠しディレクトリ）
+fn default_templates_dir() -> PathBuf {
+    PathBuf::from(".templates")
+}
+
+impl Default for BackupConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            path: Paths::current().data_dir.join("backups"),
+            retain_count: 10,
+        }
+    }
+}
+
+#[cfg(feature = "search")]
+impl Default for SearchConfig {
+    fn default() -> Self {
+        Self {
+            index_path: Paths::current().cache_dir.join("index"),
+            max_results: 100,
+            fuzzy_search: true,
+            timeout_ms: 5000, // 5 seconds
+            language: Language::default(),
+        }
+    }
+}
+
+#[cfg(feature = "cli")]
+impl Default for CliConfig {
+    fn default() -> Self {
+        let editor = std::env::var("EDITOR")
+            .or_else(|_| std::env::var("VISUAL"))
+            .unwrap_or_else(|_| {
+                if cfg!(windows) {
+                    "notepad".to_string()
+                } else {
+                    "nano".to_string()
+                }
+            });
+
+        Self {
+            editor,
+            colored_output: true,
+            max_list_items: 50,
+        }
+    }
+}
+
+#[cfg(feature = "tui")]
+impl Default for TuiConfig {
+    fn default() -> Self {
+        Self {
+            theme: "default".to_string(),
+            frame_rate: 60,
+            mouse_support: true,
+            keybindings: KeyBindings::default(),
+            graph_depth: default_graph_depth(),
+            saved_searches: BTreeMap::new(),
+        }
+    }
+}
+
+#[cfg(feature = "tui")]
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            preset: KeymapPreset::default(),
+            leader: default_leader(),
+            bindings: BTreeMap::new(),
+        }
+    }
+}
+
+/// Default leader key
+/// デフォルトのリーダーキー
+#[cfg(feature = "tui")]
+fn default_leader() -> String {
+    "space".to_string()
+}
+
+/// Default number of hops in the graph view
+/// グラフビューのデフォルトのホップ数
+#[cfg(feature = "tui")]
+const fn default_graph_depth() -> usize {
+    2
+}
+
+#[cfg(feature = "basic-storage")]
+impl Default for PeriodicConfig {
+    fn default() -> Self {
+        Self {
+            daily: PeriodSettings::new("daily", "%Y-%m-%d"),
+            weekly: PeriodSettings::new("weekly", "%G-W%V"),
+            monthly: PeriodSettings::new("monthly", "%Y-%m"),
+        }
+    }
+}
+
+#[cfg(feature = "basic-storage")]
+impl PeriodSettings {
+    /// Create settings without a template
+    /// テンプレートなしの設定を作成
+    fn new(folder: &str, format: &str) -> Self {
+        Self {
+            folder: PathBuf::from(folder),
+            format: format.to_string(),
+            template: None,
+        }
+    }
+
+    /// Collect the problems of the settings of the named period
+    /// 指定された期間の設定の問題を収集
+    fn problems(&self, name: &str, problems: &mut Vec<Problem>) {
+        use chrono::format::{Item, StrftimeItems};
+
+        if self.format.trim().is_empty()
+            || StrftimeItems::new(&self.format).any(|item| matches!(item, Item::Error))
+        {
+            problems.push(Problem::new(
+                format!("periodic.{name}.format"),
+                format!("periodic.{name}.format must be a valid strftime format"),
+            ));
+        }
+
+        if self.folder.is_absolute() || crate::utils::validate_safe_path(&self.folder).is_err() {
+            problems.push(Problem::new(
+                format!("periodic.{name}.folder"),
+                format!("periodic.{name}.folder must be a relative path inside the vault"),
+            ));
+        }
+    }
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self {
+            level: "info".to_string(),
+            file_path: None,
+            timestamp: true,
+            colored: true,
+            format: LogFormat::default(),
+            max_file_size: 10 * 1024 * 1024, // 10MB
+            max_files: 5,
+        }
+    }
+}
+
+impl Config {
+    /// Load configuration from the standard layers
+    /// 標準の層から設定を読み込み
+    ///
+    /// Merges the built-in defaults, the system file, the user `config.toml`
+    /// (see [`paths`]), the vault's `.zynapse.toml` and `ZYNAPSE_*`
+    /// environment variables; see [`layers`] for the order. Missing files are
+    /// skipped.
+    /// 組み込みのデフォルト、システムファイル、ユーザーの`config.toml`、ボルトの
+    /// `.zynapse.toml`、`ZYNAPSE_*`環境変数をマージします。順序は[`layers`]を
+    /// 参照してください。存在しないファイルは省略されます。
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// 以下の場合にエラーを返します：
+    /// - A configuration file exists but cannot be read
+    /// - A configuration file contains invalid TOML
+    /// - The merged configuration is invalid
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use zynapse::config::Config;
+    ///
+    /// let config = Config::load()?;
+    /// println!("Notes directory: {:?}", config.storage.root_path);
+    /// # Ok::<(), zynapse::ZynapseError>(())
+    /// ```
+    pub fn load() -> Result<Self> {
+        Layered::load(&Sources::standard(None, Vec::new())?).map(|layered| layered.config)
+    }
+
+    /// Load configuration from a specific file
+    /// 特定のファイルから設定を読み込み
+    ///
+    /// # Arguments
+    /// # 引数
+    ///
+    /// * `path` - Path to the configuration file / 設定ファイルへのパス
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or parsed.
+    /// ファイルが読み取れないまたは解析できない場合にエラーを返します。
+    pub fn load_from_file(path: &Path) -> Result<Self> {
+        // Older files are upgraded in memory; the file itself is left alone
+        // 古いファイルはメモリ上で更新し、ファイル自体は変更しない
+        let (content, _) = migrate::migrate_file(path, false)?;
+
+        let config: Self = toml::from_str(&content)
+            .map_err(|e| ZynapseError::config_error(format!("Invalid TOML in config file: {e}")))?;
+
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Save configuration to the default config file
+    /// デフォルト設定ファイルに設定を保存
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the configuration cannot be serialized or written.
+    /// 設定がシリアライズまたは書き込みできない場合にエラーを返します。
+    pub fn save(&self) -> Result<()> {
+        let config_path = Self::config_file_path()?;
+        self.save_to_file(&config_path)
+    }
+
+    /// Save configuration to a specific file
+    /// 特定のファイルに設定を保存
+    ///
+    /// # Arguments
+    /// # 引数
+    ///
+    /// * `path` - Path where to save the configuration / 設定を保存するパス
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// 以下の場合にエラーを返します：
+    /// - The parent directory cannot be created
+    /// - The configuration cannot be serialized to TOML
+    /// - The file cannot be written to disk
+    pub fn save_to_file(&self, path: &Path) -> Result<()> {
+        // Create parent directory if it doesn't exist
+        // 親ディレクトリが存在しない場合は作成
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| ZynapseError::io_error(e, "Failed to create config directory"))?;
+        }
+
+        let content = toml::to_string(self)
+            .map_err(|e| ZynapseError::config_error(format!("Failed to serialize config: {e}")))?;
+
+        std::fs::write(path, content).map_err(|e| {
+            ZynapseError::io_error(
+                e,
+                format!("Failed to write config file: {}", path.display()),
+            )
+        })?;
+
+        Ok(())
+    }
+
+    /// Validate the configuration
+    /// 設定を検証
+    ///
+    /// Checks that all configuration values are valid and consistent.
+    /// すべての設定値が有効で一貫していることをチェックします。
+    ///
+    /// # Errors
+    ///
+    /// Returns an error listing every problem found by [`Config::problems`].
+    /// [`Config::problems`]が見つけたすべての問題を列挙したエラーを返します。
+    pub fn validate(&self) -> Result<()> {
+        let problems = self.problems();
+        if problems.is_empty() {
+            return Ok(());
+        }
+        let messages: Vec<String> = problems
+            .into_iter()
+            .map(|problem| problem.message)
+            .collect();
+        Err(ZynapseError::config_error(messages.join("; ")))
+    }
+
+    /// Every problem of the configuration, in the order of its sections
+    /// 設定のすべての問題（セクション順）
+    ///
+    /// A problem is reported when:
+    /// 以下の場合に問題を報告します：
+    /// - `config_version` is not [`CONFIG_VERSION`]
+    /// - `max_file_size` is zero
+    /// - `retain_count` is zero
+    /// - `templates_dir` is not a relative path inside the vault
+    /// - `max_results` is zero (when search feature is enabled)
+    /// - `timeout_ms` is zero (when search feature is enabled)
+    /// - `editor` is empty (when CLI feature is enabled)
+    /// - `max_list_items` is zero (when CLI feature is enabled)
+    /// - `frame_rate` is zero or exceeds 120 (when TUI feature is enabled)
+    /// - a key binding is invalid or conflicts with another (when TUI feature is enabled)
+    /// - a `periodic` format or folder is invalid (when basic-storage feature is enabled)
+    /// - `logging.level` is not a valid log level
+    /// - a vault name is not made of letters, digits, `-` and `_`, or a vault
+    ///   has no `storage.root_path`
+    /// - `default_vault` names no vault
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use zynapse::config::Config;
+    ///
+    /// let mut config = Config::default();
+    /// config.storage.max_file_size = 0;
+    /// config.logging.level = "loud".to_string();
+    /// let keys: Vec<String> = config.problems().into_iter().map(|p| p.key).collect();
+    /// assert_eq!(keys, ["storage.max_file_size", "logging.level"]);
+    /// ```
+    #[must_use]
+    pub fn problems(&self) -> Vec<Problem> {
+        let mut problems = Vec::new();
+        if self.config_version != CONFIG_VERSION {
+            problems.push(Problem::new(
+                "config_version",
+                format!(
+                    "config_version must be {CONFIG_VERSION}, found {}",
+                    self.config_version
+                ),
+            ));
+        }
+
+        // Validate storage configuration
+        // ストレージ設定を検証
+        if self.storage.max_file_size == 0 {
+            problems.push(Problem::new(
+                "storage.max_file_size",
+                "max_file_size must be greater than 0",
+            ));
+        }
+
+        if self.storage.backup.retain_count == 0 {
+            problems.push(Problem::new(
+                "storage.backup.retain_count",
+                "backup.retain_count must be greater than 0",
+            ));
+        }
+
+        if self.storage.templates_dir.is_absolute()
+            || crate::utils::validate_safe_path(&self.storage.templates_dir).is_err()
+        {
+            problems.push(Problem::new(
+                "storage.templates_dir",
+                "storage.templates_dir must be a relative path inside the vault",
+            ));
+        }
+
+        // Validate search configuration
+        // 検索設定を検証
+        #[cfg(feature = "search")]
+        {
+            if self.search.max_results == 0 {
+                problems.push(Problem::new(
+                    "search.max_results",
+                    "search.max_results must be greater than 0",
+                ));
+            }
+
+            if self.search.timeout_ms == 0 {
+                problems.push(Problem::new(
+                    "search.timeout_ms",
+                    "search.timeout_ms must be greater than 0",
+                ));
+            }
+        }
+
+        // Validate CLI configuration
+        // CLI設定を検証
+        #[cfg(feature = "cli")]
+        {
+            if self.cli.editor.is_empty() {
+                problems.push(Problem::new("cli.editor", "cli.editor cannot be empty"));
+            }
+
+            if self.cli.max_list_items == 0 {
+                problems.push(Problem::new(
+                    "cli.max_list_items",
+                    "cli.max_list_items must be greater than 0",
+                ));
+            }
+        }
+
+        // Validate TUI configuration
+        // TUI設定を検証
+        #[cfg(feature = "tui")]
+        {
+            if self.tui.frame_rate == 0 || self.tui.frame_rate > 120 {
+                problems.push(Problem::new(
+                    "tui.frame_rate",
+                    "tui.frame_rate must be between 1 and 120",
+                ));
+            }
+
+            if self.tui.graph_depth == 0 || self.tui.graph_depth > 5 {
+                problems.push(Problem::new(
+                    "tui.graph_depth",
+                    "tui.graph_depth must be between 1 and 5",
+                ));
+            }
+
+            for (name, query) in &self.tui.saved_searches {
+                if name.trim().is_empty() {
+                    problems.push(Problem::new(
+                        "tui.saved_searches",
+                        "tui.saved_searches names cannot be empty",
+                    ));
+                } else if let Err(e) = crate::query::Query::parse(query) {
+                    problems.push(Problem::new(
+                        format!("tui.saved_searches.{name}"),
+                        format!("tui.saved_searches.{name}: {e}"),
+                    ));
+                }
+            }
+
+            problems.extend(crate::tui::keymap::Keymap::problems(&self.tui.keybindings));
+            if let Err(e) = crate::tui::theme::Theme::named(&self.tui.theme) {
+                problems.push(Problem::new("tui.theme", format!("tui.theme: {e}")));
+            }
+        }
+
+        // Validate periodic notes configuration
+        // 定期ノート設定を検証
+        #[cfg(feature = "basic-storage")]
+        {
+            for (name, settings) in [
+                ("daily", &self.periodic.daily),
+                ("weekly", &self.periodic.weekly),
+                ("monthly", &self.periodic.monthly),
+            ] {
+                settings.problems(name, &mut problems);
+            }
+        }
+
+        // Validate logging configuration
+        // ログ設定を検証
+        if !LOG_LEVELS.contains(&self.logging.level.as_str()) {
+            problems.push(Problem::new(
+                "logging.level",
+                "logging.level must be one of: error, warn, info, debug, trace",
+            ));
+        }
+
+        // Validate vault profiles
+        // ボルトのプロファイルを検証
+        for (name, profile) in &self.vaults {
+            if !is_vault_name(name) {
+                problems.push(Problem::new(
+                    format!("vaults.{name}"),
+                    format!("vault name `{name}` may only contain letters, digits, `-` and `_`"),
+                ));
+            } else if profile.root_path().is_none() {
+                problems.push(Problem::new(
+                    format!("vaults.{name}"),
+                    format!("vaults.{name}.storage.root_path must be set"),
+                ));
+            }
+        }
+
+        if let Some(name) = &self.default_vault {
+            if !self.vaults.contains_key(name) {
+                problems.push(Problem::new(
+                    "default_vault",
+                    format!("default_vault names an unknown vault `{name}`"),
+                ));
+            }
+        }
+
+        problems
+    }
+
+    /// Get the default configuration file path
+    /// デフォルト設定ファイルパスを取得
+    fn config_file_path() -> Result<PathBuf> {
+        Paths::from_env().map(|paths| paths.config_file())
+    }
+
+    /// Directory holding user theme files
+    /// ユーザーテーマファイルを格納するディレクトリ
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the home directory cannot be determined.
+    /// ホームディレクトリを特定できない場合にエラーを返します。
+    #[cfg(feature = "tui")]
+    pub fn themes_dir() -> Result<PathBuf> {
+        Paths::from_env().map(|paths| paths.config_dir.join("themes"))
+    }
+
+    /// File remembering the most recently used command palette entries
+    /// 最近使用したコマンドパレットの項目を記憶するファイル
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the home directory cannot be determined.
+    /// ホームディレクトリを特定できない場合にエラーを返します。
+    #[cfg(feature = "tui")]
+    pub fn palette_history_path() -> Result<PathBuf> {
+        Paths::from_env().map(|paths| paths.data_dir.join("palette_history"))
+    }
+
+    /// Directory the TUI writes crash reports to
+    /// TUIがクラッシュレポートを書き込むディレクトリ
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the home directory cannot be determined.
+    /// ホームディレクトリを特定できない場合にエラーを返します。
+    #[cfg(feature = "tui")]
+    pub fn crash_reports_dir() -> Result<PathBuf> {
+        Paths::from_env().map(|paths| paths.data_dir.join("crash-reports"))
+    }
+
+    /// Create all necessary directories based on the configuration
+    /// 設定に基づいて必要なすべてのディレクトリを作成
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any directory cannot be created.
+    /// いずれかのディレクトリが作成できない場合にエラーを返します。
+    pub fn create_directories(&self) -> Result<()> {
+        // Create storage directory
+        // ストレージディレクトリを作成
+        std::fs::create_dir_all(&self.storage.root_path)
+            .map_err(|e| ZynapseError::io_error(e, "Failed to create storage directory"))?;
+
+        // Create backup directory if backups are enabled
+        // バックアップが有効な場合はバックアップディレクトリを作成
+        if self.storage.backup.enabled {
+            std::fs::create_dir_all(&self.storage.backup.path)
+                .map_err(|e| ZynapseError::io_error(e, "Failed to create backup directory"))?;
+        }
+
+        // Create search index directory
+        // 検索インデックスディレクトリを作成
+        #[cfg(feature = "search")]
+        {
+            std::fs::create_dir_all(&self.search.index_path).map_err(|e| {
+                ZynapseError::io_error(e, "Failed to create search index directory")
+            })?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Whether a vault name can be used as a key and a directory name
+/// ボルト名がキーおよびディレクトリ名として使用できるかどうか
+///
+/// Matches [`VAULT_NAME_PATTERN`].
+/// [`VAULT_NAME_PATTERN`]に一致します。
+fn is_vault_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_default_config() {
+        let config = Config::default();
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_config_serialization() {
+        let config = Config::default();
+        let toml_str = toml::to_string(&config).unwrap();
+        assert!(toml_str.contains("[storage]"));
+        assert!(toml_str.contains("[logging]"));
+    }
+
+    #[test]
+    fn test_config_deserialization() {
+        // Test with minimal configuration (no feature-specific sections)
+        // 最小設定でのテスト（機能固有セクションなし）
+        let minimal_toml = r#"
+config_version = 1
+
+[storage]
+root_path = "/tmp/zynapse/notes"
+max_file_size = 5242880
+
+[storage.backup]
+retain_count = 5
+
+[logging]
+level = "debug"
+colored = false
+"#;
+
+        // Missing sections and fields fall back to their defaults
+        // 存在しないセクションとフィールドはデフォルト値になる
+        let default_config = Config::default();
+        let config: Config = toml::from_str(minimal_toml).unwrap();
+        assert_eq!(
+            config.storage.root_path,
+            PathBuf::from("/tmp/zynapse/notes")
+        );
+        assert_eq!(config.storage.max_file_size, 5_242_880);
+        assert_eq!(
+            config.storage.auto_save_interval,
+            default_config.storage.auto_save_interval
+        );
+        assert_eq!(config.storage.backup.retain_count, 5);
+        assert_eq!(
+            config.storage.backup.path,
+            default_config.storage.backup.path
+        );
+        assert_eq!(config.logging.level, "debug");
+        assert!(!config.logging.colored);
+        assert_eq!(config.logging.timestamp, default_config.logging.timestamp);
+        #[cfg(feature = "tui")]
+        assert_eq!(config.tui.frame_rate, default_config.tui.frame_rate);
+
+        // Test that the config validates correctly
+        // 設定が正しく検証されることをテスト
+        assert!(config.validate().is_ok());
+
+        // A full config round-trips
+        // 完全な設定は往復変換できる
+        let full_toml = toml::to_string(&default_config).unwrap();
+        let config: Config = toml::from_str(&full_toml).unwrap();
+        assert_eq!(config.config_version, CONFIG_VERSION);
+        assert_eq!(config.logging.level, default_config.logging.level);
+
+        // Unversioned files predate versioning and must be migrated first
+        // バージョンのないファイルはバージョン管理以前のもので、先にマイグレーションが必要
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("config.toml");
+        std::fs::write(&config_path, "[logging]\nlevel = \"warn\"\n").unwrap();
+        let config = Config::load_from_file(&config_path).unwrap();
+        assert_eq!(config.config_version, CONFIG_VERSION);
+        assert_eq!(config.logging.level, "warn");
+
+        let config = Config {
+            config_version: CONFIG_VERSION + 1,
+            ..Config::default()
+        };
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_invalid_config_validation() {
+        let mut config = Config::default();
+
+        // Test invalid max_file_size
+        config.storage.max_file_size = 0;
+        assert!(config.validate().is_err());
+
+        // Reset and test invalid log level
+        config = Config::default();
+        config.logging.level = "invalid".to_string();
+        assert!(config.validate().is_err());
+
+        #[cfg(feature = "tui")]
+        {
+            config = Config::default();
+            config.tui.theme = "light".to_string();
+            assert!(config.validate().is_ok());
+            config.tui.theme = "no-such-theme".to_string();
+            assert!(config.validate().is_err());
+
+            config = Config::default();
+            config.tui.graph_depth = 0;
+            assert!(config.validate().is_err());
+
+            config = Config::default();
+            config
+                .tui
+                .saved_searches
+                .insert("inbox".to_string(), "tag:inbox -status:done".to_string());
+            assert!(config.validate().is_ok());
+            config
+                .tui
+                .saved_searches
+                .insert("broken".to_string(), "\"unterminated".to_string());
+            assert!(config.validate().is_err());
+        }
+    }
+
+    #[test]
+    fn test_config_file_operations() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("config.toml");
+
+        let original_config = Config::default();
+        original_config.save_to_file(&config_path).unwrap();
+
+        let loaded_config = Config::load_from_file(&config_path).unwrap();
+        assert_eq!(
+            original_config.storage.max_file_size,
+            loaded_config.storage.max_file_size
+        );
+        assert_eq!(original_config.logging.level, loaded_config.logging.level);
+    }
+
+    #[cfg(feature = "cli")]
+    #[test]
+    fn test_cli_config_defaults() {
+        let config = Config::default();
+        assert!(!config.cli.editor.is_empty());
+        assert!(config.cli.colored_output);
+        assert!(config.cli.max_list_items > 0);
+    }
+
+    #[cfg(feature = "tui")]
+    #[test]
+    fn test_keybindings_validation() {
+        let mut config = Config::default();
+        let table = r#"
+preset = "vim"
+leader = ","
+quit = ["q", "ctrl+q"]
+search = "<leader>f"
+edit = ""
+"#;
+        config.tui.keybindings = toml::from_str(table).unwrap();
+        assert_eq!(config.tui.keybindings.preset, KeymapPreset::Vim);
+        assert!(config.tui.keybindings.bindings["edit"]
+            .as_slice()
+            .is_empty());
+        assert!(config.validate().is_ok());
+
+        let round_trip = toml::to_string(&config).unwrap();
+        let reloaded: Config = toml::from_str(&round_trip).unwrap();
+        assert_eq!(reloaded.tui.keybindings, config.tui.keybindings);
+
+        // `q` is already bound to `quit`
+        // `q`は既に`quit`にバインドされている
+        config
+            .tui
+            .keybindings
+            .bindings
+            .insert("reload".to_string(), KeyChords::One("q".to_string()));
+        assert!(config.validate().is_err());
+    }
+}
@@ -0,0 +1,220 @@
+//! Locations of configuration, data and cache files
+//! 設定・データ・キャッシュファイルの場所
+//!
+//! The directories are chosen in this order:
+//! ディレクトリは次の順序で決まります。
+//!
+//! 1. `ZYNAPSE_HOME`: everything lives in that one directory, laid out like
+//!    `~/.zynapse` / すべてをそのディレクトリに`~/.zynapse`と同じ構成で置く
+//! 2. an existing `~/.zynapse`, so earlier installations keep working /
+//!    既存の`~/.zynapse`（以前のインストールがそのまま動作する）
+//! 3. the XDG base directories `XDG_CONFIG_HOME`, `XDG_DATA_HOME` and
+//!    `XDG_CACHE_HOME`, each with a `zynapse` subdirectory, falling back to the
+//!    platform's conventional locations / XDGベースディレクトリ（各`zynapse`
+//!    サブディレクトリ）、なければプラットフォームの慣習的な場所
+//!
+//! The configuration directory holds `config.toml` and `themes/`, the data
+//! directory the notes, backups, palette history and crash reports, and the
+//! cache directory the search index, which can always be rebuilt.
+//! 設定ディレクトリには`config.toml`と`themes/`、データディレクトリにはノート・
+//! バックアップ・パレット履歴・クラッシュレポート、キャッシュディレクトリには
+//! いつでも再構築できる検索インデックスを置きます。
+
+use crate::{Result, ZynapseError};
+use directories::ProjectDirs;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+/// Environment variable relocating every Zynapse directory
+/// Zynapseのすべてのディレクトリを移動する環境変数
+pub const HOME_VAR: &str = "ZYNAPSE_HOME";
+
+/// Name of the directory used before the XDG layout
+/// XDGレイアウト以前に使用していたディレクトリ名
+const LEGACY_DIR: &str = ".zynapse";
+
+/// Name of the subdirectory inside each base directory
+/// 各ベースディレクトリ内のサブディレクトリ名
+const APP_DIR: &str = "zynapse";
+
+/// Base directories of one installation
+/// 1つのインストールのベースディレクトリ
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Paths {
+    /// Directory of the user configuration
+    /// ユーザー設定のディレクトリ
+    pub config_dir: PathBuf,
+
+    /// Directory of notes and other data worth keeping
+    /// ノートなど保持すべきデータのディレクトリ
+    pub data_dir: PathBuf,
+
+    /// Directory of data that can be rebuilt
+    /// 再構築可能なデータのディレクトリ
+    pub cache_dir: PathBuf,
+}
+
+impl Paths {
+    /// Directories of the current process, from its environment
+    /// 環境変数から求めた現在のプロセスのディレクトリ
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if neither `ZYNAPSE_HOME` nor the home directory is
+    /// known.
+    /// `ZYNAPSE_HOME`もホームディレクトリも判明しない場合にエラーを返します。
+    pub fn from_env() -> Result<Self> {
+        let platform = ProjectDirs::from("", "", APP_DIR).map(|dirs| Self {
+            config_dir: dirs.config_dir().to_path_buf(),
+            data_dir: dirs.data_dir().to_path_buf(),
+            cache_dir: dirs.cache_dir().to_path_buf(),
+        });
+        Self::resolve(
+            |name| std::env::var_os(name),
+            dirs::home_dir().as_deref(),
+            platform,
+        )
+        .ok_or_else(|| {
+            ZynapseError::config_error(format!("Cannot determine home directory; set {HOME_VAR}"))
+        })
+    }
+
+    /// Directories of the current process, or `./.zynapse` if they cannot be
+    /// determined
+    /// 現在のプロセスのディレクトリ（特定できなければ`./.zynapse`）
+    #[must_use]
+    pub fn current() -> Self {
+        Self::from_env().unwrap_or_else(|_| Self::single(Path::new(".").join(LEGACY_DIR)))
+    }
+
+    /// Choose the directories from an environment lookup and a home directory
+    /// 環境変数の参照とホームディレクトリからディレクトリを選択
+    ///
+    /// `platform` supplies the conventional locations used when an XDG
+    /// variable is not set; without it they are derived from `home`.
+    /// `platform`はXDG変数が未設定の場合の慣習的な場所を与えます。指定が
+    /// なければ`home`から導きます。
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::path::{Path, PathBuf};
+    /// use zynapse::config::Paths;
+    ///
+    /// let env = |name: &str| (name == "XDG_CACHE_HOME").then(|| "/var/cache".into());
+    /// let paths = Paths::resolve(env, Some(Path::new("/nonexistent/home")), None).unwrap();
+    /// assert_eq!(paths.config_dir, PathBuf::from("/nonexistent/home/.config/zynapse"));
+    /// assert_eq!(paths.cache_dir, PathBuf::from("/var/cache/zynapse"));
+    ///
+    /// let env = |name: &str| (name == "ZYNAPSE_HOME").then(|| "/srv/zynapse".into());
+    /// let paths = Paths::resolve(env, None, None).unwrap();
+    /// assert_eq!(paths.data_dir, PathBuf::from("/srv/zynapse"));
+    /// ```
+    pub fn resolve(
+        env: impl Fn(&str) -> Option<OsString>,
+        home: Option<&Path>,
+        platform: Option<Self>,
+    ) -> Option<Self> {
+        if let Some(dir) = env(HOME_VAR).filter(|dir| !dir.is_empty()) {
+            return Some(Self::single(PathBuf::from(dir)));
+        }
+        if let Some(legacy) = home
+            .map(|home| home.join(LEGACY_DIR))
+            .filter(|dir| dir.is_dir())
+        {
+            return Some(Self::single(legacy));
+        }
+
+        // XDG only accepts absolute paths; relative ones are ignored
+        // XDGは絶対パスのみを受け付け、相対パスは無視する
+        let xdg = |name: &str, fallback: Option<PathBuf>, default: &str| {
+            env(name)
+                .map(PathBuf::from)
+                .filter(|dir| dir.is_absolute())
+                .map(|dir| dir.join(APP_DIR))
+                .or(fallback)
+                .or_else(|| home.map(|home| home.join(default).join(APP_DIR)))
+        };
+        let (config, data, cache) = match platform {
+            Some(platform) => (
+                Some(platform.config_dir),
+                Some(platform.data_dir),
+                Some(platform.cache_dir),
+            ),
+            None => (None, None, None),
+        };
+        Some(Self {
+            config_dir: xdg("XDG_CONFIG_HOME", config, ".config")?,
+            data_dir: xdg("XDG_DATA_HOME", data, ".local/share")?,
+            cache_dir: xdg("XDG_CACHE_HOME", cache, ".cache")?,
+        })
+    }
+
+    /// Every directory in one place, as in `~/.zynapse`
+    /// `~/.zynapse`のようにすべてのディレクトリを1か所に置く
+    #[must_use]
+    pub fn single(dir: PathBuf) -> Self {
+        Self {
+            config_dir: dir.clone(),
+            data_dir: dir.clone(),
+            cache_dir: dir,
+        }
+    }
+
+    /// User configuration file
+    /// ユーザー設定ファイル
+    #[must_use]
+    pub fn config_file(&self) -> PathBuf {
+        self.config_dir.join("config.toml")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_resolve_layouts() {
+        let home = TempDir::new().unwrap();
+        let no_env = |_: &str| None;
+        let platform = Paths {
+            config_dir: PathBuf::from("/platform/config"),
+            data_dir: PathBuf::from("/platform/data"),
+            cache_dir: PathBuf::from("/platform/cache"),
+        };
+
+        let paths = Paths::resolve(no_env, Some(home.path()), None).unwrap();
+        assert_eq!(
+            paths.config_file(),
+            home.path().join(".config/zynapse/config.toml")
+        );
+        assert_eq!(paths.data_dir, home.path().join(".local/share/zynapse"));
+        assert_eq!(paths.cache_dir, home.path().join(".cache/zynapse"));
+        assert_eq!(
+            Paths::resolve(no_env, Some(home.path()), Some(platform.clone())),
+            Some(platform.clone())
+        );
+
+        let env = |name: &str| match name {
+            "XDG_CONFIG_HOME" => Some("/xdg/config".into()),
+            "XDG_DATA_HOME" => Some("relative/ignored".into()),
+            _ => None,
+        };
+        let paths = Paths::resolve(env, Some(home.path()), Some(platform.clone())).unwrap();
+        assert_eq!(paths.config_dir, PathBuf::from("/xdg/config/zynapse"));
+        assert_eq!(paths.data_dir, platform.data_dir);
+
+        // An existing ~/.zynapse keeps the old layout, ZYNAPSE_HOME beats both
+        // 既存の~/.zynapseは従来の構成を保ち、ZYNAPSE_HOMEはどちらにも優先する
+        std::fs::create_dir(home.path().join(".zynapse")).unwrap();
+        let paths = Paths::resolve(env, Some(home.path()), None).unwrap();
+        assert_eq!(paths, Paths::single(home.path().join(".zynapse")));
+
+        let env = |name: &str| (name == HOME_VAR).then(|| "/sandbox".into());
+        let paths = Paths::resolve(env, Some(home.path()), None).unwrap();
+        assert_eq!(paths, Paths::single(PathBuf::from("/sandbox")));
+
+        assert_eq!(Paths::resolve(no_env, None, None), None);
+    }
+}
@@ -0,0 +1,200 @@
+//! JSON Schema of the configuration file
+//! 設定ファイルのJSON Schema
+//!
+//! The schema is derived from [`Config`] and its sections, so it lists exactly
+//! the settings of the features this build was compiled with. Ranges and
+//! enumerations that [`Config::problems`] checks are declared next to the
+//! fields, and the tests below hold the two together. Editors with
+//! schema-aware TOML support can point `config.toml` at the output of
+//! `zynapse config schema`:
+//! スキーマは[`Config`]とそのセクションから導出されるため、このビルドでコンパイル
+//! された機能の設定だけを正確に列挙します。[`Config::problems`]が検査する範囲や
+//! 列挙値はフィールドの隣で宣言され、下のテストが両者を一致させます。スキーマに
+//! 対応したTOMLエディタでは、`config.toml`を`zynapse config schema`の出力に
+//! 関連付けられます：
+//!
+//! ```toml
+//! #:schema ./config.schema.json
+//! config_version = 1
+//! ```
+
+use super::Config;
+use schemars::generate::SchemaSettings;
+use serde_json::Value;
+
+/// Title of the root schema
+/// ルートスキーマのタイトル
+const TITLE: &str = "Zynapse configuration";
+
+/// JSON Schema (draft 7) describing `config.toml`
+/// `config.toml`を記述するJSON Schema（draft 7）
+///
+/// Defaults that depend on the environment, such as `storage.root_path`, are
+/// those of the current environment.
+/// `storage.root_path`のように環境に依存するデフォルト値は、現在の環境のものです。
+///
+/// # Examples
+///
+/// ```rust
+/// use zynapse::config::schema;
+///
+/// let schema = schema::generate();
+/// assert_eq!(schema["title"], "Zynapse configuration");
+/// assert!(schema["properties"]["storage"].is_object());
+/// ```
+#[must_use]
+pub fn generate() -> Value {
+    let mut schema = SchemaSettings::draft07()
+        .into_generator()
+        .into_root_schema_for::<Config>();
+    schema.insert("title".to_string(), Value::from(TITLE));
+    schema.to_value()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::layers::{is_open, known_keys, merge_into, remove, single};
+    use toml::Table;
+
+    /// Subschema of a dotted key, following references and open tables
+    /// ドット区切りのキーのサブスキーマ（参照と開いたテーブルをたどる）
+    fn property<'a>(root: &'a Value, key: &str) -> Option<&'a Value> {
+        let mut schema = resolve(root, root);
+        for part in key.split('.') {
+            let properties = &schema["properties"];
+            let next = properties
+                .get(part)
+                .or_else(|| schema.get("additionalProperties").filter(|v| v.is_object()))?;
+            schema = resolve(root, next);
+        }
+        Some(schema)
+    }
+
+    /// A schema with its `$ref` resolved against the root definitions
+    /// ルートの定義に対して`$ref`を解決したスキーマ
+    ///
+    /// Draft 7 ignores keywords next to `$ref`, so a reference with a default
+    /// is wrapped in a single-item `allOf`.
+    /// draft 7では`$ref`の隣のキーワードは無視されるため、デフォルト値を伴う参照は
+    /// 要素が1つの`allOf`に包まれます。
+    fn resolve<'a>(root: &'a Value, schema: &'a Value) -> &'a Value {
+        if let Some(reference) = schema["$ref"].as_str() {
+            return root
+                .pointer(reference.trim_start_matches('#'))
+                .expect("reference points into the schema");
+        }
+        match schema["allOf"].as_array().map(Vec::as_slice) {
+            Some([only]) => resolve(root, only),
+            _ => schema,
+        }
+    }
+
+    /// Every dotted key of a closed object in the schema
+    /// スキーマ内の閉じたオブジェクトのすべてのドット区切りのキー
+    fn properties(root: &Value, schema: &Value, prefix: &str, keys: &mut Vec<String>) {
+        let schema = resolve(root, schema);
+        let Some(properties) = schema["properties"].as_object() else {
+            return;
+        };
+        for (name, subschema) in properties {
+            let key = format!("{prefix}{name}");
+            properties_of(root, subschema, &key, keys);
+        }
+    }
+
+    /// Record `key` and descend into its properties
+    /// `key`を記録し、そのプロパティに降りる
+    fn properties_of(root: &Value, schema: &Value, key: &str, keys: &mut Vec<String>) {
+        keys.push(key.to_string());
+        properties(root, schema, &format!("{key}."), keys);
+    }
+
+    /// Default configuration as a TOML table
+    /// TOMLテーブルとしてのデフォルト設定
+    fn defaults() -> Table {
+        match toml::Value::try_from(Config::default()).unwrap() {
+            toml::Value::Table(table) => table,
+            _ => unreachable!("the configuration is a table"),
+        }
+    }
+
+    /// Whether the default configuration with `key` set to `value` loads
+    /// without a problem for that key
+    /// `key`を`value`にしたデフォルト設定が、そのキーの問題なく読み込めるかどうか
+    fn accepts(key: &str, value: toml::Value) -> bool {
+        let mut table = defaults();
+        remove(&mut table, key);
+        merge_into(&mut table, single(key, value));
+        toml::Value::Table(table)
+            .try_into::<Config>()
+            .is_ok_and(|config| config.problems().iter().all(|p| p.key != key))
+    }
+
+    #[test]
+    fn test_schema_matches_settings() {
+        let schema = generate();
+        let known = known_keys(&defaults());
+        for key in known.keys() {
+            assert!(property(&schema, key).is_some(), "{key} is missing");
+        }
+
+        let mut described = Vec::new();
+        properties(&schema, &schema, "", &mut described);
+        for key in described {
+            // Tables of user-chosen keys may be empty by default
+            // ユーザーがキーを決めるテーブルはデフォルトで空の場合がある
+            let prefix = format!("{key}.");
+            let open = key == "vaults" || is_open(&format!("{prefix}name"));
+            assert!(
+                open || known.keys().any(|k| *k == key || k.starts_with(&prefix)),
+                "{key} is not a setting"
+            );
+        }
+    }
+
+    #[test]
+    fn test_constraints_match_validation() {
+        let schema = generate();
+        let mut keys = Vec::new();
+        properties(&schema, &schema, "", &mut keys);
+        let mut checked = 0;
+        for key in keys {
+            let leaf = property(&schema, &key).unwrap();
+            if let Some(constant) = leaf["const"].as_i64() {
+                assert!(accepts(&key, constant.into()), "{key} = {constant}");
+                assert!(!accepts(&key, (constant + 1).into()));
+                checked += 1;
+                continue;
+            }
+            if let Some(min) = leaf["minimum"].as_i64() {
+                assert!(accepts(&key, min.into()), "{key} = {min}");
+                assert!(!accepts(&key, (min - 1).into()), "{key} = {}", min - 1);
+                checked += 1;
+            }
+            if let Some(max) = leaf["maximum"].as_i64() {
+                assert!(accepts(&key, max.into()), "{key} = {max}");
+                assert!(!accepts(&key, (max + 1).into()), "{key} = {}", max + 1);
+                checked += 1;
+            }
+            if leaf["minLength"].as_u64() == Some(1) {
+                assert!(!accepts(&key, "".into()), "{key} = \"\"");
+                checked += 1;
+            }
+            if let Some(values) = leaf["enum"].as_array() {
+                for value in values {
+                    let value = value.as_str().unwrap();
+                    assert!(accepts(&key, value.into()), "{key} = {value}");
+                }
+                assert!(!accepts(&key, "unknown".into()), "{key} = unknown");
+                checked += 1;
+            }
+        }
+        // Keep the test honest if the attributes disappear
+        // 属性が消えた場合にもテストが形骸化しないようにする
+        assert!(checked >= 6, "only {checked} constraints found");
+
+        let vaults = &property(&schema, "vaults").unwrap()["propertyNames"];
+        assert_eq!(vaults["pattern"], crate::config::VAULT_NAME_PATTERN);
+    }
+}
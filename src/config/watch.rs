@@ -0,0 +1,353 @@
+//! Reloading the configuration while running
+//! 実行中の設定の再読み込み
+//!
+//! A [`Watcher`] polls the modification times of the configuration files and
+//! loads the layers again when one of them changes. [`Reload::apply`] then takes
+//! over the settings listed in [`LIVE_KEYS`], which can change under a running
+//! process, and lists every other change as needing a restart. A configuration
+//! that fails to load or validate leaves the running one untouched.
+//! [`Watcher`]は設定ファイルの更新日時をポーリングし、いずれかが変更されると層を
+//! 再度読み込みます。[`Reload::apply`]は実行中のプロセスで変更できる[`LIVE_KEYS`]の
+//! 設定を引き継ぎ、その他の変更は再起動が必要なものとして列挙します。読み込みや
+//! 検証に失敗した設定は、実行中の設定に影響しません。
+
+use super::layers::{flatten, get, merge_into, remove, single, VAULT_CONFIG_FILE};
+use super::{Config, Layered, Sources};
+use crate::{Result, ZynapseError};
+use std::collections::BTreeSet;
+use std::fmt;
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime};
+use toml::Value;
+
+/// Settings that take effect without a restart
+/// 再起動なしで反映される設定
+pub const LIVE_KEYS: &[&str] = &[
+    "tui.theme",
+    "tui.keybindings",
+    "logging.level",
+    "search.max_results",
+    "search.timeout_ms",
+];
+
+/// Minimum time between two looks at the files
+/// ファイルを確認する最小間隔
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Watches the files a configuration was merged from
+/// 設定のマージ元となったファイルを監視
+#[derive(Debug)]
+pub struct Watcher {
+    /// Layers to load again, never rewritten by a reload
+    /// 再読み込みする層（再読み込みで書き換えることはない）
+    sources: Sources,
+
+    /// Watched files with their last seen modification time, `None` while
+    /// missing
+    /// 監視するファイルと最後に確認した更新日時（存在しない間は`None`）
+    files: Vec<(PathBuf, Option<SystemTime>)>,
+
+    /// When the files were last looked at
+    /// ファイルを最後に確認した時刻
+    polled: Instant,
+}
+
+impl Watcher {
+    /// Watch the system, user and vault files of `sources`
+    /// `sources`のシステム・ユーザー・ボルトファイルを監視
+    ///
+    /// `config` is the running configuration, whose vault root locates the
+    /// vault file. Files that do not exist yet are watched for their creation.
+    /// `config`は実行中の設定で、そのボルトルートからボルトファイルを求めます。
+    /// まだ存在しないファイルは作成を監視します。
+    #[must_use]
+    pub fn new(sources: Sources, config: &Config) -> Self {
+        let vault = sources
+            .vault
+            .then(|| config.storage.root_path.join(VAULT_CONFIG_FILE));
+        let files = sources
+            .system
+            .iter()
+            .chain(&sources.user)
+            .cloned()
+            .chain(vault)
+            .map(|path| {
+                let modified = modified(&path);
+                (path, modified)
+            })
+            .collect();
+        Self {
+            sources: Sources {
+                migrate: false,
+                ..sources
+            },
+            files,
+            polled: Instant::now(),
+        }
+    }
+
+    /// Load the configuration again if a file changed, at most once a second
+    /// ファイルが変更されていれば設定を再度読み込む（最大で毎秒1回）
+    ///
+    /// Returns `None` while nothing changed, and otherwise the result of
+    /// loading every layer.
+    /// 何も変更されていなければ`None`を、それ以外はすべての層を読み込んだ結果を
+    /// 返します。
+    pub fn poll(&mut self) -> Option<Result<Config>> {
+        if self.polled.elapsed() < POLL_INTERVAL {
+            return None;
+        }
+        self.check()
+    }
+
+    /// Load the configuration again if a file changed since the last check
+    /// 前回の確認以降にファイルが変更されていれば設定を再度読み込む
+    pub fn check(&mut self) -> Option<Result<Config>> {
+        self.polled = Instant::now();
+        let mut changed = false;
+        for (path, seen) in &mut self.files {
+            let current = modified(path);
+            if current != *seen {
+                *seen = current;
+                changed = true;
+            }
+        }
+        changed.then(|| Layered::load(&self.sources).map(|layered| layered.config))
+    }
+}
+
+/// Modification time of a file, `None` if it cannot be read
+/// ファイルの更新日時（読み取れなければ`None`）
+fn modified(path: &std::path::Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
+}
+
+/// Changes taken over from a reloaded configuration
+/// 再読み込みした設定から引き継いだ変更
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Reload {
+    /// Live settings that changed, from [`LIVE_KEYS`]
+    /// 変更された即時反映の設定（[`LIVE_KEYS`]のいずれか）
+    pub applied: Vec<String>,
+
+    /// Other settings that changed and keep their running value until restart
+    /// 変更されたが再起動まで実行中の値を保つその他の設定
+    pub rejected: Vec<String>,
+}
+
+impl Reload {
+    /// Take the live settings of `loaded` over into `running`
+    /// `loaded`の即時反映の設定を`running`に引き継ぐ
+    ///
+    /// A new `logging.level` also becomes the maximum level of the logger.
+    /// 新しい`logging.level`はロガーの最大レベルにもなります。
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use zynapse::config::{Config, Reload};
+    /// use std::path::PathBuf;
+    ///
+    /// let mut running = Config::default();
+    /// let mut loaded = Config::default();
+    /// loaded.logging.level = "warn".to_string();
+    /// loaded.storage.root_path = PathBuf::from("/elsewhere");
+    ///
+    /// let reload = Reload::apply(&mut running, &loaded)?;
+    /// assert_eq!(reload.applied, ["logging.level"]);
+    /// assert_eq!(reload.rejected, ["storage.root_path"]);
+    /// assert_eq!(running.logging.level, "warn");
+    /// assert_ne!(running.storage.root_path, loaded.storage.root_path);
+    /// # Ok::<(), zynapse::ZynapseError>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if either configuration cannot be converted to or from
+    /// TOML; `running` is then left unchanged.
+    /// いずれかの設定をTOMLとの間で変換できない場合にエラーを返します。その場合
+    /// `running`は変更されません。
+    pub fn apply(running: &mut Config, loaded: &Config) -> Result<Self> {
+        let mut current = table(running)?;
+        let next = table(loaded)?;
+
+        let mut before = Vec::new();
+        let mut after = Vec::new();
+        flatten(&current, "", &mut before);
+        flatten(&next, "", &mut after);
+        let mut changed = BTreeSet::new();
+        for (key, value) in &before {
+            if !after
+                .iter()
+                .any(|(other, next)| other == key && next == value)
+            {
+                changed.insert(key.clone());
+            }
+        }
+        for (key, _) in &after {
+            if !before.iter().any(|(other, _)| other == key) {
+                changed.insert(key.clone());
+            }
+        }
+
+        let mut applied = BTreeSet::new();
+        let mut rejected = Vec::new();
+        for key in changed {
+            match LIVE_KEYS.iter().find(|live| {
+                key == **live
+                    || key
+                        .strip_prefix(**live)
+                        .is_some_and(|rest| rest.starts_with('.'))
+            }) {
+                Some(live) => {
+                    applied.insert(*live);
+                }
+                None => rejected.push(key),
+            }
+        }
+
+        for key in &applied {
+            remove(&mut current, key);
+            if let Some(value) = get(&next, key) {
+                merge_into(&mut current, single(key, value.clone()));
+            }
+        }
+        *running = Value::Table(current).try_into().map_err(|e| {
+            ZynapseError::config_error(format!("Failed to apply reloaded config: {e}"))
+        })?;
+        if applied.contains("logging.level") {
+            if let Ok(level) = running.logging.level.parse() {
+                log::set_max_level(level);
+            }
+        }
+
+        Ok(Self {
+            applied: applied.into_iter().map(str::to_string).collect(),
+            rejected,
+        })
+    }
+
+    /// Whether nothing changed
+    /// 何も変更されなかったかどうか
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.applied.is_empty() && self.rejected.is_empty()
+    }
+}
+
+impl fmt::Display for Reload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.applied.is_empty() {
+            write!(f, "Reloaded {}", self.applied.join(", "))?;
+        }
+        if !self.rejected.is_empty() {
+            if !self.applied.is_empty() {
+                f.write_str("; ")?;
+            }
+            write!(f, "restart to apply {}", self.rejected.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// A configuration as a TOML table
+/// TOMLテーブルとしての設定
+fn table(config: &Config) -> Result<toml::Table> {
+    match Value::try_from(config) {
+        Ok(Value::Table(table)) => Ok(table),
+        Ok(_) => Err(ZynapseError::internal("Configuration is not a table")),
+        Err(e) => Err(ZynapseError::config_error(format!(
+            "Failed to serialize config: {e}"
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_watcher_reloads_changed_files() {
+        let dir = TempDir::new().unwrap();
+        let user = dir.path().join("config.toml");
+        let sources = Sources {
+            user: Some(user.clone()),
+            ..Sources::default()
+        };
+        let mut watcher = Watcher::new(sources, &Config::default());
+        assert!(watcher.check().is_none(), "nothing changed yet");
+
+        std::fs::write(&user, "[logging]\nlevel = \"debug\"\n").unwrap();
+        let config = watcher.check().unwrap().unwrap();
+        assert_eq!(config.logging.level, "debug");
+        assert!(watcher.check().is_none());
+
+        // An invalid file is reported, and reported only once
+        // 無効なファイルは報告され、報告は一度だけ
+        std::fs::write(&user, "[logging]\nlevel = \"loud\"\n").unwrap();
+        File::options()
+            .write(true)
+            .open(&user)
+            .unwrap()
+            .set_modified(SystemTime::now() + Duration::from_secs(5))
+            .unwrap();
+        assert!(watcher.check().unwrap().is_err());
+        assert!(watcher.check().is_none());
+        assert!(watcher.poll().is_none(), "polled too recently");
+    }
+
+    #[test]
+    fn test_reload_applies_live_settings_only() {
+        let mut running = Config::default();
+        let mut loaded = Config::default();
+        loaded.logging.colored = !running.logging.colored;
+        #[cfg(feature = "search")]
+        {
+            loaded.search.max_results = 7;
+        }
+        #[cfg(feature = "tui")]
+        {
+            loaded.tui.theme = "light".to_string();
+            loaded.tui.keybindings.bindings.insert(
+                "quit".to_string(),
+                crate::config::KeyChords::One("ctrl+q".to_string()),
+            );
+        }
+
+        let reload = Reload::apply(&mut running, &loaded).unwrap();
+        assert_eq!(reload.rejected, ["logging.colored"]);
+        assert_ne!(running.logging.colored, loaded.logging.colored);
+        #[cfg(feature = "search")]
+        assert_eq!(running.search.max_results, 7);
+        #[cfg(feature = "tui")]
+        {
+            let applied = [
+                #[cfg(feature = "search")]
+                "search.max_results",
+                "tui.keybindings",
+                "tui.theme",
+            ];
+            assert_eq!(reload.applied, applied);
+            assert_eq!(running.tui.keybindings, loaded.tui.keybindings);
+            assert!(reload
+                .to_string()
+                .starts_with(&format!("Reloaded {}", applied[0])));
+            assert!(reload
+                .to_string()
+                .ends_with("; restart to apply logging.colored"));
+
+            // A binding removed from the file is removed from the running keymap
+            // ファイルから削除したキーバインドは実行中のキーマップからも削除される
+            let reload = Reload::apply(&mut running, &Config::default()).unwrap();
+            assert!(reload.applied.contains(&"tui.keybindings".to_string()));
+            assert!(running.tui.keybindings.bindings.is_empty());
+        }
+
+        let reload = Reload::apply(&mut running.clone(), &running).unwrap();
+        assert!(reload.is_empty());
+    }
+}
@@ -0,0 +1,677 @@
+//! Environment and vault health check
+//! 環境とボルトの健全性チェック
+//!
+//! [`run`] looks at everything Zynapse depends on: the feature flags it was
+//! built with, its directories, the configuration, the notes of the vault with
+//! their identifiers and links, the freshness of the search index and the
+//! terminal. Each [`Check`] passes, warns or fails, and the ones that do not
+//! pass suggest a fix. `zynapse doctor` prints the [`Report`]; [`environment`]
+//! holds the checks [`crate::initialize`] runs on every start.
+//! [`run`]はZynapseが依存するすべてを確認します：ビルド時の機能フラグ、
+//! ディレクトリ、設定、ボルトのノートとその識別子・リンク、検索インデックスの
+//! 鮮度、ターミナル。各[`Check`]は合格・警告・失敗のいずれかで、合格しないものは
+//! 修正方法を提案します。`zynapse doctor`は[`Report`]を表示し、[`environment`]は
+//! [`crate::initialize`]が起動のたびに実行するチェックを保持します。
+
+use crate::config::{diagnostics, Layered, Paths, Severity, Sources};
+use crate::utils::plural;
+use std::fmt;
+use std::path::Path;
+
+/// Number of offending items named in a message before the rest is counted
+/// メッセージで名前を挙げる問題項目の数（残りは件数で示す）
+const SAMPLE: usize = 3;
+
+/// Outcome of a check
+/// チェックの結果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Status {
+    /// Nothing to do
+    /// 対処不要
+    Pass,
+
+    /// Works, but something deserves attention
+    /// 動作するが注意が必要
+    Warn,
+
+    /// Something does not work
+    /// 動作しない
+    Fail,
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Pass => "PASS",
+            Self::Warn => "WARN",
+            Self::Fail => "FAIL",
+        })
+    }
+}
+
+/// Result of one check
+/// 1つのチェックの結果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Check {
+    /// Area that was checked, such as `links`
+    /// チェックした領域（`links`など）
+    pub area: &'static str,
+
+    /// Outcome
+    /// 結果
+    pub status: Status,
+
+    /// What was found
+    /// 見つかった内容
+    pub message: String,
+
+    /// Suggested fix, for checks that did not pass
+    /// 合格しなかったチェックに対する修正方法の提案
+    pub fix: Option<String>,
+}
+
+impl Check {
+    /// A passing check
+    /// 合格したチェック
+    #[must_use]
+    pub fn pass(area: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            area,
+            status: Status::Pass,
+            message: message.into(),
+            fix: None,
+        }
+    }
+
+    /// A check with a warning and its fix
+    /// 警告とその修正方法を伴うチェック
+    #[must_use]
+    pub fn warn(area: &'static str, message: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            status: Status::Warn,
+            fix: Some(fix.into()),
+            ..Self::pass(area, message)
+        }
+    }
+
+    /// A failed check and its fix
+    /// 失敗したチェックとその修正方法
+    #[must_use]
+    pub fn fail(area: &'static str, message: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            status: Status::Fail,
+            fix: Some(fix.into()),
+            ..Self::pass(area, message)
+        }
+    }
+}
+
+/// Every check of a doctor run
+/// doctor実行のすべてのチェック
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Report {
+    /// Checks in the order they ran
+    /// 実行順のチェック
+    pub checks: Vec<Check>,
+}
+
+impl Report {
+    /// Number of checks with the given outcome
+    /// 指定した結果のチェック数
+    #[must_use]
+    pub fn count(&self, status: Status) -> usize {
+        self.checks
+            .iter()
+            .filter(|check| check.status == status)
+            .count()
+    }
+
+    /// Whether any check failed
+    /// いずれかのチェックが失敗したかどうか
+    #[must_use]
+    pub fn has_failures(&self) -> bool {
+        self.count(Status::Fail) > 0
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = self
+            .checks
+            .iter()
+            .map(|check| check.area.len())
+            .max()
+            .unwrap_or(0);
+        for check in &self.checks {
+            writeln!(
+                f,
+                "[{}] {:width$}  {}",
+                check.status, check.area, check.message
+            )?;
+            if let Some(fix) = &check.fix {
+                writeln!(f, "       {:width$}  fix: {fix}", "")?;
+            }
+        }
+        writeln!(
+            f,
+            "\n{} passed, {} warnings, {} failed",
+            self.count(Status::Pass),
+            self.count(Status::Warn),
+            self.count(Status::Fail)
+        )
+    }
+}
+
+/// Run every check against the configuration of `sources`
+/// `sources`の設定に対してすべてのチェックを実行
+///
+/// Configuration files are never rewritten. When the configuration cannot be
+/// loaded, the checks of the vault are skipped.
+/// 設定ファイルを書き換えることはありません。設定を読み込めない場合、ボルトの
+/// チェックは省略されます。
+#[must_use]
+pub fn run(sources: &Sources) -> Report {
+    let sources = Sources {
+        migrate: false,
+        ..sources.clone()
+    };
+    let mut checks = match Paths::from_env() {
+        Ok(paths) => environment(&paths),
+        Err(e) => vec![
+            features(),
+            Check::fail(
+                "directories",
+                e.to_string(),
+                "set ZYNAPSE_HOME to a writable directory",
+            ),
+        ],
+    };
+    checks.push(configuration(&sources));
+
+    match Layered::load(&sources) {
+        Ok(layered) => {
+            let config = &layered.config;
+            checks.push(directory("vault", &config.storage.root_path));
+            if config.storage.backup.enabled {
+                checks.push(directory("backup directory", &config.storage.backup.path));
+            }
+            #[cfg(feature = "search")]
+            checks.push(directory("search index", &config.search.index_path));
+            #[cfg(feature = "basic-storage")]
+            checks.extend(vault(config));
+        }
+        Err(_) => checks.push(Check::warn(
+            "notes",
+            "skipped the vault because the configuration does not load",
+            "fix the configuration errors first",
+        )),
+    }
+
+    #[cfg(feature = "tui")]
+    checks.push(terminal());
+    Report { checks }
+}
+
+/// Checks that need no configuration: the feature flags and the base
+/// directories
+/// 設定を必要としないチェック：機能フラグとベースディレクトリ
+#[must_use]
+pub fn environment(paths: &Paths) -> Vec<Check> {
+    let mut checks = vec![features()];
+    for (label, dir) in [
+        ("config directory", &paths.config_dir),
+        ("data directory", &paths.data_dir),
+        ("cache directory", &paths.cache_dir),
+    ] {
+        checks.push(directory(label, dir));
+    }
+    checks
+}
+
+/// Whether the enabled features form a supported combination
+/// 有効な機能が対応する組み合わせかどうか
+fn features() -> Check {
+    let enabled: Vec<&str> = [
+        ("basic-storage", cfg!(feature = "basic-storage")),
+        ("search", cfg!(feature = "search")),
+        ("cli", cfg!(feature = "cli")),
+        ("tui", cfg!(feature = "tui")),
+        ("analytics", cfg!(feature = "analytics")),
+        ("learning", cfg!(feature = "learning")),
+        ("visualization", cfg!(feature = "visualization")),
+        ("ai", cfg!(feature = "ai")),
+        ("emergence", cfg!(feature = "emergence")),
+        ("serendipity", cfg!(feature = "serendipity")),
+    ]
+    .into_iter()
+    .filter_map(|(name, on)| on.then_some(name))
+    .collect();
+    let listing = enabled.join(", ");
+
+    if cfg!(feature = "phase2") && !cfg!(feature = "phase1") {
+        return Check::fail(
+            "features",
+            "Phase 2 requires Phase 1 features",
+            "build with `--features phase1,phase2`",
+        );
+    }
+    if cfg!(feature = "phase3") && !cfg!(feature = "phase2") {
+        return Check::fail(
+            "features",
+            "Phase 3 requires Phase 2 features",
+            "build with `--features phase2,phase3`",
+        );
+    }
+    if cfg!(feature = "phase2") {
+        return Check::warn(
+            "features",
+            format!("built with {listing}; the Phase 2 and 3 features are not implemented yet"),
+            "build with the default `phase1` features",
+        );
+    }
+    Check::pass("features", format!("built with {listing}"))
+}
+
+/// Whether a directory exists and is writable, or can be created
+/// ディレクトリが存在して書き込み可能か、または作成できるか
+fn directory(label: &'static str, path: &Path) -> Check {
+    let writable_fix = format!(
+        "make {} writable or set ZYNAPSE_HOME to a writable directory",
+        path.display()
+    );
+    if path.exists() && !path.is_dir() {
+        return Check::fail(
+            "directories",
+            format!("{label} {} is not a directory", path.display()),
+            "move the file away or configure another directory",
+        );
+    }
+    if !path.exists() {
+        // The directory is created on first use if an ancestor is writable
+        // 祖先が書き込み可能なら、ディレクトリは初回使用時に作成される
+        let ancestor = path.ancestors().find(|ancestor| ancestor.exists());
+        return match ancestor {
+            Some(ancestor) if ancestor.is_dir() && probe(ancestor).is_ok() => Check::pass(
+                "directories",
+                format!("{label} {} will be created when needed", path.display()),
+            ),
+            _ => Check::fail(
+                "directories",
+                format!("{label} {} cannot be created", path.display()),
+                writable_fix,
+            ),
+        };
+    }
+    match probe(path) {
+        Ok(()) => Check::pass(
+            "directories",
+            format!("{label} {} is writable", path.display()),
+        ),
+        Err(e) => Check::fail(
+            "directories",
+            format!("{label} {} is not writable: {e}", path.display()),
+            writable_fix,
+        ),
+    }
+}
+
+/// Write and remove a file in a directory
+/// ディレクトリ内にファイルを書き込んで削除
+fn probe(dir: &Path) -> std::io::Result<()> {
+    let file = dir.join(".zynapse-doctor");
+    std::fs::write(&file, "")?;
+    std::fs::remove_file(file)
+}
+
+/// Whether every configuration layer is free of problems
+/// すべての設定の層に問題がないかどうか
+fn configuration(sources: &Sources) -> Check {
+    let found = diagnostics::check(sources);
+    let errors: Vec<String> = found
+        .iter()
+        .filter(|diagnostic| diagnostic.severity == Severity::Error)
+        .map(|diagnostic| diagnostic.message.clone())
+        .collect();
+    let warnings = found.len() - errors.len();
+    let fix = "run `zynapse config check` to see where each problem is";
+    if !errors.is_empty() {
+        return Check::fail(
+            "config",
+            format!(
+                "{} in the configuration: {}",
+                plural(errors.len(), "error"),
+                sample(&errors)
+            ),
+            fix,
+        );
+    }
+    if warnings > 0 {
+        return Check::warn(
+            "config",
+            format!("{} in the configuration", plural(warnings, "warning")),
+            fix,
+        );
+    }
+    if let Some(user) = sources.user.as_ref().filter(|user| !user.exists()) {
+        return Check::warn(
+            "config",
+            format!(
+                "no configuration file at {}; using the defaults",
+                user.display()
+            ),
+            "run `zynapse init` to choose the vault and create the file",
+        );
+    }
+    Check::pass("config", "the configuration is valid")
+}
+
+/// Checks of the notes in the vault: readability, size, identifiers, links and
+/// the freshness of the search index
+/// ボルト内のノートのチェック：読み込み可否・サイズ・識別子・リンク・検索インデックスの鮮度
+#[cfg(feature = "basic-storage")]
+fn vault(config: &crate::config::Config) -> Vec<Check> {
+    use crate::links::LinkIndex;
+    use crate::storage::NoteStore;
+    use crate::utils::format_file_size;
+    use std::collections::BTreeMap;
+
+    let root = &config.storage.root_path;
+    if !root.is_dir() {
+        return vec![Check::pass(
+            "notes",
+            format!("the vault {} has no notes yet", root.display()),
+        )];
+    }
+    // Oversized notes are reported on their own, so load them anyway
+    // サイズ超過のノートは個別に報告するため、それでも読み込む
+    let store = NoteStore::new(root, u64::MAX);
+    let paths = match store.paths() {
+        Ok(paths) => paths,
+        Err(e) => {
+            return vec![Check::fail(
+                "notes",
+                e.to_string(),
+                format!("make {} readable", root.display()),
+            )]
+        }
+    };
+
+    let mut notes = Vec::new();
+    let mut unreadable = Vec::new();
+    let mut oversized = Vec::new();
+    for path in &paths {
+        let size = std::fs::metadata(root.join(path)).map_or(0, |meta| meta.len());
+        if size > config.storage.max_file_size {
+            oversized.push(format!("{} ({})", path.display(), format_file_size(size)));
+        }
+        match store.load(path) {
+            Ok(note) => notes.push(note),
+            Err(e) => unreadable.push(format!("{}: {e}", path.display())),
+        }
+    }
+
+    let mut checks = Vec::new();
+    checks.push(if unreadable.is_empty() {
+        Check::pass(
+            "notes",
+            format!("{} in {}", plural(notes.len(), "note"), root.display()),
+        )
+    } else {
+        Check::fail(
+            "notes",
+            format!(
+                "{} cannot be read and are skipped: {}",
+                plural(unreadable.len(), "note"),
+                sample(&unreadable)
+            ),
+            "fix the front matter of these files or move them out of the vault",
+        )
+    });
+
+    let limit = format_file_size(config.storage.max_file_size);
+    checks.push(if oversized.is_empty() {
+        Check::pass("size", format!("no note exceeds {limit}"))
+    } else {
+        Check::fail(
+            "size",
+            format!(
+                "{} exceed {limit} and are skipped: {}",
+                plural(oversized.len(), "note"),
+                sample(&oversized)
+            ),
+            "split these notes or raise storage.max_file_size",
+        )
+    });
+
+    let mut ids: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+    for note in &notes {
+        ids.entry(note.id())
+            .or_default()
+            .push(note.path.display().to_string());
+    }
+    let duplicates: Vec<String> = ids
+        .iter()
+        .filter(|(_, paths)| paths.len() > 1)
+        .map(|(id, paths)| format!("{id} ({})", paths.join(", ")))
+        .collect();
+    checks.push(if duplicates.is_empty() {
+        Check::pass("ids", "every note has a unique identifier")
+    } else {
+        Check::fail(
+            "ids",
+            format!(
+                "{} shared by several notes: {}",
+                plural(duplicates.len(), "identifier"),
+                sample(&duplicates)
+            ),
+            "give all but one of these notes a new `id` in the front matter",
+        )
+    });
+
+    let links = LinkIndex::build(&notes);
+    let dangling: Vec<String> = notes
+        .iter()
+        .enumerate()
+        .flat_map(|(index, note)| {
+            links
+                .unresolved(index)
+                .iter()
+                .map(move |target| format!("{} -> [[{target}]]", note.title()))
+        })
+        .collect();
+    checks.push(if dangling.is_empty() {
+        Check::pass("links", "every wiki link resolves to a note")
+    } else {
+        Check::warn(
+            "links",
+            format!(
+                "{}: {}",
+                plural(dangling.len(), "dangling link"),
+                sample(&dangling)
+            ),
+            "create the missing notes or correct the links",
+        )
+    });
+
+    #[cfg(feature = "search")]
+    checks.push(index(&config.search.index_path, root, &paths));
+    checks
+}
+
+/// Whether the search index is at least as recent as the notes
+/// 検索インデックスがノートと同じかそれより新しいかどうか
+///
+/// Only modification times are compared, so notes missing from the index or
+/// deleted since it was built are not detected.
+/// 更新日時のみを比較するため、インデックスにないノートや構築後に削除された
+/// ノートは検出されません。
+#[cfg(all(feature = "basic-storage", feature = "search"))]
+fn index(index: &Path, root: &Path, notes: &[std::path::PathBuf]) -> Check {
+    use std::time::SystemTime;
+
+    let modified = |path: &Path| {
+        std::fs::metadata(path)
+            .and_then(|meta| meta.modified())
+            .ok()
+    };
+    let built: Option<SystemTime> = walkdir::WalkDir::new(index)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| modified(entry.path()))
+        .max();
+    let Some(built) = built else {
+        return Check::pass("index freshness", "no search index has been built");
+    };
+    let stale = notes
+        .iter()
+        .filter(|note| modified(&root.join(note)).is_some_and(|changed| changed > built))
+        .count();
+    if stale == 0 {
+        Check::pass(
+            "index freshness",
+            "the search index is up to date with the notes",
+        )
+    } else {
+        Check::warn(
+            "index freshness",
+            format!(
+                "{} changed after the search index was built",
+                plural(stale, "note")
+            ),
+            format!(
+                "the index is a cache; remove {} to discard it",
+                index.display()
+            ),
+        )
+    }
+}
+
+/// Whether the terminal can host the TUI
+/// ターミナルがTUIを表示できるかどうか
+#[cfg(feature = "tui")]
+fn terminal() -> Check {
+    use crate::tui::theme::ColorSupport;
+    use std::io::IsTerminal;
+
+    let term = std::env::var("TERM").ok().filter(|term| !term.is_empty());
+    if !std::io::stdout().is_terminal() {
+        return Check::warn(
+            "terminal",
+            "standard output is not a terminal",
+            "run `zynapse tui` from an interactive terminal",
+        );
+    }
+    let Some(term) = term.filter(|term| term != "dumb") else {
+        return Check::warn(
+            "terminal",
+            "TERM is not set or is `dumb`; the TUI may not draw correctly",
+            "set TERM, for example TERM=xterm-256color",
+        );
+    };
+    if let Ok((width, height)) = crossterm::terminal::size() {
+        if width < 80 || height < 24 {
+            return Check::warn(
+                "terminal",
+                format!("the terminal is {width}x{height}, smaller than 80x24"),
+                "enlarge the terminal window",
+            );
+        }
+    }
+    let colors = match ColorSupport::detect() {
+        ColorSupport::Monochrome => "no colors",
+        ColorSupport::Ansi16 => "16 colors",
+        ColorSupport::Ansi256 => "256 colors",
+        ColorSupport::TrueColor => "true colors",
+    };
+    Check::pass("terminal", format!("TERM={term} with {colors}"))
+}
+
+/// The first items of a list, followed by how many were left out
+/// 一覧の最初の項目と、省略した件数
+fn sample(items: &[String]) -> String {
+    let text = items[..items.len().min(SAMPLE)].join(", ");
+    match items.len().checked_sub(SAMPLE) {
+        Some(rest) if rest > 0 => format!("{text} and {rest} more"),
+        _ => text,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "basic-storage")]
+    use crate::config::Config;
+    #[cfg(feature = "basic-storage")]
+    use crate::note::Note;
+    #[cfg(feature = "basic-storage")]
+    use crate::storage::NoteStore;
+    use tempfile::TempDir;
+
+    #[cfg(feature = "basic-storage")]
+    #[test]
+    fn test_vault_checks() {
+        let dir = TempDir::new().unwrap();
+        let mut config = Config::default();
+        config.storage.root_path = dir.path().to_path_buf();
+        let store = NoteStore::new(dir.path(), u64::MAX);
+        store
+            .create(&Note::new("Alpha", "See [[Beta]] and [[Missing]]"))
+            .unwrap();
+        let beta = Note::new("Beta", "x".repeat(2048));
+        store.create(&beta).unwrap();
+        let mut copy = Note::new("Copy", "Same id as Beta");
+        copy.frontmatter.id = beta.id().to_string();
+        store.create(&copy).unwrap();
+        std::fs::write(dir.path().join("broken.md"), "---\nid: [\n---\n").unwrap();
+        config.storage.max_file_size = 1024;
+
+        let checks = vault(&config);
+        let find = |area: &str| checks.iter().find(|check| check.area == area).unwrap();
+        assert_eq!(find("notes").status, Status::Fail);
+        assert!(find("notes").message.contains("broken.md"));
+        assert_eq!(find("size").status, Status::Fail);
+        assert!(find("size").message.contains("beta.md"));
+        assert_eq!(find("ids").status, Status::Fail);
+        assert!(find("ids").message.contains(beta.id()));
+        assert_eq!(find("links").status, Status::Warn);
+        assert!(find("links").message.contains("Alpha -> [[Missing]]"));
+        assert!(checks
+            .iter()
+            .all(|check| check.status == Status::Pass || check.fix.is_some()));
+
+        config.storage.root_path = dir.path().join("empty");
+        assert_eq!(vault(&config)[0].status, Status::Pass);
+    }
+
+    #[test]
+    fn test_directories_and_report() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("file");
+        std::fs::write(&file, "").unwrap();
+        assert_eq!(directory("vault", dir.path()).status, Status::Pass);
+        assert_eq!(
+            directory("vault", &dir.path().join("new")).status,
+            Status::Pass
+        );
+        assert_eq!(directory("vault", &file).status, Status::Fail);
+        assert_eq!(directory("vault", &file.join("below")).status, Status::Fail);
+
+        let report = Report {
+            checks: vec![
+                Check::pass("features", "built with cli"),
+                Check::warn("links", "1 dangling link", "create the note"),
+            ],
+        };
+        assert!(!report.has_failures());
+        let text = report.to_string();
+        assert!(text.contains("[PASS] features  built with cli\n"));
+        assert!(text.contains("[WARN] links     1 dangling link\n"));
+        assert!(text.contains("fix: create the note\n"));
+        assert!(text.ends_with("1 passed, 1 warnings, 0 failed\n"));
+        assert_eq!(
+            sample(&["a".into(), "b".into(), "c".into(), "d".into()]),
+            "a, b, c and 1 more"
+        );
+    }
+}
@@ -196,6 +196,29 @@ impl ZynapseError {
         }
     }
 
+    /// Create a TUI error
+    /// TUIエラーを作成
+    ///
+    /// # Arguments
+    /// # 引数
+    ///
+    /// * `message` - Error description / エラー説明
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use zynapse::ZynapseError;
+    ///
+    /// let error = ZynapseError::tui_error("Failed to draw: broken pipe");
+    /// assert_eq!(error.category(), "TUI");
+    /// ```
+    #[cfg(feature = "tui")]
+    pub fn tui_error(message: impl Into<String>) -> Self {
+        Self::Tui {
+            message: message.into(),
+        }
+    }
+
     /// Create a note not found error
     /// ノートが見つからないエラーを作成
     ///
@@ -236,6 +259,36 @@ impl ZynapseError {
         }
     }
 
+    /// Create a storage error
+    /// ストレージエラーを作成
+    ///
+    /// # Arguments
+    /// # 引数
+    ///
+    /// * `operation` - Description of the failed operation / 失敗した操作の説明
+    /// * `source` - The underlying error / 基礎となるエラー
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use zynapse::ZynapseError;
+    /// use std::io;
+    ///
+    /// let source = io::Error::new(io::ErrorKind::AlreadyExists, "exists");
+    /// let error = ZynapseError::storage_error("create note", source);
+    /// assert_eq!(error.category(), "Storage");
+    /// ```
+    #[cfg(feature = "basic-storage")]
+    pub fn storage_error(
+        operation: impl Into<String>,
+        source: impl Into<Box<dyn std::error::Error + Send + Sync>>,
+    ) -> Self {
+        Self::Storage {
+            operation: operation.into(),
+            source: source.into(),
+        }
+    }
+
     /// Create an internal error
     /// 内部エラーを作成
     ///
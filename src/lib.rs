@@ -75,13 +75,34 @@ pub use error::{Result, ZynapseError};
 // コアモジュール - 常に利用可能
 pub mod error;
 
-// // Phase 1 modules - Basic functionality
-// // Phase 1モジュール - 基本機能
-// #[cfg(feature = "basic-storage")]
-// pub mod storage;
+// Phase 1 modules - Basic functionality
+// Phase 1モジュール - 基本機能
+#[cfg(feature = "basic-storage")]
+pub mod storage;
 
-// #[cfg(feature = "basic-storage")]
-// pub mod note;
+#[cfg(feature = "basic-storage")]
+pub mod note;
+
+#[cfg(feature = "basic-storage")]
+pub mod periodic;
+
+#[cfg(feature = "basic-storage")]
+pub mod query;
+
+#[cfg(feature = "basic-storage")]
+pub mod bulk;
+
+#[cfg(feature = "basic-storage")]
+pub mod backup;
+
+#[cfg(feature = "basic-storage")]
+pub mod template;
+
+#[cfg(feature = "basic-storage")]
+pub mod links;
+
+#[cfg(feature = "basic-storage")]
+pub mod tags;
 
 // #[cfg(feature = "basic-storage")]
 // pub mod context;
@@ -92,11 +113,11 @@ pub mod error;
 // #[cfg(feature = "search")]
 // pub mod search;
 
-// #[cfg(feature = "cli")]
-// pub mod cli;
+#[cfg(feature = "cli")]
+pub mod cli;
 
-// #[cfg(feature = "tui")]
-// pub mod tui;
+#[cfg(feature = "tui")]
+pub mod tui;
 
 // // Phase 2 modules - Learning and analytics
 // // Phase 2モジュール - 学習と分析
@@ -123,6 +144,8 @@ pub mod error;
 // Configuration and utilities
 // 設定とユーティリティ
 pub mod config;
+pub mod doctor;
+pub mod logging;
 pub mod utils;
 
 /// Library version information
@@ -159,8 +182,10 @@ pub fn version_info() -> String {
 /// Initialize the Zynapse library with default configuration
 /// デフォルト設定でZynapseライブラリを初期化
 ///
-/// This function sets up logging and validates the runtime environment.
-/// この関数はロギングを設定し、ランタイム環境を検証します。
+/// This function validates the runtime environment. Logging is set up by
+/// [`logging::init`] once the configuration has been loaded.
+/// この関数はランタイム環境を検証します。ロギングは設定の読み込み後に
+/// [`logging::init`]で設定されます。
 ///
 /// # Errors
 ///
@@ -182,18 +207,9 @@ pub fn version_info() -> String {
 /// }
 /// ```
 pub fn initialize() -> Result<()> {
-    // Initialize logging
-    // ロギング初期化
-    env_logger::init();
-
-    log::info!("Initializing Zynapse {VERSION}");
-
     // Validate runtime environment
     // ランタイム環境の検証
-    validate_environment()?;
-
-    log::info!("Zynapse initialization complete");
-    Ok(())
+    validate_environment()
 }
 
 /// Validate the runtime environment
@@ -210,63 +226,35 @@ pub fn initialize() -> Result<()> {
 /// - Required system capabilities are missing
 /// - Environment configuration is invalid
 fn validate_environment() -> Result<()> {
-    // Check Rust version compatibility
-    // Rustバージョン互換性チェック
-    let rust_version = std::env::var("RUSTC_VERSION").unwrap_or_else(|_| "unknown".to_string());
-    log::debug!("Rust version: {rust_version}");
-
-    // Validate feature flag consistency
-    // 機能フラグの一貫性検証
-    #[cfg(feature = "phase2")]
-    {
-        #[cfg(not(feature = "phase1"))]
-        return Err(ZynapseError::Configuration {
-            message: "Phase 2 requires Phase 1 features".to_string(),
-        });
-    }
-
-    #[cfg(feature = "phase3")]
-    {
-        #[cfg(not(feature = "phase2"))]
-        return Err(ZynapseError::Configuration {
-            message: "Phase 3 requires Phase 2 features".to_string(),
-        });
-    }
+    validate_environment_in(&config::Paths::from_env()?)
+}
 
-    // Validate system capabilities for TUI
-    // TUI用システム機能の検証
-    #[cfg(feature = "tui")]
-    {
-        if std::env::var("TERM").is_err() {
-            log::warn!("TERM environment variable not set, TUI functionality may be limited");
+/// Validate the runtime environment against the given Zynapse directories
+/// 指定されたZynapseのディレクトリに対してランタイム環境を検証
+fn validate_environment_in(paths: &config::Paths) -> Result<()> {
+    // Create the Zynapse directories before checking them
+    // 確認する前にZynapseのディレクトリを作成
+    for dir in [&paths.config_dir, &paths.data_dir, &paths.cache_dir] {
+        if !dir.exists() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| ZynapseError::io_error(e, "Failed to create Zynapse directory"))?;
         }
     }
 
-    // Check for required directories permissions
-    // 必要なディレクトリの権限チェック
-    if let Some(home_dir) = dirs::home_dir() {
-        let zynapse_dir = home_dir.join(".zynapse");
-
-        // Try to create the directory if it doesn't exist
-        // ディレクトリが存在しない場合は作成を試行
-        if !zynapse_dir.exists() {
-            std::fs::create_dir_all(&zynapse_dir)
-                .map_err(|e| ZynapseError::io_error(e, "Failed to create Zynapse directory"))?;
+    // Feature flags and directory permissions; `zynapse doctor` checks the rest
+    // 機能フラグとディレクトリの権限（残りは`zynapse doctor`が確認）
+    for check in doctor::environment(paths) {
+        match check.status {
+            doctor::Status::Pass => {}
+            doctor::Status::Warn => log::warn!("{}", check.message),
+            doctor::Status::Fail => {
+                let fix = check.fix.unwrap_or_default();
+                return Err(ZynapseError::config_error(format!(
+                    "{} ({fix})",
+                    check.message
+                )));
+            }
         }
-
-        // Verify write permissions
-        // 書き込み権限を確認
-        let test_file = zynapse_dir.join(".write_test");
-        std::fs::write(&test_file, "test")
-            .map_err(|e| ZynapseError::io_error(e, "No write permission to Zynapse directory"))?;
-
-        // Clean up test file
-        // テストファイルを削除
-        let _ = std::fs::remove_file(test_file);
-    } else {
-        return Err(ZynapseError::Configuration {
-            message: "Cannot determine home directory".to_string(),
-        });
     }
 
     Ok(())
@@ -285,13 +273,17 @@ mod tests {
 
     #[test]
     fn test_validate_environment() {
-        // Should not fail in test environment
-        // テスト環境では失敗しないはず
-        assert!(validate_environment().is_ok());
+        // Should not fail in test environment, and creates the directories
+        // テスト環境では失敗せず、ディレクトリを作成するはず
+        let dir = tempfile::TempDir::new().unwrap();
+        let paths = config::Paths::single(dir.path().join("zynapse"));
+        assert!(validate_environment_in(&paths).is_ok());
+        assert!(paths.data_dir.is_dir());
     }
 
     #[cfg(feature = "phase1")]
     #[test]
+    #[allow(clippy::assertions_on_constants)]
     fn test_phase1_features() {
         // Test that phase1 features are available
         // phase1機能が利用可能であることをテスト
@@ -0,0 +1,243 @@
+//! Resolved wiki-link index over a set of notes
+//! ノート集合に対する解決済みWikiリンクのインデックス
+//!
+//! Wiki links name their target by title, alias, identifier or file name. The
+//! index resolves every link once so outgoing links and backlinks can be looked
+//! up by note position without re-scanning note bodies.
+//! Wikiリンクはタイトル・エイリアス・識別子・ファイル名でリンク先を指定します。
+//! インデックスはすべてのリンクを一度だけ解決するため、ノート本文を再走査せずに
+//! ノートの位置から発リンクとバックリンクを参照できます。
+
+use crate::note::Note;
+use std::collections::{HashMap, VecDeque};
+
+/// Link index built from a slice of notes
+/// ノートのスライスから構築されたリンクインデックス
+///
+/// Notes are referred to by their position in the slice passed to [`LinkIndex::build`].
+/// ノートは[`LinkIndex::build`]に渡したスライス内の位置で参照されます。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LinkIndex {
+    /// Resolved outgoing links per note with their occurrence counts
+    /// ノートごとの解決済み発リンクと出現回数
+    outgoing: Vec<Vec<(usize, usize)>>,
+
+    /// Notes linking to each note
+    /// 各ノートにリンクしているノート
+    incoming: Vec<Vec<usize>>,
+
+    /// Link targets that match no note, per note
+    /// ノートごとの、どのノートにも一致しないリンク先
+    unresolved: Vec<Vec<String>>,
+
+    /// Note claiming each lowercased name
+    /// 小文字化された各名前を持つノート
+    names: HashMap<String, usize>,
+}
+
+impl LinkIndex {
+    /// Resolve the wiki links of every note
+    /// すべてのノートのWikiリンクを解決
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use zynapse::links::LinkIndex;
+    /// use zynapse::note::Note;
+    ///
+    /// let notes = vec![
+    ///     Note::new("Rust", "See [[Ownership]] and [[Missing]]"),
+    ///     Note::new("Ownership", "Back to [[rust]]"),
+    /// ];
+    /// let index = LinkIndex::build(&notes);
+    /// assert_eq!(index.backlinks(0), &[1]);
+    /// assert_eq!(index.unresolved(0), &["Missing".to_string()]);
+    /// ```
+    #[must_use]
+    pub fn build(notes: &[Note]) -> Self {
+        let mut names: HashMap<String, usize> = HashMap::new();
+        for (position, note) in notes.iter().enumerate() {
+            let frontmatter = &note.frontmatter;
+            let keys = [&frontmatter.id, &frontmatter.title, &note.stem()]
+                .into_iter()
+                .chain(&frontmatter.aliases)
+                .map(|key| key.to_lowercase())
+                .collect::<Vec<_>>();
+            for key in keys {
+                // The first note claiming a name wins, like `NoteStore::find`
+                // `NoteStore::find`と同様に、名前を最初に持つノートが優先
+                names.entry(key).or_insert(position);
+            }
+        }
+
+        let mut index = Self {
+            outgoing: vec![Vec::new(); notes.len()],
+            incoming: vec![Vec::new(); notes.len()],
+            unresolved: vec![Vec::new(); notes.len()],
+            names: HashMap::new(),
+        };
+
+        for (source, note) in notes.iter().enumerate() {
+            for link in note.links() {
+                match names.get(&link.to_lowercase()) {
+                    Some(&target) if target != source => {
+                        let outgoing = &mut index.outgoing[source];
+                        if let Some((_, count)) = outgoing
+                            .iter_mut()
+                            .find(|(existing, _)| *existing == target)
+                        {
+                            *count += 1;
+                        } else {
+                            outgoing.push((target, 1));
+                            index.incoming[target].push(source);
+                        }
+                    }
+                    Some(_) => {}
+                    None => index.unresolved[source].push(link),
+                }
+            }
+        }
+        index.names = names;
+        index
+    }
+
+    /// Note a link target resolves to
+    /// リンク先が解決されるノート
+    ///
+    /// Matching is case-insensitive on identifier, title, file name and aliases.
+    /// 識別子・タイトル・ファイル名・エイリアスに対して大文字小文字を区別せずに照合します。
+    #[must_use]
+    pub fn resolve(&self, name: &str) -> Option<usize> {
+        self.names.get(&name.trim().to_lowercase()).copied()
+    }
+
+    /// Notes linked from a note, with the number of links to each
+    /// ノートからリンクされているノートと、それぞれへのリンク数
+    #[must_use]
+    pub fn outgoing(&self, note: usize) -> &[(usize, usize)] {
+        self.outgoing.get(note).map_or(&[], Vec::as_slice)
+    }
+
+    /// Notes linking to a note
+    /// ノートにリンクしているノート
+    #[must_use]
+    pub fn backlinks(&self, note: usize) -> &[usize] {
+        self.incoming.get(note).map_or(&[], Vec::as_slice)
+    }
+
+    /// Link targets of a note that match no existing note
+    /// 既存のどのノートにも一致しないノートのリンク先
+    #[must_use]
+    pub fn unresolved(&self, note: usize) -> &[String] {
+        self.unresolved.get(note).map_or(&[], Vec::as_slice)
+    }
+
+    /// Number of wiki links between two notes, counted in both directions
+    /// 2つのノート間のWikiリンク数（両方向を合計）
+    ///
+    /// This is the strength of the synapse connecting the notes.
+    /// これはノートをつなぐシナプスの強さです。
+    #[must_use]
+    pub fn strength(&self, a: usize, b: usize) -> usize {
+        let count = |from: usize, to: usize| {
+            self.outgoing(from)
+                .iter()
+                .find(|(target, _)| *target == to)
+                .map_or(0, |(_, count)| *count)
+        };
+        count(a, b) + count(b, a)
+    }
+
+    /// Notes within `hops` links of a note in either direction
+    /// ノートからいずれかの方向に`hops`リンク以内にあるノート
+    ///
+    /// Notes are returned closest first with their distance; the note itself
+    /// comes first at distance 0.
+    /// ノートは近い順に距離とともに返され、ノート自身が距離0で最初に来ます。
+    #[must_use]
+    pub fn neighborhood(&self, note: usize, hops: usize) -> Vec<(usize, usize)> {
+        if note >= self.len() {
+            return Vec::new();
+        }
+        let mut seen = vec![false; self.len()];
+        let mut found = Vec::new();
+        let mut queue = VecDeque::from([(note, 0)]);
+        seen[note] = true;
+        while let Some((current, distance)) = queue.pop_front() {
+            found.push((current, distance));
+            if distance == hops {
+                continue;
+            }
+            let neighbors = self
+                .outgoing(current)
+                .iter()
+                .map(|(target, _)| *target)
+                .chain(self.backlinks(current).iter().copied());
+            for neighbor in neighbors {
+                if !std::mem::replace(&mut seen[neighbor], true) {
+                    queue.push_back((neighbor, distance + 1));
+                }
+            }
+        }
+        found
+    }
+
+    /// Number of indexed notes
+    /// インデックス化されたノート数
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.outgoing.len()
+    }
+
+    /// Whether the index contains no notes
+    /// インデックスにノートが含まれていないか
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.outgoing.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_links_are_counted_and_deduplicated() {
+        let mut target = Note::new("Target", "");
+        target.frontmatter.aliases.push("Alias".to_string());
+        let notes = vec![
+            Note::new("Source", "[[Target]] [[alias]] [[Target|label]] [[Source]]"),
+            target,
+            Note::new("Other", "[[target#Heading]]"),
+        ];
+
+        let index = LinkIndex::build(&notes);
+        assert_eq!(index.len(), 3);
+        assert_eq!(index.outgoing(0), &[(1, 3)]);
+        assert_eq!(index.backlinks(1), &[0, 2]);
+        assert!(index.backlinks(0).is_empty(), "self links are ignored");
+        assert!(index.outgoing(5).is_empty());
+        assert_eq!(index.resolve("ALIAS"), Some(1));
+        assert_eq!(index.resolve("Nowhere"), None);
+    }
+
+    #[test]
+    fn test_neighborhood_and_strength() {
+        let notes = vec![
+            Note::new("A", "[[B]] [[B]]"),
+            Note::new("B", "[[A]] [[C]]"),
+            Note::new("C", "[[D]]"),
+            Note::new("D", ""),
+            Note::new("E", "[[A]]"),
+        ];
+        let index = LinkIndex::build(&notes);
+        assert_eq!(index.strength(0, 1), 3);
+        assert_eq!(index.strength(1, 0), 3);
+        assert_eq!(index.strength(0, 2), 0);
+
+        assert_eq!(index.neighborhood(0, 0), [(0, 0)]);
+        assert_eq!(index.neighborhood(0, 1), [(0, 0), (1, 1), (4, 1)]);
+        assert_eq!(index.neighborhood(0, 2), [(0, 0), (1, 1), (4, 1), (2, 2)]);
+        assert!(index.neighborhood(9, 2).is_empty());
+    }
+}
@@ -0,0 +1,369 @@
+//! Logger driven by the logging configuration
+//! ログ設定に基づくロガー
+//!
+//! Records go to standard error, or to `logging.file_path` when it is set. A
+//! log file is rotated once it reaches `logging.max_file_size`: `zynapse.log`
+//! becomes `zynapse.log.1`, the previous `zynapse.log.1` becomes
+//! `zynapse.log.2`, and only `logging.max_files` rotated files are kept. Lines
+//! are plain text or, with `logging.format = "json"`, JSON objects.
+//! ログは標準エラー出力、または`logging.file_path`が設定されていればそのファイルに
+//! 出力されます。ログファイルは`logging.max_file_size`に達するとローテーションされ、
+//! `zynapse.log`は`zynapse.log.1`に、以前の`zynapse.log.1`は`zynapse.log.2`になり、
+//! ローテーション済みファイルは`logging.max_files`個のみ保持されます。各行は
+//! プレーンテキスト、または`logging.format = "json"`の場合はJSONオブジェクトです。
+//!
+//! The TUI draws on the terminal, so while it runs a logger without a file
+//! writes to `zynapse.log` in the data directory instead of standard error.
+//! TUIはターミナルに描画するため、TUI実行中はファイルのないロガーは標準エラー出力の
+//! 代わりにデータディレクトリの`zynapse.log`に書き込みます。
+
+use crate::config::{LogFormat, LoggingConfig, Paths};
+use crate::{Result, ZynapseError};
+use chrono::Local;
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use std::fmt::Write as _;
+use std::fs::{File, OpenOptions};
+use std::io::{IsTerminal, Write as _};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Name of the log file used by the TUI when no file is configured
+/// ファイルが設定されていない場合にTUIが使用するログファイル名
+pub const TUI_LOG_FILE: &str = "zynapse.log";
+
+/// Install the logger described by the configuration
+/// 設定に記述されたロガーをインストール
+///
+/// `terminal` tells whether records may be written to standard error; pass
+/// `false` while the terminal is drawn on. Only the first call in a process
+/// installs a logger.
+/// `terminal`は標準エラー出力に書き込めるかどうかを表し、ターミナルに描画する間は
+/// `false`を渡します。ロガーをインストールするのはプロセス内の最初の呼び出しのみです。
+///
+/// # Errors
+///
+/// Returns an error if the log level is invalid, the log file cannot be
+/// opened, or a logger is already installed.
+/// ログレベルが無効な場合、ログファイルを開けない場合、またはロガーが既に
+/// インストールされている場合にエラーを返します。
+pub fn init(config: &LoggingConfig, terminal: bool) -> Result<()> {
+    let level: LevelFilter = config
+        .level
+        .parse()
+        .map_err(|_| ZynapseError::config_error(format!("Invalid log level: {}", config.level)))?;
+    let logger = Logger::new(config, terminal)?;
+    log::set_boxed_logger(Box::new(logger))
+        .map_err(|e| ZynapseError::internal(format!("Failed to install logger: {e}")))?;
+    log::set_max_level(level);
+    Ok(())
+}
+
+/// Logger writing formatted records to its output
+/// 整形したレコードを出力先に書き込むロガー
+///
+/// The level is read from [`log::max_level`], so that a reloaded
+/// `logging.level` takes effect without installing a new logger.
+/// レベルは[`log::max_level`]から読み込むため、再読み込みした`logging.level`は
+/// 新しいロガーをインストールせずに反映されます。
+#[derive(Debug)]
+pub struct Logger {
+    /// Line format
+    /// 行の形式
+    format: LogFormat,
+
+    /// Prefix lines with a timestamp
+    /// 行の先頭にタイムスタンプを付ける
+    timestamp: bool,
+
+    /// Color the level of text lines
+    /// テキスト行のレベルに色を付ける
+    colored: bool,
+
+    /// Where lines are written
+    /// 行の書き込み先
+    output: Mutex<Output>,
+}
+
+/// Destination of log lines
+/// ログ行の書き込み先
+#[derive(Debug)]
+enum Output {
+    /// Standard error
+    /// 標準エラー出力
+    Stderr,
+
+    /// A log file rotated by size
+    /// サイズでローテーションするログファイル
+    File(RotatingFile),
+}
+
+impl Logger {
+    /// Create the logger described by the configuration
+    /// 設定に記述されたロガーを作成
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the log file cannot be opened.
+    /// ログファイルを開けない場合にエラーを返します。
+    pub fn new(config: &LoggingConfig, terminal: bool) -> Result<Self> {
+        let path = match &config.file_path {
+            Some(path) => Some(path.clone()),
+            None if terminal => None,
+            None => Some(Paths::from_env()?.data_dir.join(TUI_LOG_FILE)),
+        };
+        let output = match path {
+            Some(path) => Output::File(RotatingFile::open(
+                path,
+                config.max_file_size,
+                config.max_files,
+            )?),
+            None => Output::Stderr,
+        };
+        let colored =
+            config.colored && matches!(output, Output::Stderr) && std::io::stderr().is_terminal();
+        Ok(Self {
+            format: config.format,
+            timestamp: config.timestamp,
+            colored,
+            output: Mutex::new(output),
+        })
+    }
+
+    /// One log line for a record, without the line break
+    /// レコードの1行のログ（改行なし）
+    fn line(&self, record: &Record<'_>) -> String {
+        let timestamp = self
+            .timestamp
+            .then(|| Local::now().format("%Y-%m-%dT%H:%M:%S%.3f%:z").to_string());
+        match self.format {
+            LogFormat::Text => {
+                let level = if self.colored {
+                    format!("{}{:5}\x1b[0m", color(record.level()), record.level())
+                } else {
+                    format!("{:5}", record.level())
+                };
+                let mut line = String::new();
+                if let Some(timestamp) = timestamp {
+                    line.push_str(&timestamp);
+                    line.push(' ');
+                }
+                let _ = write!(line, "{level} {}: {}", record.target(), record.args());
+                line
+            }
+            LogFormat::Json => {
+                let mut object = serde_json::Map::new();
+                if let Some(timestamp) = timestamp {
+                    object.insert("timestamp".to_string(), timestamp.into());
+                }
+                object.insert("level".to_string(), record.level().as_str().into());
+                object.insert("target".to_string(), record.target().into());
+                object.insert("message".to_string(), record.args().to_string().into());
+                serde_json::Value::Object(object).to_string()
+            }
+        }
+    }
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record<'_>) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let line = self.line(record);
+        let Ok(mut output) = self.output.lock() else {
+            return;
+        };
+        // A logger has nowhere to report its own failures
+        // ロガーには自身の失敗を報告する先がない
+        match &mut *output {
+            Output::Stderr => {
+                let _ = writeln!(std::io::stderr().lock(), "{line}");
+            }
+            Output::File(file) => {
+                let _ = file.write_line(&line);
+            }
+        }
+    }
+
+    fn flush(&self) {
+        if let Ok(mut output) = self.output.lock() {
+            let _ = match &mut *output {
+                Output::Stderr => std::io::stderr().flush(),
+                Output::File(file) => file.file.flush(),
+            };
+        }
+    }
+}
+
+/// ANSI color sequence for a level
+/// レベルに対応するANSIカラーシーケンス
+const fn color(level: Level) -> &'static str {
+    match level {
+        Level::Error => "\x1b[31m",
+        Level::Warn => "\x1b[33m",
+        Level::Info => "\x1b[32m",
+        Level::Debug => "\x1b[34m",
+        Level::Trace => "\x1b[35m",
+    }
+}
+
+/// Log file that is rotated once it grows past a size
+/// 一定のサイズを超えるとローテーションされるログファイル
+#[derive(Debug)]
+struct RotatingFile {
+    /// Path of the current log file
+    /// 現在のログファイルのパス
+    path: PathBuf,
+
+    /// Open current log file
+    /// 開いている現在のログファイル
+    file: File,
+
+    /// Bytes in the current log file
+    /// 現在のログファイルのバイト数
+    size: u64,
+
+    /// Size at which the file is rotated, 0 for never
+    /// ローテーションするサイズ（0はしない）
+    max_size: u64,
+
+    /// Number of rotated files kept
+    /// 保持するローテーション済みファイルの数
+    max_files: u32,
+}
+
+impl RotatingFile {
+    /// Open a log file for appending, creating its directory
+    /// ログファイルを追記用に開き、ディレクトリを作成
+    fn open(path: PathBuf, max_size: u64, max_files: u32) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            crate::utils::ensure_directory_exists(parent)?;
+        }
+        let file = append(&path)?;
+        let size = file.metadata().map_or(0, |meta| meta.len());
+        Ok(Self {
+            path,
+            file,
+            size,
+            max_size,
+            max_files,
+        })
+    }
+
+    /// Append a line, rotating first if it would overflow the file
+    /// 1行を追記（ファイルがあふれる場合は先にローテーション）
+    fn write_line(&mut self, line: &str) -> Result<()> {
+        let length = line.len() as u64 + 1;
+        if self.max_size > 0 && self.size > 0 && self.size + length > self.max_size {
+            self.rotate()?;
+        }
+        writeln!(self.file, "{line}")
+            .map_err(|e| ZynapseError::io_error(e, "Failed to write log file"))?;
+        self.size += length;
+        Ok(())
+    }
+
+    /// Shift the rotated files by one and start an empty log file
+    /// ローテーション済みファイルを1つずらし、空のログファイルを開始
+    fn rotate(&mut self) -> Result<()> {
+        let _ = std::fs::remove_file(rotated(&self.path, self.max_files));
+        for index in (1..self.max_files).rev() {
+            let _ = std::fs::rename(rotated(&self.path, index), rotated(&self.path, index + 1));
+        }
+        if self.max_files > 0 {
+            std::fs::rename(&self.path, rotated(&self.path, 1))
+                .map_err(|e| ZynapseError::io_error(e, "Failed to rotate log file"))?;
+        } else {
+            std::fs::remove_file(&self.path)
+                .map_err(|e| ZynapseError::io_error(e, "Failed to rotate log file"))?;
+        }
+        self.file = append(&self.path)?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+/// Open a file for appending, creating it if missing
+/// ファイルを追記用に開く（存在しなければ作成）
+fn append(path: &Path) -> Result<File> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| {
+            ZynapseError::io_error(e, format!("Failed to open log file: {}", path.display()))
+        })
+}
+
+/// Path of the rotated file with the given number
+/// 指定した番号のローテーション済みファイルのパス
+fn rotated(path: &Path, index: u32) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(format!(".{index}"));
+    PathBuf::from(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn record(args: std::fmt::Arguments<'_>) -> Record<'_> {
+        Record::builder()
+            .args(args)
+            .level(Level::Warn)
+            .target("zynapse::test")
+            .build()
+    }
+
+    #[test]
+    fn test_line_formats() {
+        let dir = TempDir::new().unwrap();
+        let mut config = LoggingConfig {
+            file_path: Some(dir.path().join("zynapse.log")),
+            timestamp: false,
+            ..LoggingConfig::default()
+        };
+        let logger = Logger::new(&config, true).unwrap();
+        assert!(!logger.colored, "files are never colored");
+        assert_eq!(
+            logger.line(&record(format_args!("disk \"full\""))),
+            "WARN  zynapse::test: disk \"full\""
+        );
+
+        config.format = LogFormat::Json;
+        config.timestamp = true;
+        let logger = Logger::new(&config, true).unwrap();
+        let line = logger.line(&record(format_args!("disk \"full\"")));
+        let value: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(value["level"], "WARN");
+        assert_eq!(value["target"], "zynapse::test");
+        assert_eq!(value["message"], "disk \"full\"");
+        assert!(value["timestamp"].is_string());
+    }
+
+    #[test]
+    fn test_rotation_keeps_max_files() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("logs").join("zynapse.log");
+        let mut file = RotatingFile::open(path.clone(), 20, 2).unwrap();
+        for line in ["first line", "second line", "third line", "fourth line"] {
+            file.write_line(line).unwrap();
+        }
+
+        let read = |path: &Path| std::fs::read_to_string(path).unwrap();
+        assert_eq!(read(&path), "fourth line\n");
+        assert_eq!(read(&rotated(&path, 1)), "third line\n");
+        assert_eq!(read(&rotated(&path, 2)), "second line\n");
+        assert!(!rotated(&path, 3).exists());
+
+        // Reopening continues the current file
+        // 再度開くと現在のファイルに追記を続ける
+        let file = RotatingFile::open(path, 0, 2).unwrap();
+        assert_eq!(file.size, "fourth line\n".len() as u64);
+    }
+}
@@ -26,23 +26,36 @@
 //! # Quick Start / クイックスタート
 //!
 //! ```bash
-//! # Create a new note
-//! # 新しいノートを作成
-//! zynapse add "My first note content"
+//! # Open today's daily note
+//! # 今日の日次ノートを開く
+//! zynapse daily
 //!
-//! # Search for notes
-//! # ノートを検索
-//! zynapse search "keyword"
+//! # Create a new note and open it in the editor
+//! # 新しいノートを作成してエディタで開く
+//! zynapse new "My first note"
+//!
+//! # Print a note found by title, alias or identifier
+//! # タイトル・エイリアス・識別子で見つけたノートを表示
+//! zynapse show "My first note"
 //!
 //! # Launch interactive TUI
 //! # インタラクティブTUIを起動
 //! zynapse tui
 //! ```
 //!
+//! Errors are printed to stderr and make the process exit with status 1.
+//! エラーは標準エラー出力に表示され、プロセスはステータス1で終了します。
+//!
 //! # Configuration / 設定
 //!
-//! Zynapse uses `~/.zynapse/config.toml` for configuration.
-//! Zynapseは設定に`~/.zynapse/config.toml`を使用します。
+//! Zynapse reads `config.toml` from `$XDG_CONFIG_HOME/zynapse` (or an existing
+//! `~/.zynapse`); set `ZYNAPSE_HOME` to keep everything in one directory.
+//! Zynapseは`$XDG_CONFIG_HOME/zynapse`（または既存の`~/.zynapse`）の`config.toml`を
+//! 読み込みます。`ZYNAPSE_HOME`を設定するとすべてを1つのディレクトリに置きます。
+//! Named vaults are configured under `[vaults.<name>]` and selected with
+//! `--vault <name>` or `default_vault`.
+//! 名前付きのボルトは`[vaults.<name>]`に設定し、`--vault <name>`または
+//! `default_vault`で選択します。
 //!
 //! # Performance Requirements / パフォーマンス要件
 //!
@@ -54,6 +67,7 @@
 #![deny(missing_docs)]
 #![deny(unsafe_code)]
 
+use std::process::ExitCode;
 use zynapse::{initialize, Result};
 
 /// Main entry point for the Zynapse CLI application
@@ -64,10 +78,8 @@ use zynapse::{initialize, Result};
 /// この関数はZynapseライブラリを初期化し、コマンドライン引数を処理し、
 /// 選択された機能に基づいて適切な機能にディスパッチします。
 ///
-/// # Errors
-///
-/// Returns an error if:
-/// 以下の場合にエラーを返します：
+/// An error is printed to stderr and the exit status is non-zero when:
+/// 以下の場合、エラーを標準エラー出力に表示し、0以外の終了ステータスを返します：
 /// - Library initialization fails
 /// - Configuration is invalid
 /// - Required features are not enabled
@@ -76,81 +88,33 @@ use zynapse::{initialize, Result};
 /// # Examples
 ///
 /// ```bash
-/// # Basic usage examples (実装完了後に有効)
 /// zynapse --version
-/// zynapse add "Hello, World!"
-/// zynapse search "hello"
+/// zynapse daily
+/// zynapse weekly --prev
 /// ```
-fn main() -> Result<()> {
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("Error: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Initialize the library and run the requested command
+/// ライブラリを初期化し、要求されたコマンドを実行
+///
+/// # Errors
+///
+/// Returns an error if initialization or the command fails.
+/// 初期化またはコマンドが失敗した場合にエラーを返します。
+fn run() -> Result<()> {
     // Initialize the Zynapse library
     // Zynapseライブラリを初期化
     initialize()?;
 
-    // Display version information during Phase 1 development
-    // Phase 1開発中はバージョン情報を表示
-    println!("{}", zynapse::version_info());
-    println!();
-    println!("🚀 Zynapse Personal Knowledge Management System");
-    println!("   CLI/TUI Zettelkasten with Synapse-like Connections");
-    println!();
-    println!("📋 Current Status: Phase 1 Development");
-    println!("   ✅ Project structure and configuration");
-    println!("   🔧 Core functionality implementation in progress");
-    println!("   ⏳ CLI/TUI interfaces coming soon");
-    println!();
-    println!("🎯 Performance Targets:");
-    println!("   • CLI operations: < 100ms");
-    println!("   • Search response: < 200ms (10k notes)");
-    println!("   • TUI startup: < 1 second");
-    println!("   • Memory usage: CLI < 50MB, TUI < 200MB");
-    println!();
-
-    // Check enabled features and provide guidance
-    // 有効な機能をチェックしてガイダンスを提供
-    println!("🔧 Enabled Features:");
-
-    #[cfg(feature = "cli")]
-    println!("   ✅ CLI - Command Line Interface");
-    #[cfg(not(feature = "cli"))]
-    println!("   ❌ CLI - Enable with --features cli");
-
-    #[cfg(feature = "tui")]
-    println!("   ✅ TUI - Terminal User Interface");
-    #[cfg(not(feature = "tui"))]
-    println!("   ❌ TUI - Enable with --features tui");
-
-    #[cfg(feature = "search")]
-    println!("   ✅ Search - Full-text search with Tantivy");
-    #[cfg(not(feature = "search"))]
-    println!("   ❌ Search - Enable with --features search");
-
-    #[cfg(feature = "basic-storage")]
-    println!("   ✅ Storage - File-based note storage");
-    #[cfg(not(feature = "basic-storage"))]
-    println!("   ❌ Storage - Enable with --features basic-storage");
-
-    println!();
-    println!("📚 Documentation: https://docs.rs/zynapse");
-    println!("🐛 Issues: https://github.com/your-org/zynapse/issues");
-    println!();
-    println!("💡 Phase 1 implementation is in progress!");
-    println!("   Check back soon for full CLI/TUI functionality.");
-
-    // TODO: Phase 1 implementation
-    // When CLI module is implemented, replace the above with:
-    // CLIモジュールが実装されたら、上記を以下に置き換え：
-    //
-    // #[cfg(feature = "cli")]
-    // {
-    //     use zynapse::cli;
-    //     cli::run()
-    // }
-    // #[cfg(not(feature = "cli"))]
-    // {
-    //     eprintln!("Error: CLI feature not enabled");
-    //     eprintln!("Build with: cargo build --features cli");
-    //     std::process::exit(1);
-    // }
-
-    Ok(())
+    // Dispatch to the requested subcommand
+    // 要求されたサブコマンドにディスパッチ
+    zynapse::cli::run()
 }
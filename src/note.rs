@@ -0,0 +1,559 @@
+//! Note model and Markdown frontmatter handling
+//! ノートモデルとMarkdownフロントマター処理
+//!
+//! A note is a Markdown file with an optional YAML frontmatter block that carries
+//! its identity (id, title, aliases, tags) and timestamps. Wiki links of the form
+//! `[[target]]` in the body are the raw material for the link graph.
+//! ノートはID・タイトル・エイリアス・タグ・タイムスタンプを保持する
+//! YAMLフロントマター付きのMarkdownファイルです。本文中の`[[target]]`形式の
+//! Wikiリンクがリンクグラフの素材となります。
+//!
+//! Tags are hierarchical: `project/zynapse/search` lies below `project/zynapse`
+//! and `project`, and a note tagged with it counts as tagged with both.
+//! タグは階層的です。`project/zynapse/search`は`project/zynapse`と`project`の
+//! 下位にあり、このタグを持つノートは両方のタグを持つものとして扱われます。
+
+use crate::{Result, ZynapseError};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Delimiter line that opens and closes a YAML frontmatter block
+/// YAMLフロントマターブロックの開始・終了区切り行
+const FRONTMATTER_DELIMITER: &str = "---";
+
+/// Metadata stored in the YAML frontmatter of a note
+/// ノートのYAMLフロントマターに保存されるメタデータ
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Frontmatter {
+    /// Unique note identifier
+    /// 一意のノート識別子
+    pub id: String,
+
+    /// Human-readable note title
+    /// 人間が読めるノートタイトル
+    pub title: String,
+
+    /// Alternative names the note can be linked by
+    /// ノートをリンクできる別名
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
+
+    /// Tags attached to the note (without leading `#`), with `/` between levels
+    /// ノートに付与されたタグ（先頭の`#`なし、階層は`/`で区切る）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+
+    /// Creation timestamp
+    /// 作成日時
+    pub created: DateTime<Utc>,
+
+    /// Last modification timestamp
+    /// 最終更新日時
+    pub updated: DateTime<Utc>,
+
+    /// Any additional user-defined fields, preserved verbatim
+    /// そのまま保持されるユーザー定義の追加フィールド
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, serde_yaml::Value>,
+}
+
+/// A single Markdown note
+/// 単一のMarkdownノート
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Note {
+    /// Path of the note file relative to the vault root
+    /// ボルトルートからのノートファイルの相対パス
+    pub path: PathBuf,
+
+    /// Parsed frontmatter
+    /// 解析済みフロントマター
+    pub frontmatter: Frontmatter,
+
+    /// Markdown body without the frontmatter block
+    /// フロントマターブロックを除いたMarkdown本文
+    pub body: String,
+}
+
+impl Note {
+    /// Create a new note with a fresh identifier and timestamps
+    /// 新しい識別子とタイムスタンプでノートを作成
+    ///
+    /// The file path is derived from the sanitized title.
+    /// ファイルパスはサニタイズされたタイトルから導出されます。
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use zynapse::note::Note;
+    ///
+    /// let note = Note::new("My First Note", "Hello");
+    /// assert_eq!(note.title(), "My First Note");
+    /// assert_eq!(note.path.to_str().unwrap(), "my-first-note.md");
+    /// ```
+    #[must_use]
+    pub fn new(title: impl Into<String>, body: impl Into<String>) -> Self {
+        let title = title.into();
+        let now = Utc::now();
+        let path = PathBuf::from(format!("{}.md", file_stem_for(&title)));
+
+        Self {
+            path,
+            frontmatter: Frontmatter {
+                id: generate_id(),
+                title,
+                aliases: Vec::new(),
+                tags: Vec::new(),
+                created: now,
+                updated: now,
+                extra: BTreeMap::new(),
+            },
+            body: body.into(),
+        }
+    }
+
+    /// Parse a note from its file content
+    /// ファイル内容からノートを解析
+    ///
+    /// Files without frontmatter, or with frontmatter that lacks some of the
+    /// required fields, are accepted: the identifier falls back to the file
+    /// stem, the title to the first heading (or the file stem) and the
+    /// timestamps to the current time.
+    /// フロントマターのないファイルや、必須フィールドの一部が欠けたフロントマターの
+    /// ファイルも受け付けます：識別子はファイル名、タイトルは最初の見出し
+    /// （またはファイル名）、タイムスタンプは現在時刻にフォールバックします。
+    ///
+    /// # Arguments
+    /// # 引数
+    ///
+    /// * `path` - Path relative to the vault root / ボルトルートからの相対パス
+    /// * `content` - Raw file content / ファイルの生の内容
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the frontmatter block is unterminated or is not valid YAML.
+    /// フロントマターブロックが閉じられていないか、有効なYAMLでない場合にエラーを返します。
+    pub fn parse(path: impl Into<PathBuf>, content: &str) -> Result<Self> {
+        Self::parse_at(path, content, Utc::now())
+    }
+
+    /// Parse a note, using `modified` for missing timestamps
+    /// ノートを解析（欠けているタイムスタンプには`modified`を使用）
+    ///
+    /// # Errors
+    ///
+    /// Same as [`Note::parse`].
+    /// [`Note::parse`]と同じです。
+    pub(crate) fn parse_at(
+        path: impl Into<PathBuf>,
+        content: &str,
+        modified: DateTime<Utc>,
+    ) -> Result<Self> {
+        let path = path.into();
+        let content = crate::utils::normalize_line_endings(content);
+        let invalid = |e: serde_yaml::Error| {
+            ZynapseError::invalid_content(format!("Invalid frontmatter in {}: {e}", path.display()))
+        };
+
+        let (mut fields, body) = match split_frontmatter(&content)? {
+            Some((yaml, body)) => match serde_yaml::from_str(yaml).map_err(invalid)? {
+                serde_yaml::Value::Mapping(fields) => (fields, body.to_string()),
+                serde_yaml::Value::Null => (serde_yaml::Mapping::new(), body.to_string()),
+                _ => {
+                    return Err(ZynapseError::invalid_content(format!(
+                        "Invalid frontmatter in {}: expected a mapping",
+                        path.display()
+                    )))
+                }
+            },
+            None => (serde_yaml::Mapping::new(), content.clone()),
+        };
+
+        let stem = path.file_stem().map_or_else(
+            || "untitled".to_string(),
+            |s| s.to_string_lossy().into_owned(),
+        );
+        let title = first_heading(&body).unwrap_or_else(|| stem.clone());
+        let modified = serde_yaml::to_value(modified)?;
+        for (key, fallback) in [
+            ("id", stem.into()),
+            ("title", title.into()),
+            ("created", modified.clone()),
+            ("updated", modified),
+        ] {
+            if fields.get(key).map_or(true, serde_yaml::Value::is_null) {
+                fields.insert(key.into(), fallback);
+            }
+        }
+
+        Ok(Self {
+            frontmatter: serde_yaml::from_value(serde_yaml::Value::Mapping(fields))
+                .map_err(invalid)?,
+            path,
+            body,
+        })
+    }
+
+    /// Render the note back to Markdown with its frontmatter
+    /// フロントマター付きでノートをMarkdownに書き戻す
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the frontmatter cannot be serialized to YAML.
+    /// フロントマターをYAMLにシリアライズできない場合にエラーを返します。
+    pub fn to_markdown(&self) -> Result<String> {
+        let yaml = serde_yaml::to_string(&self.frontmatter)?;
+        Ok(format!(
+            "{FRONTMATTER_DELIMITER}\n{yaml}{FRONTMATTER_DELIMITER}\n{}",
+            self.body
+        ))
+    }
+
+    /// Replace the generated identifier, for notes of another
+    /// [`IdScheme`](crate::config::IdScheme)
+    /// 生成された識別子を置き換える（別の[`IdScheme`](crate::config::IdScheme)のノート用）
+    #[must_use]
+    pub fn with_id(mut self, id: impl Into<String>) -> Self {
+        self.frontmatter.id = id.into();
+        self
+    }
+
+    /// Note identifier
+    /// ノート識別子
+    #[must_use]
+    pub fn id(&self) -> &str {
+        &self.frontmatter.id
+    }
+
+    /// Note title
+    /// ノートタイトル
+    #[must_use]
+    pub fn title(&self) -> &str {
+        &self.frontmatter.title
+    }
+
+    /// File stem of the note path
+    /// ノートパスのファイル名（拡張子なし）
+    #[must_use]
+    pub fn stem(&self) -> String {
+        self.path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    }
+
+    /// Wiki link targets referenced from the note body
+    /// ノート本文から参照されるWikiリンクのターゲット
+    #[must_use]
+    pub fn links(&self) -> Vec<String> {
+        extract_wiki_links(&self.body)
+    }
+
+    /// Check whether a name refers to this note
+    /// 名前がこのノートを指しているかチェック
+    ///
+    /// The name is compared case-insensitively against the identifier, title,
+    /// aliases and file stem.
+    /// 名前は識別子・タイトル・エイリアス・ファイル名と大文字小文字を区別せずに比較されます。
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use zynapse::note::Note;
+    ///
+    /// let mut note = Note::new("Rust Ownership", "");
+    /// note.frontmatter.aliases.push("ownership".to_string());
+    /// assert!(note.is_named("rust ownership"));
+    /// assert!(note.is_named("Ownership"));
+    /// assert!(!note.is_named("borrowing"));
+    /// ```
+    #[must_use]
+    pub fn is_named(&self, name: &str) -> bool {
+        let name = name.trim();
+        self.id().eq_ignore_ascii_case(name)
+            || self.title().to_lowercase() == name.to_lowercase()
+            || self.stem().to_lowercase() == name.to_lowercase()
+            || self
+                .frontmatter
+                .aliases
+                .iter()
+                .any(|alias| alias.to_lowercase() == name.to_lowercase())
+    }
+
+    /// Check whether the note carries a tag or a tag below it
+    /// ノートがタグまたはその下位のタグを持つかチェック
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use zynapse::note::Note;
+    ///
+    /// let mut note = Note::new("Search", "");
+    /// note.frontmatter.tags.push("project/zynapse/search".to_string());
+    /// assert!(note.has_tag("project"));
+    /// assert!(note.has_tag("#Project/Zynapse"));
+    /// assert!(!note.has_tag("project/zyn"));
+    /// ```
+    #[must_use]
+    pub fn has_tag(&self, tag: &str) -> bool {
+        let tag = normalize_tag(tag);
+        self.frontmatter
+            .tags
+            .iter()
+            .any(|candidate| tag_is_within(candidate, &tag))
+    }
+
+    /// Mark the note as modified now
+    /// ノートを現在時刻で更新済みとしてマーク
+    pub fn touch(&mut self) {
+        self.frontmatter.updated = Utc::now();
+    }
+}
+
+/// Normalize a tag name: no leading `#`, no surrounding whitespace or slashes
+/// タグ名を正規化（先頭の`#`、前後の空白やスラッシュを除去）
+#[must_use]
+pub fn normalize_tag(name: &str) -> String {
+    name.trim()
+        .trim_start_matches('#')
+        .trim_matches('/')
+        .to_string()
+}
+
+/// Check whether `tag` is `ancestor` itself or lies below it, ignoring ASCII case
+/// `tag`が`ancestor`自身またはその下位にあるかチェック（ASCIIの大文字小文字は無視）
+#[must_use]
+pub fn tag_is_within(tag: &str, ancestor: &str) -> bool {
+    tag.len() >= ancestor.len()
+        && tag.is_char_boundary(ancestor.len())
+        && tag[..ancestor.len()].eq_ignore_ascii_case(ancestor)
+        && (tag.len() == ancestor.len() || tag[ancestor.len()..].starts_with('/'))
+}
+
+/// Every level of a hierarchical tag, from the top down, ending with the tag itself
+/// 階層タグの各レベル（上位から順に、最後はタグ自身）
+///
+/// # Examples
+///
+/// ```rust
+/// use zynapse::note::tag_ancestors;
+///
+/// let levels: Vec<&str> = tag_ancestors("project/zynapse/search").collect();
+/// assert_eq!(levels, ["project", "project/zynapse", "project/zynapse/search"]);
+/// ```
+pub fn tag_ancestors(tag: &str) -> impl Iterator<Item = &str> {
+    tag.match_indices('/')
+        .map(|(index, _)| &tag[..index])
+        .chain(std::iter::once(tag))
+}
+
+/// Generate a new unique note identifier
+/// 新しい一意のノート識別子を生成
+#[must_use]
+pub fn generate_id() -> String {
+    uuid::Uuid::new_v4().simple().to_string()
+}
+
+/// Derive a file stem from a note title
+/// ノートタイトルからファイル名を導出
+///
+/// Falls back to `untitled` when the title sanitizes to an empty string.
+/// タイトルのサニタイズ結果が空の場合は`untitled`にフォールバックします。
+#[must_use]
+pub fn file_stem_for(title: &str) -> String {
+    let stem = crate::utils::sanitize_filename(title);
+    if stem.is_empty() {
+        "untitled".to_string()
+    } else {
+        stem
+    }
+}
+
+/// Extract wiki link targets (`[[target]]`) from Markdown text
+/// Markdownテキストから Wikiリンクのターゲット（`[[target]]`）を抽出
+///
+/// Display text (`[[target|label]]`) and heading anchors (`[[target#heading]]`)
+/// are stripped so that only the target note name remains.
+/// 表示テキスト（`[[target|label]]`）と見出しアンカー（`[[target#heading]]`）は
+/// 取り除かれ、ターゲットのノート名のみが残ります。
+///
+/// # Examples
+///
+/// ```rust
+/// use zynapse::note::extract_wiki_links;
+///
+/// let links = extract_wiki_links("See [[Alpha]] and [[Beta|the second]] or [[Gamma#Intro]].");
+/// assert_eq!(links, vec!["Alpha", "Beta", "Gamma"]);
+/// ```
+#[must_use]
+pub fn extract_wiki_links(text: &str) -> Vec<String> {
+    let mut links = Vec::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find("[[") {
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("]]") else {
+            break;
+        };
+        let inner = &after[..end];
+        if !inner.contains('\n') {
+            let target = inner.split(['|', '#']).next().unwrap_or_default().trim();
+            if !target.is_empty() {
+                links.push(target.to_string());
+            }
+        }
+        rest = &after[end + 2..];
+    }
+
+    links
+}
+
+/// Split content into the frontmatter YAML and the remaining body
+/// 内容をフロントマターYAMLと残りの本文に分割
+pub(crate) fn split_frontmatter(content: &str) -> Result<Option<(&str, &str)>> {
+    let Some(after_open) = content
+        .strip_prefix(FRONTMATTER_DELIMITER)
+        .and_then(|rest| rest.strip_prefix('\n'))
+    else {
+        return Ok(None);
+    };
+
+    let mut offset = 0;
+    for line in after_open.split_inclusive('\n') {
+        if line.trim_end() == FRONTMATTER_DELIMITER {
+            let yaml = &after_open[..offset];
+            let body = &after_open[offset + line.len()..];
+            return Ok(Some((yaml, body)));
+        }
+        offset += line.len();
+    }
+
+    Err(ZynapseError::invalid_content(
+        "Frontmatter block is not terminated",
+    ))
+}
+
+/// Find the text of the first Markdown heading
+/// 最初のMarkdown見出しのテキストを探す
+fn first_heading(content: &str) -> Option<String> {
+    content.lines().find_map(|line| {
+        let trimmed = line.trim_start();
+        let text = trimmed.trim_start_matches('#');
+        if trimmed.starts_with('#') && text.starts_with(' ') && !text.trim().is_empty() {
+            Some(text.trim().to_string())
+        } else {
+            None
+        }
+    })
+}
+
+/// Resolve a path relative to the vault root, rejecting unsafe paths
+/// ボルトルートからの相対パスを解決し、安全でないパスを拒否
+///
+/// # Errors
+///
+/// Returns an error if the relative path fails [`crate::utils::validate_safe_path`].
+/// 相対パスが[`crate::utils::validate_safe_path`]を通過しない場合にエラーを返します。
+pub fn resolve_in_root(root: &Path, relative: &Path) -> Result<PathBuf> {
+    crate::utils::validate_safe_path(relative)?;
+    Ok(root.join(relative))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_note() {
+        let note = Note::new("Hello World", "body");
+        assert_eq!(note.title(), "Hello World");
+        assert_eq!(note.path, PathBuf::from("hello-world.md"));
+        assert_eq!(note.id().len(), 32);
+        assert_eq!(note.frontmatter.created, note.frontmatter.updated);
+    }
+
+    #[test]
+    fn test_roundtrip_markdown() {
+        let mut note = Note::new("Roundtrip", "# Roundtrip\n\nSome text\n");
+        note.frontmatter.tags.push("rust".to_string());
+        note.frontmatter.extra.insert(
+            "status".to_string(),
+            serde_yaml::Value::String("draft".to_string()),
+        );
+
+        let markdown = note.to_markdown().unwrap();
+        assert!(markdown.starts_with("---\n"));
+
+        let parsed = Note::parse("roundtrip.md", &markdown).unwrap();
+        assert_eq!(parsed, note);
+    }
+
+    #[test]
+    fn test_parse_without_frontmatter() {
+        let note = Note::parse("imported.md", "Intro line\n\n## Imported Title\n").unwrap();
+        assert_eq!(note.id(), "imported");
+        assert_eq!(note.title(), "Imported Title");
+        assert!(note.body.starts_with("Intro line"));
+
+        let untitled = Note::parse("plain.md", "no headings here").unwrap();
+        assert_eq!(untitled.title(), "plain");
+    }
+
+    #[test]
+    fn test_parse_partial_frontmatter() {
+        let note = Note::parse("obs.md", "---\ntags: [project]\n---\n# Observation\n").unwrap();
+        assert_eq!(note.id(), "obs");
+        assert_eq!(note.title(), "Observation");
+        assert_eq!(note.frontmatter.tags, vec!["project"]);
+        assert_eq!(note.body, "# Observation\n");
+
+        let created = "---\nid: x\ncreated: 2024-01-01T00:00:00Z\n---\n";
+        let note = Note::parse("dated.md", created).unwrap();
+        assert_eq!(note.id(), "x");
+        assert_eq!(note.title(), "dated");
+        assert_eq!(
+            note.frontmatter.created.to_rfc3339(),
+            "2024-01-01T00:00:00+00:00"
+        );
+
+        assert!(Note::parse("empty.md", "---\n---\nbody").is_ok());
+    }
+
+    #[test]
+    fn test_parse_invalid_frontmatter() {
+        assert!(Note::parse("a.md", "---\nid: x\n").is_err());
+        assert!(Note::parse("b.md", "---\n: [\n---\nbody").is_err());
+        assert!(Note::parse("c.md", "---\n- list\n---\nbody").is_err());
+        assert!(Note::parse("d.md", "---\ncreated: yesterday\n---\n").is_err());
+    }
+
+    #[test]
+    fn test_extract_wiki_links() {
+        let text =
+            "[[One]] text [[Two|label]] [[Three#part]] [[ ]] [[broken\nlink]] [[unterminated";
+        assert_eq!(extract_wiki_links(text), vec!["One", "Two", "Three"]);
+        assert!(extract_wiki_links("no links").is_empty());
+    }
+
+    #[test]
+    fn test_is_named() {
+        let mut note = Note::new("Zettelkasten Method", "");
+        note.frontmatter.aliases.push("ZK".to_string());
+        assert!(note.is_named("zettelkasten method"));
+        assert!(note.is_named("zettelkasten-method"));
+        assert!(note.is_named("zk"));
+        assert!(note.is_named(&note.id().to_uppercase()));
+        assert!(!note.is_named("other"));
+    }
+
+    #[test]
+    fn test_resolve_in_root() {
+        let root = Path::new("/vault");
+        assert_eq!(
+            resolve_in_root(root, Path::new("daily/2024-01-01.md")).unwrap(),
+            PathBuf::from("/vault/daily/2024-01-01.md")
+        );
+        assert!(resolve_in_root(root, Path::new("../escape.md")).is_err());
+    }
+}
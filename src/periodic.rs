@@ -0,0 +1,400 @@
+//! Daily, weekly and monthly periodic notes
+//! 日次・週次・月次の定期ノート
+//!
+//! Each period maps a calendar date to exactly one note, stored in the folder and
+//! named with the format configured in [`PeriodicConfig`]. New period notes link
+//! to the previous and next period so the log becomes part of the link graph.
+//! 各期間はカレンダー日付を1つのノートに対応付け、[`PeriodicConfig`]で設定された
+//! フォルダと形式で保存されます。新しい定期ノートは前後の期間へリンクするため、
+//! ログがリンクグラフの一部になります。
+
+use crate::config::{PeriodSettings, PeriodicConfig};
+use crate::note::{file_stem_for, resolve_in_root, Note};
+use crate::storage::{NoteStore, NOTE_EXTENSION};
+use crate::template::{Prompt, Template, TemplateContext};
+use crate::Result;
+use chrono::{Datelike, Days, Local, Months, NaiveDate, NaiveTime, TimeZone};
+use std::fmt::Write as _;
+use std::path::PathBuf;
+
+/// Kind of periodic note
+/// 定期ノートの種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Period {
+    /// One note per calendar day
+    /// カレンダー日ごとに1ノート
+    Daily,
+
+    /// One note per ISO week (Monday to Sunday)
+    /// ISO週（月曜〜日曜）ごとに1ノート
+    Weekly,
+
+    /// One note per calendar month
+    /// カレンダー月ごとに1ノート
+    Monthly,
+}
+
+impl Period {
+    /// Lowercase name of the period, also used as its tag
+    /// 期間の小文字名（タグとしても使用）
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Daily => "daily",
+            Self::Weekly => "weekly",
+            Self::Monthly => "monthly",
+        }
+    }
+
+    /// First day of the period containing `date`
+    /// `date`を含む期間の初日
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use chrono::NaiveDate;
+    /// use zynapse::periodic::Period;
+    ///
+    /// let date = NaiveDate::from_ymd_opt(2024, 5, 16).unwrap(); // Thursday
+    /// assert_eq!(Period::Weekly.start(date), NaiveDate::from_ymd_opt(2024, 5, 13).unwrap());
+    /// assert_eq!(Period::Monthly.start(date), NaiveDate::from_ymd_opt(2024, 5, 1).unwrap());
+    /// ```
+    #[must_use]
+    pub fn start(self, date: NaiveDate) -> NaiveDate {
+        match self {
+            Self::Daily => date,
+            Self::Weekly => date - Days::new(u64::from(date.weekday().num_days_from_monday())),
+            Self::Monthly => date.with_day(1).unwrap_or(date),
+        }
+    }
+
+    /// First day of the period following the one containing `date`
+    /// `date`を含む期間の次の期間の初日
+    #[must_use]
+    pub fn next(self, date: NaiveDate) -> NaiveDate {
+        let start = self.start(date);
+        match self {
+            Self::Daily => start + Days::new(1),
+            Self::Weekly => start + Days::new(7),
+            Self::Monthly => start + Months::new(1),
+        }
+    }
+
+    /// First day of the period preceding the one containing `date`
+    /// `date`を含む期間の前の期間の初日
+    #[must_use]
+    pub fn previous(self, date: NaiveDate) -> NaiveDate {
+        let start = self.start(date);
+        match self {
+            Self::Daily => start - Days::new(1),
+            Self::Weekly => start - Days::new(7),
+            Self::Monthly => start - Months::new(1),
+        }
+    }
+
+    /// Period and start date a periodic note was created for
+    /// 定期ノートが作成された期間と開始日
+    ///
+    /// This reads the `period` and `date` frontmatter fields written when the
+    /// note was created; other notes yield `None`.
+    /// ノート作成時に書き込まれる`period`と`date`のフロントマターフィールドを
+    /// 読み取ります。それ以外のノートでは`None`を返します。
+    #[must_use]
+    pub fn of(note: &Note) -> Option<(Self, NaiveDate)> {
+        let field = |key: &str| note.frontmatter.extra.get(key)?.as_str();
+        let period = match field("period")? {
+            "daily" => Self::Daily,
+            "weekly" => Self::Weekly,
+            "monthly" => Self::Monthly,
+            _ => return None,
+        };
+        let date = field("date")?.parse().ok()?;
+        Some((period, date))
+    }
+
+    /// Settings for this period from the configuration
+    /// 設定からこの期間の設定を取得
+    #[must_use]
+    pub const fn settings(self, config: &PeriodicConfig) -> &PeriodSettings {
+        match self {
+            Self::Daily => &config.daily,
+            Self::Weekly => &config.weekly,
+            Self::Monthly => &config.monthly,
+        }
+    }
+}
+
+/// Result of opening a periodic note
+/// 定期ノートを開いた結果
+#[derive(Debug, Clone)]
+pub struct PeriodicNote {
+    /// The opened or newly created note
+    /// 開いた、または新規作成されたノート
+    pub note: Note,
+
+    /// Whether the note was created by this call
+    /// この呼び出しでノートが作成されたかどうか
+    pub created: bool,
+}
+
+/// Periodic note manager bound to a store and configuration
+/// ストアと設定に紐づく定期ノートマネージャー
+#[derive(Debug, Clone, Copy)]
+pub struct PeriodicNotes<'a> {
+    /// Note store holding the vault
+    /// ボルトを保持するノートストア
+    store: &'a NoteStore,
+
+    /// Periodic notes configuration
+    /// 定期ノート設定
+    config: &'a PeriodicConfig,
+}
+
+impl<'a> PeriodicNotes<'a> {
+    /// Create a manager for the given store and configuration
+    /// 指定されたストアと設定のマネージャーを作成
+    #[must_use]
+    pub const fn new(store: &'a NoteStore, config: &'a PeriodicConfig) -> Self {
+        Self { store, config }
+    }
+
+    /// Title of the note for the period containing `date`
+    /// `date`を含む期間のノートタイトル
+    #[must_use]
+    pub fn title(&self, period: Period, date: NaiveDate) -> String {
+        let settings = period.settings(self.config);
+        period.start(date).format(&settings.format).to_string()
+    }
+
+    /// Vault-relative path of the note for the period containing `date`
+    /// `date`を含む期間のノートのボルト相対パス
+    #[must_use]
+    pub fn path(&self, period: Period, date: NaiveDate) -> PathBuf {
+        let settings = period.settings(self.config);
+        let stem = file_stem_for(&self.title(period, date));
+        settings.folder.join(format!("{stem}.{NOTE_EXTENSION}"))
+    }
+
+    /// Open the note for the period containing `date`, creating it if needed
+    /// `date`を含む期間のノートを開き、必要に応じて作成
+    ///
+    /// `prompt` answers any `{{prompt:...}}` placeholders in the period template.
+    /// `prompt`は定期テンプレート内の`{{prompt:...}}`プレースホルダに回答します。
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the existing note cannot be loaded, the template
+    /// cannot be read or rendered, or the new note cannot be written.
+    /// 既存ノートを読み込めない、テンプレートを読み込めないかレンダリングできない、
+    /// または新しいノートを書き込めない場合にエラーを返します。
+    pub fn open_or_create(
+        &self,
+        period: Period,
+        date: NaiveDate,
+        prompt: &mut Prompt<'_>,
+    ) -> Result<PeriodicNote> {
+        let path = self.path(period, date);
+        if self.store.exists(&path) {
+            return Ok(PeriodicNote {
+                note: self.store.load(&path)?,
+                created: false,
+            });
+        }
+
+        let note = self.build(period, date, path, prompt)?;
+        self.store.create(&note)?;
+        log::info!("Created {} note {}", period.name(), note.path.display());
+
+        Ok(PeriodicNote {
+            note,
+            created: true,
+        })
+    }
+
+    /// Build a new periodic note from the template or the default layout
+    /// テンプレートまたはデフォルトレイアウトから新しい定期ノートを構築
+    fn build(
+        &self,
+        period: Period,
+        date: NaiveDate,
+        path: PathBuf,
+        prompt: &mut Prompt<'_>,
+    ) -> Result<Note> {
+        let start = period.start(date);
+        let title = self.title(period, start);
+        let prev = self.title(period, period.previous(start));
+        let next = self.title(period, period.next(start));
+
+        let id = self.store.new_id();
+        let mut note = match &period.settings(self.config).template {
+            Some(template) => {
+                let mut context = TemplateContext::new(&title, id)
+                    .with_variable("prev", &prev)
+                    .with_variable("next", &next);
+                if let Some(timestamp) = Local
+                    .from_local_datetime(&start.and_time(NaiveTime::MIN))
+                    .earliest()
+                {
+                    context = context.at(timestamp);
+                }
+                self.read_template(template)?
+                    .instantiate(&context, prompt)?
+            }
+            None => Note::new(&title, format!("# {title}\n")).with_id(id),
+        };
+
+        // Guarantee the navigation links even when a template omits them
+        // テンプレートが省略した場合でもナビゲーションリンクを保証
+        let links = note.links();
+        if !links.contains(&prev) || !links.contains(&next) {
+            if !note.body.is_empty() && !note.body.ends_with('\n') {
+                note.body.push('\n');
+            }
+            let _ = write!(note.body, "\n← [[{prev}]] | [[{next}]] →\n");
+        }
+
+        note.path = path;
+        if !note.frontmatter.tags.iter().any(|tag| tag == period.name()) {
+            note.frontmatter.tags.push(period.name().to_string());
+        }
+        note.frontmatter.extra.insert(
+            "period".to_string(),
+            serde_yaml::Value::String(period.name().to_string()),
+        );
+        note.frontmatter.extra.insert(
+            "date".to_string(),
+            serde_yaml::Value::String(start.to_string()),
+        );
+        Ok(note)
+    }
+
+    /// Read a template file, resolving relative paths against the vault root
+    /// テンプレートファイルを読み込み、相対パスはボルトルート基準で解決
+    fn read_template(&self, template: &std::path::Path) -> Result<Template> {
+        let path = if template.is_absolute() {
+            template.to_path_buf()
+        } else {
+            resolve_in_root(self.store.root(), template)?
+        };
+        Template::from_file(&path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[allow(clippy::unnecessary_wraps)]
+    fn no_prompt(_: &str, default: Option<&str>) -> Result<String> {
+        Ok(default.unwrap_or_default().to_string())
+    }
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_period_navigation() {
+        let day = date(2024, 3, 1);
+        assert_eq!(Period::Daily.previous(day), date(2024, 2, 29));
+        assert_eq!(Period::Daily.next(day), date(2024, 3, 2));
+
+        assert_eq!(Period::Weekly.start(date(2024, 3, 3)), date(2024, 2, 26));
+        assert_eq!(Period::Weekly.next(day), date(2024, 3, 4));
+        assert_eq!(Period::Weekly.previous(day), date(2024, 2, 19));
+
+        assert_eq!(
+            Period::Monthly.previous(date(2024, 1, 31)),
+            date(2023, 12, 1)
+        );
+        assert_eq!(Period::Monthly.next(date(2024, 1, 31)), date(2024, 2, 1));
+    }
+
+    #[test]
+    fn test_titles_and_paths() {
+        let store = NoteStore::new("/vault", 1024);
+        let config = PeriodicConfig::default();
+        let notes = PeriodicNotes::new(&store, &config);
+        let day = date(2024, 12, 30);
+
+        assert_eq!(notes.title(Period::Daily, day), "2024-12-30");
+        assert_eq!(notes.title(Period::Weekly, day), "2025-W01");
+        assert_eq!(notes.title(Period::Monthly, day), "2024-12");
+        assert_eq!(
+            notes.path(Period::Weekly, day),
+            PathBuf::from("weekly/2025-w01.md")
+        );
+    }
+
+    #[test]
+    fn test_open_or_create_links_neighbours() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = NoteStore::new(temp_dir.path(), 1024 * 1024);
+        let config = PeriodicConfig::default();
+        let notes = PeriodicNotes::new(&store, &config);
+
+        let first = notes
+            .open_or_create(Period::Daily, date(2024, 5, 16), &mut no_prompt)
+            .unwrap();
+        assert!(first.created);
+        assert_eq!(first.note.title(), "2024-05-16");
+        assert_eq!(first.note.links(), vec!["2024-05-15", "2024-05-17"]);
+        assert_eq!(first.note.frontmatter.tags, vec!["daily"]);
+        assert_eq!(
+            Period::of(&first.note),
+            Some((Period::Daily, date(2024, 5, 16)))
+        );
+        assert_eq!(Period::of(&Note::new("Plain", "")), None);
+
+        let again = notes
+            .open_or_create(Period::Daily, date(2024, 5, 16), &mut no_prompt)
+            .unwrap();
+        assert!(!again.created);
+        assert_eq!(again.note.id(), first.note.id());
+    }
+
+    #[test]
+    fn test_template_rendering() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(
+            temp_dir.path().join("daily.md"),
+            "---\ntags: [journal]\n---\n# Log {{title}}\nStarted {{date}} ({{date:%A}})\n",
+        )
+        .unwrap();
+        std::fs::write(
+            temp_dir.path().join("linked.md"),
+            "Back: [[{{prev}}]] Forward: [[{{next}}]]\n",
+        )
+        .unwrap();
+        let store = NoteStore::new(temp_dir.path(), 1024 * 1024);
+        let mut config = PeriodicConfig::default();
+        config.daily.template = Some(PathBuf::from("daily.md"));
+        config.monthly.template = Some(PathBuf::from("linked.md"));
+        let notes = PeriodicNotes::new(&store, &config);
+
+        let note = notes
+            .open_or_create(Period::Daily, date(2024, 5, 16), &mut no_prompt)
+            .unwrap()
+            .note;
+        assert!(note
+            .body
+            .starts_with("# Log 2024-05-16\nStarted 2024-05-16 (Thursday)\n"));
+        assert_eq!(note.links(), vec!["2024-05-15", "2024-05-17"]);
+        assert_eq!(note.frontmatter.tags, vec!["journal", "daily"]);
+
+        // Templates that already link both neighbours are left as-is
+        // 前後両方にリンク済みのテンプレートはそのまま
+        let monthly = notes
+            .open_or_create(Period::Monthly, date(2024, 5, 16), &mut no_prompt)
+            .unwrap()
+            .note;
+        assert_eq!(monthly.body, "Back: [[2024-04]] Forward: [[2024-06]]\n");
+
+        config.weekly.template = Some(PathBuf::from("missing.md"));
+        let notes = PeriodicNotes::new(&store, &config);
+        assert!(notes
+            .open_or_create(Period::Weekly, date(2024, 5, 16), &mut no_prompt)
+            .is_err());
+    }
+}
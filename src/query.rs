@@ -0,0 +1,300 @@
+//! Note selection queries
+//! ノート選択クエリ
+//!
+//! A query is a whitespace-separated list of terms that must all match. Terms
+//! prefixed with `-` are negated, and double quotes group words into one term.
+//! クエリは空白区切りの条件の並びで、すべてが一致する必要があります。
+//! `-`で始まる条件は否定され、ダブルクォートで複数の単語を1つの条件にまとめます。
+//!
+//! | Term | Matches |
+//! |------|---------|
+//! | `word` | title or body contains `word` (case-insensitive) |
+//! | `tag:name` | note has the tag `name` or a tag below it (`name/...`) |
+//! | `title:text` | title contains `text` |
+//! | `path:prefix` | vault-relative path starts with `prefix` |
+//! | `key:value` | frontmatter field `key` equals (or contains) `value` |
+//! | `has:key` | frontmatter field `key` is present |
+//! | `*` | every note |
+
+use crate::note::Note;
+use crate::{Result, ZynapseError};
+use std::fmt;
+use std::str::FromStr;
+
+/// A parsed note query
+/// 解析済みのノートクエリ
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Query {
+    /// Terms that must all match
+    /// すべて一致する必要がある条件
+    terms: Vec<Term>,
+
+    /// Original query text
+    /// 元のクエリ文字列
+    source: String,
+}
+
+/// A single query condition
+/// 単一のクエリ条件
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Term {
+    /// Condition to test
+    /// 判定する条件
+    kind: TermKind,
+
+    /// Whether the condition is negated
+    /// 条件が否定されているか
+    negated: bool,
+}
+
+/// Kinds of query conditions
+/// クエリ条件の種類
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TermKind {
+    /// Matches every note
+    /// すべてのノートに一致
+    All,
+
+    /// Lowercased text searched in title and body
+    /// タイトルと本文で検索する小文字化されたテキスト
+    Text(String),
+
+    /// Tag name without the leading `#`
+    /// 先頭の`#`を除いたタグ名
+    Tag(String),
+
+    /// Lowercased text searched in the title
+    /// タイトルで検索する小文字化されたテキスト
+    Title(String),
+
+    /// Vault-relative path prefix
+    /// ボルトからの相対パスの接頭辞
+    Path(String),
+
+    /// Frontmatter field and expected value
+    /// フロントマターのフィールドと期待値
+    Field(String, String),
+
+    /// Frontmatter field that must be present
+    /// 存在する必要があるフロントマターのフィールド
+    Has(String),
+}
+
+impl Query {
+    /// Parse a query string
+    /// クエリ文字列を解析
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query is empty, has an unterminated quote, or
+    /// contains a term with an empty key or value.
+    /// クエリが空、引用符が閉じられていない、またはキーや値が空の条件を含む場合に
+    /// エラーを返します。
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use zynapse::query::Query;
+    ///
+    /// let query = Query::parse(r#"tag:project -status:done "meeting notes""#).unwrap();
+    /// assert_eq!(query.to_string(), r#"tag:project -status:done "meeting notes""#);
+    /// assert!(Query::parse("   ").is_err());
+    /// ```
+    pub fn parse(input: &str) -> Result<Self> {
+        let terms = tokenize(input)?
+            .into_iter()
+            .map(|token| Term::parse(&token))
+            .collect::<Result<Vec<_>>>()?;
+
+        if terms.is_empty() {
+            return Err(ZynapseError::invalid_content(
+                "Empty query; use `*` to select every note",
+            ));
+        }
+
+        Ok(Self {
+            terms,
+            source: input.trim().to_string(),
+        })
+    }
+
+    /// Check whether a note satisfies every term
+    /// ノートがすべての条件を満たすかチェック
+    #[must_use]
+    pub fn matches(&self, note: &Note) -> bool {
+        self.terms.iter().all(|term| term.matches(note))
+    }
+}
+
+impl FromStr for Query {
+    type Err = ZynapseError;
+
+    fn from_str(input: &str) -> Result<Self> {
+        Self::parse(input)
+    }
+}
+
+impl fmt::Display for Query {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+impl Term {
+    /// Parse a single token into a term
+    /// 単一のトークンを条件に解析
+    fn parse(token: &str) -> Result<Self> {
+        let (negated, token) = match token.strip_prefix('-') {
+            Some(rest) if !rest.is_empty() => (true, rest),
+            _ => (false, token),
+        };
+
+        let kind = if token == "*" {
+            TermKind::All
+        } else if let Some((key, value)) = token.split_once(':') {
+            if key.is_empty() || value.is_empty() {
+                return Err(ZynapseError::invalid_content(format!(
+                    "Invalid query term `{token}`: expected `key:value`"
+                )));
+            }
+            match key.to_lowercase().as_str() {
+                "tag" => TermKind::Tag(value.trim_start_matches('#').to_string()),
+                "title" => TermKind::Title(value.to_lowercase()),
+                "path" => TermKind::Path(value.to_string()),
+                "has" => TermKind::Has(value.to_string()),
+                _ => TermKind::Field(key.to_string(), value.to_string()),
+            }
+        } else {
+            TermKind::Text(token.to_lowercase())
+        };
+
+        Ok(Self { kind, negated })
+    }
+
+    /// Evaluate the term against a note
+    /// ノートに対して条件を評価
+    fn matches(&self, note: &Note) -> bool {
+        let frontmatter = &note.frontmatter;
+        let matched = match &self.kind {
+            TermKind::All => true,
+            TermKind::Text(text) => {
+                note.title().to_lowercase().contains(text)
+                    || note.body.to_lowercase().contains(text)
+            }
+            TermKind::Tag(tag) => note.has_tag(tag),
+            TermKind::Title(text) => note.title().to_lowercase().contains(text),
+            TermKind::Path(prefix) => note.path.to_string_lossy().starts_with(prefix.as_str()),
+            TermKind::Has(key) => frontmatter.extra.contains_key(key),
+            TermKind::Field(key, value) => frontmatter
+                .extra
+                .get(key)
+                .is_some_and(|field| value_matches(field, value)),
+        };
+        matched != self.negated
+    }
+}
+
+/// Compare a frontmatter value with query text
+/// フロントマターの値とクエリテキストを比較
+///
+/// Scalars are compared case-insensitively; sequences match if any item does.
+/// スカラーは大文字小文字を区別せずに比較し、シーケンスはいずれかの要素が一致すれば一致します。
+fn value_matches(field: &serde_yaml::Value, expected: &str) -> bool {
+    use serde_yaml::Value;
+    match field {
+        Value::String(text) => text.eq_ignore_ascii_case(expected),
+        Value::Bool(flag) => flag.to_string() == expected.to_lowercase(),
+        Value::Number(number) => number.to_string() == expected,
+        Value::Sequence(items) => items.iter().any(|item| value_matches(item, expected)),
+        Value::Tagged(tagged) => value_matches(&tagged.value, expected),
+        Value::Null | Value::Mapping(_) => false,
+    }
+}
+
+/// Split a query into tokens, honouring double quotes
+/// ダブルクォートを考慮してクエリをトークンに分割
+fn tokenize(input: &str) -> Result<Vec<String>> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+
+    for c in input.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+
+    if quoted {
+        return Err(ZynapseError::invalid_content(format!(
+            "Unterminated quote in query: {input}"
+        )));
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn sample() -> Note {
+        let mut note = Note::new("Weekly Meeting", "Discussed the roadmap\n");
+        note.path = PathBuf::from("work/weekly-meeting.md");
+        note.frontmatter.tags = vec!["project/zynapse".to_string(), "Meeting".to_string()];
+        note.frontmatter
+            .extra
+            .insert("status".to_string(), "draft".into());
+        note.frontmatter
+            .extra
+            .insert("priority".to_string(), 2.into());
+        note
+    }
+
+    fn matches(query: &str) -> bool {
+        Query::parse(query).unwrap().matches(&sample())
+    }
+
+    #[test]
+    fn test_term_kinds() {
+        assert!(matches("*"));
+        assert!(matches("roadmap"));
+        assert!(matches("tag:#meeting"));
+        assert!(matches("tag:project tag:Project/Zynapse"));
+        assert!(matches("title:weekly"));
+        assert!(matches("path:work/"));
+        assert!(matches("status:DRAFT priority:2"));
+        assert!(matches("has:status"));
+
+        assert!(!matches("tag:personal"));
+        assert!(!matches("tag:project/zyn"));
+        assert!(!matches("tag:zynapse"));
+        assert!(!matches("title:roadmap"));
+        assert!(!matches("path:archive/"));
+        assert!(!matches("has:due"));
+    }
+
+    #[test]
+    fn test_negation_and_quotes() {
+        assert!(matches("-status:done tag:project"));
+        assert!(!matches("-tag:project"));
+        assert!(matches(r#""the roadmap""#));
+        assert!(!matches(r#""roadmap the""#));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(Query::parse("").is_err());
+        assert!(Query::parse("tag:").is_err());
+        assert!(Query::parse(":value").is_err());
+        assert!(Query::parse(r#""open"#).is_err());
+    }
+}
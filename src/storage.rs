@@ -0,0 +1,501 @@
+//! File-based note storage
+//! ファイルベースのノートストレージ
+//!
+//! Notes live as Markdown files below the vault root (`storage.root_path`).
+//! The store is deliberately stateless: every call reads from disk, so external
+//! edits made in other editors are always visible.
+//! ノートはボルトルート（`storage.root_path`）以下のMarkdownファイルとして保存されます。
+//! ストアは意図的にステートレスで、すべての呼び出しがディスクから読み込むため、
+//! 他のエディタでの外部編集が常に反映されます。
+
+use crate::config::{IdScheme, StorageConfig};
+use crate::note::{resolve_in_root, Note};
+use crate::{Result, ZynapseError};
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// File extension used for notes
+/// ノートに使用されるファイル拡張子
+pub const NOTE_EXTENSION: &str = "md";
+
+/// File-based note store rooted at a vault directory
+/// ボルトディレクトリをルートとするファイルベースのノートストア
+#[derive(Debug, Clone)]
+pub struct NoteStore {
+    /// Vault root directory
+    /// ボルトルートディレクトリ
+    root: PathBuf,
+
+    /// Maximum allowed note size in bytes
+    /// 許可される最大ノートサイズ（バイト）
+    max_file_size: u64,
+
+    /// Identifier scheme of new notes
+    /// 新しいノートの識別子の形式
+    id_scheme: IdScheme,
+}
+
+impl NoteStore {
+    /// Create a store for the given root directory
+    /// 指定ルートディレクトリのストアを作成
+    #[must_use]
+    pub fn new(root: impl Into<PathBuf>, max_file_size: u64) -> Self {
+        Self {
+            root: root.into(),
+            max_file_size,
+            id_scheme: IdScheme::default(),
+        }
+    }
+
+    /// Open the store described by the storage configuration
+    /// ストレージ設定に記述されたストアを開く
+    ///
+    /// Creates the vault root directory if it does not exist yet.
+    /// ボルトルートディレクトリが存在しない場合は作成します。
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the root directory cannot be created.
+    /// ルートディレクトリを作成できない場合にエラーを返します。
+    pub fn open(config: &StorageConfig) -> Result<Self> {
+        crate::utils::ensure_directory_exists(&config.root_path)?;
+        Ok(Self {
+            id_scheme: config.id_scheme,
+            ..Self::new(&config.root_path, config.max_file_size)
+        })
+    }
+
+    /// Generate the identifier of a new note in the configured scheme
+    /// 設定された形式で新しいノートの識別子を生成
+    #[must_use]
+    pub fn new_id(&self) -> String {
+        self.id_scheme.generate()
+    }
+
+    /// Vault root directory
+    /// ボルトルートディレクトリ
+    #[must_use]
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Absolute path of a note file
+    /// ノートファイルの絶対パス
+    #[must_use]
+    pub fn absolute_path(&self, note: &Note) -> PathBuf {
+        self.root.join(&note.path)
+    }
+
+    /// Check whether a note file exists at the relative path
+    /// 相対パスにノートファイルが存在するかチェック
+    #[must_use]
+    pub fn exists(&self, relative: &Path) -> bool {
+        resolve_in_root(&self.root, relative).is_ok_and(|path| path.is_file())
+    }
+
+    /// List all notes in the vault
+    /// ボルト内のすべてのノートを一覧表示
+    ///
+    /// Hidden files and directories (starting with `.`) are skipped. Notes are
+    /// returned sorted by path. Files that cannot be loaded, such as notes with
+    /// broken front matter or over `max_file_size`, are skipped with a warning
+    /// so one bad file does not hide the rest of the vault; `zynapse doctor`
+    /// lists them.
+    /// 隠しファイル・ディレクトリ（`.`で始まるもの）はスキップされます。
+    /// ノートはパス順にソートされて返されます。フロントマターが壊れている、
+    /// `max_file_size`を超えるなど読み込めないファイルは、1つの不正なファイルで
+    /// ボルトの残りが見えなくならないよう警告付きでスキップされます。
+    /// `zynapse doctor`がそれらを一覧表示します。
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the vault cannot be traversed.
+    /// ボルトを走査できない場合にエラーを返します。
+    pub fn list(&self) -> Result<Vec<Note>> {
+        Ok(self
+            .paths()?
+            .iter()
+            .filter_map(|relative| match self.load(relative) {
+                Ok(note) => Some(note),
+                Err(e) => {
+                    log::warn!("Skipping {}: {e}", relative.display());
+                    None
+                }
+            })
+            .collect())
+    }
+
+    /// Paths of all note files relative to the vault root, without reading them
+    /// 読み込まずに取得する、ボルトルートからの相対パスによるすべてのノートファイルのパス
+    ///
+    /// Hidden files and directories are skipped as in [`NoteStore::list`].
+    /// [`NoteStore::list`]と同様に隠しファイル・ディレクトリはスキップされます。
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the vault cannot be traversed.
+    /// ボルトを走査できない場合にエラーを返します。
+    pub fn paths(&self) -> Result<Vec<PathBuf>> {
+        let mut paths = Vec::new();
+
+        let walker = walkdir::WalkDir::new(&self.root)
+            .sort_by_file_name()
+            .into_iter()
+            .filter_entry(|entry| entry.depth() == 0 || !is_hidden(entry.file_name()));
+
+        for entry in walker {
+            let entry = entry.map_err(|e| ZynapseError::storage_error("list notes", e))?;
+            let path = entry.path();
+            if entry.file_type().is_file()
+                && path.extension().is_some_and(|ext| ext == NOTE_EXTENSION)
+            {
+                paths.push(crate::utils::relative_path(&self.root, path));
+            }
+        }
+
+        Ok(paths)
+    }
+
+    /// Copy the Markdown files of another folder into the vault
+    /// 別のフォルダのMarkdownファイルをボルトにコピー
+    ///
+    /// Files keep their path relative to `source` and their content, so notes
+    /// without an identifier in their front matter keep their file stem as
+    /// identifier. Files that
+    /// already exist in the vault are left alone. Returns the paths of the
+    /// copied notes.
+    /// ファイルは`source`からの相対パスと内容を保つため、フロントマターに識別子の
+    /// ないノートはファイル名を識別子として保ちます。ボルトに既に存在するファイルは
+    /// 変更しません。コピーしたノートのパスを返します。
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `source` cannot be traversed or a file cannot be
+    /// copied.
+    /// `source`を走査できない場合、またはファイルをコピーできない場合にエラーを
+    /// 返します。
+    pub fn import(&self, source: &Path) -> Result<Vec<PathBuf>> {
+        let mut imported = Vec::new();
+        for relative in Self::new(source, self.max_file_size).paths()? {
+            let target = self.root.join(&relative);
+            if target.exists() {
+                continue;
+            }
+            if let Some(parent) = target.parent() {
+                crate::utils::ensure_directory_exists(parent)?;
+            }
+            std::fs::copy(source.join(&relative), &target)
+                .map_err(|e| ZynapseError::storage_error("import notes", e))?;
+            imported.push(relative);
+        }
+        Ok(imported)
+    }
+
+    /// Load a single note by its path relative to the vault root
+    /// ボルトルートからの相対パスで単一のノートを読み込み
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// 以下の場合にエラーを返します：
+    /// - The path is unsafe or the file does not exist
+    /// - The file exceeds `max_file_size`
+    /// - The content cannot be parsed
+    pub fn load(&self, relative: &Path) -> Result<Note> {
+        let path = resolve_in_root(&self.root, relative)?;
+        if !path.is_file() {
+            return Err(ZynapseError::note_not_found(relative.display().to_string()));
+        }
+
+        let metadata = std::fs::metadata(&path)
+            .map_err(|e| ZynapseError::io_error(e, format!("Failed to stat {}", path.display())))?;
+        if metadata.len() > self.max_file_size {
+            return Err(ZynapseError::invalid_content(format!(
+                "{} exceeds the maximum file size of {}",
+                relative.display(),
+                crate::utils::format_file_size(self.max_file_size)
+            )));
+        }
+
+        let content = std::fs::read_to_string(&path)
+            .map_err(|e| ZynapseError::io_error(e, format!("Failed to read {}", path.display())))?;
+        // Notes without timestamps in their frontmatter date from their file
+        // フロントマターにタイムスタンプのないノートはファイルの日時を使う
+        let modified = metadata
+            .modified()
+            .map_or_else(|_| chrono::Utc::now(), chrono::DateTime::from);
+        Note::parse_at(relative, &content, modified)
+    }
+
+    /// Find a note by identifier, title, alias or file stem
+    /// 識別子・タイトル・エイリアス・ファイル名でノートを検索
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ZynapseError::NoteNotFound`] if no note matches, or any error
+    /// from [`NoteStore::list`].
+    /// 一致するノートがない場合は[`ZynapseError::NoteNotFound`]を、
+    /// または[`NoteStore::list`]からのエラーを返します。
+    pub fn find(&self, name: &str) -> Result<Note> {
+        self.list()?
+            .into_iter()
+            .find(|note| note.is_named(name))
+            .ok_or_else(|| ZynapseError::note_not_found(name))
+    }
+
+    /// Write a new note to disk
+    /// 新しいノートをディスクに書き込み
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a file already exists at the note path, or if the
+    /// note cannot be written.
+    /// ノートパスにファイルが既に存在する場合、またはノートを書き込めない場合に
+    /// エラーを返します。
+    pub fn create(&self, note: &Note) -> Result<()> {
+        if self.exists(&note.path) {
+            return Err(ZynapseError::storage_error(
+                "create note",
+                io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    format!("{} already exists", note.path.display()),
+                ),
+            ));
+        }
+        self.write(note)
+    }
+
+    /// Save an existing note, updating its modification time
+    /// 既存のノートを保存し、更新日時を更新
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the note cannot be serialized or written.
+    /// ノートをシリアライズまたは書き込みできない場合にエラーを返します。
+    pub fn save(&self, note: &mut Note) -> Result<()> {
+        note.touch();
+        self.write(note)
+    }
+
+    /// Delete a note file
+    /// ノートファイルを削除
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the path is unsafe or the file cannot be removed.
+    /// パスが安全でないか、ファイルを削除できない場合にエラーを返します。
+    pub fn delete(&self, note: &Note) -> Result<()> {
+        let path = resolve_in_root(&self.root, &note.path)?;
+        std::fs::remove_file(&path)
+            .map_err(|e| ZynapseError::storage_error(format!("delete {}", note.path.display()), e))
+    }
+
+    /// Save a note under a new vault-relative path and remove the old file
+    /// ノートを新しいボルト相対パスに保存し、古いファイルを削除
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a file already exists at the new path, or if the
+    /// note cannot be written or the old file cannot be removed.
+    /// 新しいパスにファイルが既に存在する場合、ノートを書き込めない場合、
+    /// または古いファイルを削除できない場合にエラーを返します。
+    pub fn relocate(&self, note: &mut Note, to: PathBuf) -> Result<()> {
+        let old = Note {
+            path: std::mem::replace(&mut note.path, to),
+            ..note.clone()
+        };
+        note.touch();
+        if let Err(error) = self.create(note) {
+            note.path = old.path;
+            return Err(error);
+        }
+        self.delete(&old)
+    }
+
+    /// Serialize and write a note, creating parent directories as needed
+    /// ノートをシリアライズして書き込み、必要に応じて親ディレクトリを作成
+    fn write(&self, note: &Note) -> Result<()> {
+        let path = resolve_in_root(&self.root, &note.path)?;
+        let content = note.to_markdown()?;
+
+        if content.len() as u64 > self.max_file_size {
+            return Err(ZynapseError::invalid_content(format!(
+                "{} exceeds the maximum file size of {}",
+                note.path.display(),
+                crate::utils::format_file_size(self.max_file_size)
+            )));
+        }
+
+        if let Some(parent) = path.parent() {
+            crate::utils::ensure_directory_exists(parent)?;
+        }
+        std::fs::write(&path, content)
+            .map_err(|e| ZynapseError::storage_error(format!("write {}", note.path.display()), e))
+    }
+}
+
+/// Check whether a file name denotes a hidden entry
+/// ファイル名が隠しエントリを表すかチェック
+fn is_hidden(name: &std::ffi::OsStr) -> bool {
+    name.to_string_lossy().starts_with('.')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn test_store() -> (TempDir, NoteStore) {
+        let temp_dir = TempDir::new().unwrap();
+        let store = NoteStore::new(temp_dir.path(), 1024 * 1024);
+        (temp_dir, store)
+    }
+
+    #[test]
+    fn test_create_and_load() {
+        let (_temp_dir, store) = test_store();
+        let note = Note::new("First Note", "Hello\n");
+
+        store.create(&note).unwrap();
+        assert!(store.exists(&note.path));
+
+        let loaded = store.load(&note.path).unwrap();
+        assert_eq!(loaded, note);
+
+        // Creating the same path twice is rejected
+        // 同じパスへの二重作成は拒否される
+        assert!(store.create(&note).is_err());
+    }
+
+    #[test]
+    fn test_list_skips_hidden_and_non_markdown() {
+        let (temp_dir, store) = test_store();
+        let mut nested = Note::new("Nested", "");
+        nested.path = PathBuf::from("folder/nested.md");
+        store.create(&Note::new("Top", "")).unwrap();
+        store.create(&nested).unwrap();
+
+        std::fs::create_dir_all(temp_dir.path().join(".hidden")).unwrap();
+        std::fs::write(temp_dir.path().join(".hidden/secret.md"), "x").unwrap();
+        std::fs::write(temp_dir.path().join("readme.txt"), "x").unwrap();
+
+        let titles: Vec<String> = store
+            .list()
+            .unwrap()
+            .iter()
+            .map(|n| n.title().to_string())
+            .collect();
+        assert_eq!(titles, vec!["Nested", "Top"]);
+    }
+
+    #[test]
+    fn test_find() {
+        let (_temp_dir, store) = test_store();
+        let mut note = Note::new("Synapse Strength", "");
+        note.frontmatter.aliases.push("strength".to_string());
+        store.create(&note).unwrap();
+
+        assert_eq!(store.find("synapse strength").unwrap().id(), note.id());
+        assert_eq!(store.find("STRENGTH").unwrap().id(), note.id());
+        assert!(matches!(
+            store.find("missing"),
+            Err(ZynapseError::NoteNotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn test_save_updates_timestamp_and_delete() {
+        let (_temp_dir, store) = test_store();
+        let mut note = Note::new("Editable", "v1");
+        store.create(&note).unwrap();
+        let created = note.frontmatter.updated;
+
+        note.body = "v2".to_string();
+        store.save(&mut note).unwrap();
+        assert!(note.frontmatter.updated >= created);
+        assert_eq!(store.load(&note.path).unwrap().body, "v2");
+
+        store.delete(&note).unwrap();
+        assert!(!store.exists(&note.path));
+    }
+
+    #[test]
+    fn test_max_file_size() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = NoteStore::new(temp_dir.path(), 64);
+        let note = Note::new("Big", "x".repeat(128));
+        assert!(store.create(&note).is_err());
+    }
+
+    #[test]
+    fn test_list_skips_unloadable_notes() {
+        let (temp_dir, store) = test_store();
+        store.create(&Note::new("Good", "body")).unwrap();
+        std::fs::write(temp_dir.path().join("broken.md"), "---\nid: [\n---\n").unwrap();
+        std::fs::write(temp_dir.path().join("big.md"), "x".repeat(2 * 1024 * 1024)).unwrap();
+
+        let notes = store.list().unwrap();
+        assert_eq!(notes.len(), 1);
+        assert_eq!(notes[0].title(), "Good");
+        assert_eq!(store.find("good").unwrap().title(), "Good");
+    }
+
+    #[test]
+    fn test_unsafe_paths_rejected() {
+        let (_temp_dir, store) = test_store();
+        let mut note = Note::new("Escape", "");
+        note.path = PathBuf::from("../escape.md");
+        assert!(store.create(&note).is_err());
+        assert!(store.load(Path::new("../escape.md")).is_err());
+    }
+
+    #[test]
+    fn test_import_copies_markdown_files() {
+        let (temp_dir, store) = test_store();
+        let source = TempDir::new().unwrap();
+        std::fs::create_dir_all(source.path().join("projects")).unwrap();
+        std::fs::write(source.path().join("plain.md"), "# Plain\n").unwrap();
+        std::fs::write(source.path().join("projects/idea.md"), "An idea\n").unwrap();
+        std::fs::write(source.path().join("image.png"), "x").unwrap();
+        std::fs::write(
+            source.path().join("obs.md"),
+            "---\ntags: [project]\n---\nSeen\n",
+        )
+        .unwrap();
+        std::fs::write(temp_dir.path().join("plain.md"), "# Kept\n").unwrap();
+
+        let imported = store.import(source.path()).unwrap();
+        assert_eq!(
+            imported,
+            [PathBuf::from("obs.md"), PathBuf::from("projects/idea.md")]
+        );
+        assert_eq!(store.find("idea").unwrap().body, "An idea\n");
+        assert_eq!(store.list().unwrap().len(), 3);
+
+        let partial = store.find("obs").unwrap();
+        assert_eq!(partial.frontmatter.tags, vec!["project"]);
+        let modified = std::fs::metadata(temp_dir.path().join("obs.md"))
+            .unwrap()
+            .modified()
+            .unwrap();
+        assert_eq!(
+            partial.frontmatter.updated,
+            chrono::DateTime::<chrono::Utc>::from(modified)
+        );
+        assert_eq!(store.load(Path::new("plain.md")).unwrap().title(), "Kept");
+    }
+
+    #[test]
+    fn test_new_id_follows_scheme() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = StorageConfig {
+            root_path: temp_dir.path().to_path_buf(),
+            id_scheme: IdScheme::Timestamp,
+            ..StorageConfig::default()
+        };
+        let id = NoteStore::open(&config).unwrap().new_id();
+        assert_eq!(id.len(), 14);
+        assert!(id.chars().all(|c| c.is_ascii_digit()));
+        assert_eq!(NoteStore::new(temp_dir.path(), 1024).new_id().len(), 32);
+    }
+}
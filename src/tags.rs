@@ -0,0 +1,222 @@
+//! Tree of hierarchical tags
+//! 階層タグのツリー
+//!
+//! Tags such as `project/zynapse/search` are split at `/` into a tree whose
+//! nodes count the notes tagged with the node or anything below it. Tags that
+//! differ only in ASCII case share a node, spelled as first seen.
+//! `project/zynapse/search`のようなタグは`/`で分割されてツリーになり、各ノードは
+//! そのノードまたは下位のタグを持つノートの数を数えます。ASCIIの大文字小文字
+//! だけが異なるタグは、最初に見つかった表記の1つのノードにまとめられます。
+
+use crate::note::{normalize_tag, tag_ancestors, Note};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// One level of a hierarchical tag
+/// 階層タグの1つのレベル
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagNode {
+    /// Full tag, e.g. `project/zynapse`
+    /// 完全なタグ（例：`project/zynapse`）
+    pub path: String,
+
+    /// Number of levels above the node
+    /// ノードより上のレベル数
+    pub depth: usize,
+
+    /// Number of notes tagged with the node or a tag below it
+    /// ノードまたはその下位のタグを持つノートの数
+    pub notes: usize,
+
+    /// Whether other tags lie below the node
+    /// ノードの下位に他のタグがあるかどうか
+    pub has_children: bool,
+}
+
+impl TagNode {
+    /// Last level of the tag, e.g. `zynapse` for `project/zynapse`
+    /// タグの最後のレベル（例：`project/zynapse`なら`zynapse`）
+    #[must_use]
+    pub fn name(&self) -> &str {
+        self.path.rsplit('/').next().unwrap_or(&self.path)
+    }
+}
+
+/// Tags of a vault in depth-first order, siblings sorted by name
+/// 深さ優先順のボルトのタグ（兄弟は名前順）
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TagTree {
+    nodes: Vec<TagNode>,
+}
+
+impl TagTree {
+    /// Build the tree of the tags used by `notes`
+    /// `notes`で使われているタグのツリーを構築
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use zynapse::note::Note;
+    /// use zynapse::tags::TagTree;
+    ///
+    /// let mut search = Note::new("Search", "");
+    /// search.frontmatter.tags.push("project/zynapse/search".to_string());
+    /// let mut tui = Note::new("TUI", "");
+    /// tui.frontmatter.tags.push("project/zynapse/tui".to_string());
+    ///
+    /// let tree = TagTree::build(&[search, tui]);
+    /// let counts: Vec<(&str, usize)> = tree
+    ///     .nodes()
+    ///     .iter()
+    ///     .map(|node| (node.path.as_str(), node.notes))
+    ///     .collect();
+    /// assert_eq!(
+    ///     counts,
+    ///     [
+    ///         ("project", 2),
+    ///         ("project/zynapse", 2),
+    ///         ("project/zynapse/search", 1),
+    ///         ("project/zynapse/tui", 1),
+    ///     ]
+    /// );
+    /// ```
+    #[must_use]
+    pub fn build(notes: &[Note]) -> Self {
+        // Keyed by lowercase levels so that children follow their parent
+        // 子が親の直後に続くよう小文字のレベルの列をキーにする
+        let mut levels: BTreeMap<Vec<String>, (String, BTreeSet<usize>)> = BTreeMap::new();
+        for (index, note) in notes.iter().enumerate() {
+            for tag in &note.frontmatter.tags {
+                let tag = normalize_tag(tag);
+                if tag.is_empty() {
+                    continue;
+                }
+                for level in tag_ancestors(&tag) {
+                    let key = level.split('/').map(str::to_lowercase).collect();
+                    levels
+                        .entry(key)
+                        .or_insert_with(|| (level.to_string(), BTreeSet::new()))
+                        .1
+                        .insert(index);
+                }
+            }
+        }
+
+        let mut nodes: Vec<TagNode> = levels
+            .into_iter()
+            .map(|(key, (path, notes))| TagNode {
+                path,
+                depth: key.len() - 1,
+                notes: notes.len(),
+                has_children: false,
+            })
+            .collect();
+        for index in 1..nodes.len() {
+            if nodes[index].depth > nodes[index - 1].depth {
+                nodes[index - 1].has_children = true;
+            }
+        }
+        Self { nodes }
+    }
+
+    /// All nodes, parents before their children
+    /// すべてのノード（親は子より前）
+    #[must_use]
+    pub fn nodes(&self) -> &[TagNode] {
+        &self.nodes
+    }
+
+    /// Index of the node of a tag, ignoring ASCII case
+    /// タグのノードのインデックス（ASCIIの大文字小文字は無視）
+    #[must_use]
+    pub fn position(&self, tag: &str) -> Option<usize> {
+        let tag = normalize_tag(tag);
+        self.nodes
+            .iter()
+            .position(|node| node.path.eq_ignore_ascii_case(&tag))
+    }
+
+    /// Index of the parent node
+    /// 親ノードのインデックス
+    #[must_use]
+    pub fn parent(&self, index: usize) -> Option<usize> {
+        let depth = self.nodes.get(index)?.depth.checked_sub(1)?;
+        self.nodes[..index]
+            .iter()
+            .rposition(|node| node.depth == depth)
+    }
+
+    /// Indices of the nodes not hidden below a collapsed node
+    /// 折りたたまれたノードの下に隠れていないノードのインデックス
+    ///
+    /// `collapsed` holds lowercase tag paths.
+    /// `collapsed`は小文字のタグパスを保持します。
+    #[must_use]
+    pub fn visible(&self, collapsed: &BTreeSet<String>) -> Vec<usize> {
+        let mut visible = Vec::new();
+        let mut hidden_below: Option<usize> = None;
+        for (index, node) in self.nodes.iter().enumerate() {
+            match hidden_below {
+                Some(depth) if node.depth > depth => continue,
+                _ => hidden_below = None,
+            }
+            visible.push(index);
+            if node.has_children && collapsed.contains(&node.path.to_lowercase()) {
+                hidden_below = Some(node.depth);
+            }
+        }
+        visible
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tagged(tags: &[&str]) -> Note {
+        let mut note = Note::new("Tagged", "");
+        note.frontmatter.tags = tags.iter().map(ToString::to_string).collect();
+        note
+    }
+
+    #[test]
+    fn test_tree_order_counts_and_collapsing() {
+        let notes = [
+            tagged(&["a/b", "a-b", "#A/c/"]),
+            tagged(&["a/B/x"]),
+            tagged(&["z", "a/b"]),
+        ];
+        let tree = TagTree::build(&notes);
+        let summary = tree
+            .nodes()
+            .iter()
+            .map(|node| {
+                (
+                    node.path.as_str(),
+                    node.depth,
+                    node.notes,
+                    node.has_children,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            [
+                ("a", 0, 3, true),
+                ("a/b", 1, 3, true),
+                ("a/B/x", 2, 1, false),
+                ("A/c", 1, 1, false),
+                ("a-b", 0, 1, false),
+                ("z", 0, 1, false),
+            ]
+        );
+        assert_eq!(tree.nodes()[2].name(), "x");
+        assert_eq!(tree.position("#A/B"), Some(1));
+        assert_eq!(tree.parent(2), Some(1));
+        assert_eq!(tree.parent(4), None);
+
+        let collapsed = BTreeSet::from(["a/b".to_string()]);
+        assert_eq!(tree.visible(&collapsed), [0, 1, 3, 4, 5]);
+        let collapsed = BTreeSet::from(["a".to_string(), "z".to_string()]);
+        assert_eq!(tree.visible(&collapsed), [0, 4, 5]);
+    }
+}
@@ -0,0 +1,571 @@
+//! Note templates with variable substitution
+//! 変数置換付きのノートテンプレート
+//!
+//! Templates are Markdown files stored in the vault's templates directory
+//! (`storage.templates_dir`). A template may start with a frontmatter block whose
+//! `tags`, `aliases` and custom fields are copied into every note created from it.
+//! テンプレートはボルトのテンプレートディレクトリ（`storage.templates_dir`）に保存された
+//! Markdownファイルです。テンプレート先頭のフロントマターの`tags`・`aliases`・
+//! カスタムフィールドは、そこから作成されるすべてのノートにコピーされます。
+//!
+//! # Placeholders / プレースホルダ
+//!
+//! - `{{title}}`, `{{id}}`: title and identifier of the new note
+//! - `{{date}}`, `{{time}}`, `{{datetime}}`: creation date/time
+//! - `{{date:FORMAT}}`: creation date in a custom `strftime` format
+//! - `{{uuid}}`: a freshly generated UUID
+//! - `{{prompt:Question}}` / `{{prompt:Question|default}}`: ask the user
+//!
+//! Unknown placeholders are left untouched. In the frontmatter, values are
+//! substituted after the YAML is parsed, so they need no quoting; a
+//! placeholder that makes up a whole value becomes a number or boolean when
+//! its text is one.
+//! 未知のプレースホルダはそのまま残されます。フロントマターでは値がYAMLの解析後に
+//! 置換されるため、引用符は不要です。値全体を占めるプレースホルダは、その
+//! テキストが数値または真偽値であればその型になります。
+
+use crate::note::{split_frontmatter, Note};
+use crate::storage::NOTE_EXTENSION;
+use crate::{Result, ZynapseError};
+use chrono::{DateTime, Local};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Frontmatter keys owned by the note itself, which templates cannot set
+/// ノート自身が管理するため、テンプレートでは設定できないフロントマターキー
+const RESERVED_KEYS: &[&str] = &["id", "title", "created", "updated"];
+
+/// Callback used to ask the user for `{{prompt:...}}` values
+/// `{{prompt:...}}`の値をユーザーに尋ねるためのコールバック
+///
+/// Receives the question and the optional default value.
+/// 質問と任意のデフォルト値を受け取ります。
+pub type Prompt<'a> = dyn FnMut(&str, Option<&str>) -> Result<String> + 'a;
+
+/// Values available to placeholders while rendering
+/// レンダリング中にプレースホルダが利用できる値
+#[derive(Debug, Clone)]
+pub struct TemplateContext {
+    /// Title of the note being created
+    /// 作成中のノートのタイトル
+    pub title: String,
+
+    /// Identifier of the note being created
+    /// 作成中のノートの識別子
+    pub id: String,
+
+    /// Reference time for date placeholders
+    /// 日付プレースホルダの基準時刻
+    pub timestamp: DateTime<Local>,
+
+    /// Additional variables, taking precedence over the built-in ones
+    /// 組み込み変数より優先される追加変数
+    pub variables: BTreeMap<String, String>,
+}
+
+impl TemplateContext {
+    /// Create a context for a new note, using the current time
+    /// 現在時刻を使用して新しいノートのコンテキストを作成
+    #[must_use]
+    pub fn new(title: impl Into<String>, id: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            id: id.into(),
+            timestamp: Local::now(),
+            variables: BTreeMap::new(),
+        }
+    }
+
+    /// Use a different reference time for date placeholders
+    /// 日付プレースホルダに別の基準時刻を使用
+    #[must_use]
+    pub const fn at(mut self, timestamp: DateTime<Local>) -> Self {
+        self.timestamp = timestamp;
+        self
+    }
+
+    /// Add or override a variable
+    /// 変数を追加または上書き
+    #[must_use]
+    pub fn with_variable(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.variables.insert(name.into(), value.into());
+        self
+    }
+
+    /// Resolve a built-in or additional variable
+    /// 組み込み変数または追加変数を解決
+    fn resolve(&self, name: &str) -> Result<Option<String>> {
+        if let Some(value) = self.variables.get(name) {
+            return Ok(Some(value.clone()));
+        }
+
+        let value = match name {
+            "title" => self.title.clone(),
+            "id" => self.id.clone(),
+            "date" => self.timestamp.format("%Y-%m-%d").to_string(),
+            "time" => self.timestamp.format("%H:%M").to_string(),
+            "datetime" => self.timestamp.to_rfc3339(),
+            "uuid" => uuid::Uuid::new_v4().to_string(),
+            _ => match name.strip_prefix("date:") {
+                Some(format) => {
+                    use chrono::format::{Item, StrftimeItems};
+                    if StrftimeItems::new(format).any(|item| matches!(item, Item::Error)) {
+                        return Err(ZynapseError::invalid_content(format!(
+                            "Invalid date format in template placeholder: {format}"
+                        )));
+                    }
+                    self.timestamp.format(format).to_string()
+                }
+                None => return Ok(None),
+            },
+        };
+        Ok(Some(value))
+    }
+}
+
+/// A note template loaded from disk
+/// ディスクから読み込まれたノートテンプレート
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    /// Template name (file stem)
+    /// テンプレート名（ファイル名）
+    pub name: String,
+
+    /// Raw template content
+    /// テンプレートの生の内容
+    pub content: String,
+}
+
+impl Template {
+    /// Create a template from its name and content
+    /// 名前と内容からテンプレートを作成
+    #[must_use]
+    pub fn new(name: impl Into<String>, content: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            content: content.into(),
+        }
+    }
+
+    /// Read a template from a file
+    /// ファイルからテンプレートを読み込み
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read.
+    /// ファイルを読み込めない場合にエラーを返します。
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            ZynapseError::io_error(e, format!("Failed to read template: {}", path.display()))
+        })?;
+        let name = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        Ok(Self::new(name, content))
+    }
+
+    /// Render the template and build a new note from it
+    /// テンプレートをレンダリングし、新しいノートを構築
+    ///
+    /// The note receives the context's title and identifier; tags, aliases and
+    /// custom fields from the template frontmatter are merged in.
+    /// ノートはコンテキストのタイトルと識別子を受け取り、テンプレートの
+    /// フロントマターのタグ・エイリアス・カスタムフィールドがマージされます。
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a placeholder is invalid, the prompt fails, or the
+    /// template frontmatter is not a valid YAML mapping.
+    /// プレースホルダが無効、プロンプトが失敗、またはテンプレートの
+    /// フロントマターが有効なYAMLマッピングでない場合にエラーを返します。
+    pub fn instantiate(&self, context: &TemplateContext, prompt: &mut Prompt<'_>) -> Result<Note> {
+        let mut answers = BTreeMap::new();
+        let mut note = Note::new(context.title.clone(), String::new());
+        note.frontmatter.id.clone_from(&context.id);
+
+        let Some((yaml, body)) = split_frontmatter(&self.content)? else {
+            note.body = render_with(&self.content, context, prompt, &mut answers)?;
+            return Ok(note);
+        };
+
+        // Parse the frontmatter with stand-ins, so values cannot break the YAML
+        // 値がYAMLを壊さないよう、代わりの値を入れてフロントマターを解析する
+        let mut placeholders = Vec::new();
+        let masked = substitute(yaml, |raw, _| {
+            placeholders.push(raw.to_string());
+            Ok(stand_in(placeholders.len() - 1))
+        })?;
+        let values = placeholders
+            .iter()
+            .map(|raw| render_with(raw, context, prompt, &mut answers))
+            .collect::<Result<Vec<_>>>()?;
+        note.body = render_with(body, context, prompt, &mut answers)?;
+
+        let fields: BTreeMap<String, serde_yaml::Value> = if masked.trim().is_empty() {
+            BTreeMap::new()
+        } else {
+            serde_yaml::from_str(&masked).map_err(|e| {
+                ZynapseError::invalid_content(format!(
+                    "Invalid frontmatter in template {}: {e}",
+                    self.name
+                ))
+            })?
+        };
+
+        for (key, value) in fields {
+            let key = unmask_str(&key, &values);
+            let value = unmask(value, &values);
+            match key.as_str() {
+                "tags" => note.frontmatter.tags.extend(string_list(&value)),
+                "aliases" => note.frontmatter.aliases.extend(string_list(&value)),
+                key if RESERVED_KEYS.contains(&key) => {
+                    log::warn!(
+                        "Template {} sets reserved field `{key}`, ignoring",
+                        self.name
+                    );
+                }
+                _ => {
+                    note.frontmatter.extra.insert(key, value);
+                }
+            }
+        }
+
+        Ok(note)
+    }
+}
+
+/// Templates directory of a vault
+/// ボルトのテンプレートディレクトリ
+#[derive(Debug, Clone)]
+pub struct Templates {
+    /// Directory containing the template files
+    /// テンプレートファイルを含むディレクトリ
+    dir: PathBuf,
+}
+
+impl Templates {
+    /// Create a handle for the given templates directory
+    /// 指定されたテンプレートディレクトリのハンドルを作成
+    #[must_use]
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Templates directory path
+    /// テンプレートディレクトリのパス
+    #[must_use]
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Names of all available templates, sorted
+    /// 利用可能なすべてのテンプレート名（ソート済み）
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory exists but cannot be read.
+    /// ディレクトリが存在するが読み込めない場合にエラーを返します。
+    pub fn list(&self) -> Result<Vec<String>> {
+        if !self.dir.is_dir() {
+            return Ok(Vec::new());
+        }
+
+        let entries = std::fs::read_dir(&self.dir).map_err(|e| {
+            ZynapseError::io_error(
+                e,
+                format!("Failed to read templates directory: {}", self.dir.display()),
+            )
+        })?;
+
+        let mut names: Vec<String> = entries
+            .filter_map(std::result::Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == NOTE_EXTENSION))
+            .filter_map(|path| path.file_stem().map(|s| s.to_string_lossy().into_owned()))
+            .collect();
+        names.sort();
+        Ok(names)
+    }
+
+    /// Load a template by name
+    /// 名前でテンプレートを読み込み
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the name is unsafe or no such template exists.
+    /// 名前が安全でないか、該当するテンプレートが存在しない場合にエラーを返します。
+    pub fn load(&self, name: &str) -> Result<Template> {
+        let file = PathBuf::from(format!("{name}.{NOTE_EXTENSION}"));
+        let path = crate::note::resolve_in_root(&self.dir, &file)?;
+        if !path.is_file() {
+            return Err(ZynapseError::invalid_content(format!(
+                "Template `{name}` not found in {}",
+                self.dir.display()
+            )));
+        }
+        Template::from_file(&path)
+    }
+}
+
+/// Substitute all `{{...}}` placeholders in a template text
+/// テンプレートテキスト内のすべての`{{...}}`プレースホルダを置換
+///
+/// Each distinct prompt is asked only once, even if it appears several times.
+/// 同じプロンプトは複数回出現しても一度だけ尋ねられます。
+///
+/// # Errors
+///
+/// Returns an error if a `{{date:...}}` format is invalid or the prompt fails.
+/// `{{date:...}}`の形式が無効、またはプロンプトが失敗した場合にエラーを返します。
+///
+/// # Examples
+///
+/// ```rust
+/// use zynapse::template::{render, TemplateContext};
+///
+/// let context = TemplateContext::new("Reading List", "abc");
+/// let mut prompt = |_: &str, default: Option<&str>| Ok(default.unwrap_or("").to_string());
+/// let text = render("# {{title}} ({{prompt:Author|unknown}})", &context, &mut prompt)?;
+/// assert_eq!(text, "# Reading List (unknown)");
+/// # Ok::<(), zynapse::ZynapseError>(())
+/// ```
+pub fn render(text: &str, context: &TemplateContext, prompt: &mut Prompt<'_>) -> Result<String> {
+    render_with(text, context, prompt, &mut BTreeMap::new())
+}
+
+/// Substitute placeholders, reusing and recording prompt `answers`
+/// プレースホルダを置換し、プロンプトの`answers`を再利用・記録
+fn render_with(
+    text: &str,
+    context: &TemplateContext,
+    prompt: &mut Prompt<'_>,
+    answers: &mut BTreeMap<String, String>,
+) -> Result<String> {
+    substitute(text, |raw, name| {
+        if let Some(question) = name.strip_prefix("prompt:") {
+            let (question, default) = match question.split_once('|') {
+                Some((question, default)) => (question.trim(), Some(default.trim())),
+                None => (question.trim(), None),
+            };
+            if !answers.contains_key(question) {
+                let answer = prompt(question, default)?;
+                answers.insert(question.to_string(), answer);
+            }
+            Ok(answers[question].clone())
+        } else {
+            Ok(context.resolve(name)?.unwrap_or_else(|| raw.to_string()))
+        }
+    })
+}
+
+/// Replace every `{{...}}` in `text` with what `replace` returns for the raw
+/// placeholder and its trimmed name
+/// `text`内のすべての`{{...}}`を、生のプレースホルダとトリムした名前に対して
+/// `replace`が返す値で置き換える
+fn substitute(text: &str, mut replace: impl FnMut(&str, &str) -> Result<String>) -> Result<String> {
+    let mut output = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        let raw = &rest[start..start + 2 + end + 2];
+        output.push_str(&rest[..start]);
+        output.push_str(&replace(raw, raw[2..raw.len() - 2].trim())?);
+        rest = &rest[start + raw.len()..];
+    }
+
+    output.push_str(rest);
+    Ok(output)
+}
+
+/// Stand-in for the `index`-th placeholder of a frontmatter block, a valid
+/// plain YAML scalar anywhere a placeholder can appear
+/// フロントマターブロックの`index`番目のプレースホルダの代わり（プレースホルダが
+/// 現れうるどこでも有効なYAMLのプレーンスカラー）
+fn stand_in(index: usize) -> String {
+    format!("__zynapse_placeholder_{index}__")
+}
+
+/// Put the rendered `values` back in place of their stand-ins
+/// 代わりの値をレンダリングされた`values`に戻す
+fn unmask(value: serde_yaml::Value, values: &[String]) -> serde_yaml::Value {
+    use serde_yaml::Value;
+
+    match value {
+        Value::String(text) => (0..values.len())
+            .find(|&index| text == stand_in(index))
+            .map_or_else(
+                || Value::String(unmask_str(&text, values)),
+                |index| match serde_yaml::from_str(&values[index]) {
+                    Ok(typed @ (Value::Number(_) | Value::Bool(_))) => typed,
+                    _ => Value::String(values[index].clone()),
+                },
+            ),
+        Value::Sequence(items) => items.into_iter().map(|item| unmask(item, values)).collect(),
+        Value::Mapping(fields) => Value::Mapping(
+            fields
+                .into_iter()
+                .map(|(key, value)| (unmask(key, values), unmask(value, values)))
+                .collect(),
+        ),
+        other => other,
+    }
+}
+
+/// Replace the stand-ins inside a string with the rendered `values`
+/// 文字列内の代わりの値をレンダリングされた`values`で置き換える
+fn unmask_str(text: &str, values: &[String]) -> String {
+    values
+        .iter()
+        .enumerate()
+        .fold(text.to_string(), |text, (index, value)| {
+            text.replace(&stand_in(index), value)
+        })
+}
+
+/// Interpret a YAML value as a list of strings
+/// YAML値を文字列のリストとして解釈
+fn string_list(value: &serde_yaml::Value) -> Vec<String> {
+    match value {
+        serde_yaml::Value::Sequence(items) => items
+            .iter()
+            .filter_map(|item| item.as_str().map(str::to_string))
+            .collect(),
+        serde_yaml::Value::String(item) => item
+            .split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .map(str::to_string)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use tempfile::TempDir;
+
+    #[allow(clippy::unnecessary_wraps)]
+    fn no_prompt(_: &str, default: Option<&str>) -> Result<String> {
+        Ok(default.unwrap_or_default().to_string())
+    }
+
+    fn context() -> TemplateContext {
+        TemplateContext::new("Meeting", "note-1")
+            .at(Local.with_ymd_and_hms(2024, 5, 16, 9, 30, 0).unwrap())
+    }
+
+    #[test]
+    fn test_render_builtin_variables() {
+        let text = render(
+            "{{title}}|{{id}}|{{date}}|{{time}}|{{date:%d/%m}}|{{ unknown }}",
+            &context(),
+            &mut no_prompt,
+        )
+        .unwrap();
+        assert_eq!(text, "Meeting|note-1|2024-05-16|09:30|16/05|{{ unknown }}");
+
+        let uuid = render("{{uuid}}", &context(), &mut no_prompt).unwrap();
+        assert!(uuid::Uuid::parse_str(&uuid).is_ok());
+
+        assert!(render("{{date:%Q}}", &context(), &mut no_prompt).is_err());
+    }
+
+    #[test]
+    fn test_render_variables_override_builtins() {
+        let context = context().with_variable("date", "custom");
+        assert_eq!(
+            render("{{date}} {{title}}", &context, &mut no_prompt).unwrap(),
+            "custom Meeting"
+        );
+    }
+
+    #[test]
+    fn test_render_prompts_once() {
+        let mut asked = Vec::new();
+        let mut prompt = |question: &str, _: Option<&str>| {
+            asked.push(question.to_string());
+            Ok("Alice".to_string())
+        };
+        let text = render(
+            "{{prompt:Attendee}} and {{ prompt:Attendee }} {{unterminated",
+            &context(),
+            &mut prompt,
+        )
+        .unwrap();
+        assert_eq!(text, "Alice and Alice {{unterminated");
+        assert_eq!(asked, vec!["Attendee"]);
+    }
+
+    #[test]
+    fn test_instantiate_with_frontmatter() {
+        let template = Template::new(
+            "meeting",
+            "---\ntags: [meeting, \"{{prompt:Project|zynapse}}\"]\nstatus: open\ntitle: ignored\n---\n# {{title}}\n",
+        );
+        let note = template.instantiate(&context(), &mut no_prompt).unwrap();
+
+        assert_eq!(note.id(), "note-1");
+        assert_eq!(note.title(), "Meeting");
+        assert_eq!(note.frontmatter.tags, vec!["meeting", "zynapse"]);
+        assert_eq!(
+            note.frontmatter.extra.get("status"),
+            Some(&serde_yaml::Value::String("open".to_string()))
+        );
+        assert!(!note.frontmatter.extra.contains_key("title"));
+        assert_eq!(note.body, "# Meeting\n");
+    }
+
+    #[test]
+    fn test_instantiate_escapes_frontmatter_values() {
+        let template = Template::new(
+            "design",
+            "---\nsubject: {{title}}\ntags: [{{prompt:Tag}}]\nsummary: \"{{title}} notes\"\npriority: {{prompt:Priority}}\n---\n# {{title}} {{prompt:Tag}}\n",
+        );
+        let context = TemplateContext::new("Design: v2 #b", "note-2");
+        let mut asked = Vec::new();
+        let mut prompt = |question: &str, _: Option<&str>| {
+            asked.push(question.to_string());
+            Ok(if question == "Tag" { "a: b" } else { "2" }.to_string())
+        };
+        let note = template.instantiate(&context, &mut prompt).unwrap();
+
+        assert_eq!(asked, vec!["Tag", "Priority"]);
+        let field = |key: &str| note.frontmatter.extra.get(key).cloned();
+        assert_eq!(field("subject"), Some("Design: v2 #b".into()));
+        assert_eq!(field("summary"), Some("Design: v2 #b notes".into()));
+        assert_eq!(field("priority"), Some(2.into()));
+        assert_eq!(note.frontmatter.tags, vec!["a: b"]);
+        assert_eq!(note.body, "# Design: v2 #b a: b\n");
+    }
+
+    #[test]
+    fn test_instantiate_without_frontmatter() {
+        let template = Template::new("plain", "Body for {{title}}");
+        let note = template.instantiate(&context(), &mut no_prompt).unwrap();
+        assert_eq!(note.body, "Body for Meeting");
+        assert!(note.frontmatter.tags.is_empty());
+    }
+
+    #[test]
+    fn test_templates_directory() {
+        let temp_dir = TempDir::new().unwrap();
+        let templates = Templates::new(temp_dir.path().join("missing"));
+        assert!(templates.list().unwrap().is_empty());
+
+        let templates = Templates::new(temp_dir.path());
+        std::fs::write(temp_dir.path().join("permanent.md"), "P").unwrap();
+        std::fs::write(temp_dir.path().join("literature.md"), "L").unwrap();
+        std::fs::write(temp_dir.path().join("notes.txt"), "x").unwrap();
+
+        assert_eq!(templates.list().unwrap(), vec!["literature", "permanent"]);
+        assert_eq!(templates.load("permanent").unwrap().content, "P");
+        assert!(templates.load("missing").is_err());
+        assert!(templates.load("../escape").is_err());
+    }
+}
@@ -0,0 +1,249 @@
+//! User-triggerable TUI actions
+//! ユーザーが実行できるTUIアクション
+
+use crate::{Result, ZynapseError};
+use std::fmt;
+use std::str::FromStr;
+
+/// Something the user can ask the TUI to do
+/// ユーザーがTUIに要求できる操作
+///
+/// Actions are named in `snake_case` in the `[tui.keybindings]` table.
+/// アクションは`[tui.keybindings]`テーブルで`snake_case`の名前で指定します。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Action {
+    /// Leave the TUI
+    /// TUIを終了
+    Quit,
+
+    /// Focus the search bar
+    /// 検索バーにフォーカス
+    Search,
+
+    /// Create a new note
+    /// 新しいノートを作成
+    NewNote,
+
+    /// Edit the selected note in the built-in editor
+    /// 選択中のノートを組み込みエディタで編集
+    Edit,
+
+    /// Edit the selected note in the external editor
+    /// 選択中のノートを外部エディタで編集
+    EditExternal,
+
+    /// Move the selection up
+    /// 選択を上に移動
+    Up,
+
+    /// Move the selection down
+    /// 選択を下に移動
+    Down,
+
+    /// Move left; between panes, or between nodes in the graph view
+    /// 左に移動（ペイン間、またはグラフビューのノード間）
+    Left,
+
+    /// Move right; between panes, or between nodes in the graph view
+    /// 右に移動（ペイン間、またはグラフビューのノード間）
+    Right,
+
+    /// Jump to the first entry
+    /// 最初の項目へ移動
+    Top,
+
+    /// Jump to the last entry
+    /// 最後の項目へ移動
+    Bottom,
+
+    /// Scroll one page up
+    /// 1ページ上にスクロール
+    PageUp,
+
+    /// Scroll one page down
+    /// 1ページ下にスクロール
+    PageDown,
+
+    /// Focus the next pane
+    /// 次のペインにフォーカス
+    FocusNext,
+
+    /// Focus the previous pane
+    /// 前のペインにフォーカス
+    FocusPrevious,
+
+    /// Select the next wiki link in the preview
+    /// プレビューの次のWikiリンクを選択
+    NextLink,
+
+    /// Select the previous wiki link in the preview
+    /// プレビューの前のWikiリンクを選択
+    PreviousLink,
+
+    /// Open the selected entry (e.g. follow a backlink or wiki link)
+    /// 選択中の項目を開く（バックリンクやWikiリンクをたどるなど）
+    Open,
+
+    /// Show or hide the link graph around the selected note
+    /// 選択中のノート周辺のリンクグラフを表示または非表示
+    Graph,
+
+    /// Show or hide the timeline of note activity
+    /// ノートの活動のタイムラインを表示または非表示
+    Timeline,
+
+    /// Show or hide the month calendar of note activity
+    /// ノートの活動の月カレンダーを表示または非表示
+    Calendar,
+
+    /// Show or hide the tag tree
+    /// タグツリーを表示または非表示
+    Tags,
+
+    /// Rename the tag selected in the tag tree across the vault
+    /// タグツリーで選択中のタグをボルト全体で名前変更
+    RenameTag,
+
+    /// Open the command palette
+    /// コマンドパレットを開く
+    Palette,
+
+    /// Reload notes from disk
+    /// ディスクからノートを再読み込み
+    Reload,
+
+    /// Stop the TUI and return to the shell until resumed with `fg`
+    /// TUIを停止し、`fg`で再開するまでシェルに戻る
+    Suspend,
+}
+
+impl Action {
+    /// Every action, in the order they are listed to the user
+    /// ユーザーに表示する順のすべてのアクション
+    pub const ALL: [Self; 26] = [
+        Self::Quit,
+        Self::Search,
+        Self::NewNote,
+        Self::Edit,
+        Self::EditExternal,
+        Self::Up,
+        Self::Down,
+        Self::Left,
+        Self::Right,
+        Self::Top,
+        Self::Bottom,
+        Self::PageUp,
+        Self::PageDown,
+        Self::FocusNext,
+        Self::FocusPrevious,
+        Self::NextLink,
+        Self::PreviousLink,
+        Self::Open,
+        Self::Graph,
+        Self::Timeline,
+        Self::Calendar,
+        Self::Tags,
+        Self::RenameTag,
+        Self::Palette,
+        Self::Reload,
+        Self::Suspend,
+    ];
+
+    /// Name used in the configuration
+    /// 設定で使用する名前
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Quit => "quit",
+            Self::Search => "search",
+            Self::NewNote => "new_note",
+            Self::Edit => "edit",
+            Self::EditExternal => "edit_external",
+            Self::Up => "up",
+            Self::Down => "down",
+            Self::Left => "left",
+            Self::Right => "right",
+            Self::Top => "top",
+            Self::Bottom => "bottom",
+            Self::PageUp => "page_up",
+            Self::PageDown => "page_down",
+            Self::FocusNext => "focus_next",
+            Self::FocusPrevious => "focus_previous",
+            Self::NextLink => "next_link",
+            Self::PreviousLink => "previous_link",
+            Self::Open => "open",
+            Self::Graph => "graph",
+            Self::Timeline => "timeline",
+            Self::Calendar => "calendar",
+            Self::Tags => "tags",
+            Self::RenameTag => "rename_tag",
+            Self::Palette => "palette",
+            Self::Reload => "reload",
+            Self::Suspend => "suspend",
+        }
+    }
+
+    /// Short human-readable description
+    /// 人が読める短い説明
+    #[must_use]
+    pub const fn description(self) -> &'static str {
+        match self {
+            Self::Quit => "Quit",
+            Self::Search => "Search notes",
+            Self::NewNote => "New note",
+            Self::Edit => "Edit note",
+            Self::EditExternal => "Edit note in external editor",
+            Self::Up => "Move up",
+            Self::Down => "Move down",
+            Self::Left => "Move left",
+            Self::Right => "Move right",
+            Self::Top => "Go to top",
+            Self::Bottom => "Go to bottom",
+            Self::PageUp => "Page up",
+            Self::PageDown => "Page down",
+            Self::FocusNext => "Focus next pane",
+            Self::FocusPrevious => "Focus previous pane",
+            Self::NextLink => "Select next link",
+            Self::PreviousLink => "Select previous link",
+            Self::Open => "Open selection",
+            Self::Graph => "Toggle graph view",
+            Self::Timeline => "Toggle timeline view",
+            Self::Calendar => "Toggle calendar view",
+            Self::Tags => "Toggle tag tree",
+            Self::RenameTag => "Rename tag",
+            Self::Palette => "Open command palette",
+            Self::Reload => "Reload notes",
+            Self::Suspend => "Suspend to the shell",
+        }
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Action {
+    type Err = ZynapseError;
+
+    fn from_str(name: &str) -> Result<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|action| action.name() == name)
+            .ok_or_else(|| ZynapseError::config_error(format!("Unknown TUI action `{name}`")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_names_round_trip() {
+        for action in Action::ALL {
+            assert_eq!(action.name().parse::<Action>().unwrap(), action);
+        }
+        assert!("jump".parse::<Action>().is_err());
+    }
+}
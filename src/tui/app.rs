@@ -0,0 +1,2006 @@
+//! TUI application state and input handling
+//! TUIアプリケーションの状態と入力処理
+//!
+//! The state is independent of the terminal so it can be driven by tests; the
+//! event loop feeds it key and mouse events and carries out the [`Effect`]s it
+//! requests.
+//! 状態はターミナルから独立しているためテストから操作できます。イベントループは
+//! キー・マウスイベントを渡し、要求された[`Effect`]を実行します。
+
+use super::action::Action;
+use super::editor::{Editor, Request};
+use super::graph::Graph;
+use super::keymap::{Keymap, Step};
+use super::markdown::{self, Document};
+use super::palette::{Entry, History, Outcome, Palette, Target};
+use super::theme::{ColorSupport, Role, Theme};
+use super::timeline::{self, Timeline, View};
+use crate::backup::Backups;
+use crate::bulk::{self, Edit, Plan};
+use crate::config::{Config, Reload};
+use crate::links::LinkIndex;
+use crate::note::{normalize_tag, Note};
+use crate::query::Query;
+use crate::storage::NoteStore;
+use crate::tags::{TagNode, TagTree};
+use crate::Result;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use ratatui::layout::Rect;
+use ratatui::text::Line;
+use ratatui::widgets::ListState;
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+/// How long status messages stay visible
+/// ステータスメッセージの表示時間
+const STATUS_TIMEOUT: Duration = Duration::from_secs(4);
+
+/// Lines scrolled by page-wise movement
+/// ページ単位の移動でスクロールする行数
+const PAGE_SIZE: isize = 10;
+
+/// Pane that receives navigation keys
+/// ナビゲーションキーを受け取るペイン
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Focus {
+    /// Tag tree, when shown
+    /// タグツリー（表示時）
+    Tags,
+
+    /// Note list
+    /// ノート一覧
+    List,
+
+    /// Preview of the selected note
+    /// 選択中のノートのプレビュー
+    Preview,
+
+    /// Backlinks of the selected note
+    /// 選択中のノートのバックリンク
+    Backlinks,
+}
+
+impl Focus {
+    /// Next pane in tab order
+    /// タブ順で次のペイン
+    const fn next(self) -> Self {
+        match self {
+            Self::Tags => Self::List,
+            Self::List => Self::Preview,
+            Self::Preview => Self::Backlinks,
+            Self::Backlinks => Self::Tags,
+        }
+    }
+
+    /// Previous pane in tab order
+    /// タブ順で前のペイン
+    const fn previous(self) -> Self {
+        match self {
+            Self::Tags => Self::Backlinks,
+            Self::List => Self::Tags,
+            Self::Preview => Self::List,
+            Self::Backlinks => Self::Preview,
+        }
+    }
+}
+
+/// Text input currently being edited, if any
+/// 編集中のテキスト入力
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Input {
+    /// Keys go to the panes
+    /// キーはペインに送られる
+    None,
+
+    /// Editing the search query
+    /// 検索クエリを編集中
+    Search,
+
+    /// Typing the title of a new note
+    /// 新しいノートのタイトルを入力中
+    NewNote,
+
+    /// Choosing an entry of the command palette
+    /// コマンドパレットの項目を選択中
+    Palette,
+
+    /// Typing the new name of the selected tag
+    /// 選択中のタグの新しい名前を入力中
+    RenameTag,
+
+    /// Confirming the planned tag rename
+    /// 計画されたタグ名の変更を確認中
+    ConfirmRenameTag,
+}
+
+/// Work the event loop has to carry out on behalf of the state
+/// 状態に代わってイベントループが実行する処理
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Effect {
+    /// Suspend the TUI and open a file in the external editor
+    /// TUIを一時停止し、外部エディタでファイルを開く
+    EditExternal(PathBuf),
+
+    /// Hand the terminal back to the shell and stop until continued
+    /// ターミナルをシェルに返し、再開されるまで停止
+    Suspend,
+}
+
+/// Screen areas of the panes from the last draw, used for mouse hit testing
+/// マウスの当たり判定に使う、前回描画時の各ペインの画面領域
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Areas {
+    /// Search bar
+    /// 検索バー
+    pub search: Rect,
+
+    /// Tag tree, empty while hidden
+    /// タグツリー（非表示の間は空）
+    pub tags: Rect,
+
+    /// Note list
+    /// ノート一覧
+    pub list: Rect,
+
+    /// Preview pane
+    /// プレビューペイン
+    pub preview: Rect,
+
+    /// Backlinks pane
+    /// バックリンクペイン
+    pub backlinks: Rect,
+
+    /// Graph view, replacing the panes while shown
+    /// グラフビュー（表示中はペインを置き換える）
+    pub graph: Rect,
+
+    /// Timeline list or calendar, replacing the panes while shown
+    /// タイムライン一覧またはカレンダー（表示中はペインを置き換える）
+    pub timeline: Rect,
+}
+
+/// Complete TUI state
+/// TUIの全状態
+#[derive(Debug)]
+pub struct App {
+    /// Loaded configuration
+    /// 読み込まれた設定
+    pub config: Config,
+
+    /// Store the notes are read from and written to
+    /// ノートの読み書き先ストア
+    pub store: NoteStore,
+
+    /// All notes of the vault
+    /// ボルトのすべてのノート
+    pub notes: Vec<Note>,
+
+    /// Resolved links between `notes`
+    /// `notes`間の解決済みリンク
+    pub links: LinkIndex,
+
+    /// Hierarchical tags of `notes`
+    /// `notes`の階層タグ
+    pub tags: TagTree,
+
+    /// Whether the tag tree is shown next to the note list
+    /// ノート一覧の横にタグツリーを表示するかどうか
+    pub show_tags: bool,
+
+    /// Selection within the visible tag tree nodes
+    /// 表示中のタグツリーノード内の選択
+    pub tags_state: ListState,
+
+    /// Lowercase paths of the collapsed tag tree nodes
+    /// 折りたたまれたタグツリーノードの小文字のパス
+    pub collapsed: BTreeSet<String>,
+
+    /// Indices into `notes` matching the search, in display order
+    /// 検索に一致する`notes`のインデックス（表示順）
+    pub filtered: Vec<usize>,
+
+    /// Selection within `filtered`
+    /// `filtered`内の選択
+    pub list_state: ListState,
+
+    /// Selection within the backlinks of the current note
+    /// 現在のノートのバックリンク内の選択
+    pub backlinks_state: ListState,
+
+    /// Vertical scroll offset of the preview
+    /// プレビューの垂直スクロール位置
+    pub preview_scroll: u16,
+
+    /// Rendered preview of the selected note
+    /// 選択中のノートの描画済みプレビュー
+    pub preview: Document,
+
+    /// Selected wiki link of the preview
+    /// プレビューで選択中のWikiリンク
+    pub preview_link: Option<usize>,
+
+    /// Built-in editor, shown in place of the preview while open
+    /// 組み込みエディタ（開いている間はプレビューの代わりに表示）
+    pub editor: Option<Editor>,
+
+    /// Link graph around the selected note, shown instead of the panes
+    /// 選択中のノート周辺のリンクグラフ（ペインの代わりに表示）
+    pub graph: Option<Graph>,
+
+    /// Timeline or calendar of note activity, shown instead of the panes
+    /// ノートの活動のタイムラインまたはカレンダー（ペインの代わりに表示）
+    pub timeline: Option<Timeline>,
+
+    /// Command palette, open while `input` is [`Input::Palette`]
+    /// コマンドパレット（`input`が[`Input::Palette`]の間は開いている）
+    pub palette: Option<Palette>,
+
+    /// Most recently used palette entries
+    /// 最近使用したパレットの項目
+    pub history: History,
+
+    /// Focused pane
+    /// フォーカス中のペイン
+    pub focus: Focus,
+
+    /// Active text input
+    /// アクティブなテキスト入力
+    pub input: Input,
+
+    /// Search query text
+    /// 検索クエリのテキスト
+    pub search: String,
+
+    /// Title typed for a new note
+    /// 新しいノート用に入力されたタイトル
+    pub new_title: String,
+
+    /// New name typed for the selected tag
+    /// 選択中のタグ用に入力された新しい名前
+    pub tag_name: String,
+
+    /// Planned tag rename waiting for confirmation
+    /// 確認待ちのタグ名変更の計画
+    pub rename_plan: Option<Plan>,
+
+    /// Message shown in the status line and whether it reports an error
+    /// ステータス行に表示するメッセージと、それがエラーかどうか
+    pub status: Option<(String, bool, Instant)>,
+
+    /// Pane areas from the last draw
+    /// 前回描画時のペイン領域
+    pub areas: Areas,
+
+    /// Set once the user asked to quit
+    /// ユーザーが終了を要求すると設定される
+    pub should_quit: bool,
+
+    /// Key bindings and the chord being typed
+    /// キーバインドと入力中のキーコード
+    pub keymap: Keymap,
+
+    /// Styles adapted to the terminal's colors
+    /// ターミナルの色に合わせたスタイル
+    pub theme: Theme,
+}
+
+impl App {
+    /// Load all notes and build the initial state
+    /// すべてのノートを読み込み、初期状態を構築
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key bindings or the theme are invalid, or the
+    /// notes cannot be listed.
+    /// キーバインドまたはテーマが無効な場合、あるいはノートを一覧表示できない場合に
+    /// エラーを返します。
+    pub fn new(config: Config, store: NoteStore) -> Result<Self> {
+        let keymap = Keymap::new(&config.tui.keybindings)?;
+        let theme = Theme::named(&config.tui.theme)?.degrade(ColorSupport::detect());
+        let mut app = Self {
+            config,
+            store,
+            notes: Vec::new(),
+            links: LinkIndex::default(),
+            tags: TagTree::default(),
+            show_tags: false,
+            tags_state: ListState::default(),
+            collapsed: BTreeSet::new(),
+            filtered: Vec::new(),
+            list_state: ListState::default(),
+            backlinks_state: ListState::default(),
+            preview_scroll: 0,
+            preview: Document::default(),
+            preview_link: None,
+            editor: None,
+            graph: None,
+            timeline: None,
+            palette: None,
+            history: History::default(),
+            focus: Focus::List,
+            input: Input::None,
+            search: String::new(),
+            new_title: String::new(),
+            tag_name: String::new(),
+            rename_plan: None,
+            status: None,
+            areas: Areas::default(),
+            should_quit: false,
+            keymap,
+            theme,
+        };
+        app.reload()?;
+        Ok(app)
+    }
+
+    /// Re-read the vault, keeping the selected note when it still exists
+    /// ボルトを再読み込みし、選択中のノートが存在すれば選択を維持
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the notes cannot be listed.
+    /// ノートを一覧表示できない場合にエラーを返します。
+    pub fn reload(&mut self) -> Result<()> {
+        let selected = self.selected_note().map(|note| note.path.clone());
+        let tag = self.selected_tag().map(|node| node.path.clone());
+        self.notes = self.store.list()?;
+        self.rebuild(selected, tag.as_deref());
+        Ok(())
+    }
+
+    /// Replace a saved note in memory without re-reading the vault
+    /// ボルトを再読み込みせずに、保存されたノートをメモリ上で置き換える
+    fn update_note(&mut self, note: Note) -> Result<()> {
+        let Some(index) = self.notes.iter().position(|known| known.path == note.path) else {
+            return self.reload();
+        };
+        let selected = self.selected_note().map(|current| current.path.clone());
+        let tag = self.selected_tag().map(|node| node.path.clone());
+        self.notes[index] = note;
+        self.rebuild(selected, tag.as_deref());
+        Ok(())
+    }
+
+    /// Rebuild the links, tags and views from `notes`, selecting `selected`
+    /// and `tag` again when they still exist
+    /// `notes`からリンク・タグ・ビューを再構築し、`selected`と`tag`が存在すれば
+    /// 再び選択
+    fn rebuild(&mut self, selected: Option<PathBuf>, tag: Option<&str>) {
+        self.links = LinkIndex::build(&self.notes);
+        self.tags = TagTree::build(&self.notes);
+        self.select_tag(tag);
+        self.apply_search();
+        if let Some(path) = selected {
+            self.select_path(&path);
+        }
+        if self.graph.is_some() {
+            self.show_graph();
+        }
+        if let Some(previous) = self.timeline.take() {
+            let mut timeline = Timeline::build(
+                &self.notes,
+                &chrono::Local,
+                chrono::Local::now().date_naive(),
+                previous.view,
+            );
+            timeline.day = previous.day;
+            timeline.offset = previous.offset;
+            timeline.selected = previous
+                .selected
+                .min(timeline.events.len().saturating_sub(1));
+            self.timeline = Some(timeline);
+        }
+    }
+
+    /// Index into `notes` of the selected note
+    /// 選択中のノートの`notes`内インデックス
+    #[must_use]
+    pub fn selected_index(&self) -> Option<usize> {
+        self.list_state
+            .selected()
+            .and_then(|position| self.filtered.get(position).copied())
+    }
+
+    /// The selected note
+    /// 選択中のノート
+    #[must_use]
+    pub fn selected_note(&self) -> Option<&Note> {
+        self.selected_index().map(|index| &self.notes[index])
+    }
+
+    /// Backlinks of the selected note
+    /// 選択中のノートのバックリンク
+    #[must_use]
+    pub fn backlinks(&self) -> &[usize] {
+        self.selected_index()
+            .map_or(&[], |index| self.links.backlinks(index))
+    }
+
+    /// Indices into the tag tree's nodes that are not collapsed away
+    /// 折りたたみで隠れていないタグツリーのノードのインデックス
+    #[must_use]
+    pub fn visible_tags(&self) -> Vec<usize> {
+        self.tags.visible(&self.collapsed)
+    }
+
+    /// The tag tree node selected in the tag tree
+    /// タグツリーで選択中のノード
+    #[must_use]
+    pub fn selected_tag(&self) -> Option<&TagNode> {
+        let position = self.tags_state.selected()?;
+        let index = *self.visible_tags().get(position)?;
+        self.tags.nodes().get(index)
+    }
+
+    /// Take over a reloaded configuration, reporting what needs a restart
+    /// 再読み込みした設定を引き継ぎ、再起動が必要な変更を報告
+    ///
+    /// A configuration that failed to load, or whose key bindings or theme
+    /// cannot be built, is reported and the running one is kept.
+    /// 読み込みに失敗した設定、またはキーバインドやテーマを構築できない設定は
+    /// 報告され、実行中の設定が維持されます。
+    pub fn apply_config(&mut self, loaded: Result<Config>) {
+        let reload = loaded.and_then(|loaded| {
+            let mut config = self.config.clone();
+            let reload = Reload::apply(&mut config, &loaded)?;
+            let keymap = Keymap::new(&config.tui.keybindings)?;
+            let theme = Theme::named(&config.tui.theme)?.degrade(ColorSupport::detect());
+            self.config = config;
+            self.keymap = keymap;
+            self.theme = theme;
+            Ok(reload)
+        });
+        match reload {
+            Ok(reload) if reload.is_empty() => {}
+            Ok(reload) if reload.rejected.is_empty() => self.notify(reload.to_string()),
+            Ok(reload) => self.report(reload.to_string()),
+            Err(error) => self.report(format!("Configuration not reloaded: {error}")),
+        }
+    }
+
+    /// Show a message in the status line
+    /// ステータス行にメッセージを表示
+    pub fn notify(&mut self, message: impl Into<String>) {
+        self.status = Some((message.into(), false, Instant::now()));
+    }
+
+    /// Show an error in the status line
+    /// ステータス行にエラーを表示
+    pub fn report(&mut self, message: impl Into<String>) {
+        self.status = Some((message.into(), true, Instant::now()));
+    }
+
+    /// Periodic housekeeping; returns whether a redraw is needed
+    /// 定期的な後処理。再描画が必要かどうかを返す
+    pub fn on_tick(&mut self) -> bool {
+        let interval = Duration::from_secs(self.config.storage.auto_save_interval);
+        if self
+            .editor
+            .as_ref()
+            .is_some_and(|editor| editor.autosave_due(interval))
+        {
+            self.save_editor("Auto-saved", false);
+            return true;
+        }
+        match &self.status {
+            Some((_, _, shown)) if shown.elapsed() >= STATUS_TIMEOUT => {
+                self.status = None;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Handle a key press
+    /// キー入力を処理
+    pub fn handle_key(&mut self, key: KeyEvent) -> Option<Effect> {
+        match self.input {
+            Input::Search => {
+                self.edit_search(key);
+                None
+            }
+            Input::NewNote => self.edit_new_title(key),
+            Input::Palette => self.edit_palette(key),
+            Input::RenameTag => {
+                self.edit_tag_name(key);
+                None
+            }
+            Input::ConfirmRenameTag => {
+                self.confirm_rename_tag(key);
+                None
+            }
+            Input::None if self.editor.is_some() => {
+                self.edit(key);
+                None
+            }
+            Input::None => {
+                let Step::Action(action) = self.keymap.feed(key) else {
+                    return None;
+                };
+                self.perform(action)
+            }
+        }
+    }
+
+    /// Carry out an action
+    /// アクションを実行
+    pub fn perform(&mut self, action: Action) -> Option<Effect> {
+        if self.graph.is_some() && self.perform_in_graph(action) {
+            return None;
+        }
+        if self.timeline.is_some() && self.perform_in_timeline(action) {
+            return None;
+        }
+        match action {
+            Action::Quit => self.should_quit = true,
+            Action::Suspend => {
+                #[cfg(unix)]
+                return Some(Effect::Suspend);
+                #[cfg(not(unix))]
+                self.notify("Suspending is not supported on this platform");
+            }
+            Action::Search => self.input = Input::Search,
+            Action::NewNote => {
+                self.new_title.clear();
+                self.input = Input::NewNote;
+            }
+            Action::Edit => self.open_editor(),
+            Action::EditExternal => {
+                return self
+                    .selected_note()
+                    .map(|note| Effect::EditExternal(self.store.absolute_path(note)));
+            }
+            Action::Up => self.move_by(-1),
+            Action::Down => self.move_by(1),
+            Action::PageUp => self.move_by(-PAGE_SIZE),
+            Action::PageDown => self.move_by(PAGE_SIZE),
+            Action::Top => self.move_by(isize::MIN),
+            Action::Bottom => self.move_by(isize::MAX),
+            Action::Right if self.focus == Focus::Tags => self.fold_tag(false),
+            Action::Left if self.focus == Focus::Tags => self.fold_tag(true),
+            Action::FocusNext | Action::Right => self.cycle_focus(true),
+            Action::FocusPrevious | Action::Left => self.cycle_focus(false),
+            Action::NextLink => self.cycle_link(true),
+            Action::PreviousLink => self.cycle_link(false),
+            Action::Open => self.open_selected(),
+            Action::Graph => self.show_graph(),
+            Action::Timeline => self.show_timeline(View::List),
+            Action::Calendar => self.show_timeline(View::Calendar),
+            Action::Tags => self.toggle_tags(),
+            Action::RenameTag => self.start_rename_tag(),
+            Action::Palette => self.open_palette(),
+            Action::Reload => match self.reload() {
+                Ok(()) => self.notify(format!("Reloaded {} notes", self.notes.len())),
+                Err(error) => self.report(error.to_string()),
+            },
+        }
+        None
+    }
+
+    /// Handle a mouse event against the last drawn layout
+    /// 前回描画したレイアウトに対してマウスイベントを処理
+    pub fn handle_mouse(&mut self, mouse: MouseEvent) {
+        if self.editor.is_some() || self.input == Input::Palette {
+            return;
+        }
+        if self.graph.is_some() {
+            if mouse.kind == MouseEventKind::Down(MouseButton::Left) {
+                self.click_graph(mouse.column, mouse.row);
+            }
+            return;
+        }
+        if self.timeline.is_some() {
+            self.timeline_mouse(mouse);
+            return;
+        }
+        let hit = |area: Rect| {
+            (area.x..area.x + area.width).contains(&mouse.column)
+                && (area.y..area.y + area.height).contains(&mouse.row)
+        };
+        let pane = [
+            (self.areas.tags, Focus::Tags),
+            (self.areas.list, Focus::List),
+            (self.areas.preview, Focus::Preview),
+            (self.areas.backlinks, Focus::Backlinks),
+        ]
+        .into_iter()
+        .find(|&(area, _)| hit(area));
+
+        match mouse.kind {
+            MouseEventKind::ScrollUp | MouseEventKind::ScrollDown => {
+                if let Some((_, focus)) = pane {
+                    let previous = std::mem::replace(&mut self.focus, focus);
+                    let delta = if mouse.kind == MouseEventKind::ScrollUp {
+                        -1
+                    } else {
+                        1
+                    };
+                    self.move_by(delta);
+                    self.focus = previous;
+                }
+            }
+            MouseEventKind::Down(MouseButton::Left) => {
+                if hit(self.areas.search) {
+                    self.input = Input::Search;
+                    return;
+                }
+                let Some((area, focus)) = pane else {
+                    return;
+                };
+                self.focus = focus;
+                // Rows start below the top border
+                // 行は上枠線の下から始まる
+                let row = usize::from(mouse.row.saturating_sub(area.y + 1));
+                match focus {
+                    Focus::Tags => {
+                        let position = self.tags_state.offset() + row;
+                        if position >= self.visible_tags().len() {
+                            return;
+                        }
+                        if self.tags_state.selected() == Some(position) {
+                            self.open_selected();
+                        } else {
+                            self.tags_state.select(Some(position));
+                            self.filter_by_tag();
+                        }
+                    }
+                    Focus::List => {
+                        let position = self.list_state.offset() + row;
+                        if position < self.filtered.len() {
+                            self.select_position(position);
+                        }
+                    }
+                    Focus::Backlinks => {
+                        let position = self.backlinks_state.offset() + row;
+                        if position < self.backlinks().len() {
+                            self.backlinks_state.select(Some(position));
+                        }
+                    }
+                    Focus::Preview => {
+                        let line = usize::from(self.preview_scroll) + row;
+                        let column = usize::from(mouse.column.saturating_sub(area.x + 1));
+                        if let Some(link) = self.preview.link_at(line, column) {
+                            self.preview_link = Some(link);
+                            self.follow_link(link);
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Move within the focused pane by a signed amount
+    /// フォーカス中のペイン内で符号付きの量だけ移動
+    fn move_by(&mut self, delta: isize) {
+        match self.focus {
+            Focus::Tags => {
+                let len = self.visible_tags().len();
+                self.tags_state
+                    .select(step(self.tags_state.selected(), len, delta));
+                self.filter_by_tag();
+            }
+            Focus::List => {
+                if let Some(position) = step(self.list_state.selected(), self.filtered.len(), delta)
+                {
+                    self.select_position(position);
+                }
+            }
+            Focus::Backlinks => {
+                let len = self.backlinks().len();
+                self.backlinks_state
+                    .select(step(self.backlinks_state.selected(), len, delta));
+            }
+            Focus::Preview => {
+                let scrolled = usize::from(self.preview_scroll).saturating_add_signed(delta);
+                self.preview_scroll = u16::try_from(scrolled).unwrap_or(u16::MAX);
+            }
+        }
+    }
+
+    /// Select a position in the filtered list and reset dependent panes
+    /// 絞り込み済み一覧の位置を選択し、依存するペインをリセット
+    fn select_position(&mut self, position: usize) {
+        if self.list_state.selected() != Some(position) {
+            self.list_state.select(Some(position));
+            self.preview_scroll = 0;
+            self.preview_link = None;
+        }
+        let has_backlinks = !self.backlinks().is_empty();
+        self.backlinks_state.select(has_backlinks.then_some(0));
+    }
+
+    /// Select the note stored at a path, clearing the search if it hides the note
+    /// パスに保存されたノートを選択（ノートが検索で隠れている場合は検索を解除）
+    pub fn select_path(&mut self, path: &std::path::Path) {
+        let Some(index) = self.notes.iter().position(|note| note.path == path) else {
+            return;
+        };
+        if !self.filtered.contains(&index) {
+            self.search.clear();
+            self.apply_search();
+        }
+        if let Some(position) = self.filtered.iter().position(|&i| i == index) {
+            self.select_position(position);
+        }
+    }
+
+    /// Follow the selection of the focused pane
+    /// フォーカス中のペインの選択をたどる
+    fn open_selected(&mut self) {
+        match self.focus {
+            Focus::Tags => match self.selected_tag() {
+                Some(node) if node.has_children => {
+                    let path = node.path.to_lowercase();
+                    if !self.collapsed.remove(&path) {
+                        self.collapsed.insert(path);
+                    }
+                }
+                Some(_) => self.focus = Focus::List,
+                None => {}
+            },
+            Focus::Backlinks => {
+                let target = self
+                    .backlinks_state
+                    .selected()
+                    .and_then(|position| self.backlinks().get(position).copied());
+                if let Some(index) = target {
+                    let path = self.notes[index].path.clone();
+                    self.select_path(&path);
+                    self.focus = Focus::List;
+                }
+            }
+            Focus::List => self.focus = Focus::Preview,
+            Focus::Preview => {
+                if let Some(link) = self.preview_link {
+                    self.follow_link(link);
+                }
+            }
+        }
+    }
+
+    /// Open the selected note in the built-in editor
+    /// 選択中のノートを組み込みエディタで開く
+    fn open_editor(&mut self) {
+        let Some(note) = self.selected_note().cloned() else {
+            return;
+        };
+        let names = self
+            .notes
+            .iter()
+            .map(|note| note.title().to_string())
+            .collect();
+        self.graph = None;
+        self.timeline = None;
+        self.editor = Some(Editor::new(note, names));
+    }
+
+    /// Pass a key to the built-in editor and carry out its request
+    /// 組み込みエディタにキーを渡し、その要求を実行
+    fn edit(&mut self, key: KeyEvent) {
+        let Some(editor) = &mut self.editor else {
+            return;
+        };
+        match editor.handle_key(key) {
+            Request::None => {}
+            Request::Save => self.save_editor("Saved", true),
+            Request::Close => {
+                if editor.is_modified() {
+                    self.save_editor("Saved", true);
+                }
+                // Keep the editor open when saving failed so nothing is lost
+                // 保存に失敗した場合は内容を失わないようエディタを開いたままにする
+                if !self.editor.as_ref().is_some_and(Editor::is_modified) {
+                    self.editor = None;
+                }
+            }
+        }
+    }
+
+    /// Save the note of the built-in editor and update it in the notes
+    /// 組み込みエディタのノートを保存し、ノート一覧内で更新
+    ///
+    /// With `confirmed`, a save requested again after a conflict overwrites
+    /// the file changed on disk.
+    /// `confirmed`の場合、競合の後に再度要求された保存はディスク上で変更された
+    /// ファイルを上書きします。
+    fn save_editor(&mut self, verb: &str, confirmed: bool) {
+        let Some(editor) = &mut self.editor else {
+            return;
+        };
+        let saved = if confirmed && editor.has_conflict() {
+            editor.overwrite(&self.store)
+        } else {
+            editor.save(&self.store)
+        };
+        let saved = saved.map(|()| editor.note.clone());
+        let path = editor.note.path.display().to_string();
+        match saved.and_then(|note| self.update_note(note)) {
+            Ok(()) => self.notify(format!("{verb} {path}")),
+            Err(error) => self.report(error.to_string()),
+        }
+    }
+
+    /// Show the link graph around the selected note
+    /// 選択中のノート周辺のリンクグラフを表示
+    fn show_graph(&mut self) {
+        if let Some(index) = self.selected_index() {
+            let graph = Graph::build(&self.links, index, self.config.tui.graph_depth);
+            self.graph = Some(graph);
+            self.timeline = None;
+        } else {
+            self.graph = None;
+            self.notify("No note to show a graph for");
+        }
+    }
+
+    /// Carry out an action in the graph view; returns `false` for actions
+    /// that behave as in the panes
+    /// グラフビューでアクションを実行。ペインと同じ動作のアクションでは`false`を返す
+    fn perform_in_graph(&mut self, action: Action) -> bool {
+        let Some(graph) = &mut self.graph else {
+            return false;
+        };
+        let moved = match action {
+            Action::Graph => {
+                self.graph = None;
+                return true;
+            }
+            Action::Up => graph.step(0.0, 1.0),
+            Action::Down => graph.step(0.0, -1.0),
+            Action::Left => graph.step(-1.0, 0.0),
+            Action::Right => graph.step(1.0, 0.0),
+            Action::FocusNext | Action::NextLink => {
+                graph.cycle(true);
+                true
+            }
+            Action::FocusPrevious | Action::PreviousLink => {
+                graph.cycle(false);
+                true
+            }
+            Action::Top => {
+                graph.selected = 0;
+                true
+            }
+            Action::Open => {
+                self.show_graph();
+                return true;
+            }
+            _ => return false,
+        };
+        if moved {
+            self.select_graph_node();
+        }
+        true
+    }
+
+    /// Make the note of the selected graph node the selected note
+    /// 選択中のグラフノードのノートを選択中のノートにする
+    fn select_graph_node(&mut self) {
+        let Some(index) = self.graph.as_ref().and_then(Graph::selected_note) else {
+            return;
+        };
+        let path = self.notes[index].path.clone();
+        self.select_path(&path);
+    }
+
+    /// Select the graph node under a click; clicking the selected node
+    /// centers the graph on it
+    /// クリック位置のグラフノードを選択。選択中のノードをクリックするとそこを中心にする
+    fn click_graph(&mut self, column: u16, row: u16) {
+        let area = self.areas.graph;
+        let (width, height) = (area.width.saturating_sub(2), area.height.saturating_sub(2));
+        let Some(graph) = &mut self.graph else {
+            return;
+        };
+        if width == 0 || height == 0 {
+            return;
+        }
+        // Cell centers in layout coordinates, with y pointing up
+        // レイアウト座標でのセルの中心（yは上向き）
+        let x = (f64::from(column) - f64::from(area.x + 1) + 0.5) / f64::from(width);
+        let y = 1.0 - (f64::from(row) - f64::from(area.y + 1) + 0.5) / f64::from(height);
+        let Some(position) = graph.node_at(x, y, (f64::from(width), f64::from(height)), 2.0) else {
+            return;
+        };
+        if position == graph.selected {
+            self.select_graph_node();
+            self.show_graph();
+        } else {
+            graph.selected = position;
+            self.select_graph_node();
+        }
+    }
+
+    /// Show the timeline in a view, or switch an open timeline to it
+    /// タイムラインを指定の形式で表示（開いている場合はその形式に切り替え）
+    fn show_timeline(&mut self, view: View) {
+        self.graph = None;
+        self.timeline = Some(Timeline::build(
+            &self.notes,
+            &chrono::Local,
+            chrono::Local::now().date_naive(),
+            view,
+        ));
+    }
+
+    /// Carry out an action in the timeline or calendar; returns `false` for
+    /// actions that behave as in the panes
+    /// タイムラインまたはカレンダーでアクションを実行。ペインと同じ動作の
+    /// アクションでは`false`を返す
+    fn perform_in_timeline(&mut self, action: Action) -> bool {
+        let Some(timeline) = &mut self.timeline else {
+            return false;
+        };
+        match (timeline.view, action) {
+            (View::List, Action::Timeline) | (View::Calendar, Action::Calendar) => {
+                self.timeline = None;
+            }
+            (View::Calendar, Action::Timeline | Action::FocusNext | Action::FocusPrevious) => {
+                timeline.show_day();
+            }
+            (View::List, Action::Calendar | Action::FocusNext | Action::FocusPrevious) => {
+                timeline.view = View::Calendar;
+            }
+            (View::List, Action::Up) => timeline.select_by(-1),
+            (View::List, Action::Down) => timeline.select_by(1),
+            (View::List, Action::PageUp) => timeline.select_by(-PAGE_SIZE),
+            (View::List, Action::PageDown) => timeline.select_by(PAGE_SIZE),
+            (View::List, Action::Top) => timeline.select_by(isize::MIN),
+            (View::List, Action::Bottom) => timeline.select_by(isize::MAX),
+            (View::List, Action::Left | Action::Right) => {}
+            (View::List, Action::Open) => {
+                self.select_event();
+                self.timeline = None;
+                self.focus = Focus::Preview;
+                return true;
+            }
+            (View::Calendar, Action::Left) => timeline.move_days(-1),
+            (View::Calendar, Action::Right) => timeline.move_days(1),
+            (View::Calendar, Action::Up) => timeline.move_days(-7),
+            (View::Calendar, Action::Down) => timeline.move_days(7),
+            (View::Calendar, Action::PageUp) => timeline.move_months(-1),
+            (View::Calendar, Action::PageDown) => timeline.move_months(1),
+            (View::Calendar, Action::Top) => timeline.day = timeline.today,
+            (View::Calendar, Action::Bottom) => {
+                timeline.day = timeline.latest().unwrap_or(timeline.today);
+            }
+            (View::Calendar, Action::Open) => self.open_day(),
+            _ => return false,
+        }
+        if matches!(&self.timeline, Some(timeline) if timeline.view == View::List) {
+            self.select_event();
+        }
+        true
+    }
+
+    /// Make the note of the selected timeline event the selected note
+    /// 選択中のタイムラインのイベントのノートを選択中のノートにする
+    fn select_event(&mut self) {
+        let Some(event) = self.timeline.as_ref().and_then(Timeline::selected_event) else {
+            return;
+        };
+        let path = self.notes[event.note].path.clone();
+        self.select_path(&path);
+    }
+
+    /// Jump from the selected calendar day to its notes in the timeline
+    /// カレンダーで選択中の日からタイムラインのその日のノートへ移動
+    fn open_day(&mut self) {
+        let Some(timeline) = &mut self.timeline else {
+            return;
+        };
+        let day = timeline.day;
+        let notes = timeline.notes_on(day);
+        match notes[..] {
+            [] => self.notify(format!("No notes on {day}")),
+            // A daily note without other activity is opened directly
+            // 他の活動のないデイリーノートは直接開く
+            [note] if !timeline.events.iter().any(|event| event.date == day) => {
+                let path = self.notes[note].path.clone();
+                self.select_path(&path);
+                self.timeline = None;
+                self.focus = Focus::Preview;
+            }
+            _ => timeline.show_day(),
+        }
+    }
+
+    /// Handle a mouse event in the timeline or calendar
+    /// タイムラインまたはカレンダーでマウスイベントを処理
+    fn timeline_mouse(&mut self, mouse: MouseEvent) {
+        let area = self.areas.timeline;
+        let Some(timeline) = &mut self.timeline else {
+            return;
+        };
+        let inside = (area.x + 1..area.right().saturating_sub(1)).contains(&mouse.column)
+            && (area.y + 1..area.bottom().saturating_sub(1)).contains(&mouse.row);
+        let row = usize::from(mouse.row.saturating_sub(area.y + 1));
+        let column = mouse.column.saturating_sub(area.x + 1);
+        let action = match (timeline.view, mouse.kind) {
+            (View::List, MouseEventKind::ScrollUp) => Action::Up,
+            (View::List, MouseEventKind::ScrollDown) => Action::Down,
+            (View::Calendar, MouseEventKind::ScrollUp) => Action::PageUp,
+            (View::Calendar, MouseEventKind::ScrollDown) => Action::PageDown,
+            (View::List, MouseEventKind::Down(MouseButton::Left)) if inside => {
+                let position = timeline.offset + row;
+                if position >= timeline.events.len() {
+                    return;
+                }
+                if position != timeline.selected {
+                    timeline.selected = position;
+                    timeline.day = timeline.events[position].date;
+                    self.select_event();
+                    return;
+                }
+                Action::Open
+            }
+            (View::Calendar, MouseEventKind::Down(MouseButton::Left)) if inside => {
+                // Rows below the weekday header hold one week each
+                // 曜日の見出しの下の行はそれぞれ1週間
+                let weekday = usize::from(column / timeline::CELL_WIDTH);
+                let Some(Some(date)) = row
+                    .checked_sub(1)
+                    .and_then(|week| timeline.weeks().get(week).copied())
+                    .and_then(|week| week.get(weekday).copied())
+                else {
+                    return;
+                };
+                if date != timeline.day {
+                    timeline.day = date;
+                    return;
+                }
+                Action::Open
+            }
+            _ => return,
+        };
+        self.perform_in_timeline(action);
+    }
+
+    /// Render the selected note for a preview `width` columns wide
+    /// 選択中のノートを幅`width`桁のプレビュー用に描画
+    ///
+    /// The selected wiki link is highlighted while the preview has focus.
+    /// プレビューにフォーカスがある間は選択中のWikiリンクが強調されます。
+    pub fn render_preview(&mut self, width: usize) {
+        let Some(note) = self.selected_note() else {
+            self.preview = Document::default();
+            return;
+        };
+        let exists = |name: &str| self.links.resolve(name).is_some();
+        let mut preview = markdown::render(&note.body, width, &self.theme, &exists);
+        preview.prepend(vec![
+            Line::styled(note.title().to_string(), self.theme.style(Role::Heading1)),
+            Line::styled(
+                format!(
+                    "{}  {}",
+                    note.path.display(),
+                    note.frontmatter.updated.format("%Y-%m-%d %H:%M")
+                ),
+                self.theme.style(Role::Muted),
+            ),
+            Line::default(),
+        ]);
+        if let Some(link) = self.preview_link.filter(|_| self.focus == Focus::Preview) {
+            preview.highlight(link, self.theme.style(Role::Selection));
+        }
+        self.preview = preview;
+    }
+
+    /// Select the next or previous wiki link of the preview and scroll to it
+    /// プレビューの次または前のWikiリンクを選択し、そこまでスクロール
+    fn cycle_link(&mut self, forward: bool) {
+        let count = self.preview.links.len();
+        if count == 0 {
+            self.notify("No links in this note");
+            return;
+        }
+        self.focus = Focus::Preview;
+        let link = match (self.preview_link, forward) {
+            (Some(link), true) => (link + 1) % count,
+            (Some(link), false) => (link + count - 1) % count,
+            (None, true) => 0,
+            (None, false) => count - 1,
+        };
+        self.preview_link = Some(link);
+
+        let Some(line) = self.preview.links[link]
+            .positions
+            .first()
+            .map(|position| position.line)
+        else {
+            return;
+        };
+        // Inner height of the preview pane, between its borders
+        // 枠線の内側のプレビューペインの高さ
+        let height = usize::from(self.areas.preview.height.saturating_sub(2)).max(1);
+        let scroll = usize::from(self.preview_scroll);
+        if line < scroll {
+            self.preview_scroll = u16::try_from(line).unwrap_or(u16::MAX);
+        } else if line >= scroll + height {
+            self.preview_scroll = u16::try_from(line + 1 - height).unwrap_or(u16::MAX);
+        }
+    }
+
+    /// Select the note a wiki link of the preview points to
+    /// プレビューのWikiリンクが指すノートを選択
+    fn follow_link(&mut self, link: usize) {
+        let Some(target) = self.preview.links.get(link).map(|link| link.target.clone()) else {
+            return;
+        };
+        if let Some(index) = self.links.resolve(&target) {
+            let path = self.notes[index].path.clone();
+            self.select_path(&path);
+            self.focus = Focus::Preview;
+        } else {
+            self.report(format!("No note named '{target}'"));
+        }
+    }
+
+    /// Open the command palette over every action, note, tag and saved search
+    /// すべてのアクション・ノート・タグ・保存済み検索に対してコマンドパレットを開く
+    fn open_palette(&mut self) {
+        let actions = Action::ALL
+            .into_iter()
+            .filter(|&action| action != Action::Palette)
+            .map(|action| Entry {
+                target: Target::Action(action),
+                label: action.description().to_string(),
+                detail: self.keymap.hint(action).unwrap_or_default(),
+            });
+        let notes = self.notes.iter().map(|note| Entry {
+            target: Target::Note(note.path.clone()),
+            label: note.title().to_string(),
+            detail: note.path.display().to_string(),
+        });
+        let tags = self.tags.nodes().iter().map(|node| Entry {
+            target: Target::Tag(node.path.clone()),
+            label: format!("#{}", node.path),
+            detail: format!("{} notes", node.notes),
+        });
+        let searches = self
+            .config
+            .tui
+            .saved_searches
+            .iter()
+            .map(|(name, query)| Entry {
+                target: Target::Search(name.clone()),
+                label: name.clone(),
+                detail: query.clone(),
+            });
+
+        let entries = actions.chain(notes).chain(tags).chain(searches).collect();
+        self.palette = Some(Palette::new(entries, &self.history));
+        self.input = Input::Palette;
+    }
+
+    /// Pass a key to the command palette and carry out the chosen entry
+    /// コマンドパレットにキーを渡し、選択された項目を実行
+    fn edit_palette(&mut self, key: KeyEvent) -> Option<Effect> {
+        let outcome = self.palette.as_mut()?.handle_key(key);
+        match outcome {
+            Outcome::None => None,
+            Outcome::Cancel => {
+                self.palette = None;
+                self.input = Input::None;
+                None
+            }
+            Outcome::Choose(target) => {
+                self.palette = None;
+                self.input = Input::None;
+                self.history.record(target.clone());
+                self.run_target(target)
+            }
+        }
+    }
+
+    /// Carry out a command palette entry
+    /// コマンドパレットの項目を実行
+    fn run_target(&mut self, target: Target) -> Option<Effect> {
+        match target {
+            Target::Action(action) => return self.perform(action),
+            Target::Note(path) => {
+                if self.notes.iter().any(|note| note.path == path) {
+                    self.graph = None;
+                    self.timeline = None;
+                    self.select_path(&path);
+                    self.focus = Focus::List;
+                } else {
+                    self.report(format!("No note at {}", path.display()));
+                }
+            }
+            Target::Tag(tag) => self.run_search(tag_query(&tag)),
+            Target::Search(name) => match self.config.tui.saved_searches.get(&name) {
+                Some(query) => self.run_search(query.clone()),
+                None => self.report(format!("No saved search named '{name}'")),
+            },
+        }
+        None
+    }
+
+    /// Replace the search query and show its results in the note list
+    /// 検索クエリを置き換え、その結果をノート一覧に表示
+    fn run_search(&mut self, query: String) {
+        self.search = query;
+        self.graph = None;
+        self.timeline = None;
+        self.focus = Focus::List;
+        self.apply_search();
+    }
+
+    /// Move focus to the next or previous pane, skipping the hidden tag tree
+    /// 次または前のペインへフォーカスを移動（非表示のタグツリーは飛ばす）
+    fn cycle_focus(&mut self, forward: bool) {
+        let cycle = |focus: Focus| {
+            if forward {
+                focus.next()
+            } else {
+                focus.previous()
+            }
+        };
+        self.focus = cycle(self.focus);
+        if self.focus == Focus::Tags && !self.show_tags {
+            self.focus = cycle(self.focus);
+        }
+    }
+
+    /// Show the tag tree with focus, or hide it
+    /// タグツリーをフォーカス付きで表示、または非表示にする
+    fn toggle_tags(&mut self) {
+        self.show_tags = !self.show_tags;
+        if self.show_tags {
+            self.graph = None;
+            self.timeline = None;
+            self.focus = Focus::Tags;
+            if self.tags_state.selected().is_none() && !self.tags.nodes().is_empty() {
+                self.tags_state.select(Some(0));
+            }
+        } else if self.focus == Focus::Tags {
+            self.focus = Focus::List;
+        }
+    }
+
+    /// Select the visible node of a tag, or keep the selection within bounds
+    /// タグの表示中のノードを選択（なければ選択を範囲内に収める）
+    fn select_tag(&mut self, tag: Option<&str>) {
+        let visible = self.visible_tags();
+        let position = tag
+            .and_then(|tag| self.tags.position(tag))
+            .and_then(|index| visible.iter().position(|&i| i == index))
+            .or_else(|| {
+                self.tags_state
+                    .selected()
+                    .map(|position| position.min(visible.len().saturating_sub(1)))
+            });
+        self.tags_state
+            .select(position.filter(|_| !visible.is_empty()));
+    }
+
+    /// Show the notes of the selected tag in the note list
+    /// 選択中のタグのノートをノート一覧に表示
+    fn filter_by_tag(&mut self) {
+        if let Some(node) = self.selected_tag() {
+            self.search = tag_query(&node.path);
+            self.apply_search();
+        }
+    }
+
+    /// Collapse or expand the selected tag
+    /// 選択中のタグを折りたたむまたは展開
+    ///
+    /// Collapsing a tag that is already collapsed, or has no children, selects
+    /// its parent instead; expanding one passes focus to the note list.
+    /// 既に折りたたまれているか子のないタグを折りたたむと代わりに親を選択し、
+    /// 展開するとフォーカスをノート一覧へ渡します。
+    fn fold_tag(&mut self, collapse: bool) {
+        let Some(node) = self.selected_tag() else {
+            self.cycle_focus(!collapse);
+            return;
+        };
+        let path = node.path.to_lowercase();
+        let collapsed = self.collapsed.contains(&path);
+        if node.has_children && collapsed != collapse {
+            if collapse {
+                self.collapsed.insert(path);
+            } else {
+                self.collapsed.remove(&path);
+            }
+        } else if collapse {
+            let parent = self
+                .tags
+                .position(&path)
+                .and_then(|index| self.tags.parent(index))
+                .map(|index| self.tags.nodes()[index].path.clone());
+            if let Some(parent) = parent {
+                self.select_tag(Some(&parent));
+                self.filter_by_tag();
+            }
+        } else {
+            self.cycle_focus(true);
+        }
+    }
+
+    /// Start typing a new name for the tag selected in the tag tree
+    /// タグツリーで選択中のタグの新しい名前の入力を開始
+    fn start_rename_tag(&mut self) {
+        let selected = self
+            .selected_tag()
+            .filter(|_| self.show_tags && self.focus == Focus::Tags)
+            .map(|node| node.path.clone());
+        if let Some(path) = selected {
+            self.tag_name = path;
+            self.input = Input::RenameTag;
+        } else {
+            self.notify("Select a tag in the tag tree to rename it");
+        }
+    }
+
+    /// Edit the new tag name and plan the rename on Enter
+    /// 新しいタグ名を編集し、Enterで名前変更を計画
+    fn edit_tag_name(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => self.input = Input::None,
+            KeyCode::Backspace => {
+                self.tag_name.pop();
+            }
+            KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.tag_name.clear();
+            }
+            KeyCode::Char(c) => self.tag_name.push(c),
+            KeyCode::Enter => {
+                self.input = Input::None;
+                self.plan_rename_tag();
+            }
+            _ => {}
+        }
+    }
+
+    /// Selected tag and the normalized new name typed for it
+    /// 選択中のタグと、それに対して入力された正規化済みの新しい名前
+    fn tag_rename(&self) -> Option<(String, String)> {
+        let from = self.selected_tag()?.path.clone();
+        Some((from, normalize_tag(&self.tag_name)))
+    }
+
+    /// Plan renaming the selected tag and the tags below it in every note, then
+    /// ask for confirmation
+    /// 選択中のタグとその下位のタグのすべてのノートでの名前変更を計画し、確認を求める
+    fn plan_rename_tag(&mut self) {
+        let Some((from, to)) = self.tag_rename() else {
+            return;
+        };
+        if to.is_empty() {
+            self.report("Tag name cannot be empty");
+            return;
+        }
+        if to == from {
+            return;
+        }
+        let edits = [Edit::RenameTag { from, to }];
+        match Query::parse("*").and_then(|all| bulk::plan(&self.store, &all, &edits)) {
+            Ok(plan) if plan.changes.is_empty() => self.notify("No notes to change"),
+            Ok(plan) => {
+                self.rename_plan = Some(plan);
+                self.input = Input::ConfirmRenameTag;
+            }
+            Err(error) => self.report(error.to_string()),
+        }
+    }
+
+    /// Apply the planned rename on `y` and drop it on `n` or Esc
+    /// `y`で計画された名前変更を適用し、`n`またはEscで破棄
+    fn confirm_rename_tag(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Char('y' | 'Y') => {
+                self.input = Input::None;
+                self.rename_tag();
+            }
+            KeyCode::Char('n' | 'N') | KeyCode::Esc => {
+                self.input = Input::None;
+                self.rename_plan = None;
+                self.notify("Rename cancelled");
+            }
+            _ => {}
+        }
+    }
+
+    /// Back the vault up when backups are enabled, then apply the planned rename
+    /// バックアップが有効ならボルトをバックアップし、計画された名前変更を適用
+    fn rename_tag(&mut self) {
+        let (Some(plan), Some((from, to))) = (self.rename_plan.take(), self.tag_rename()) else {
+            return;
+        };
+        let backups = &self.config.storage.backup;
+        let backup = if backups.enabled {
+            match Backups::new(backups).create(&self.store, "rename-tag") {
+                Ok(backup) => Some(backup),
+                Err(error) => {
+                    self.report(format!("Rename aborted, backup failed: {error}"));
+                    return;
+                }
+            }
+        } else {
+            None
+        };
+        let count = match bulk::apply(&self.store, plan) {
+            Ok(count) => count,
+            Err(error) => {
+                self.report(error.to_string());
+                return;
+            }
+        };
+        if self.search == tag_query(&from) {
+            self.search = tag_query(&to);
+        }
+        match self.reload() {
+            Ok(()) => {
+                self.select_tag(Some(&to));
+                let backed_up = backup.map_or_else(String::new, |backup| {
+                    format!(", backed up to {}", backup.path.display())
+                });
+                self.notify(format!(
+                    "Renamed #{from} to #{to} in {count} notes{backed_up}"
+                ));
+            }
+            Err(error) => self.report(error.to_string()),
+        }
+    }
+
+    /// Edit the search query; the note list follows every keystroke
+    /// 検索クエリを編集。ノート一覧は入力ごとに追従する
+    fn edit_search(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => {
+                self.search.clear();
+                self.input = Input::None;
+            }
+            KeyCode::Enter => {
+                self.input = Input::None;
+                return;
+            }
+            KeyCode::Backspace => {
+                self.search.pop();
+            }
+            KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.search.clear();
+            }
+            KeyCode::Char(c) => self.search.push(c),
+            _ => return,
+        }
+        self.apply_search();
+    }
+
+    /// Edit the new note title and create the note on Enter
+    /// 新しいノートのタイトルを編集し、Enterでノートを作成
+    fn edit_new_title(&mut self, key: KeyEvent) -> Option<Effect> {
+        match key.code {
+            KeyCode::Esc => self.input = Input::None,
+            KeyCode::Backspace => {
+                self.new_title.pop();
+            }
+            KeyCode::Char(c) => self.new_title.push(c),
+            KeyCode::Enter => {
+                self.input = Input::None;
+                let title = self.new_title.trim().to_string();
+                if title.is_empty() {
+                    return None;
+                }
+                let note = Note::new(&title, format!("# {title}\n")).with_id(self.store.new_id());
+                let created = self.store.create(&note).and_then(|()| self.reload());
+                match created {
+                    Ok(()) => {
+                        self.select_path(&note.path);
+                        self.notify(format!("Created {}", note.path.display()));
+                        self.open_editor();
+                    }
+                    Err(error) => self.report(error.to_string()),
+                }
+            }
+            _ => {}
+        }
+        None
+    }
+
+    /// Recompute the filtered list from the search query
+    /// 検索クエリから絞り込み済み一覧を再計算
+    ///
+    /// An invalid query keeps the previous results and reports the problem.
+    /// 無効なクエリでは前回の結果を維持し、問題を報告します。
+    fn apply_search(&mut self) {
+        let selected = self.selected_index();
+        if self.search.trim().is_empty() {
+            self.filtered = (0..self.notes.len()).collect();
+        } else {
+            match Query::parse(&self.search) {
+                Ok(query) => {
+                    self.filtered = (0..self.notes.len())
+                        .filter(|&index| query.matches(&self.notes[index]))
+                        .collect();
+                }
+                Err(error) => {
+                    self.report(error.to_string());
+                    return;
+                }
+            }
+        }
+
+        let position = selected
+            .and_then(|index| self.filtered.iter().position(|&i| i == index))
+            .or_else(|| (!self.filtered.is_empty()).then_some(0));
+        if let Some(position) = position {
+            self.select_position(position);
+        } else {
+            self.list_state.select(None);
+            self.backlinks_state.select(None);
+        }
+    }
+}
+
+/// Search query matching the notes of a tag, quoted when the tag has spaces
+/// タグのノートに一致する検索クエリ（タグに空白があれば引用符で囲む）
+fn tag_query(tag: &str) -> String {
+    if tag.contains(char::is_whitespace) {
+        format!("tag:\"{tag}\"")
+    } else {
+        format!("tag:{tag}")
+    }
+}
+
+/// Move a selection by `delta` within `len` items, clamping at both ends
+/// `len`個の項目内で選択を`delta`だけ移動（両端で制限）
+fn step(current: Option<usize>, len: usize, delta: isize) -> Option<usize> {
+    if len == 0 {
+        return None;
+    }
+    let current = current.unwrap_or(0);
+    Some(current.saturating_add_signed(delta).min(len - 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Datelike;
+    use tempfile::TempDir;
+
+    fn app() -> (TempDir, App) {
+        let dir = TempDir::new().unwrap();
+        let store = NoteStore::new(dir.path().to_path_buf(), 1024 * 1024);
+        store
+            .create(&Note::new("Alpha", "Links to [[Gamma]]"))
+            .unwrap();
+        store.create(&Note::new("Beta", "Also [[Gamma]]")).unwrap();
+        store.create(&Note::new("Gamma", "Target")).unwrap();
+        let app = App::new(Config::default(), store).unwrap();
+        (dir, app)
+    }
+
+    /// Peak resident memory of the process in bytes, where the platform
+    /// reports it
+    /// プロセスの最大常駐メモリ（バイト、プラットフォームが報告する場合）
+    fn peak_memory() -> Option<u64> {
+        let status = std::fs::read_to_string("/proc/self/status").ok()?;
+        let line = status.lines().find(|line| line.starts_with("VmHWM:"))?;
+        let kilobytes: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+        Some(kilobytes * 1024)
+    }
+
+    // Startup targets of the TUI for a large vault: under a second and under
+    // 200 MB. Timings are only meaningful in release builds:
+    // 大きなボルトでのTUI起動目標：1秒未満かつ200MB未満。時間はリリースビルドでのみ
+    // 意味を持つ：
+    // cargo test --release --lib startup_with_large_vault -- --ignored
+    #[test]
+    #[ignore = "writes 10,000 notes; run in release mode"]
+    fn test_startup_with_large_vault() {
+        const NOTES: usize = 10_000;
+        let dir = TempDir::new().unwrap();
+        let store = NoteStore::new(dir.path().to_path_buf(), 1024 * 1024);
+        let paragraph = "Lorem ipsum dolor sit amet, consectetur adipiscing elit. ".repeat(16);
+        for i in 0..NOTES {
+            let mut note = Note::new(
+                format!("Note {i}"),
+                format!(
+                    "# Note {i}\n\nSee [[Note {}]] and [[Note {}]].\n\n{paragraph}\n",
+                    (i + 1) % NOTES,
+                    i * 7 % NOTES
+                ),
+            );
+            note.frontmatter.tags = vec![format!("area/{}", i % 20), format!("topic{}", i % 100)];
+            store.create(&note).unwrap();
+        }
+
+        let started = Instant::now();
+        let app = App::new(Config::default(), store).unwrap();
+        let elapsed = started.elapsed();
+
+        assert_eq!(app.notes.len(), NOTES);
+        assert!(elapsed < Duration::from_secs(1), "startup took {elapsed:?}");
+        if let Some(peak) = peak_memory() {
+            assert!(peak < 200 * 1024 * 1024, "peak memory was {peak} bytes");
+        }
+    }
+
+    fn press(app: &mut App, code: KeyCode) -> Option<Effect> {
+        app.handle_key(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    #[test]
+    fn test_navigation_and_backlinks() {
+        let (_dir, mut app) = app();
+        assert_eq!(app.selected_note().unwrap().title(), "Alpha");
+
+        press(&mut app, KeyCode::Char('G'));
+        assert_eq!(app.selected_note().unwrap().title(), "Gamma");
+        assert_eq!(app.backlinks().len(), 2);
+
+        // Follow the second backlink
+        // 2番目のバックリンクをたどる
+        press(&mut app, KeyCode::Tab);
+        press(&mut app, KeyCode::Tab);
+        assert_eq!(app.focus, Focus::Backlinks);
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.selected_note().unwrap().title(), "Beta");
+        assert_eq!(app.focus, Focus::List);
+
+        press(&mut app, KeyCode::Char('q'));
+        assert!(app.should_quit);
+    }
+
+    #[test]
+    fn test_search_filters_live() {
+        let (_dir, mut app) = app();
+        press(&mut app, KeyCode::Char('/'));
+        assert_eq!(app.input, Input::Search);
+        for c in "gamma".chars() {
+            press(&mut app, KeyCode::Char(c));
+        }
+        // Alpha and Beta mention Gamma in their bodies
+        // AlphaとBetaは本文でGammaに言及している
+        assert_eq!(app.filtered.len(), 3);
+
+        app.search = "title:gam".to_string();
+        app.apply_search();
+        assert_eq!(app.filtered.len(), 1);
+        assert_eq!(app.selected_note().unwrap().title(), "Gamma");
+
+        press(&mut app, KeyCode::Char('"'));
+        assert!(app.status.as_ref().is_some_and(|(_, error, _)| *error));
+        assert_eq!(app.filtered.len(), 1, "invalid query keeps results");
+
+        press(&mut app, KeyCode::Esc);
+        assert_eq!(app.filtered.len(), 3);
+        assert_eq!(app.input, Input::None);
+    }
+
+    #[test]
+    fn test_apply_reloaded_config() {
+        let (_dir, mut app) = app();
+        let mut loaded = app.config.clone();
+        loaded.tui.theme = "light".to_string();
+        loaded.tui.keybindings.bindings.insert(
+            "quit".to_string(),
+            crate::config::KeyChords::One("x".to_string()),
+        );
+        app.apply_config(Ok(loaded.clone()));
+        assert_eq!(app.theme.name, "light");
+        assert!(app.status.as_ref().is_some_and(|(_, error, _)| !*error));
+        press(&mut app, KeyCode::Char('x'));
+        assert!(app.should_quit, "new binding is live");
+        app.should_quit = false;
+
+        // A restart-only change keeps its running value and is reported
+        // 再起動が必要な変更は実行中の値を保ち、報告される
+        loaded.tui.frame_rate = 5;
+        app.apply_config(Ok(loaded));
+        assert_ne!(app.config.tui.frame_rate, 5);
+        let (message, error, _) = app.status.clone().unwrap();
+        assert!(error);
+        assert!(message.contains("tui.frame_rate"));
+
+        app.apply_config(Err(crate::ZynapseError::config_error("broken")));
+        assert_eq!(app.theme.name, "light");
+        assert!(app.status.unwrap().0.contains("broken"));
+    }
+
+    #[test]
+    fn test_command_palette() {
+        let (_dir, mut app) = app();
+        let mut tagged = Note::new("Delta", "Tagged");
+        tagged.frontmatter.tags.push("draft".to_string());
+        app.store.create(&tagged).unwrap();
+        app.config
+            .tui
+            .saved_searches
+            .insert("linked".to_string(), "gamma".to_string());
+        app.reload().unwrap();
+        let type_text = |app: &mut App, text: &str| {
+            for c in text.chars() {
+                press(app, KeyCode::Char(c));
+            }
+        };
+
+        app.handle_key(KeyEvent::new(KeyCode::Char('p'), KeyModifiers::CONTROL));
+        assert_eq!(app.input, Input::Palette);
+        let palette = app.palette.as_ref().unwrap();
+        let graph = palette
+            .matches()
+            .find(|entry| entry.target == Target::Action(Action::Graph))
+            .unwrap();
+        assert_eq!(graph.detail, "v", "actions show their key");
+        assert!(palette
+            .matches()
+            .any(|entry| entry.target == Target::Tag("draft".to_string())));
+
+        type_text(&mut app, "gamma");
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.input, Input::None);
+        assert_eq!(app.selected_note().unwrap().title(), "Gamma");
+
+        press(&mut app, KeyCode::Char(':'));
+        type_text(&mut app, "linked");
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.search, "gamma");
+        assert_eq!(app.filtered.len(), 3);
+
+        press(&mut app, KeyCode::Char(':'));
+        type_text(&mut app, "#draft");
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.search, "tag:draft");
+        assert_eq!(app.selected_note().unwrap().title(), "Delta");
+
+        // The last choice comes first, so repeating it takes a single Enter
+        // 最後の選択が先頭に来るため、繰り返しはEnter1回で済む
+        press(&mut app, KeyCode::Char(':'));
+        let first = app.palette.as_ref().unwrap().matches().next().unwrap();
+        assert_eq!(first.target, Target::Tag("draft".to_string()));
+        assert_eq!(
+            app.history.targets()[1],
+            Target::Search("linked".to_string())
+        );
+
+        type_text(&mut app, "graph view");
+        press(&mut app, KeyCode::Enter);
+        assert!(app.graph.is_some());
+
+        press(&mut app, KeyCode::Char(':'));
+        press(&mut app, KeyCode::Esc);
+        assert_eq!(app.input, Input::None);
+        assert!(app.palette.is_none());
+    }
+
+    #[test]
+    fn test_tag_tree_filters_and_renames() {
+        let (_dir, mut app) = app();
+        let backups = TempDir::new().unwrap();
+        app.config.storage.backup.path = backups.path().to_path_buf();
+        for (title, tags) in [
+            ("Search", &["project/zynapse/search"][..]),
+            ("TUI", &["project/zynapse/tui", "draft"]),
+        ] {
+            let mut note = Note::new(title, "");
+            note.frontmatter.tags = tags.iter().map(ToString::to_string).collect();
+            app.store.create(&note).unwrap();
+        }
+        app.reload().unwrap();
+
+        app.perform(Action::Tags);
+        assert_eq!(app.focus, Focus::Tags);
+        assert_eq!(app.selected_tag().unwrap().path, "draft");
+        press(&mut app, KeyCode::Down);
+        assert_eq!(app.selected_tag().unwrap().path, "project");
+        assert_eq!(app.search, "tag:project");
+        assert_eq!(app.filtered.len(), 2);
+
+        // Left collapses the selected tag, Right expands it again
+        // Leftで選択中のタグを折りたたみ、Rightで再び展開する
+        press(&mut app, KeyCode::Left);
+        assert_eq!(app.visible_tags().len(), 2);
+        press(&mut app, KeyCode::Right);
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Down);
+        assert_eq!(app.selected_tag().unwrap().path, "project/zynapse/search");
+        assert_eq!(app.selected_note().unwrap().title(), "Search");
+        press(&mut app, KeyCode::Left);
+        assert_eq!(app.selected_tag().unwrap().path, "project/zynapse");
+
+        press(&mut app, KeyCode::Char('R'));
+        assert_eq!(app.input, Input::RenameTag);
+        app.handle_key(KeyEvent::new(KeyCode::Char('u'), KeyModifiers::CONTROL));
+        for c in "work/zynapse".chars() {
+            press(&mut app, KeyCode::Char(c));
+        }
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.input, Input::ConfirmRenameTag);
+        assert_eq!(app.rename_plan.as_ref().unwrap().changes.len(), 2);
+
+        // Declining leaves the vault as it was
+        // 拒否するとボルトはそのまま残る
+        press(&mut app, KeyCode::Char('n'));
+        assert_eq!(app.input, Input::None);
+        assert!(app.rename_plan.is_none());
+        assert_eq!(app.selected_tag().unwrap().path, "project/zynapse");
+
+        press(&mut app, KeyCode::Char('R'));
+        app.handle_key(KeyEvent::new(KeyCode::Char('u'), KeyModifiers::CONTROL));
+        for c in "work/zynapse".chars() {
+            press(&mut app, KeyCode::Char(c));
+        }
+        press(&mut app, KeyCode::Enter);
+        press(&mut app, KeyCode::Char('y'));
+        assert_eq!(app.input, Input::None);
+        let snapshots = Backups::new(&app.config.storage.backup).list().unwrap();
+        assert_eq!(snapshots.len(), 1);
+        assert_eq!(app.selected_tag().unwrap().path, "work/zynapse");
+        assert_eq!(app.search, "tag:work/zynapse");
+        assert_eq!(app.filtered.len(), 2);
+        let tui = app.notes.iter().find(|note| note.title() == "TUI").unwrap();
+        assert_eq!(tui.frontmatter.tags, ["work/zynapse/tui", "draft"]);
+        assert!(app.tags.position("project").is_none());
+
+        press(&mut app, KeyCode::Tab);
+        assert_eq!(app.focus, Focus::List);
+        app.perform(Action::Tags);
+        press(&mut app, KeyCode::BackTab);
+        assert_eq!(app.focus, Focus::Backlinks, "hidden tree is skipped");
+    }
+
+    #[test]
+    fn test_timeline_and_calendar() {
+        let (_dir, mut app) = app();
+        let periodic = app.config.periodic.clone();
+        let day = chrono::NaiveDate::from_ymd_opt(2024, 5, 16).unwrap();
+        crate::periodic::PeriodicNotes::new(&app.store, &periodic)
+            .open_or_create(crate::periodic::Period::Daily, day, &mut |_, default| {
+                Ok(default.unwrap_or_default().to_string())
+            })
+            .unwrap();
+        app.reload().unwrap();
+
+        press(&mut app, KeyCode::Char('t'));
+        let timeline = app.timeline.as_ref().unwrap();
+        assert_eq!(timeline.view, View::List);
+        assert_eq!(timeline.events.len(), 4);
+        press(&mut app, KeyCode::Char('j'));
+        let second = app.timeline.as_ref().unwrap().events[1].note;
+        assert_eq!(app.selected_index(), Some(second));
+        press(&mut app, KeyCode::Enter);
+        assert!(app.timeline.is_none());
+        assert_eq!(app.focus, Focus::Preview);
+        assert_eq!(app.selected_index(), Some(second));
+
+        press(&mut app, KeyCode::Char('c'));
+        let today = app.timeline.as_ref().unwrap().today;
+        assert_eq!(app.timeline.as_ref().unwrap().day, today);
+        press(&mut app, KeyCode::Char('h'));
+        press(&mut app, KeyCode::Enter);
+        assert!(app.status.as_ref().unwrap().0.starts_with("No notes on"));
+
+        // Clicking a day selects it; clicking it again lists its notes
+        // 日をクリックすると選択し、もう一度クリックするとその日のノートを一覧表示
+        app.areas.timeline = Rect::new(0, 0, 37, 10);
+        let timeline = app.timeline.as_mut().unwrap();
+        timeline.day = today;
+        let week = timeline
+            .weeks()
+            .iter()
+            .position(|week| week.contains(&Some(today)))
+            .unwrap();
+        timeline.move_days(-1);
+        let column = 1 + u16::try_from(today.weekday().num_days_from_monday()).unwrap() * 5 + 1;
+        let row = 2 + u16::try_from(week).unwrap();
+        let click = MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        };
+        app.handle_mouse(click);
+        assert_eq!(app.timeline.as_ref().unwrap().day, today);
+        app.handle_mouse(click);
+        assert_eq!(app.timeline.as_ref().unwrap().view, View::List);
+
+        // A day with only its daily note opens the note directly
+        // デイリーノートだけの日はそのノートを直接開く
+        press(&mut app, KeyCode::Tab);
+        assert_eq!(app.timeline.as_ref().unwrap().view, View::Calendar);
+        app.timeline.as_mut().unwrap().day = day;
+        press(&mut app, KeyCode::Enter);
+        assert!(app.timeline.is_none());
+        assert_eq!(app.selected_note().unwrap().title(), "2024-05-16");
+    }
+
+    #[test]
+    fn test_preview_links_are_followed() {
+        let (_dir, mut app) = app();
+        app.store
+            .create(&Note::new("Delta", "See [[alpha]] and [[Nowhere]]"))
+            .unwrap();
+        app.reload().unwrap();
+        app.search = "title:delta".to_string();
+        app.apply_search();
+        app.render_preview(40);
+        assert_eq!(app.preview.links.len(), 2);
+        assert!(!app.preview.links[1].exists);
+
+        press(&mut app, KeyCode::Char('['));
+        assert_eq!(app.focus, Focus::Preview);
+        assert_eq!(app.preview_link, Some(1));
+        press(&mut app, KeyCode::Enter);
+        assert!(app.status.as_ref().is_some_and(|(_, error, _)| *error));
+
+        press(&mut app, KeyCode::Char(']'));
+        assert_eq!(app.preview_link, Some(0));
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.selected_note().unwrap().title(), "Alpha");
+        assert_eq!(app.preview_link, None);
+        assert_eq!(app.search, "", "following clears a hiding search");
+    }
+
+    #[test]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn test_graph_view_navigation() {
+        let (_dir, mut app) = app();
+        press(&mut app, KeyCode::Char('v'));
+        let graph = app.graph.as_ref().unwrap();
+        assert_eq!(graph.nodes.len(), 3, "Alpha, Gamma and Beta two hops away");
+
+        press(&mut app, KeyCode::Tab);
+        let selected = app.graph.as_ref().unwrap().selected_note();
+        assert_eq!(selected, app.selected_index());
+        assert_eq!(app.selected_note().unwrap().title(), "Gamma");
+
+        press(&mut app, KeyCode::Enter);
+        let graph = app.graph.as_ref().unwrap();
+        assert_eq!(graph.selected, 0);
+        assert_eq!(graph.selected_note(), app.selected_index());
+
+        app.areas.graph = Rect::new(0, 0, 42, 22);
+        let node = &app.graph.as_ref().unwrap().nodes[2];
+        let column = u16::try_from((node.x * 40.0) as usize + 1).unwrap();
+        let row = u16::try_from(((1.0 - node.y) * 20.0) as usize + 1).unwrap();
+        app.handle_mouse(MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        });
+        assert_eq!(app.graph.as_ref().unwrap().selected, 2);
+
+        press(&mut app, KeyCode::Char('v'));
+        assert!(app.graph.is_none());
+        assert_eq!(app.focus, Focus::List);
+    }
+
+    #[test]
+    fn test_new_note_opens_editor() {
+        let (_dir, mut app) = app();
+        press(&mut app, KeyCode::Char('n'));
+        for c in "Delta".chars() {
+            press(&mut app, KeyCode::Char(c));
+        }
+        assert_eq!(press(&mut app, KeyCode::Enter), None);
+        assert_eq!(app.notes.len(), 4);
+        assert_eq!(app.selected_note().unwrap().title(), "Delta");
+        assert_eq!(app.editor.as_ref().unwrap().note.title(), "Delta");
+
+        // Type a link through completion, then save and close
+        // 補完でリンクを入力し、保存して閉じる
+        for c in "Go[[gam".chars() {
+            press(&mut app, KeyCode::Char(c));
+        }
+        press(&mut app, KeyCode::Enter);
+        press(&mut app, KeyCode::Esc);
+        app.store.create(&Note::new("Epsilon", "")).unwrap();
+        press(&mut app, KeyCode::Char('q'));
+        assert!(app.editor.is_none());
+        assert_eq!(app.selected_note().unwrap().body, "# Delta\n[[Gamma]]\n");
+        let gamma = app.links.resolve("Gamma").unwrap();
+        assert_eq!(app.links.backlinks(gamma).len(), 3, "links are refreshed");
+        assert_eq!(app.notes.len(), 4, "saving does not re-read the vault");
+
+        let effect = press(&mut app, KeyCode::Char('E'));
+        assert!(matches!(effect, Some(Effect::EditExternal(path)) if path.ends_with("delta.md")));
+    }
+}
@@ -0,0 +1,798 @@
+//! Embedded modal editor for note bodies
+//! ノート本文用の組み込みモーダルエディタ
+//!
+//! A small Vim-like editor so notes can be changed without leaving the TUI.
+//! Normal mode moves and edits by command, insert mode types text. Every
+//! command and every insert session is one undo step. Typing `[[` opens a
+//! completion list of note titles. The key bindings are fixed; the external
+//! editor remains available as a fallback. A save never overwrites changes
+//! made to the file by another program unless it is asked for a second time.
+//! TUIを離れずにノートを変更するための小さなVim風エディタです。ノーマルモードでは
+//! コマンドで移動・編集し、挿入モードでテキストを入力します。各コマンドと各挿入
+//! セッションがそれぞれ1つの取り消し単位になります。`[[`を入力するとノートタイトルの
+//! 補完一覧が開きます。キーバインドは固定で、外部エディタも代替手段として利用できます。
+//! 他のプログラムがファイルに加えた変更は、2回目の要求がない限り保存で上書きされません。
+//!
+//! | Normal mode | Action |
+//! |-------------|--------|
+//! | `h` `j` `k` `l`, arrows | Move |
+//! | `w` `b` | Next / previous word |
+//! | `0` `$` | Line start / end |
+//! | `g g` `G` | First / last line |
+//! | `i` `a` `I` `A` `o` `O` | Enter insert mode |
+//! | `x` `d d` | Delete character / line |
+//! | `u` `ctrl+r` | Undo / redo |
+//! | `ctrl+s` | Save |
+//! | `q` | Save and close |
+
+use crate::note::Note;
+use crate::storage::NoteStore;
+use crate::{Result, ZynapseError};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::time::{Duration, Instant};
+use unicode_width::UnicodeWidthChar;
+
+/// Most undo steps kept
+/// 保持する取り消し単位の最大数
+const UNDO_LIMIT: usize = 100;
+
+/// Most entries of the link completion list
+/// リンク補完一覧の最大項目数
+const COMPLETION_LIMIT: usize = 8;
+
+/// Editing mode
+/// 編集モード
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// Keys are commands
+    /// キーはコマンド
+    Normal,
+
+    /// Keys insert text
+    /// キーはテキストを挿入
+    Insert,
+}
+
+/// What the editor asks of the application after a key
+/// キー入力後にエディタがアプリケーションに要求すること
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Request {
+    /// Nothing to do
+    /// 何もしない
+    None,
+
+    /// Save the note
+    /// ノートを保存
+    Save,
+
+    /// Save the note if modified and close the editor
+    /// 変更があればノートを保存してエディタを閉じる
+    Close,
+}
+
+/// Open wiki-link completion
+/// 開いているWikiリンク補完
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Completion {
+    /// Column right after the `[[` being completed
+    /// 補完中の`[[`の直後の列
+    start: usize,
+
+    /// Note titles matching the typed text
+    /// 入力されたテキストに一致するノートタイトル
+    pub matches: Vec<String>,
+
+    /// Highlighted entry of `matches`
+    /// `matches`で強調表示中の項目
+    pub selected: usize,
+}
+
+/// Text and cursor at one point of the history
+/// 履歴のある時点のテキストとカーソル
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Snapshot {
+    lines: Vec<String>,
+    row: usize,
+    column: usize,
+}
+
+/// Editor state for one note
+/// 1つのノートのエディタ状態
+#[derive(Debug, Clone)]
+pub struct Editor {
+    /// Note being edited; its body is replaced on save
+    /// 編集中のノート（保存時に本文が置き換えられる）
+    pub note: Note,
+
+    /// The note as last read from or written to disk
+    /// 最後にディスクから読み込んだ、またはディスクに書き込んだノート
+    base: Note,
+
+    /// Whether the file changed on disk since it was opened
+    /// 開いてからディスク上のファイルが変更されたか
+    conflict: bool,
+
+    /// Current mode
+    /// 現在のモード
+    pub mode: Mode,
+
+    /// First visible line and display column, kept by [`Editor::scroll_to_cursor`]
+    /// 最初に表示される行と表示列（[`Editor::scroll_to_cursor`]が維持）
+    pub offset: (usize, usize),
+
+    lines: Vec<String>,
+    row: usize,
+    /// Cursor column in characters
+    /// 文字単位のカーソル列
+    column: usize,
+    undo: Vec<Snapshot>,
+    redo: Vec<Snapshot>,
+    /// Time of the first change since the last save
+    /// 最後の保存以降の最初の変更時刻
+    changed_at: Option<Instant>,
+    /// First key of a two-key command such as `d d`
+    /// `d d`のような2キーコマンドの最初のキー
+    pending: Option<char>,
+    completion: Option<Completion>,
+    names: Vec<String>,
+}
+
+impl Editor {
+    /// Start editing a note in normal mode
+    /// ノーマルモードでノートの編集を開始
+    ///
+    /// `names` are the note titles offered by link completion.
+    /// `names`はリンク補完で候補となるノートタイトルです。
+    #[must_use]
+    pub fn new(note: Note, names: Vec<String>) -> Self {
+        let mut lines: Vec<String> = note.body.lines().map(str::to_string).collect();
+        if lines.is_empty() {
+            lines.push(String::new());
+        }
+        Self {
+            base: note.clone(),
+            note,
+            conflict: false,
+            mode: Mode::Normal,
+            offset: (0, 0),
+            lines,
+            row: 0,
+            column: 0,
+            undo: Vec::new(),
+            redo: Vec::new(),
+            changed_at: None,
+            pending: None,
+            completion: None,
+            names,
+        }
+    }
+
+    /// Lines of the text
+    /// テキストの行
+    #[must_use]
+    pub fn lines(&self) -> &[String] {
+        &self.lines
+    }
+
+    /// Cursor line and character column
+    /// カーソルの行と文字単位の列
+    #[must_use]
+    pub const fn cursor(&self) -> (usize, usize) {
+        (self.row, self.column)
+    }
+
+    /// Display column of the cursor, counting wide characters as two
+    /// カーソルの表示列（全角文字は2桁として数える）
+    #[must_use]
+    pub fn cursor_column(&self) -> usize {
+        self.lines[self.row]
+            .chars()
+            .take(self.column)
+            .map(|c| c.width().unwrap_or(0))
+            .sum()
+    }
+
+    /// Open link completion, if any
+    /// 開いているリンク補完
+    #[must_use]
+    pub const fn completion(&self) -> Option<&Completion> {
+        self.completion.as_ref()
+    }
+
+    /// Whether there are unsaved changes
+    /// 未保存の変更があるか
+    #[must_use]
+    pub const fn is_modified(&self) -> bool {
+        self.changed_at.is_some()
+    }
+
+    /// Whether the last save was refused because the file changed on disk
+    /// ディスク上のファイルが変更されたため最後の保存が拒否されたか
+    #[must_use]
+    pub const fn has_conflict(&self) -> bool {
+        self.conflict
+    }
+
+    /// Whether unsaved changes are older than the auto-save interval
+    /// 未保存の変更が自動保存間隔より古いか
+    ///
+    /// A zero interval disables auto-save, and so does a conflict.
+    /// 間隔が0の場合、および競合がある場合、自動保存は無効です。
+    #[must_use]
+    pub fn autosave_due(&self, interval: Duration) -> bool {
+        !interval.is_zero()
+            && !self.conflict
+            && self
+                .changed_at
+                .is_some_and(|changed| changed.elapsed() >= interval)
+    }
+
+    /// The edited body
+    /// 編集された本文
+    #[must_use]
+    pub fn text(&self) -> String {
+        let mut text = self.lines.join("\n");
+        if !text.is_empty() {
+            text.push('\n');
+        }
+        text
+    }
+
+    /// Write the edited body through the store, unless the file changed on
+    /// disk since it was opened or last saved
+    /// 開いてから、または最後の保存以降にディスク上のファイルが変更されていなければ、
+    /// 編集された本文をストア経由で書き込む
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file changed on disk, or if the note cannot be
+    /// written. After a conflict, [`Editor::overwrite`] saves anyway.
+    /// ディスク上のファイルが変更された場合、またはノートを書き込めない場合に
+    /// エラーを返します。競合の後は[`Editor::overwrite`]で強制的に保存できます。
+    pub fn save(&mut self, store: &NoteStore) -> Result<()> {
+        if store.load(&self.note.path).ok().as_ref() != Some(&self.base) {
+            self.conflict = true;
+            return Err(ZynapseError::tui_error(format!(
+                "{} changed on disk; save again to overwrite it",
+                self.note.path.display()
+            )));
+        }
+        self.overwrite(store)
+    }
+
+    /// Write the edited body through the store, replacing the file on disk
+    /// 編集された本文をストア経由で書き込み、ディスク上のファイルを置き換える
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the note cannot be written.
+    /// ノートを書き込めない場合にエラーを返します。
+    pub fn overwrite(&mut self, store: &NoteStore) -> Result<()> {
+        self.note.body = self.text();
+        store.save(&mut self.note)?;
+        self.base = self.note.clone();
+        self.conflict = false;
+        self.changed_at = None;
+        Ok(())
+    }
+
+    /// Adjust the offset so the cursor is inside a `width` x `height` view
+    /// カーソルが`width`×`height`の表示範囲に入るようにオフセットを調整
+    pub fn scroll_to_cursor(&mut self, width: usize, height: usize) {
+        let column = self.cursor_column();
+        let (top, left) = &mut self.offset;
+        if self.row < *top {
+            *top = self.row;
+        } else if self.row >= *top + height.max(1) {
+            *top = self.row + 1 - height.max(1);
+        }
+        if column < *left {
+            *left = column;
+        } else if column >= *left + width.max(1) {
+            *left = column + 1 - width.max(1);
+        }
+    }
+
+    /// Handle a key press
+    /// キー入力を処理
+    pub fn handle_key(&mut self, key: KeyEvent) -> Request {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        if ctrl && key.code == KeyCode::Char('s') {
+            return Request::Save;
+        }
+        match self.mode {
+            Mode::Normal => self.normal(key.code, ctrl),
+            Mode::Insert => {
+                self.insert(key.code, ctrl);
+                Request::None
+            }
+        }
+    }
+
+    fn normal(&mut self, code: KeyCode, ctrl: bool) -> Request {
+        let pending = self.pending.take();
+        match (pending, code) {
+            (Some('g'), KeyCode::Char('g')) => self.move_to(0, 0),
+            (Some('d'), KeyCode::Char('d')) => {
+                self.checkpoint();
+                if self.lines.len() == 1 {
+                    self.lines[0].clear();
+                } else {
+                    self.lines.remove(self.row);
+                }
+                let row = self.row.min(self.lines.len() - 1);
+                self.move_to(row, 0);
+                self.changed();
+            }
+            (_, KeyCode::Char(c @ ('g' | 'd'))) => self.pending = Some(c),
+            (_, KeyCode::Char('r')) if ctrl => self.restore(true),
+            (_, KeyCode::Char('u')) => self.restore(false),
+            (_, KeyCode::Char('q')) => return Request::Close,
+            (_, KeyCode::Char('h') | KeyCode::Left) => {
+                self.move_to(self.row, self.column.saturating_sub(1));
+            }
+            (_, KeyCode::Char('l') | KeyCode::Right) => self.move_to(self.row, self.column + 1),
+            (_, KeyCode::Char('k') | KeyCode::Up) => {
+                self.move_to(self.row.saturating_sub(1), self.column);
+            }
+            (_, KeyCode::Char('j') | KeyCode::Down) => self.move_to(self.row + 1, self.column),
+            (_, KeyCode::Char('0') | KeyCode::Home) => self.move_to(self.row, 0),
+            (_, KeyCode::Char('$') | KeyCode::End) => self.move_to(self.row, usize::MAX),
+            (_, KeyCode::Char('G')) => self.move_to(usize::MAX, 0),
+            (_, KeyCode::Char('w')) => self.word(true),
+            (_, KeyCode::Char('b')) => self.word(false),
+            (_, KeyCode::Char('x') | KeyCode::Delete) if self.column < self.line_len() => {
+                self.checkpoint();
+                let at = self.byte(self.column);
+                self.lines[self.row].remove(at);
+                self.move_to(self.row, self.column);
+                self.changed();
+            }
+            (_, KeyCode::Char('i')) => self.start_insert(self.column),
+            (_, KeyCode::Char('a')) => self.start_insert(self.column + 1),
+            (_, KeyCode::Char('I')) => self.start_insert(0),
+            (_, KeyCode::Char('A')) => self.start_insert(usize::MAX),
+            (_, KeyCode::Char(c @ ('o' | 'O'))) => {
+                self.checkpoint();
+                let row = if c == 'o' { self.row + 1 } else { self.row };
+                self.lines.insert(row, String::new());
+                self.row = row;
+                self.column = 0;
+                self.mode = Mode::Insert;
+                self.changed();
+            }
+            _ => {}
+        }
+        Request::None
+    }
+
+    fn insert(&mut self, code: KeyCode, ctrl: bool) {
+        if let Some(completion) = &mut self.completion {
+            let count = completion.matches.len();
+            match code {
+                KeyCode::Down | KeyCode::Tab => {
+                    completion.selected = (completion.selected + 1) % count;
+                    return;
+                }
+                KeyCode::Char('n') if ctrl => {
+                    completion.selected = (completion.selected + 1) % count;
+                    return;
+                }
+                KeyCode::Up | KeyCode::BackTab => {
+                    completion.selected = (completion.selected + count - 1) % count;
+                    return;
+                }
+                KeyCode::Char('p') if ctrl => {
+                    completion.selected = (completion.selected + count - 1) % count;
+                    return;
+                }
+                KeyCode::Enter => {
+                    self.accept_completion();
+                    return;
+                }
+                KeyCode::Esc => {
+                    self.completion = None;
+                    return;
+                }
+                _ => {}
+            }
+        }
+
+        match code {
+            KeyCode::Esc => {
+                self.mode = Mode::Normal;
+                // An insert session that changed nothing is no undo step
+                // 何も変更しなかった挿入セッションは取り消し単位にしない
+                if self
+                    .undo
+                    .last()
+                    .is_some_and(|last| last.lines == self.lines)
+                {
+                    self.undo.pop();
+                }
+                self.move_to(self.row, self.column.saturating_sub(1));
+            }
+            KeyCode::Char(c) if !ctrl => {
+                let at = self.byte(self.column);
+                self.lines[self.row].insert(at, c);
+                self.column += 1;
+                self.changed();
+            }
+            KeyCode::Tab => {
+                let at = self.byte(self.column);
+                self.lines[self.row].insert_str(at, "    ");
+                self.column += 4;
+                self.changed();
+            }
+            KeyCode::Enter => {
+                let at = self.byte(self.column);
+                let rest = self.lines[self.row].split_off(at);
+                self.lines.insert(self.row + 1, rest);
+                self.row += 1;
+                self.column = 0;
+                self.changed();
+            }
+            KeyCode::Backspace => {
+                if self.column > 0 {
+                    self.column -= 1;
+                    let at = self.byte(self.column);
+                    self.lines[self.row].remove(at);
+                    self.changed();
+                } else if self.row > 0 {
+                    let line = self.lines.remove(self.row);
+                    self.row -= 1;
+                    self.column = self.line_len();
+                    self.lines[self.row].push_str(&line);
+                    self.changed();
+                }
+            }
+            KeyCode::Delete => {
+                if self.column < self.line_len() {
+                    let at = self.byte(self.column);
+                    self.lines[self.row].remove(at);
+                    self.changed();
+                } else if self.row + 1 < self.lines.len() {
+                    let line = self.lines.remove(self.row + 1);
+                    self.lines[self.row].push_str(&line);
+                    self.changed();
+                }
+            }
+            KeyCode::Left => self.column = self.column.saturating_sub(1),
+            KeyCode::Right => self.column = (self.column + 1).min(self.line_len()),
+            KeyCode::Up => self.move_to(self.row.saturating_sub(1), self.column),
+            KeyCode::Down => self.move_to(self.row + 1, self.column),
+            KeyCode::Home => self.column = 0,
+            KeyCode::End => self.column = self.line_len(),
+            _ => {}
+        }
+        self.update_completion();
+    }
+
+    /// Enter insert mode at a column, starting an undo step
+    /// 列を指定して挿入モードに入り、取り消し単位を開始
+    fn start_insert(&mut self, column: usize) {
+        self.checkpoint();
+        self.mode = Mode::Insert;
+        self.column = column.min(self.line_len());
+    }
+
+    /// Number of characters on the cursor line
+    /// カーソル行の文字数
+    fn line_len(&self) -> usize {
+        self.lines[self.row].chars().count()
+    }
+
+    /// Byte offset of a character column on the cursor line
+    /// カーソル行の文字単位の列のバイトオフセット
+    fn byte(&self, column: usize) -> usize {
+        let line = &self.lines[self.row];
+        line.char_indices()
+            .nth(column)
+            .map_or(line.len(), |(at, _)| at)
+    }
+
+    /// Move the cursor, clamping to the text; normal mode stays on a character
+    /// カーソルを移動しテキスト内に制限（ノーマルモードでは文字上に留まる）
+    fn move_to(&mut self, row: usize, column: usize) {
+        self.row = row.min(self.lines.len() - 1);
+        let len = self.line_len();
+        let last = if self.mode == Mode::Normal {
+            len.saturating_sub(1)
+        } else {
+            len
+        };
+        self.column = column.min(last);
+    }
+
+    /// Jump to the start of the next or previous word, across lines
+    /// 次または前の単語の先頭へ移動（行をまたぐ）
+    fn word(&mut self, forward: bool) {
+        let chars: Vec<char> = self.lines[self.row].chars().collect();
+        let is_word = |c: &char| !c.is_whitespace();
+        let mut column = self.column;
+        if forward {
+            while column < chars.len() && is_word(&chars[column]) {
+                column += 1;
+            }
+            while column < chars.len() && !is_word(&chars[column]) {
+                column += 1;
+            }
+            if column >= chars.len() && self.row + 1 < self.lines.len() {
+                let row = self.row + 1;
+                let start = self.lines[row]
+                    .chars()
+                    .take_while(char::is_ascii_whitespace)
+                    .count();
+                self.move_to(row, start);
+            } else {
+                self.move_to(self.row, column);
+            }
+        } else {
+            if column == 0 && self.row > 0 {
+                self.move_to(self.row - 1, usize::MAX);
+                return;
+            }
+            while column > 0 && !is_word(&chars[column - 1]) {
+                column -= 1;
+            }
+            while column > 0 && is_word(&chars[column - 1]) {
+                column -= 1;
+            }
+            self.move_to(self.row, column);
+        }
+    }
+
+    /// Record the current text as an undo step
+    /// 現在のテキストを取り消し単位として記録
+    fn checkpoint(&mut self) {
+        self.undo.push(self.snapshot());
+        if self.undo.len() > UNDO_LIMIT {
+            self.undo.remove(0);
+        }
+        self.redo.clear();
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            lines: self.lines.clone(),
+            row: self.row,
+            column: self.column,
+        }
+    }
+
+    /// Undo or redo one step
+    /// 1ステップ取り消すまたはやり直す
+    fn restore(&mut self, redo: bool) {
+        let (from, to) = if redo {
+            (&mut self.redo, &mut self.undo)
+        } else {
+            (&mut self.undo, &mut self.redo)
+        };
+        let Some(snapshot) = from.pop() else {
+            return;
+        };
+        to.push(Snapshot {
+            lines: std::mem::replace(&mut self.lines, snapshot.lines),
+            row: self.row,
+            column: self.column,
+        });
+        self.move_to(snapshot.row, snapshot.column);
+        self.changed();
+    }
+
+    fn changed(&mut self) {
+        self.changed_at.get_or_insert_with(Instant::now);
+    }
+
+    /// Open, refresh or close completion from the text before the cursor
+    /// カーソル前のテキストから補完を開く・更新する・閉じる
+    fn update_completion(&mut self) {
+        let before: String = self.lines[self.row].chars().take(self.column).collect();
+        let query = before.rfind("[[").and_then(|at| {
+            let query = &before[at + 2..];
+            (!query.contains("]]")).then_some((before[..at + 2].chars().count(), query))
+        });
+        let Some((start, query)) = query else {
+            self.completion = None;
+            return;
+        };
+
+        let query = query.to_lowercase();
+        let mut matches: Vec<(bool, &String)> = self
+            .names
+            .iter()
+            .filter_map(|name| {
+                let lower = name.to_lowercase();
+                lower
+                    .contains(&query)
+                    .then(|| (!lower.starts_with(&query), name))
+            })
+            .collect();
+        matches.sort();
+        let matches: Vec<String> = matches
+            .into_iter()
+            .take(COMPLETION_LIMIT)
+            .map(|(_, name)| name.clone())
+            .collect();
+
+        if matches.is_empty() {
+            self.completion = None;
+        } else {
+            let selected = self
+                .completion
+                .as_ref()
+                .filter(|completion| completion.start == start)
+                .map_or(0, |completion| completion.selected.min(matches.len() - 1));
+            self.completion = Some(Completion {
+                start,
+                matches,
+                selected,
+            });
+        }
+    }
+
+    /// Replace the typed text with the selected title and close the link
+    /// 入力されたテキストを選択中のタイトルに置き換え、リンクを閉じる
+    fn accept_completion(&mut self) {
+        let Some(completion) = self.completion.take() else {
+            return;
+        };
+        let name = &completion.matches[completion.selected];
+        let (from, to) = (self.byte(completion.start), self.byte(self.column));
+        let line = &mut self.lines[self.row];
+        let closed = line[to..].starts_with("]]");
+        let replacement = if closed {
+            name.clone()
+        } else {
+            format!("{name}]]")
+        };
+        line.replace_range(from..to, &replacement);
+        self.column = completion.start + replacement.chars().count();
+        self.changed();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(editor: &mut Editor, text: &str) {
+        for c in text.chars() {
+            let code = match c {
+                '\n' => KeyCode::Enter,
+                '\x1b' => KeyCode::Esc,
+                c => KeyCode::Char(c),
+            };
+            editor.handle_key(KeyEvent::new(code, KeyModifiers::NONE));
+        }
+    }
+
+    fn editor(body: &str) -> Editor {
+        Editor::new(
+            Note::new("Test", body),
+            vec![
+                "Rust".to_string(),
+                "Trust issues".to_string(),
+                "Go".to_string(),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_modes_and_editing() {
+        let mut editor = editor("first line\nsecond");
+        keys(&mut editor, "wix\x1b");
+        assert_eq!(editor.lines()[0], "first xline");
+        assert_eq!(editor.mode, Mode::Normal);
+        assert!(editor.is_modified());
+
+        keys(&mut editor, "jAed\x1b");
+        assert_eq!(editor.lines()[1], "seconded");
+        keys(&mut editor, "ggdd");
+        assert_eq!(editor.lines(), ["seconded"]);
+        keys(&mut editor, "onew\nline\x1b");
+        assert_eq!(editor.text(), "seconded\nnew\nline\n");
+        assert_eq!(editor.cursor(), (2, 3));
+
+        // 日本語 is three wide characters
+        // 日本語は3つの全角文字
+        keys(&mut editor, "O日本語\x1b");
+        assert_eq!(editor.cursor(), (2, 2));
+        assert_eq!(editor.cursor_column(), 4);
+    }
+
+    #[test]
+    fn test_undo_and_redo() {
+        let mut editor = editor("abc");
+        keys(&mut editor, "Adef\x1b");
+        keys(&mut editor, "0x");
+        assert_eq!(editor.lines(), ["bcdef"]);
+        keys(&mut editor, "u");
+        assert_eq!(editor.lines(), ["abcdef"]);
+        keys(&mut editor, "u");
+        assert_eq!(editor.lines(), ["abc"]);
+        keys(&mut editor, "u");
+        assert_eq!(editor.lines(), ["abc"]);
+        editor.handle_key(KeyEvent::new(KeyCode::Char('r'), KeyModifiers::CONTROL));
+        assert_eq!(editor.lines(), ["abcdef"]);
+
+        // An empty insert session leaves no undo step behind
+        // 空の挿入セッションは取り消し単位を残さない
+        keys(&mut editor, "i\x1bu");
+        assert_eq!(editor.lines(), ["abc"]);
+    }
+
+    #[test]
+    fn test_link_completion() {
+        let mut editor = editor("");
+        keys(&mut editor, "iSee [[ru");
+        let completion = editor.completion().unwrap();
+        assert_eq!(completion.matches, ["Rust", "Trust issues"]);
+
+        editor.handle_key(KeyEvent::new(KeyCode::Tab, KeyModifiers::NONE));
+        keys(&mut editor, "\n and more");
+        assert_eq!(editor.lines(), ["See [[Trust issues]] and more"]);
+        assert!(editor.completion().is_none());
+
+        keys(&mut editor, " [[zzz");
+        assert!(editor.completion().is_none(), "no match closes the list");
+        keys(&mut editor, "\x1b");
+        assert_eq!(editor.mode, Mode::Normal);
+    }
+
+    #[test]
+    fn test_save_and_autosave() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let store = NoteStore::new(dir.path().to_path_buf(), 1024 * 1024);
+        let note = Note::new("Saved", "old\n");
+        store.create(&note).unwrap();
+
+        let mut editor = Editor::new(note.clone(), Vec::new());
+        assert!(!editor.autosave_due(Duration::ZERO));
+        keys(&mut editor, "inew\x1b");
+        assert!(editor.autosave_due(Duration::from_nanos(1)));
+        assert!(
+            !editor.autosave_due(Duration::ZERO),
+            "zero disables auto-save"
+        );
+        assert_eq!(
+            editor.handle_key(KeyEvent::new(KeyCode::Char('s'), KeyModifiers::CONTROL)),
+            Request::Save
+        );
+        editor.save(&store).unwrap();
+        assert!(!editor.is_modified());
+        assert_eq!(store.load(&note.path).unwrap().body, "newold\n");
+
+        keys(&mut editor, "x");
+        editor.save(&store).unwrap();
+        assert_eq!(store.load(&note.path).unwrap().body, "neold\n");
+    }
+
+    #[test]
+    fn test_save_refuses_external_changes() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let store = NoteStore::new(dir.path().to_path_buf(), 1024 * 1024);
+        let note = Note::new("Shared", "old\n");
+        store.create(&note).unwrap();
+        let mut editor = Editor::new(note.clone(), Vec::new());
+        keys(&mut editor, "imine \x1b");
+
+        let mut external = store.load(&note.path).unwrap();
+        external.body = "theirs\n".to_string();
+        store.save(&mut external).unwrap();
+
+        assert!(editor.save(&store).is_err());
+        assert!(editor.has_conflict());
+        assert!(editor.is_modified());
+        assert!(!editor.autosave_due(Duration::from_nanos(1)));
+        assert_eq!(store.load(&note.path).unwrap().body, "theirs\n");
+
+        editor.overwrite(&store).unwrap();
+        assert!(!editor.has_conflict());
+        assert_eq!(store.load(&note.path).unwrap().body, "mine old\n");
+
+        store.delete(&note).unwrap();
+        keys(&mut editor, "x");
+        assert!(editor.save(&store).is_err(), "a deleted file is a conflict");
+    }
+}
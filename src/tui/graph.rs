@@ -0,0 +1,394 @@
+//! Force-directed layout of a note's link neighborhood
+//! ノートのリンク近傍の力指向レイアウト
+//!
+//! The graph view shows every note within a few hops of the current note. Nodes
+//! are placed by a deterministic Fruchterman–Reingold simulation in the unit
+//! square, with `y` pointing up like the canvas they are drawn on. Edges carry
+//! the synapse strength, the number of wiki links between two notes, which both
+//! pulls the notes closer together and thickens the drawn line.
+//! グラフビューは現在のノートから数ホップ以内のすべてのノートを表示します。
+//! ノードは決定的なFruchterman–Reingoldシミュレーションにより単位正方形内に配置され、
+//! 描画先のキャンバスと同様に`y`は上向きです。エッジは2つのノート間のWikiリンク数で
+//! あるシナプスの強さを持ち、ノート同士を引き寄せるとともに描画される線を太くします。
+
+use crate::links::LinkIndex;
+use std::f64::consts::PI;
+
+/// Most notes laid out at once; the closest ones are kept
+/// 一度に配置するノートの最大数（近いものが残される）
+const MAX_NODES: usize = 150;
+
+/// Simulation steps of the layout
+/// レイアウトのシミュレーションのステップ数
+const ITERATIONS: usize = 200;
+
+/// Margin kept free around the laid out nodes
+/// 配置されたノードの周囲に空ける余白
+const MARGIN: f64 = 0.08;
+
+/// A note placed in the graph
+/// グラフに配置されたノート
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Node {
+    /// Index of the note in the link index
+    /// リンクインデックス内のノートのインデックス
+    pub note: usize,
+
+    /// Hops from the center note
+    /// 中心のノートからのホップ数
+    pub depth: usize,
+
+    /// Horizontal position between 0 and 1
+    /// 0から1の間の水平位置
+    pub x: f64,
+
+    /// Vertical position between 0 and 1, upwards
+    /// 0から1の間の垂直位置（上向き）
+    pub y: f64,
+}
+
+/// A link between two nodes
+/// 2つのノード間のリンク
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Edge {
+    /// Position of one end in [`Graph::nodes`]
+    /// 一方の端の[`Graph::nodes`]内の位置
+    pub from: usize,
+
+    /// Position of the other end in [`Graph::nodes`]
+    /// もう一方の端の[`Graph::nodes`]内の位置
+    pub to: usize,
+
+    /// Number of wiki links between the notes in either direction
+    /// ノート間の両方向のWikiリンク数
+    pub strength: usize,
+}
+
+/// Laid out neighborhood of a note
+/// 配置されたノートの近傍
+#[derive(Debug, Clone, PartialEq)]
+pub struct Graph {
+    /// Nodes, the center note first
+    /// ノード（中心のノートが最初）
+    pub nodes: Vec<Node>,
+
+    /// Edges between the nodes
+    /// ノード間のエッジ
+    pub edges: Vec<Edge>,
+
+    /// Hops shown around the center note
+    /// 中心のノートの周囲に表示するホップ数
+    pub hops: usize,
+
+    /// Position of the selected node in `nodes`
+    /// `nodes`内の選択中のノードの位置
+    pub selected: usize,
+}
+
+impl Graph {
+    /// Lay out the notes within `hops` links of `center`
+    /// `center`から`hops`リンク以内のノートを配置
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use zynapse::links::LinkIndex;
+    /// use zynapse::note::Note;
+    /// use zynapse::tui::graph::Graph;
+    ///
+    /// let notes = vec![
+    ///     Note::new("Hub", "[[Spoke]] [[Spoke]]"),
+    ///     Note::new("Spoke", "[[Rim]]"),
+    ///     Note::new("Rim", ""),
+    /// ];
+    /// let graph = Graph::build(&LinkIndex::build(&notes), 0, 1);
+    /// assert_eq!(graph.nodes.len(), 2);
+    /// assert_eq!(graph.edges[0].strength, 2);
+    /// ```
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn build(links: &LinkIndex, center: usize, hops: usize) -> Self {
+        let mut neighborhood = links.neighborhood(center, hops);
+        neighborhood.truncate(MAX_NODES);
+
+        let count = neighborhood.len();
+        let nodes = neighborhood
+            .into_iter()
+            .enumerate()
+            .map(|(position, (note, depth))| {
+                // Start on rings by depth, spread by the golden angle so no two
+                // nodes share a position
+                // 深さごとの円周上から開始し、黄金角で広げて位置の重複を避ける
+                let angle = position as f64 * PI * (3.0 - 5f64.sqrt());
+                let radius = 0.4 * depth as f64 / hops.max(1) as f64;
+                Node {
+                    note,
+                    depth,
+                    x: 0.5 + radius * angle.cos(),
+                    y: 0.5 + radius * angle.sin(),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let mut edges = Vec::new();
+        for from in 0..count {
+            for to in from + 1..count {
+                let strength = links.strength(nodes[from].note, nodes[to].note);
+                if strength > 0 {
+                    edges.push(Edge { from, to, strength });
+                }
+            }
+        }
+
+        let mut graph = Self {
+            nodes,
+            edges,
+            hops,
+            selected: 0,
+        };
+        graph.layout();
+        graph
+    }
+
+    /// Note of the selected node
+    /// 選択中のノードのノート
+    #[must_use]
+    pub fn selected_note(&self) -> Option<usize> {
+        self.nodes.get(self.selected).map(|node| node.note)
+    }
+
+    /// Select the node showing a note, if it is in the graph
+    /// ノートを表すノードがグラフにあれば選択
+    pub fn select_note(&mut self, note: usize) {
+        if let Some(position) = self.nodes.iter().position(|node| node.note == note) {
+            self.selected = position;
+        }
+    }
+
+    /// Select the next or previous node in order of distance from the center
+    /// 中心からの距離順で次または前のノードを選択
+    pub fn cycle(&mut self, forward: bool) {
+        let count = self.nodes.len();
+        if count > 0 {
+            self.selected = if forward {
+                (self.selected + 1) % count
+            } else {
+                (self.selected + count - 1) % count
+            };
+        }
+    }
+
+    /// Select the closest node in a direction from the selected one
+    /// 選択中のノードからある方向で最も近いノードを選択
+    ///
+    /// Nodes within 60 degrees of the direction are preferred; otherwise any
+    /// node on that side is taken. Returns whether the selection moved.
+    /// 方向から60度以内のノードを優先し、なければその側にある任意のノードを選びます。
+    /// 選択が移動したかどうかを返します。
+    pub fn step(&mut self, dx: f64, dy: f64) -> bool {
+        let Some(origin) = self.nodes.get(self.selected) else {
+            return false;
+        };
+        let candidates = self
+            .nodes
+            .iter()
+            .enumerate()
+            .filter(|&(position, _)| position != self.selected)
+            .filter_map(|(position, node)| {
+                let (vx, vy) = (node.x - origin.x, node.y - origin.y);
+                let distance = vx.hypot(vy);
+                let cosine = vx.mul_add(dx, vy * dy) / (distance * dx.hypot(dy)).max(f64::EPSILON);
+                (cosine > 0.0).then_some((position, distance, cosine))
+            })
+            .collect::<Vec<_>>();
+
+        let closest = |cone: f64| {
+            candidates
+                .iter()
+                .filter(|(_, _, cosine)| *cosine >= cone)
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|(position, _, _)| *position)
+        };
+        match closest(0.5).or_else(|| closest(0.0)) {
+            Some(position) => {
+                self.selected = position;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Node closest to a point, if within `radius`
+    /// 点に最も近いノード（`radius`以内の場合）
+    ///
+    /// `scale` stretches the axes before measuring, so the radius can be given
+    /// in terminal cells.
+    /// `scale`は測定前に軸を伸縮させるため、半径をターミナルのセル単位で指定できます。
+    #[must_use]
+    pub fn node_at(&self, x: f64, y: f64, scale: (f64, f64), radius: f64) -> Option<usize> {
+        self.nodes
+            .iter()
+            .enumerate()
+            .map(|(position, node)| {
+                let distance = ((node.x - x) * scale.0).hypot((node.y - y) * scale.1);
+                (position, distance)
+            })
+            .filter(|(_, distance)| *distance <= radius)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(position, _)| position)
+    }
+
+    /// Run the force simulation and fit the result into the unit square
+    /// 力のシミュレーションを実行し、結果を単位正方形に収める
+    #[allow(clippy::cast_precision_loss)]
+    fn layout(&mut self) {
+        let count = self.nodes.len();
+        if count < 2 {
+            for node in &mut self.nodes {
+                (node.x, node.y) = (0.5, 0.5);
+            }
+            return;
+        }
+
+        // Ideal distance between nodes for the unit area
+        // 単位面積に対するノード間の理想的な距離
+        let k = (1.0 / count as f64).sqrt();
+        let mut temperature = 0.1;
+        let cooling = temperature / ITERATIONS as f64;
+        let mut shift = vec![(0.0, 0.0); count];
+
+        for _ in 0..ITERATIONS {
+            shift.fill((0.0, 0.0));
+            for a in 0..count {
+                for b in a + 1..count {
+                    let (dx, dy, distance) = self.offset(a, b);
+                    let force = k * k / distance;
+                    let (fx, fy) = (dx / distance * force, dy / distance * force);
+                    shift[a].0 += fx;
+                    shift[a].1 += fy;
+                    shift[b].0 -= fx;
+                    shift[b].1 -= fy;
+                }
+            }
+            for edge in &self.edges {
+                let (dx, dy, distance) = self.offset(edge.from, edge.to);
+                // Stronger synapses pull harder
+                // 強いシナプスほど強く引き寄せる
+                let force = distance * distance / k * (1.0 + (edge.strength as f64).ln());
+                let (fx, fy) = (dx / distance * force, dy / distance * force);
+                shift[edge.from].0 -= fx;
+                shift[edge.from].1 -= fy;
+                shift[edge.to].0 += fx;
+                shift[edge.to].1 += fy;
+            }
+
+            // The center note stays pinned
+            // 中心のノートは固定されたまま
+            for (node, (sx, sy)) in self.nodes.iter_mut().zip(&shift).skip(1) {
+                let length = sx.hypot(*sy).max(f64::EPSILON);
+                let step = length.min(temperature);
+                node.x += sx / length * step;
+                node.y += sy / length * step;
+            }
+            temperature -= cooling;
+        }
+        self.fit();
+    }
+
+    /// Vector from node `b` to node `a` and its non-zero length
+    /// ノード`b`からノード`a`へのベクトルとその非ゼロの長さ
+    fn offset(&self, a: usize, b: usize) -> (f64, f64, f64) {
+        let (a, b) = (&self.nodes[a], &self.nodes[b]);
+        let (dx, dy) = (a.x - b.x, a.y - b.y);
+        (dx, dy, dx.hypot(dy).max(1e-4))
+    }
+
+    /// Scale the layout to fill the unit square inside the margin
+    /// 余白の内側の単位正方形を満たすようにレイアウトを拡大縮小
+    fn fit(&mut self) {
+        let bounds = |value: fn(&Node) -> f64| {
+            self.nodes
+                .iter()
+                .map(value)
+                .fold((f64::MAX, f64::MIN), |(low, high), v| {
+                    (low.min(v), high.max(v))
+                })
+        };
+        let (left, right) = bounds(|node| node.x);
+        let (bottom, top) = bounds(|node| node.y);
+        let span = 2.0f64.mul_add(-MARGIN, 1.0);
+        let scale = |value: f64, low: f64, high: f64| {
+            if (high - low).abs() < f64::EPSILON {
+                0.5
+            } else {
+                ((value - low) / (high - low)).mul_add(span, MARGIN)
+            }
+        };
+        for node in &mut self.nodes {
+            node.x = scale(node.x, left, right);
+            node.y = scale(node.y, bottom, top);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::note::Note;
+
+    fn star() -> LinkIndex {
+        LinkIndex::build(&[
+            Note::new("Center", "[[North]] [[East]] [[East]] [[Far]]"),
+            Note::new("North", ""),
+            Note::new("East", "[[Beyond]]"),
+            Note::new("Far", ""),
+            Note::new("Beyond", ""),
+            Note::new("Isolated", ""),
+        ])
+    }
+
+    #[test]
+    fn test_layout_is_bounded_and_deterministic() {
+        let links = star();
+        let graph = Graph::build(&links, 0, 2);
+        assert_eq!(graph.nodes.len(), 5, "isolated note is left out");
+        assert_eq!(graph.nodes[0].note, 0);
+        assert_eq!(graph.edges.len(), 4);
+        assert!(graph.edges.iter().any(|edge| edge.strength == 2));
+        for node in &graph.nodes {
+            assert!((0.0..=1.0).contains(&node.x) && (0.0..=1.0).contains(&node.y));
+        }
+        for (i, a) in graph.nodes.iter().enumerate() {
+            for b in &graph.nodes[i + 1..] {
+                assert!((a.x - b.x).hypot(a.y - b.y) > 0.05, "nodes overlap");
+            }
+        }
+        assert_eq!(graph, Graph::build(&links, 0, 2));
+
+        let single = Graph::build(&links, 5, 2);
+        assert_eq!((single.nodes[0].x, single.nodes[0].y), (0.5, 0.5));
+    }
+
+    #[test]
+    fn test_navigation() {
+        let mut graph = Graph::build(&star(), 0, 1);
+        for node in &mut graph.nodes {
+            (node.x, node.y) = (0.5, 0.5);
+        }
+        graph.nodes[1].y = 0.9;
+        graph.nodes[2].x = 0.9;
+        graph.nodes[3].x = 0.1;
+
+        assert!(graph.step(0.0, 1.0));
+        assert_eq!(graph.selected_note(), Some(1));
+        assert!(!graph.step(0.0, 1.0), "nothing further up");
+        assert!(graph.step(1.0, 0.0));
+        assert_eq!(graph.selected_note(), Some(2));
+        assert_eq!(graph.node_at(0.12, 0.5, (10.0, 10.0), 1.0), Some(3));
+        assert_eq!(graph.node_at(0.3, 0.3, (10.0, 10.0), 1.0), None);
+
+        graph.select_note(3);
+        graph.cycle(true);
+        assert_eq!(graph.selected, 0);
+    }
+}
@@ -0,0 +1,341 @@
+//! Lightweight syntax highlighting for code blocks
+//! コードブロック用の軽量なシンタックスハイライト
+//!
+//! Lines are highlighted one at a time with a small table of keywords, comment
+//! markers and string quotes per language. This covers the common cases in
+//! notes without shipping full grammars; block comments spanning several lines
+//! are not tracked.
+//! 言語ごとのキーワード・コメント記号・文字列の引用符の小さな表を使って1行ずつ
+//! ハイライトします。完全な文法を同梱せずにノートでよくあるケースをカバーします。
+//! 複数行にわたるブロックコメントは追跡しません。
+
+use super::theme::{Role, Theme};
+use ratatui::style::Style;
+use ratatui::text::Span;
+
+/// Highlighting rules of a language
+/// 言語のハイライト規則
+#[derive(Debug)]
+struct Syntax {
+    /// Reserved words
+    /// 予約語
+    keywords: &'static [&'static str],
+
+    /// Markers starting a comment that runs to the end of the line
+    /// 行末まで続くコメントの開始記号
+    comments: &'static [&'static str],
+
+    /// Characters delimiting string literals
+    /// 文字列リテラルの区切り文字
+    quotes: &'static [char],
+}
+
+const RUST: Syntax = Syntax {
+    keywords: &[
+        "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum",
+        "extern", "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move",
+        "mut", "pub", "ref", "return", "self", "Self", "static", "struct", "super", "trait",
+        "true", "type", "unsafe", "use", "where", "while",
+    ],
+    comments: &["//"],
+    quotes: &['"'],
+};
+
+const PYTHON: Syntax = Syntax {
+    keywords: &[
+        "and", "as", "assert", "async", "await", "break", "class", "continue", "def", "del",
+        "elif", "else", "except", "False", "finally", "for", "from", "global", "if", "import",
+        "in", "is", "lambda", "None", "nonlocal", "not", "or", "pass", "raise", "return", "True",
+        "try", "while", "with", "yield",
+    ],
+    comments: &["#"],
+    quotes: &['"', '\''],
+};
+
+const JAVASCRIPT: Syntax = Syntax {
+    keywords: &[
+        "async",
+        "await",
+        "break",
+        "case",
+        "catch",
+        "class",
+        "const",
+        "continue",
+        "default",
+        "delete",
+        "do",
+        "else",
+        "export",
+        "extends",
+        "false",
+        "finally",
+        "for",
+        "from",
+        "function",
+        "if",
+        "import",
+        "in",
+        "instanceof",
+        "interface",
+        "let",
+        "new",
+        "null",
+        "return",
+        "static",
+        "super",
+        "switch",
+        "this",
+        "throw",
+        "true",
+        "try",
+        "type",
+        "typeof",
+        "undefined",
+        "var",
+        "void",
+        "while",
+        "yield",
+    ],
+    comments: &["//"],
+    quotes: &['"', '\'', '`'],
+};
+
+const GO: Syntax = Syntax {
+    keywords: &[
+        "break",
+        "case",
+        "chan",
+        "const",
+        "continue",
+        "default",
+        "defer",
+        "else",
+        "false",
+        "fallthrough",
+        "for",
+        "func",
+        "go",
+        "goto",
+        "if",
+        "import",
+        "interface",
+        "map",
+        "nil",
+        "package",
+        "range",
+        "return",
+        "select",
+        "struct",
+        "switch",
+        "true",
+        "type",
+        "var",
+    ],
+    comments: &["//"],
+    quotes: &['"', '`'],
+};
+
+const C_LIKE: Syntax = Syntax {
+    keywords: &[
+        "auto",
+        "bool",
+        "break",
+        "case",
+        "catch",
+        "char",
+        "class",
+        "const",
+        "continue",
+        "default",
+        "delete",
+        "do",
+        "double",
+        "else",
+        "enum",
+        "extends",
+        "false",
+        "final",
+        "float",
+        "for",
+        "if",
+        "implements",
+        "import",
+        "int",
+        "interface",
+        "long",
+        "namespace",
+        "new",
+        "null",
+        "nullptr",
+        "package",
+        "private",
+        "protected",
+        "public",
+        "return",
+        "short",
+        "static",
+        "struct",
+        "switch",
+        "template",
+        "this",
+        "throw",
+        "true",
+        "try",
+        "typedef",
+        "unsigned",
+        "using",
+        "var",
+        "void",
+        "volatile",
+        "while",
+    ],
+    comments: &["//"],
+    quotes: &['"', '\''],
+};
+
+const SHELL: Syntax = Syntax {
+    keywords: &[
+        "case", "do", "done", "echo", "elif", "else", "esac", "export", "fi", "for", "function",
+        "if", "in", "local", "return", "then", "until", "while",
+    ],
+    comments: &["#"],
+    quotes: &['"', '\''],
+};
+
+const SQL: Syntax = Syntax {
+    keywords: &[
+        "and", "as", "by", "create", "delete", "from", "group", "insert", "into", "join", "left",
+        "limit", "not", "null", "on", "or", "order", "select", "set", "table", "update", "values",
+        "where", "AND", "AS", "BY", "CREATE", "DELETE", "FROM", "GROUP", "INSERT", "INTO", "JOIN",
+        "LEFT", "LIMIT", "NOT", "NULL", "ON", "OR", "ORDER", "SELECT", "SET", "TABLE", "UPDATE",
+        "VALUES", "WHERE",
+    ],
+    comments: &["--"],
+    quotes: &['\''],
+};
+
+const DATA: Syntax = Syntax {
+    keywords: &["true", "false", "null", "yes", "no"],
+    comments: &["#"],
+    quotes: &['"', '\''],
+};
+
+/// Highlighting rules for a code block language tag
+/// コードブロックの言語タグに対するハイライト規則
+fn syntax(language: &str) -> Option<&'static Syntax> {
+    Some(match language.to_ascii_lowercase().as_str() {
+        "rust" | "rs" => &RUST,
+        "python" | "py" => &PYTHON,
+        "javascript" | "js" | "jsx" | "typescript" | "ts" | "tsx" => &JAVASCRIPT,
+        "go" | "golang" => &GO,
+        "c" | "h" | "cpp" | "c++" | "hpp" | "java" | "cs" | "csharp" | "kotlin" | "kt" => &C_LIKE,
+        "sh" | "bash" | "zsh" | "fish" | "shell" | "console" => &SHELL,
+        "sql" => &SQL,
+        "toml" | "yaml" | "yml" | "json" | "ini" => &DATA,
+        _ => return None,
+    })
+}
+
+/// Highlight one line of a code block
+/// コードブロックの1行をハイライト
+///
+/// Unknown languages are rendered in the plain code block style.
+/// 不明な言語は通常のコードブロックのスタイルで描画されます。
+#[must_use]
+pub fn highlight(line: &str, language: &str, theme: &Theme) -> Vec<Span<'static>> {
+    let base = theme.style(Role::CodeBlock);
+    let Some(syntax) = syntax(language) else {
+        return vec![Span::styled(line.to_string(), base)];
+    };
+    let style = |role| base.patch(theme.style(role));
+
+    let mut spans: Vec<Span<'static>> = Vec::new();
+    let mut push = |text: &str, style: Style| match spans.last_mut() {
+        Some(last) if last.style == style => last.content.to_mut().push_str(text),
+        _ => spans.push(Span::styled(text.to_string(), style)),
+    };
+
+    let mut rest = line;
+    while let Some(c) = rest.chars().next() {
+        if syntax
+            .comments
+            .iter()
+            .any(|marker| rest.starts_with(marker))
+        {
+            push(rest, style(Role::CodeComment));
+            break;
+        }
+
+        let end = if syntax.quotes.contains(&c) {
+            let end = string_end(rest, c);
+            push(&rest[..end], style(Role::CodeString));
+            end
+        } else if c.is_ascii_digit() {
+            let end = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '.'))
+                .unwrap_or(rest.len());
+            push(&rest[..end], style(Role::CodeNumber));
+            end
+        } else if c.is_alphabetic() || c == '_' {
+            let end = rest
+                .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+            let word = &rest[..end];
+            if syntax.keywords.contains(&word) {
+                push(word, style(Role::CodeKeyword));
+            } else {
+                push(word, base);
+            }
+            end
+        } else {
+            push(&rest[..c.len_utf8()], base);
+            c.len_utf8()
+        };
+        rest = &rest[end..];
+    }
+    spans
+}
+
+/// Byte length of a string literal starting at the opening quote
+/// 開き引用符から始まる文字列リテラルのバイト長
+///
+/// Unterminated strings run to the end of the line.
+/// 閉じられていない文字列は行末まで続きます。
+fn string_end(text: &str, quote: char) -> usize {
+    let mut escaped = false;
+    for (offset, c) in text.char_indices().skip(1) {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == quote {
+            return offset + c.len_utf8();
+        }
+    }
+    text.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rust_line() {
+        let theme = Theme::builtin("dark").unwrap();
+        let spans = highlight(r#"let s = "a \"b\""; // note 42"#, "rust", &theme);
+        let text = |role| {
+            spans
+                .iter()
+                .filter(|span| span.style == theme.style(Role::CodeBlock).patch(theme.style(role)))
+                .map(|span| span.content.as_ref())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(text(Role::CodeKeyword), ["let"]);
+        assert_eq!(text(Role::CodeString), [r#""a \"b\"""#]);
+        assert_eq!(text(Role::CodeComment), ["// note 42"]);
+
+        let plain = highlight("let x = 1;", "brainfuck", &theme);
+        assert_eq!(plain.len(), 1);
+    }
+}
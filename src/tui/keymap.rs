@@ -0,0 +1,667 @@
+//! Key chord parsing and key-to-action dispatch
+//! キーコードの解析とキーからアクションへのディスパッチ
+//!
+//! A chord is a whitespace-separated sequence of keys. Each key is a character
+//! or a key name, optionally prefixed by `ctrl+`, `alt+` and `shift+`; the
+//! token `<leader>` stands for the configured leader key.
+//! キーコードは空白区切りのキーの並びです。各キーは文字またはキー名で、`ctrl+`・
+//! `alt+`・`shift+`を前置できます。`<leader>`トークンは設定されたリーダーキーを表します。
+//!
+//! ```text
+//! q            ctrl+k         g g          <leader>f
+//! alt+<        shift+tab      ctrl+x ctrl+c   f5
+//! ```
+//!
+//! No chord may be a prefix of another one, so a key sequence is dispatched as
+//! soon as it is complete, without timeouts.
+//! キーコードは他のキーコードの前置になれないため、キーの並びはタイムアウトなしで
+//! 完成した時点でディスパッチされます。
+
+use super::action::Action;
+use crate::config::{KeyBindings, KeymapPreset, Problem};
+use crate::{Result, ZynapseError};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::fmt;
+use std::str::FromStr;
+
+/// Named keys accepted in chords besides single characters
+/// 単一文字以外にキーコードで使用できる名前付きキー
+const NAMED_KEYS: [(&str, KeyCode); 17] = [
+    ("space", KeyCode::Char(' ')),
+    ("enter", KeyCode::Enter),
+    ("esc", KeyCode::Esc),
+    ("tab", KeyCode::Tab),
+    ("backtab", KeyCode::BackTab),
+    ("backspace", KeyCode::Backspace),
+    ("delete", KeyCode::Delete),
+    ("insert", KeyCode::Insert),
+    ("up", KeyCode::Up),
+    ("down", KeyCode::Down),
+    ("left", KeyCode::Left),
+    ("right", KeyCode::Right),
+    ("home", KeyCode::Home),
+    ("end", KeyCode::End),
+    ("pageup", KeyCode::PageUp),
+    ("pagedown", KeyCode::PageDown),
+    ("leader", KeyCode::Null),
+];
+
+/// A single key with modifiers, normalized so that it compares equal to the
+/// matching terminal event
+/// 修飾キー付きの単一キー（対応するターミナルイベントと等しくなるよう正規化）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Key {
+    /// Key code
+    /// キーコード
+    pub code: KeyCode,
+
+    /// Control, Alt and Shift modifiers
+    /// Control・Alt・Shift修飾キー
+    pub modifiers: KeyModifiers,
+}
+
+impl Key {
+    /// Create a normalized key
+    /// 正規化されたキーを作成
+    ///
+    /// Shift is folded into characters (`shift+g` is `G`) and into `backtab`.
+    /// Shiftは文字（`shift+g`は`G`）と`backtab`に取り込まれます。
+    #[must_use]
+    pub fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        let mut modifiers =
+            modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT);
+        let code = match code {
+            KeyCode::Char(c) if modifiers.contains(KeyModifiers::SHIFT) => {
+                modifiers.remove(KeyModifiers::SHIFT);
+                KeyCode::Char(c.to_uppercase().next().unwrap_or(c))
+            }
+            KeyCode::Tab if modifiers.contains(KeyModifiers::SHIFT) => {
+                modifiers.remove(KeyModifiers::SHIFT);
+                KeyCode::BackTab
+            }
+            KeyCode::BackTab => {
+                modifiers.remove(KeyModifiers::SHIFT);
+                KeyCode::BackTab
+            }
+            code => code,
+        };
+        Self { code, modifiers }
+    }
+}
+
+impl From<KeyEvent> for Key {
+    fn from(event: KeyEvent) -> Self {
+        Self::new(event.code, event.modifiers)
+    }
+}
+
+impl FromStr for Key {
+    type Err = ZynapseError;
+
+    fn from_str(text: &str) -> Result<Self> {
+        let invalid =
+            |reason: &str| ZynapseError::config_error(format!("Invalid key `{text}`: {reason}"));
+
+        let mut rest = text;
+        let mut modifiers = KeyModifiers::NONE;
+        loop {
+            let lower = rest.to_ascii_lowercase();
+            let (modifier, prefix) = if lower.starts_with("ctrl+") {
+                (KeyModifiers::CONTROL, "ctrl+")
+            } else if lower.starts_with("alt+") {
+                (KeyModifiers::ALT, "alt+")
+            } else if lower.starts_with("shift+") {
+                (KeyModifiers::SHIFT, "shift+")
+            } else {
+                break;
+            };
+            modifiers |= modifier;
+            rest = &rest[prefix.len()..];
+        }
+
+        // `<tab>` and `tab` are the same key
+        // `<tab>`と`tab`は同じキー
+        if rest.len() > 2 && rest.starts_with('<') && rest.ends_with('>') {
+            rest = &rest[1..rest.len() - 1];
+        }
+
+        let mut chars = rest.chars();
+        let code = match (chars.next(), chars.next()) {
+            (None, _) => return Err(invalid("missing key")),
+            (Some(c), None) => KeyCode::Char(c),
+            _ => {
+                let name = rest.to_ascii_lowercase();
+                if let Some(number) = name.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+                    if !(1..=12).contains(&number) {
+                        return Err(invalid("function keys range from f1 to f12"));
+                    }
+                    KeyCode::F(number)
+                } else {
+                    match NAMED_KEYS.iter().find(|(known, _)| *known == name) {
+                        Some((_, KeyCode::Null)) => {
+                            return Err(invalid("`<leader>` must start a chord token"))
+                        }
+                        Some(&(_, code)) => code,
+                        None => return Err(invalid("unknown key name")),
+                    }
+                }
+            }
+        };
+        Ok(Self::new(code, modifiers))
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            f.write_str("ctrl+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            f.write_str("alt+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            f.write_str("shift+")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => f.write_str("space"),
+            KeyCode::Char(c) => write!(f, "{c}"),
+            KeyCode::F(number) => write!(f, "f{number}"),
+            code => {
+                let name = NAMED_KEYS
+                    .iter()
+                    .find(|&&(_, known)| known == code)
+                    .map_or("?", |(name, _)| name);
+                f.write_str(name)
+            }
+        }
+    }
+}
+
+/// A sequence of keys bound to an action
+/// アクションにバインドされたキーの並び
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Chord(Vec<Key>);
+
+impl Chord {
+    /// Parse a chord, expanding `<leader>` to the given key
+    /// キーコードを解析し、`<leader>`を指定されたキーに展開
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the chord is empty or contains an invalid key.
+    /// キーコードが空、または無効なキーを含む場合にエラーを返します。
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use zynapse::tui::keymap::{Chord, Key};
+    ///
+    /// let leader: Key = "space".parse()?;
+    /// let chord = Chord::parse("<leader>f", &leader)?;
+    /// assert_eq!(chord.to_string(), "space f");
+    /// assert_eq!(Chord::parse("ctrl+x  ctrl+c", &leader)?.keys().len(), 2);
+    /// # Ok::<(), zynapse::ZynapseError>(())
+    /// ```
+    pub fn parse(text: &str, leader: &Key) -> Result<Self> {
+        const LEADER: &str = "<leader>";
+
+        let mut keys = Vec::new();
+        for token in text.split_whitespace() {
+            let is_leader = token
+                .get(..LEADER.len())
+                .is_some_and(|head| head.eq_ignore_ascii_case(LEADER));
+            if is_leader {
+                keys.push(*leader);
+                let rest = &token[LEADER.len()..];
+                if !rest.is_empty() {
+                    keys.push(rest.parse()?);
+                }
+            } else {
+                keys.push(token.parse()?);
+            }
+        }
+        if keys.is_empty() {
+            return Err(ZynapseError::config_error("Key chord cannot be empty"));
+        }
+        Ok(Self(keys))
+    }
+
+    /// Keys of the chord in order
+    /// キーコードのキー（順番通り）
+    #[must_use]
+    pub fn keys(&self) -> &[Key] {
+        &self.0
+    }
+}
+
+impl fmt::Display for Chord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (position, key) in self.0.iter().enumerate() {
+            if position > 0 {
+                f.write_str(" ")?;
+            }
+            write!(f, "{key}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Result of feeding a key to the keymap
+/// キーマップにキーを渡した結果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    /// A chord was completed
+    /// キーコードが完成した
+    Action(Action),
+
+    /// The keys so far start at least one chord
+    /// ここまでのキーが少なくとも1つのキーコードの先頭に一致する
+    Pending,
+
+    /// The key is not bound
+    /// キーがバインドされていない
+    Unbound,
+}
+
+/// Chords of every action and the keys typed towards the next one
+/// 全アクションのキーコードと、次のキーコードに向けて入力されたキー
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Keymap {
+    /// Chords with their actions, in preset order
+    /// キーコードとそのアクション（プリセット順）
+    bindings: Vec<(Chord, Action)>,
+
+    /// Keys of an incomplete chord
+    /// 未完成のキーコードのキー
+    pending: Vec<Key>,
+}
+
+impl Keymap {
+    /// Build the keymap from the configured preset and overrides
+    /// 設定されたプリセットと上書きからキーマップを構築
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an action name is unknown, a chord is invalid, or
+    /// two chords conflict: the same chord bound to different actions, or one
+    /// chord being a prefix of another.
+    /// アクション名が不明な場合、キーコードが無効な場合、またはキーコードが衝突する
+    /// 場合（同じキーコードが異なるアクションにバインドされている、あるキーコードが
+    /// 別のキーコードの前置になっている）にエラーを返します。
+    pub fn new(config: &KeyBindings) -> Result<Self> {
+        let (keymap, problems) = Self::build(config);
+        match problems.into_iter().next() {
+            Some(problem) => Err(ZynapseError::config_error(problem.message)),
+            None => Ok(keymap),
+        }
+    }
+
+    /// Every problem of the configured bindings, in preset order
+    /// 設定されたキーバインドのすべての問題（プリセット順）
+    #[must_use]
+    pub fn problems(config: &KeyBindings) -> Vec<Problem> {
+        Self::build(config).1
+    }
+
+    /// Build the keymap, skipping invalid and conflicting chords
+    /// 無効なキーコードと衝突するキーコードを除いてキーマップを構築
+    fn build(config: &KeyBindings) -> (Self, Vec<Problem>) {
+        let mut problems = Vec::new();
+        let leader: Key = config.leader.parse().unwrap_or_else(|e| {
+            problems.push(Problem::new(
+                "tui.keybindings.leader",
+                format!("tui.keybindings.leader: {e}"),
+            ));
+            Key::new(KeyCode::Char(' '), KeyModifiers::NONE)
+        });
+
+        for name in config.bindings.keys() {
+            if let Err(e) = name.parse::<Action>() {
+                problems.push(Problem::new(
+                    format!("tui.keybindings.{name}"),
+                    format!("tui.keybindings: {e}"),
+                ));
+            }
+        }
+
+        let mut bindings: Vec<(Chord, Action)> = Vec::new();
+        for (action, defaults) in preset(config.preset) {
+            let chords: Vec<&str> = config.bindings.get(action.name()).map_or_else(
+                || defaults.to_vec(),
+                |chords| chords.as_slice().iter().map(String::as_str).collect(),
+            );
+            for text in chords {
+                let chord = match Chord::parse(text, &leader) {
+                    Ok(chord) => chord,
+                    Err(e) => {
+                        problems.push(Problem::new(
+                            format!("tui.keybindings.{action}"),
+                            format!("tui.keybindings.{action}: {e}"),
+                        ));
+                        continue;
+                    }
+                };
+                if let Some(problem) = conflict(config, &bindings, &chord, *action) {
+                    problems.push(problem);
+                    continue;
+                }
+                if !bindings.contains(&(chord.clone(), *action)) {
+                    bindings.push((chord, *action));
+                }
+            }
+        }
+
+        let keymap = Self {
+            bindings,
+            pending: Vec::new(),
+        };
+        (keymap, problems)
+    }
+
+    /// Feed a key press, returning the action once a chord is complete
+    /// キー入力を渡し、キーコードが完成したらアクションを返す
+    ///
+    /// A key that does not continue the pending chord starts a new one.
+    /// 保留中のキーコードを継続しないキーは新しいキーコードを開始します。
+    pub fn feed(&mut self, event: KeyEvent) -> Step {
+        let key = Key::from(event);
+        self.pending.push(key);
+        if let Some(step) = self.lookup() {
+            return step;
+        }
+        if self.pending.len() > 1 {
+            self.pending.clear();
+            self.pending.push(key);
+            if let Some(step) = self.lookup() {
+                return step;
+            }
+        }
+        self.pending.clear();
+        Step::Unbound
+    }
+
+    /// Keys typed towards an incomplete chord
+    /// 未完成のキーコードに向けて入力されたキー
+    #[must_use]
+    pub fn pending(&self) -> &[Key] {
+        &self.pending
+    }
+
+    /// Chords bound to an action
+    /// アクションにバインドされたキーコード
+    pub fn chords(&self, action: Action) -> impl Iterator<Item = &Chord> {
+        self.bindings
+            .iter()
+            .filter(move |(_, bound)| *bound == action)
+            .map(|(chord, _)| chord)
+    }
+
+    /// First chord of an action for display, if the action is bound
+    /// 表示用のアクションの最初のキーコード（バインドされている場合）
+    #[must_use]
+    pub fn hint(&self, action: Action) -> Option<String> {
+        self.chords(action).next().map(ToString::to_string)
+    }
+
+    /// Match the pending keys against the bindings
+    /// 保留中のキーをバインドと照合
+    fn lookup(&mut self) -> Option<Step> {
+        let mut is_prefix = false;
+        for (chord, action) in &self.bindings {
+            if chord.0 == self.pending {
+                self.pending.clear();
+                return Some(Step::Action(*action));
+            }
+            is_prefix |= chord.0.starts_with(&self.pending);
+        }
+        is_prefix.then_some(Step::Pending)
+    }
+}
+
+/// Collision of a chord with one already bound, if any
+/// 既にバインドされたキーコードとの衝突（あれば）
+fn conflict(
+    config: &KeyBindings,
+    bindings: &[(Chord, Action)],
+    chord: &Chord,
+    action: Action,
+) -> Option<Problem> {
+    for (existing, bound) in bindings {
+        let message = if existing == chord {
+            if *bound == action {
+                continue;
+            }
+            format!("`{chord}` is bound to both `{bound}` and `{action}`")
+        } else if chord.0.starts_with(&existing.0) || existing.0.starts_with(&chord.0) {
+            format!(
+                "`{existing}` (`{bound}`) and `{chord}` (`{action}`) conflict because one is a prefix of the other"
+            )
+        } else {
+            continue;
+        };
+        // Point at the binding the user wrote rather than at a preset one
+        // プリセットではなくユーザーが書いたキーバインドを指す
+        let culprit = if config.bindings.contains_key(action.name()) {
+            action
+        } else {
+            *bound
+        };
+        return Some(Problem::new(
+            format!("tui.keybindings.{culprit}"),
+            format!("tui.keybindings: {message}"),
+        ));
+    }
+    None
+}
+
+/// Default chords of every action for a preset
+/// プリセットにおける全アクションのデフォルトのキーコード
+#[must_use]
+pub const fn preset(preset: KeymapPreset) -> &'static [(Action, &'static [&'static str])] {
+    match preset {
+        KeymapPreset::Default => &[
+            (Action::Quit, &["q", "ctrl+c"]),
+            (Action::Search, &["/"]),
+            (Action::NewNote, &["n"]),
+            (Action::Edit, &["e"]),
+            (Action::EditExternal, &["E"]),
+            (Action::Up, &["k", "up"]),
+            (Action::Down, &["j", "down"]),
+            (Action::Left, &["h", "left"]),
+            (Action::Right, &["l", "right"]),
+            (Action::Top, &["g", "home"]),
+            (Action::Bottom, &["G", "end"]),
+            (Action::PageUp, &["pageup"]),
+            (Action::PageDown, &["pagedown"]),
+            (Action::FocusNext, &["tab"]),
+            (Action::FocusPrevious, &["backtab"]),
+            (Action::NextLink, &["]"]),
+            (Action::PreviousLink, &["["]),
+            (Action::Open, &["enter"]),
+            (Action::Graph, &["v"]),
+            (Action::Timeline, &["t"]),
+            (Action::Calendar, &["c"]),
+            (Action::Tags, &["#"]),
+            (Action::RenameTag, &["R"]),
+            (Action::Palette, &["ctrl+p", ":"]),
+            (Action::Reload, &["ctrl+r"]),
+            (Action::Suspend, &["ctrl+z"]),
+        ],
+        KeymapPreset::Vim => &[
+            (Action::Quit, &["q", "ctrl+c"]),
+            (Action::Search, &["/"]),
+            (Action::NewNote, &["<leader>n"]),
+            (Action::Edit, &["i", "<leader>e"]),
+            (Action::EditExternal, &["<leader>E"]),
+            (Action::Up, &["k", "up"]),
+            (Action::Down, &["j", "down"]),
+            (Action::Left, &["h", "left"]),
+            (Action::Right, &["l", "right"]),
+            (Action::Top, &["g g", "home"]),
+            (Action::Bottom, &["G", "end"]),
+            (Action::PageUp, &["ctrl+u", "ctrl+b", "pageup"]),
+            (Action::PageDown, &["ctrl+d", "ctrl+f", "pagedown"]),
+            (Action::FocusNext, &["ctrl+w w", "ctrl+w l", "tab"]),
+            (Action::FocusPrevious, &["ctrl+w W", "ctrl+w h", "backtab"]),
+            (Action::NextLink, &["]"]),
+            (Action::PreviousLink, &["["]),
+            (Action::Open, &["enter"]),
+            (Action::Graph, &["<leader>g"]),
+            (Action::Timeline, &["<leader>t"]),
+            (Action::Calendar, &["<leader>c"]),
+            (Action::Tags, &["<leader>#"]),
+            (Action::RenameTag, &["<leader>R"]),
+            (Action::Palette, &[":", "ctrl+p"]),
+            (Action::Reload, &["<leader>r", "ctrl+r"]),
+            (Action::Suspend, &["ctrl+z"]),
+        ],
+        KeymapPreset::Emacs => &[
+            (Action::Quit, &["ctrl+x ctrl+c", "ctrl+c"]),
+            (Action::Search, &["ctrl+s"]),
+            (Action::NewNote, &["ctrl+x ctrl+f"]),
+            (Action::Edit, &["ctrl+x ctrl+e"]),
+            (Action::EditExternal, &["ctrl+x e"]),
+            (Action::Up, &["ctrl+p", "up"]),
+            (Action::Down, &["ctrl+n", "down"]),
+            (Action::Left, &["ctrl+b", "left"]),
+            (Action::Right, &["ctrl+f", "right"]),
+            (Action::Top, &["alt+<", "home"]),
+            (Action::Bottom, &["alt+>", "end"]),
+            (Action::PageUp, &["alt+v", "pageup"]),
+            (Action::PageDown, &["ctrl+v", "pagedown"]),
+            (Action::FocusNext, &["ctrl+x o", "tab"]),
+            (Action::FocusPrevious, &["backtab"]),
+            (Action::NextLink, &["alt+n"]),
+            (Action::PreviousLink, &["alt+p"]),
+            (Action::Open, &["enter"]),
+            (Action::Graph, &["ctrl+x g"]),
+            (Action::Timeline, &["ctrl+x t"]),
+            (Action::Calendar, &["ctrl+x c"]),
+            (Action::Tags, &["ctrl+x #"]),
+            (Action::RenameTag, &["ctrl+x r"]),
+            (Action::Palette, &["alt+x"]),
+            (Action::Reload, &["ctrl+x ctrl+r"]),
+            (Action::Suspend, &["ctrl+z", "ctrl+x ctrl+z"]),
+        ],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::KeyChords;
+
+    fn event(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn test_key_parsing_and_display() {
+        let key: Key = "ctrl+k".parse().unwrap();
+        assert_eq!(
+            key,
+            Key::from(event(KeyCode::Char('k'), KeyModifiers::CONTROL))
+        );
+        assert_eq!(key.to_string(), "ctrl+k");
+
+        // Shift is folded into characters the way terminals report them
+        // Shiftはターミナルの報告と同様に文字に取り込まれる
+        let upper: Key = "shift+g".parse().unwrap();
+        assert_eq!(
+            upper,
+            Key::from(event(KeyCode::Char('G'), KeyModifiers::SHIFT))
+        );
+        assert_eq!(upper.to_string(), "G");
+        assert_eq!(
+            "shift+tab".parse::<Key>().unwrap(),
+            Key::from(event(KeyCode::BackTab, KeyModifiers::SHIFT))
+        );
+
+        assert_eq!("<Tab>".parse::<Key>().unwrap().code, KeyCode::Tab);
+        assert_eq!("F5".parse::<Key>().unwrap().code, KeyCode::F(5));
+        assert_eq!("alt+<".parse::<Key>().unwrap().to_string(), "alt+<");
+        assert!("f13".parse::<Key>().is_err());
+        assert!("hyper+x".parse::<Key>().is_err());
+        assert!("ctrl+".parse::<Key>().is_err());
+    }
+
+    #[test]
+    fn test_sequences_and_leader() {
+        let mut config = KeyBindings {
+            preset: KeymapPreset::Vim,
+            ..KeyBindings::default()
+        };
+        config.bindings.insert(
+            "search".to_string(),
+            KeyChords::One("<leader>f".to_string()),
+        );
+        let mut keymap = Keymap::new(&config).unwrap();
+
+        let g = event(KeyCode::Char('g'), KeyModifiers::NONE);
+        assert_eq!(keymap.feed(g), Step::Pending);
+        assert_eq!(keymap.pending().len(), 1);
+        assert_eq!(keymap.feed(g), Step::Action(Action::Top));
+        assert!(keymap.pending().is_empty());
+
+        // An unrelated key abandons the pending chord and is tried on its own
+        // 無関係なキーは保留中のキーコードを破棄し、単独で試される
+        assert_eq!(keymap.feed(g), Step::Pending);
+        let j = event(KeyCode::Char('j'), KeyModifiers::NONE);
+        assert_eq!(keymap.feed(j), Step::Action(Action::Down));
+
+        assert_eq!(
+            keymap.feed(event(KeyCode::Char(' '), KeyModifiers::NONE)),
+            Step::Pending
+        );
+        assert_eq!(
+            keymap.feed(event(KeyCode::Char('f'), KeyModifiers::NONE)),
+            Step::Action(Action::Search)
+        );
+        assert_eq!(
+            keymap.feed(event(KeyCode::Char('/'), KeyModifiers::NONE)),
+            Step::Unbound,
+            "overrides replace the preset chords"
+        );
+        assert_eq!(keymap.hint(Action::Search).unwrap(), "space f");
+    }
+
+    #[test]
+    fn test_conflicts_and_errors_are_rejected() {
+        let with = |name: &str, chords: KeyChords| {
+            let mut config = KeyBindings::default();
+            config.bindings.insert(name.to_string(), chords);
+            Keymap::new(&config)
+        };
+
+        let duplicate = with("edit", KeyChords::One("q".to_string())).unwrap_err();
+        assert!(duplicate.to_string().contains("bound to both"));
+
+        // `g` is bound to `top` in the default preset
+        // デフォルトプリセットでは`g`は`top`にバインドされている
+        let prefix = with("reload", KeyChords::One("g r".to_string())).unwrap_err();
+        assert!(prefix.to_string().contains("prefix"));
+
+        assert!(with("jump", KeyChords::One("x".to_string())).is_err());
+        assert!(with("edit", KeyChords::One("ctrl+nope".to_string())).is_err());
+
+        // An empty list unbinds the action
+        // 空の一覧はアクションのバインドを解除する
+        let keymap = with("quit", KeyChords::Many(Vec::new())).unwrap();
+        assert_eq!(keymap.chords(Action::Quit).count(), 0);
+
+        for preset in [
+            KeymapPreset::Default,
+            KeymapPreset::Vim,
+            KeymapPreset::Emacs,
+        ] {
+            let keymap = Keymap::new(&KeyBindings {
+                preset,
+                ..KeyBindings::default()
+            })
+            .unwrap();
+            for action in Action::ALL {
+                assert!(keymap.hint(action).is_some(), "{preset:?} binds {action}");
+            }
+        }
+    }
+}
@@ -0,0 +1,991 @@
+//! Markdown rendering for the preview pane
+//! プレビューペイン用のMarkdown描画
+//!
+//! Notes are parsed with `pulldown-cmark` and turned into styled, pre-wrapped
+//! lines. Wrapping is done here rather than by the paragraph widget so the
+//! position of every wiki link is known: the preview can then select a link
+//! and follow it from the keyboard or a mouse click. Widths are measured in
+//! terminal columns, so East Asian wide characters take two columns and lines
+//! may break between any two of them.
+//! ノートは`pulldown-cmark`で解析され、スタイル付きで折り返し済みの行に変換されます。
+//! 折り返しを段落ウィジェットではなくここで行うことで、すべてのWikiリンクの位置が
+//! 分かり、プレビューでリンクを選択してキーボードやマウスクリックでたどれます。
+//! 幅はターミナルの桁数で測るため、東アジアの全角文字は2桁を占め、全角文字の間では
+//! どこでも改行できます。
+
+use super::highlight::highlight;
+use super::theme::{Role, Theme};
+use pulldown_cmark::{Alignment, CodeBlockKind, Event, HeadingLevel, Options, Parser, Tag, TagEnd};
+use ratatui::style::Style;
+use ratatui::text::{Line, Span};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// Bullets of unordered lists by nesting depth
+/// 入れ子の深さごとの順序なしリストの記号
+const BULLETS: [&str; 3] = ["•", "◦", "▪"];
+
+/// Where part of a wiki link was drawn
+/// Wikiリンクの一部が描画された位置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    /// Line index in the document
+    /// ドキュメント内の行インデックス
+    pub line: usize,
+
+    /// Span index within the line
+    /// 行内のスパンインデックス
+    pub span: usize,
+
+    /// First terminal column
+    /// 最初のターミナル桁
+    pub column: usize,
+
+    /// Width in terminal columns
+    /// ターミナルの桁数での幅
+    pub width: usize,
+}
+
+/// A wiki link of the rendered document
+/// 描画されたドキュメントのWikiリンク
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WikiLink {
+    /// Name of the linked note
+    /// リンク先のノート名
+    pub target: String,
+
+    /// Whether the target names an existing note
+    /// リンク先が既存のノートを指しているか
+    pub exists: bool,
+
+    /// Drawn pieces of the link; more than one when it wraps
+    /// リンクの描画された部分（折り返される場合は複数）
+    pub positions: Vec<Position>,
+}
+
+/// Rendered Markdown
+/// 描画されたMarkdown
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Document {
+    /// Wrapped lines
+    /// 折り返し済みの行
+    pub lines: Vec<Line<'static>>,
+
+    /// Wiki links in reading order
+    /// 読み順のWikiリンク
+    pub links: Vec<WikiLink>,
+}
+
+impl Document {
+    /// Insert lines before the document, shifting link positions
+    /// ドキュメントの前に行を挿入し、リンクの位置をずらす
+    pub fn prepend(&mut self, lines: Vec<Line<'static>>) {
+        let count = lines.len();
+        self.lines.splice(0..0, lines);
+        for position in self.links.iter_mut().flat_map(|link| &mut link.positions) {
+            position.line += count;
+        }
+    }
+
+    /// Link drawn at a line and column
+    /// 行と桁に描画されたリンク
+    #[must_use]
+    pub fn link_at(&self, line: usize, column: usize) -> Option<usize> {
+        self.links.iter().position(|link| {
+            link.positions.iter().any(|position| {
+                position.line == line
+                    && (position.column..position.column + position.width).contains(&column)
+            })
+        })
+    }
+
+    /// Patch the style of every piece of a link
+    /// リンクのすべての部分のスタイルを上書き
+    pub fn highlight(&mut self, link: usize, style: Style) {
+        let Some(link) = self.links.get(link) else {
+            return;
+        };
+        for position in &link.positions {
+            if let Some(span) = self.lines[position.line].spans.get_mut(position.span) {
+                span.style = span.style.patch(style);
+            }
+        }
+    }
+}
+
+/// Render Markdown to lines at most `width` columns wide
+/// Markdownを最大`width`桁の行に描画
+///
+/// `exists` tells whether a wiki link target names an existing note.
+/// `exists`はWikiリンク先が既存のノートを指しているかを返します。
+///
+/// # Examples
+///
+/// ```rust
+/// use zynapse::tui::markdown::render;
+/// use zynapse::tui::theme::Theme;
+///
+/// let theme = Theme::builtin("dark").unwrap();
+/// let document = render("# Title\n\nSee [[Other|the other note]].", 40, &theme, &|_| true);
+/// assert_eq!(document.lines.len(), 3);
+/// assert_eq!(document.links[0].target, "Other");
+/// ```
+#[must_use]
+pub fn render(
+    markdown: &str,
+    width: usize,
+    theme: &Theme,
+    exists: &dyn Fn(&str) -> bool,
+) -> Document {
+    let options =
+        Options::ENABLE_TABLES | Options::ENABLE_TASKLISTS | Options::ENABLE_STRIKETHROUGH;
+    let mut renderer = Renderer::new(width.max(1), theme, exists);
+    for event in Parser::new_ext(markdown, options) {
+        renderer.event(event);
+    }
+    renderer.finish()
+}
+
+/// Piece of inline content before wrapping
+/// 折り返し前のインライン内容の断片
+#[derive(Debug)]
+struct Segment {
+    text: String,
+    style: Style,
+    link: Option<usize>,
+}
+
+/// Block container contributing a prefix to each of its lines
+/// 各行に接頭辞を付けるブロックコンテナ
+#[derive(Debug)]
+enum Container {
+    /// Fixed prefix such as the bar of a block quote
+    /// 引用ブロックの縦線のような固定の接頭辞
+    Prefix(&'static str, Style),
+
+    /// List item: the marker on its first line, spaces on the others
+    /// リスト項目（最初の行に記号、それ以外の行に空白）
+    Item {
+        indent: usize,
+        marker: Option<Span<'static>>,
+    },
+}
+
+/// Table being collected
+/// 収集中のテーブル
+#[derive(Debug, Default)]
+struct Table {
+    alignments: Vec<Alignment>,
+    rows: Vec<Vec<String>>,
+    header_rows: usize,
+    cell: String,
+}
+
+/// Event-driven renderer state
+/// イベント駆動の描画状態
+struct Renderer<'a> {
+    width: usize,
+    theme: &'a Theme,
+    exists: &'a dyn Fn(&str) -> bool,
+    document: Document,
+    containers: Vec<Container>,
+    /// Next number of each open list, `None` for bullet lists
+    /// 開いている各リストの次の番号（箇条書きリストは`None`）
+    lists: Vec<Option<u64>>,
+    /// Inline styles, innermost last
+    /// インラインスタイル（最も内側が最後）
+    styles: Vec<Style>,
+    segments: Vec<Segment>,
+    /// Text not yet scanned for wiki links, with its style
+    /// Wikiリンクの走査前のテキストとそのスタイル
+    text: String,
+    text_style: Style,
+    code: Option<(String, String)>,
+    table: Option<Table>,
+}
+
+impl<'a> Renderer<'a> {
+    fn new(width: usize, theme: &'a Theme, exists: &'a dyn Fn(&str) -> bool) -> Self {
+        Self {
+            width,
+            theme,
+            exists,
+            document: Document::default(),
+            containers: Vec::new(),
+            lists: Vec::new(),
+            styles: Vec::new(),
+            segments: Vec::new(),
+            text: String::new(),
+            text_style: Style::new(),
+            code: None,
+            table: None,
+        }
+    }
+
+    fn finish(mut self) -> Document {
+        self.finish_line();
+        while self
+            .document
+            .lines
+            .last()
+            .is_some_and(|line| line.width() == 0)
+        {
+            self.document.lines.pop();
+        }
+        self.document
+    }
+
+    fn style(&self, role: Role) -> Style {
+        self.theme.style(role)
+    }
+
+    /// Combined inline style
+    /// 合成されたインラインスタイル
+    fn current(&self) -> Style {
+        self.styles
+            .iter()
+            .fold(Style::new(), |style, inner| style.patch(*inner))
+    }
+
+    fn event(&mut self, event: Event<'_>) {
+        if let Some(table) = &mut self.table {
+            match event {
+                Event::Text(text) | Event::Code(text) => {
+                    table.cell.push_str(&text);
+                    return;
+                }
+                Event::End(TagEnd::Table) => {}
+                Event::Start(Tag::TableHead | Tag::TableRow) => {
+                    table.rows.push(Vec::new());
+                    return;
+                }
+                Event::End(TagEnd::TableHead) => {
+                    table.header_rows = table.rows.len();
+                    return;
+                }
+                Event::End(TagEnd::TableCell) => {
+                    let cell = std::mem::take(&mut table.cell);
+                    if let Some(row) = table.rows.last_mut() {
+                        row.push(cell.trim().to_string());
+                    }
+                    return;
+                }
+                _ => return,
+            }
+        }
+
+        if let Some((_, code)) = &mut self.code {
+            match event {
+                Event::Text(text) => {
+                    code.push_str(&text);
+                    return;
+                }
+                Event::End(TagEnd::CodeBlock) => {}
+                _ => return,
+            }
+        }
+
+        match event {
+            Event::Start(tag) => self.start(tag),
+            Event::End(tag) => self.end(tag),
+            Event::Text(text) => self.push_text(&text, self.current()),
+            Event::Code(code) => {
+                let style = self.current().patch(self.style(Role::Code));
+                self.push_verbatim(&code, style);
+            }
+            Event::Html(html) | Event::InlineHtml(html) => {
+                let style = self.style(Role::Muted);
+                for (index, line) in html.lines().enumerate() {
+                    if index > 0 {
+                        self.finish_line();
+                    }
+                    self.push_verbatim(line, style);
+                }
+            }
+            Event::FootnoteReference(name) => {
+                let style = self.style(Role::Muted);
+                self.push_verbatim(&format!("[^{name}]"), style);
+            }
+            Event::SoftBreak => self.push_text(" ", self.current()),
+            Event::HardBreak => self.finish_line(),
+            Event::Rule => {
+                self.block_start();
+                let style = self.style(Role::Rule);
+                let width = self.width.saturating_sub(self.prefix_width());
+                self.push_verbatim(&"─".repeat(width.max(1)), style);
+                self.finish_line();
+            }
+            Event::TaskListMarker(done) => {
+                let (text, role) = if done {
+                    ("[x] ", Role::TaskDone)
+                } else {
+                    ("[ ] ", Role::TaskOpen)
+                };
+                let style = self.style(role);
+                if let Some(Container::Item { indent, marker }) = self.containers.last_mut() {
+                    *indent = text.len();
+                    *marker = Some(Span::styled(text, style));
+                }
+            }
+        }
+    }
+
+    fn start(&mut self, tag: Tag<'_>) {
+        match tag {
+            Tag::Paragraph => {
+                self.finish_line();
+                self.blank();
+            }
+            Tag::Heading { level, .. } => {
+                self.block_start();
+                let role = match level {
+                    HeadingLevel::H1 => Role::Heading1,
+                    HeadingLevel::H2 => Role::Heading2,
+                    HeadingLevel::H3 => Role::Heading3,
+                    _ => Role::Heading,
+                };
+                self.styles.push(self.style(role));
+            }
+            Tag::BlockQuote => {
+                self.block_start();
+                let style = self.style(Role::Quote);
+                self.containers.push(Container::Prefix("│ ", style));
+                self.styles.push(style);
+            }
+            Tag::CodeBlock(kind) => {
+                self.block_start();
+                let language = match kind {
+                    CodeBlockKind::Fenced(info) => info
+                        .split_whitespace()
+                        .next()
+                        .unwrap_or_default()
+                        .to_string(),
+                    CodeBlockKind::Indented => String::new(),
+                };
+                self.code = Some((language, String::new()));
+            }
+            Tag::List(start) => {
+                self.finish_line();
+                if !self.in_item() {
+                    self.blank();
+                }
+                self.lists.push(start);
+            }
+            Tag::Item => {
+                self.finish_line();
+                let depth = self.lists.len().saturating_sub(1);
+                let text = match self.lists.last_mut() {
+                    Some(Some(number)) => {
+                        let text = format!("{number}. ");
+                        *number += 1;
+                        text
+                    }
+                    _ => format!("{} ", BULLETS[depth % BULLETS.len()]),
+                };
+                let style = self.style(Role::ListMarker);
+                self.containers.push(Container::Item {
+                    indent: text.width(),
+                    marker: Some(Span::styled(text, style)),
+                });
+            }
+            Tag::Table(alignments) => {
+                self.block_start();
+                self.table = Some(Table {
+                    alignments,
+                    ..Table::default()
+                });
+            }
+            Tag::Emphasis => self.styles.push(self.style(Role::Emphasis)),
+            Tag::Strong => self.styles.push(self.style(Role::Strong)),
+            Tag::Strikethrough => self.styles.push(self.style(Role::Strikethrough)),
+            Tag::Link { .. } => self.styles.push(self.style(Role::Link)),
+            Tag::Image { .. } => {
+                self.styles.push(self.style(Role::Link));
+                self.push_text("[image: ", self.current());
+            }
+            Tag::HtmlBlock => self.block_start(),
+            Tag::FootnoteDefinition(name) => {
+                self.block_start();
+                let style = self.style(Role::Muted);
+                self.push_verbatim(&format!("[^{name}]: "), style);
+            }
+            Tag::TableHead | Tag::TableRow | Tag::TableCell | Tag::MetadataBlock(_) => {}
+        }
+    }
+
+    fn end(&mut self, tag: TagEnd) {
+        match tag {
+            TagEnd::Paragraph | TagEnd::HtmlBlock | TagEnd::FootnoteDefinition => {
+                self.finish_line();
+            }
+            TagEnd::Heading(_) => {
+                self.finish_line();
+                self.styles.pop();
+            }
+            TagEnd::BlockQuote => {
+                self.finish_line();
+                self.containers.pop();
+                self.styles.pop();
+            }
+            TagEnd::CodeBlock => self.end_code_block(),
+            TagEnd::List(_) => {
+                self.finish_line();
+                self.lists.pop();
+            }
+            TagEnd::Item => {
+                self.finish_line();
+                self.containers.pop();
+            }
+            TagEnd::Table => self.end_table(),
+            TagEnd::Image => {
+                self.push_text("]", self.current());
+                self.styles.pop();
+            }
+            TagEnd::Emphasis | TagEnd::Strong | TagEnd::Strikethrough | TagEnd::Link => {
+                self.flush_text();
+                self.styles.pop();
+            }
+            TagEnd::TableHead | TagEnd::TableRow | TagEnd::TableCell | TagEnd::MetadataBlock(_) => {
+            }
+        }
+    }
+
+    fn in_item(&self) -> bool {
+        self.containers
+            .iter()
+            .any(|container| matches!(container, Container::Item { .. }))
+    }
+
+    /// Start a block separated from the previous one by a blank line
+    /// 前のブロックと空行で区切られたブロックを開始
+    fn block_start(&mut self) {
+        self.finish_line();
+        self.blank();
+    }
+
+    /// Add a blank line unless at the start, after a blank line, or right
+    /// after a list marker
+    /// 先頭・空行の後・リスト記号の直後を除き空行を追加
+    fn blank(&mut self) {
+        let marker_pending = self.containers.iter().any(|container| {
+            matches!(
+                container,
+                Container::Item {
+                    marker: Some(_),
+                    ..
+                }
+            )
+        });
+        let after_blank = self.document.lines.last().map_or(true, |line| {
+            line.spans
+                .iter()
+                .all(|span| span.content.trim().is_empty() || span.content.trim() == "│")
+        });
+        if marker_pending || after_blank {
+            return;
+        }
+        let spans = self
+            .containers
+            .iter()
+            .filter_map(|container| match container {
+                Container::Prefix(text, style) => Some(Span::styled(text.trim_end(), *style)),
+                Container::Item { .. } => None,
+            })
+            .collect::<Vec<_>>();
+        self.document.lines.push(Line::from(spans));
+    }
+
+    /// Width of the container prefixes
+    /// コンテナの接頭辞の幅
+    fn prefix_width(&self) -> usize {
+        self.containers
+            .iter()
+            .map(|container| match container {
+                Container::Prefix(text, _) => text.width(),
+                Container::Item { indent, .. } => *indent,
+            })
+            .sum()
+    }
+
+    /// Prefix spans of the next line, consuming pending list markers
+    /// 次の行の接頭辞スパン（保留中のリスト記号を消費）
+    fn take_prefix(&mut self) -> Vec<Span<'static>> {
+        self.containers
+            .iter_mut()
+            .map(|container| match container {
+                Container::Prefix(text, style) => Span::styled(*text, *style),
+                Container::Item { indent, marker } => marker
+                    .take()
+                    .unwrap_or_else(|| Span::raw(" ".repeat(*indent))),
+            })
+            .collect()
+    }
+
+    fn push_text(&mut self, text: &str, style: Style) {
+        if style != self.text_style {
+            self.flush_text();
+            self.text_style = style;
+        }
+        self.text.push_str(text);
+    }
+
+    fn push_verbatim(&mut self, text: &str, style: Style) {
+        self.flush_text();
+        self.segments.push(Segment {
+            text: text.to_string(),
+            style,
+            link: None,
+        });
+    }
+
+    /// Split buffered text into segments, turning wiki links into link segments
+    /// バッファされたテキストをセグメントに分割し、Wikiリンクをリンクセグメントにする
+    fn flush_text(&mut self) {
+        let text = std::mem::take(&mut self.text);
+        let style = self.text_style;
+        let mut rest = text.as_str();
+        while let Some(start) = rest.find("[[") {
+            let after = &rest[start + 2..];
+            let Some(end) = after.find("]]") else {
+                break;
+            };
+            let inner = &after[..end];
+            let target = inner.split(['|', '#']).next().unwrap_or_default().trim();
+            if target.is_empty() {
+                self.push_segment(&rest[..start + 2], style, None);
+                rest = after;
+                continue;
+            }
+            let label = inner.split_once('|').map_or(inner, |(_, label)| label);
+            let exists = (self.exists)(target);
+            let role = if exists {
+                Role::WikiLink
+            } else {
+                Role::WikiLinkMissing
+            };
+            self.push_segment(&rest[..start], style, None);
+            self.document.links.push(WikiLink {
+                target: target.to_string(),
+                exists,
+                positions: Vec::new(),
+            });
+            let link = self.document.links.len() - 1;
+            self.push_segment(label, style.patch(self.style(role)), Some(link));
+            rest = &after[end + 2..];
+        }
+        self.push_segment(rest, style, None);
+    }
+
+    fn push_segment(&mut self, text: &str, style: Style, link: Option<usize>) {
+        if !text.is_empty() {
+            self.segments.push(Segment {
+                text: text.to_string(),
+                style,
+                link,
+            });
+        }
+    }
+
+    /// Wrap the pending segments into lines
+    /// 保留中のセグメントを行に折り返す
+    fn finish_line(&mut self) {
+        self.flush_text();
+        if self.segments.is_empty() {
+            return;
+        }
+        let segments = std::mem::take(&mut self.segments);
+        let indent = self.prefix_width();
+        let mut spans = self.take_prefix();
+        let mut column = indent;
+        // Segment index of the last span, to extend it instead of adding spans
+        // スパンを追加せず延長するための、最後のスパンのセグメント番号
+        let mut last: Option<usize> = None;
+
+        for (index, segment) in segments.iter().enumerate() {
+            for (token, width, space) in tokens(&segment.text) {
+                if column + width > self.width && column > indent {
+                    let link = last.and_then(|last| segments[last].link);
+                    self.trim_end(&mut spans, link);
+                    self.document
+                        .lines
+                        .push(Line::from(std::mem::take(&mut spans)));
+                    spans = self.take_prefix();
+                    column = indent;
+                    last = None;
+                    if space {
+                        continue;
+                    }
+                }
+                for piece in split_to_fit(
+                    token,
+                    width,
+                    self.width.saturating_sub(column),
+                    indent,
+                    self.width,
+                ) {
+                    if piece.is_empty() {
+                        self.document
+                            .lines
+                            .push(Line::from(std::mem::take(&mut spans)));
+                        spans = self.take_prefix();
+                        column = indent;
+                        last = None;
+                        continue;
+                    }
+                    let piece_width = piece.width();
+                    if last == Some(index) {
+                        if let Some(span) = spans.last_mut() {
+                            span.content.to_mut().push_str(piece);
+                        }
+                        if let Some(link) = segment.link {
+                            if let Some(position) = self.document.links[link].positions.last_mut() {
+                                position.width += piece_width;
+                            }
+                        }
+                    } else {
+                        if let Some(link) = segment.link {
+                            self.document.links[link].positions.push(Position {
+                                line: self.document.lines.len(),
+                                span: spans.len(),
+                                column,
+                                width: piece_width,
+                            });
+                        }
+                        spans.push(Span::styled(piece.to_string(), segment.style));
+                        last = Some(index);
+                    }
+                    column += piece_width;
+                }
+            }
+        }
+        self.document.lines.push(Line::from(spans));
+    }
+
+    /// Drop trailing spaces left on a line broken by wrapping
+    /// 折り返しで分けられた行に残った末尾の空白を削除
+    fn trim_end(&mut self, spans: &mut [Span<'static>], link: Option<usize>) {
+        let Some(span) = spans.last_mut() else {
+            return;
+        };
+        let kept = span.content.trim_end().len();
+        let removed = span.content[kept..].width();
+        span.content.to_mut().truncate(kept);
+        if let Some(position) = link.and_then(|link| self.document.links[link].positions.last_mut())
+        {
+            position.width -= removed;
+        }
+    }
+
+    fn end_code_block(&mut self) {
+        let Some((language, code)) = self.code.take() else {
+            return;
+        };
+        let base = self.style(Role::CodeBlock);
+        self.containers.push(Container::Prefix("  ", base));
+        for line in code.lines() {
+            for span in highlight(line, &language, self.theme) {
+                self.segments.push(Segment {
+                    text: span.content.into_owned(),
+                    style: span.style,
+                    link: None,
+                });
+            }
+            if self.segments.is_empty() {
+                self.segments.push(Segment {
+                    text: " ".to_string(),
+                    style: base,
+                    link: None,
+                });
+            }
+            self.finish_line();
+        }
+        self.containers.pop();
+    }
+
+    fn end_table(&mut self) {
+        let Some(table) = self.table.take() else {
+            return;
+        };
+        let columns = table.rows.iter().map(Vec::len).max().unwrap_or(0);
+        if columns == 0 {
+            return;
+        }
+
+        let mut widths = vec![1; columns];
+        for row in &table.rows {
+            for (column, cell) in row.iter().enumerate() {
+                widths[column] = widths[column].max(cell.width());
+            }
+        }
+        // Shrink the widest columns until the table fits
+        // テーブルが収まるまで最も広い列を縮める
+        let available = self.width.saturating_sub(self.prefix_width());
+        let frame = 3 * columns + 1;
+        while widths.iter().sum::<usize>() + frame > available {
+            let Some(widest) = widths.iter_mut().max() else {
+                break;
+            };
+            if *widest <= 3 {
+                break;
+            }
+            *widest -= 1;
+        }
+
+        let border = self.style(Role::TableBorder);
+        let header = self.style(Role::TableHeader);
+        let rule = |left: &str, middle: &str, right: &str| {
+            let inner = widths
+                .iter()
+                .map(|width| "─".repeat(width + 2))
+                .collect::<Vec<_>>()
+                .join(middle);
+            vec![Span::styled(format!("{left}{inner}{right}"), border)]
+        };
+
+        let mut lines = vec![rule("┌", "┬", "┐")];
+        for (index, row) in table.rows.iter().enumerate() {
+            if index == table.header_rows && index > 0 {
+                lines.push(rule("├", "┼", "┤"));
+            }
+            let style = if index < table.header_rows {
+                header
+            } else {
+                Style::new()
+            };
+            let mut spans = vec![Span::styled("│", border)];
+            for (column, width) in widths.iter().enumerate() {
+                let cell = row.get(column).map_or("", String::as_str);
+                let alignment = table
+                    .alignments
+                    .get(column)
+                    .copied()
+                    .unwrap_or(Alignment::None);
+                spans.push(Span::styled(
+                    format!(" {} ", align(cell, *width, alignment)),
+                    style,
+                ));
+                spans.push(Span::styled("│", border));
+            }
+            lines.push(spans);
+        }
+        lines.push(rule("└", "┴", "┘"));
+
+        for spans in lines {
+            let mut line = self.take_prefix();
+            line.extend(spans);
+            self.document.lines.push(Line::from(line));
+        }
+    }
+}
+
+/// Split text into wrapping units: runs of spaces, runs of narrow
+/// non-space characters and single wide characters
+/// テキストを折り返し単位（空白の連続、半角の非空白文字の連続、全角文字1文字）に分割
+fn tokens(text: &str) -> Vec<(&str, usize, bool)> {
+    #[derive(PartialEq)]
+    enum Kind {
+        Space,
+        Word,
+        Wide,
+    }
+    let kind = |c: char| {
+        if c.is_whitespace() {
+            Kind::Space
+        } else if c.width().unwrap_or(0) > 1 {
+            Kind::Wide
+        } else {
+            Kind::Word
+        }
+    };
+
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    let mut current: Option<Kind> = None;
+    for (offset, c) in text.char_indices() {
+        let next = kind(c);
+        let continues = current
+            .as_ref()
+            .is_some_and(|kind| *kind == next && next != Kind::Wide);
+        if !continues && offset > start {
+            let token = &text[start..offset];
+            tokens.push((token, token.width(), current == Some(Kind::Space)));
+            start = offset;
+        }
+        current = Some(next);
+    }
+    if start < text.len() {
+        let token = &text[start..];
+        tokens.push((token, token.width(), current == Some(Kind::Space)));
+    }
+    tokens
+}
+
+/// Split a token that is wider than the room left on the line
+/// 行の残り幅より広いトークンを分割
+///
+/// Tokens that fit are returned whole. Longer ones are cut at character
+/// boundaries; an empty piece marks a line break between the pieces.
+/// 収まるトークンはそのまま返します。長いトークンは文字境界で切られ、
+/// 空の断片は断片間の改行を表します。
+fn split_to_fit(
+    token: &str,
+    width: usize,
+    room: usize,
+    indent: usize,
+    line_width: usize,
+) -> Vec<&str> {
+    if width <= room || room == 0 && width <= line_width.saturating_sub(indent) {
+        return vec![token];
+    }
+    let full = line_width.saturating_sub(indent).max(1);
+    let mut pieces = Vec::new();
+    let mut room = room.max(1);
+    let mut start = 0;
+    let mut used = 0;
+    for (offset, c) in token.char_indices() {
+        let char_width = c.width().unwrap_or(0);
+        if used + char_width > room && offset > start {
+            pieces.push(&token[start..offset]);
+            pieces.push("");
+            start = offset;
+            used = 0;
+            room = full;
+        }
+        used += char_width;
+    }
+    pieces.push(&token[start..]);
+    pieces
+}
+
+/// Pad or truncate a cell to a width according to its alignment
+/// 配置に従ってセルを指定幅に埋めるまたは切り詰める
+fn align(text: &str, width: usize, alignment: Alignment) -> String {
+    let mut fitted = String::new();
+    let mut used = 0;
+    let truncate = text.width() > width;
+    let limit = if truncate {
+        width.saturating_sub(1)
+    } else {
+        width
+    };
+    for c in text.chars() {
+        let char_width = c.width().unwrap_or(0);
+        if used + char_width > limit {
+            break;
+        }
+        fitted.push(c);
+        used += char_width;
+    }
+    if truncate {
+        fitted.push('…');
+        used += 1;
+    }
+    let padding = width.saturating_sub(used);
+    let (left, right) = match alignment {
+        Alignment::Right => (padding, 0),
+        Alignment::Center => (padding / 2, padding - padding / 2),
+        Alignment::Left | Alignment::None => (0, padding),
+    };
+    format!("{}{fitted}{}", " ".repeat(left), " ".repeat(right))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plain(document: &Document) -> Vec<String> {
+        document
+            .lines
+            .iter()
+            .map(|line| {
+                line.spans
+                    .iter()
+                    .map(|span| span.content.as_ref())
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn theme() -> Theme {
+        Theme::builtin("dark").unwrap()
+    }
+
+    #[test]
+    fn test_blocks() {
+        let markdown = "# Title\n\nSome *text*.\n\n- one\n- [x] done\n  1. nested\n\n> quoted\n\n```rust\nfn main() {}\n```\n\n---\n";
+        let document = render(markdown, 30, &theme(), &|_| true);
+        let lines = plain(&document);
+        assert_eq!(
+            lines,
+            [
+                "Title",
+                "",
+                "Some text.",
+                "",
+                "• one",
+                "[x] done",
+                "    1. nested",
+                "",
+                "│ quoted",
+                "",
+                "  fn main() {}",
+                "",
+                &"─".repeat(30),
+            ]
+        );
+        let title = &document.lines[0].spans[0];
+        assert_eq!(title.style, theme().style(Role::Heading1));
+    }
+
+    #[test]
+    fn test_wrapping_wide_characters() {
+        let document = render("日本語の文章を折り返す word", 10, &theme(), &|_| true);
+        let lines = plain(&document);
+        assert_eq!(lines, ["日本語の文", "章を折り返", "す word"]);
+        assert!(document.lines.iter().all(|line| line.width() <= 10));
+
+        let document = render("- abcdefghijklmnop", 8, &theme(), &|_| true);
+        assert_eq!(plain(&document), ["• abcdef", "  ghijkl", "  mnop"]);
+    }
+
+    #[test]
+    fn test_wiki_links_are_located() {
+        let document = render(
+            "Go to [[Alpha]] or [[beta|the second one]]",
+            16,
+            &theme(),
+            &|name| name == "Alpha",
+        );
+        assert_eq!(plain(&document), ["Go to Alpha or", "the second one"]);
+        assert_eq!(document.links.len(), 2);
+        assert!(document.links[0].exists);
+        assert!(!document.links[1].exists);
+        assert_eq!(document.link_at(0, 7), Some(0));
+        assert_eq!(document.link_at(0, 12), None);
+        assert_eq!(document.link_at(1, 0), Some(1));
+
+        let mut shifted = document;
+        shifted.prepend(vec![Line::default()]);
+        assert_eq!(shifted.link_at(2, 3), Some(1));
+        shifted.highlight(0, theme().style(Role::Selection));
+        assert_eq!(
+            shifted.lines[1].spans[1].style.bg,
+            theme().style(Role::Selection).bg
+        );
+    }
+
+    #[test]
+    fn test_tables() {
+        let markdown = "| Name | Qty |\n|:-----|----:|\n| 林檎 | 3 |\n";
+        let lines = plain(&render(markdown, 40, &theme(), &|_| true));
+        assert_eq!(
+            lines,
+            [
+                "┌──────┬─────┐",
+                "│ Name │ Qty │",
+                "├──────┼─────┤",
+                "│ 林檎 │   3 │",
+                "└──────┴─────┘",
+            ]
+        );
+    }
+}
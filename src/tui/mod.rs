@@ -0,0 +1,208 @@
+//! Terminal user interface
+//! ターミナルユーザーインターフェース
+//!
+//! A three-pane browser over the vault: the note list on the left, a preview of
+//! the selected note in the middle and its backlinks on the right, with a
+//! search bar on top and a status line below. A command palette reaches every
+//! action, note, tag and saved search from the keyboard.
+//! ボルトを閲覧する3ペインのブラウザです。左にノート一覧、中央に選択中のノートの
+//! プレビュー、右にそのバックリンクを表示し、上部に検索バー、下部にステータス行を
+//! 配置します。コマンドパレットからすべてのアクション・ノート・タグ・保存済み検索に
+//! キーボードでアクセスできます。
+//!
+//! The event loop redraws at most `tui.frame_rate` times per second and only
+//! when something changed, so an idle TUI does not consume CPU. While idle it
+//! also watches the configuration files and takes over the theme, key bindings
+//! and other live settings when they change.
+//! イベントループは変更があった場合のみ、最大で毎秒`tui.frame_rate`回再描画するため、
+//! アイドル中のTUIはCPUを消費しません。アイドル中は設定ファイルも監視し、変更された
+//! テーマ・キーバインドなどの即時反映の設定を引き継ぎます。
+
+pub mod action;
+pub mod app;
+pub mod editor;
+pub mod graph;
+pub mod highlight;
+pub mod keymap;
+pub mod markdown;
+pub mod palette;
+pub mod terminal;
+pub mod theme;
+pub mod timeline;
+pub mod ui;
+
+pub use action::Action;
+pub use app::App;
+pub use keymap::Keymap;
+pub use theme::Theme;
+
+use crate::config::{Config, Watcher};
+use crate::storage::NoteStore;
+use crate::{Result, ZynapseError};
+use app::Effect;
+use crossterm::event::{self, Event, KeyEventKind};
+use std::time::{Duration, Instant};
+use terminal::{terminal_error, Signal, Signals, TerminalGuard};
+
+/// Run the TUI until the user quits
+/// ユーザーが終了するまでTUIを実行
+///
+/// The terminal is restored however the TUI ends. A failing terminal also
+/// leaves a crash report behind. With a `watcher`, changes to the configuration
+/// files are applied while running.
+/// TUIがどのように終了してもターミナルは復元されます。ターミナルの障害時は
+/// クラッシュレポートも残します。`watcher`を指定すると、設定ファイルの変更が
+/// 実行中に反映されます。
+///
+/// # Errors
+///
+/// Returns an error if the vault cannot be read or the terminal cannot be
+/// driven.
+/// ボルトを読み込めない場合、またはターミナルを操作できない場合にエラーを返します。
+pub fn run(config: &Config, mut watcher: Option<Watcher>) -> Result<()> {
+    let store = NoteStore::open(&config.storage)?;
+    let mut app = App::new(config.clone(), store)?;
+
+    // A history that cannot be read only costs the palette its ordering
+    // 読み込めない履歴はパレットの並び順が失われるだけ
+    let history = Config::palette_history_path();
+    if let Ok(path) = &history {
+        app.history = palette::History::load(path).unwrap_or_default();
+    }
+
+    let result = Signals::register().and_then(|mut signals| {
+        let mut guard = TerminalGuard::enter(config.tui.mouse_support)?;
+        let result = event_loop(&mut guard, &mut signals, &mut app, watcher.as_mut());
+        let restored = guard.release();
+        result.and(restored)
+    });
+    if let Err(error @ ZynapseError::Tui { .. }) = &result {
+        terminal::report_crash(error.category(), &error.to_string());
+    }
+    let saved = history.and_then(|path| app.history.save(&path));
+    result.and(saved)
+}
+
+/// Interval between frames for a frame rate, treating zero as one per second
+/// フレームレートに対するフレーム間隔（0は毎秒1回として扱う）
+fn frame_interval(frame_rate: u32) -> Duration {
+    Duration::from_secs(1) / frame_rate.max(1)
+}
+
+/// Process events and signals and redraw until the app asks to quit
+/// アプリが終了を要求するまでイベントとシグナルを処理して再描画
+fn event_loop(
+    guard: &mut TerminalGuard,
+    signals: &mut Signals,
+    app: &mut App,
+    mut watcher: Option<&mut Watcher>,
+) -> Result<()> {
+    let interval = frame_interval(app.config.tui.frame_rate);
+    let mouse = app.config.tui.mouse_support;
+    let mut dirty = true;
+    // Draw the first frame immediately
+    // 最初のフレームは即座に描画
+    let mut last_frame = Instant::now()
+        .checked_sub(interval)
+        .unwrap_or_else(Instant::now);
+
+    while !app.should_quit {
+        for signal in signals.pending() {
+            match signal {
+                Signal::Suspend => guard.suspend()?,
+                // The shell may have reset the terminal while the TUI was stopped
+                // 停止中にシェルがターミナルをリセットした可能性がある
+                Signal::Continue => guard.resume()?,
+                Signal::Resize => guard
+                    .terminal()
+                    .autoresize()
+                    .map_err(|e| terminal_error(&e, "Failed to resize"))?,
+                Signal::Terminate => app.should_quit = true,
+            }
+            dirty = true;
+        }
+        if app.should_quit {
+            break;
+        }
+
+        // Checked on every pass, as steady input may keep the poll below from
+        // timing out; the watcher limits itself to one look per second
+        // 連続した入力で下のポーリングがタイムアウトしないことがあるため毎回確認する。
+        // ウォッチャー自身が確認を毎秒1回に制限する
+        if let Some(loaded) = watcher.as_mut().and_then(|watcher| watcher.poll()) {
+            app.apply_config(loaded);
+            dirty = true;
+        }
+
+        if dirty && last_frame.elapsed() >= interval {
+            guard
+                .terminal()
+                .draw(|frame| ui::draw(frame, app))
+                .map_err(|e| terminal_error(&e, "Failed to draw"))?;
+            dirty = false;
+            last_frame = Instant::now();
+        }
+
+        // Wake up in time for the next frame while changes are pending
+        // 変更が保留中の間は次のフレームに間に合うように起床
+        let timeout = if dirty {
+            interval.saturating_sub(last_frame.elapsed())
+        } else {
+            interval
+        };
+        if !event::poll(timeout).map_err(|e| terminal_error(&e, "Failed to poll events"))? {
+            dirty |= app.on_tick();
+            continue;
+        }
+
+        let effect = match event::read().map_err(|e| terminal_error(&e, "Failed to read event"))? {
+            Event::Key(key) if key.kind != KeyEventKind::Release => app.handle_key(key),
+            Event::Mouse(event) if mouse => {
+                app.handle_mouse(event);
+                None
+            }
+            Event::Resize(..) => None,
+            _ => continue,
+        };
+        dirty = true;
+
+        match effect {
+            Some(Effect::EditExternal(path)) => {
+                guard.release()?;
+                let edited = crate::utils::launch_editor(&editor(&app.config), &path);
+                guard.resume()?;
+                if let Err(error) = edited.and_then(|()| app.reload()) {
+                    app.report(error.to_string());
+                }
+            }
+            Some(Effect::Suspend) => guard.suspend()?,
+            None => {}
+        }
+    }
+    Ok(())
+}
+
+/// Editor command used for editing notes
+/// ノートの編集に使うエディタコマンド
+fn editor(config: &Config) -> String {
+    #[cfg(feature = "cli")]
+    {
+        config.cli.editor.clone()
+    }
+    #[cfg(not(feature = "cli"))]
+    {
+        let _ = config;
+        std::env::var("EDITOR").unwrap_or_else(|_| "vi".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_interval() {
+        assert_eq!(frame_interval(60), Duration::from_secs(1) / 60);
+        assert_eq!(frame_interval(0), Duration::from_secs(1));
+    }
+}
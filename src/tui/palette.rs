@@ -0,0 +1,399 @@
+//! Command palette over actions, notes, tags and saved searches
+//! アクション・ノート・タグ・保存済み検索を対象とするコマンドパレット
+//!
+//! Every entry is fuzzy-matched by its label. Entries chosen before are kept in
+//! a most-recently-used [`History`]: with an empty query they are listed first,
+//! most recent on top, and they win ties between equal scores, so repeating a
+//! jump only takes the palette key and Enter.
+//! すべての項目はラベルでファジーマッチされます。選択された項目は最近使用した順の
+//! [`History`]に保持され、クエリが空の場合は最新のものから先頭に並び、同じスコアの
+//! 場合も優先されます。そのため同じジャンプの繰り返しはパレットキーとEnterだけで
+//! 行えます。
+
+use super::action::Action;
+use crate::{Result, ZynapseError};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use fuzzy_matcher::skim::SkimMatcherV2;
+use fuzzy_matcher::FuzzyMatcher;
+use std::path::{Path, PathBuf};
+
+/// Maximum number of entries remembered by the history
+/// 履歴に記憶する項目の最大数
+const HISTORY_LIMIT: usize = 50;
+
+/// What choosing a palette entry does
+/// パレットの項目を選択したときの動作
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Target {
+    /// Perform an action
+    /// アクションを実行
+    Action(Action),
+
+    /// Select the note stored at a vault-relative path
+    /// ボルト相対パスに保存されたノートを選択
+    Note(PathBuf),
+
+    /// Filter the notes by a tag
+    /// タグでノートを絞り込む
+    Tag(String),
+
+    /// Run the saved search of this name
+    /// この名前の保存済み検索を実行
+    Search(String),
+}
+
+impl Target {
+    /// Short name of the kind of target, shown next to its label
+    /// ラベルの横に表示する、対象の種類の短い名前
+    #[must_use]
+    pub const fn kind(&self) -> &'static str {
+        match self {
+            Self::Action(_) => "action",
+            Self::Note(_) => "note",
+            Self::Tag(_) => "tag",
+            Self::Search(_) => "search",
+        }
+    }
+
+    /// Line of the history file for this target
+    /// この対象の履歴ファイルの行
+    fn to_line(&self) -> String {
+        let value = match self {
+            Self::Action(action) => action.name().to_string(),
+            Self::Note(path) => path.display().to_string(),
+            Self::Tag(value) | Self::Search(value) => value.clone(),
+        };
+        format!("{}:{value}", self.kind())
+    }
+
+    /// Parse a line of the history file; unknown lines yield `None`
+    /// 履歴ファイルの行を解析（不明な行は`None`）
+    fn from_line(line: &str) -> Option<Self> {
+        let (kind, value) = line.split_once(':')?;
+        if value.is_empty() {
+            return None;
+        }
+        match kind {
+            "action" => value.parse().ok().map(Self::Action),
+            "note" => Some(Self::Note(PathBuf::from(value))),
+            "tag" => Some(Self::Tag(value.to_string())),
+            "search" => Some(Self::Search(value.to_string())),
+            _ => None,
+        }
+    }
+}
+
+/// Most-recently-used palette targets, most recent first
+/// 最近使用したパレットの対象（新しい順）
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct History {
+    targets: Vec<Target>,
+}
+
+impl History {
+    /// Read the history from a file, one target per line
+    /// ファイルから履歴を読み込む（1行に1つの対象）
+    ///
+    /// A missing file is an empty history and unreadable lines are skipped, so
+    /// a stale history never keeps the TUI from starting.
+    /// ファイルが存在しない場合は空の履歴となり、読めない行は読み飛ばすため、
+    /// 古い履歴がTUIの起動を妨げることはありません。
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file exists but cannot be read.
+    /// ファイルが存在するが読み込めない場合にエラーを返します。
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+                return Ok(Self::default())
+            }
+            Err(error) => {
+                return Err(ZynapseError::io_error(
+                    error,
+                    format!("Failed to read {}", path.display()),
+                ))
+            }
+        };
+        let mut history = Self::default();
+        for target in content.lines().rev().filter_map(Target::from_line) {
+            history.record(target);
+        }
+        Ok(history)
+    }
+
+    /// Write the history to a file, creating its directory if needed
+    /// 履歴をファイルに書き込む（必要に応じてディレクトリを作成）
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written.
+    /// ファイルに書き込めない場合にエラーを返します。
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| {
+                ZynapseError::io_error(e, format!("Failed to create {}", parent.display()))
+            })?;
+        }
+        let mut content = String::new();
+        for target in &self.targets {
+            content.push_str(&target.to_line());
+            content.push('\n');
+        }
+        std::fs::write(path, content)
+            .map_err(|e| ZynapseError::io_error(e, format!("Failed to write {}", path.display())))
+    }
+
+    /// Move a target to the front, forgetting the oldest beyond the limit
+    /// 対象を先頭に移動（上限を超えた古いものは破棄）
+    pub fn record(&mut self, target: Target) {
+        self.targets.retain(|known| *known != target);
+        self.targets.insert(0, target);
+        self.targets.truncate(HISTORY_LIMIT);
+    }
+
+    /// Position of a target in the history, 0 being the most recent
+    /// 履歴内での対象の位置（0が最新）
+    #[must_use]
+    pub fn rank(&self, target: &Target) -> Option<usize> {
+        self.targets.iter().position(|known| known == target)
+    }
+
+    /// Remembered targets, most recent first
+    /// 記憶されている対象（新しい順）
+    #[must_use]
+    pub fn targets(&self) -> &[Target] {
+        &self.targets
+    }
+}
+
+/// One choice of the palette
+/// パレットの選択肢1つ
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    /// What choosing the entry does
+    /// 選択したときの動作
+    pub target: Target,
+
+    /// Text the query is matched against
+    /// クエリと照合されるテキスト
+    pub label: String,
+
+    /// Secondary text shown on the right, such as a key or a path
+    /// 右側に表示する補助テキスト（キーやパスなど）
+    pub detail: String,
+}
+
+/// Result of handling a key press in the palette
+/// パレットでのキー入力処理の結果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    /// Keep the palette open
+    /// パレットを開いたままにする
+    None,
+
+    /// Close the palette without choosing anything
+    /// 何も選択せずにパレットを閉じる
+    Cancel,
+
+    /// Close the palette and carry out a target
+    /// パレットを閉じて対象を実行
+    Choose(Target),
+}
+
+/// State of the open command palette
+/// 開いているコマンドパレットの状態
+#[derive(Debug, Clone)]
+pub struct Palette {
+    /// Text typed so far
+    /// これまでに入力されたテキスト
+    pub query: String,
+
+    /// Position of the highlighted entry within `matches`
+    /// `matches`内で強調表示中の項目の位置
+    pub selected: usize,
+
+    entries: Vec<Entry>,
+    ranks: Vec<Option<usize>>,
+    matches: Vec<usize>,
+}
+
+impl Palette {
+    /// Open the palette over `entries`, ordered with the help of `history`
+    /// `history`を使って並べた`entries`に対してパレットを開く
+    #[must_use]
+    pub fn new(entries: Vec<Entry>, history: &History) -> Self {
+        let ranks = entries
+            .iter()
+            .map(|entry| history.rank(&entry.target))
+            .collect();
+        let mut palette = Self {
+            query: String::new(),
+            selected: 0,
+            entries,
+            ranks,
+            matches: Vec::new(),
+        };
+        palette.refilter();
+        palette
+    }
+
+    /// Entries matching the query, best first
+    /// クエリに一致する項目（良い順）
+    #[must_use]
+    pub fn matches(&self) -> impl ExactSizeIterator<Item = &Entry> {
+        self.matches.iter().map(|&index| &self.entries[index])
+    }
+
+    /// Recompute the matches for the current query
+    /// 現在のクエリで一致を再計算
+    fn refilter(&mut self) {
+        let query = self.query.trim();
+        let matcher = SkimMatcherV2::default();
+        let mut scored: Vec<(i64, usize, usize)> = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter_map(|(index, entry)| {
+                let rank = self.ranks[index].unwrap_or(usize::MAX);
+                if query.is_empty() {
+                    return Some((0, rank, index));
+                }
+                matcher
+                    .fuzzy_match(&entry.label, query)
+                    .map(|score| (score, rank, index))
+            })
+            .collect();
+
+        // Higher scores first, then recently used entries, then the given order
+        // スコアの高い順、次に最近使用した順、最後に与えられた順
+        scored.sort_by_key(|&(score, rank, index)| (std::cmp::Reverse(score), rank, index));
+        self.matches = scored.into_iter().map(|(_, _, index)| index).collect();
+        self.selected = 0;
+    }
+
+    /// Update the state for a key press
+    /// キー入力に応じて状態を更新
+    pub fn handle_key(&mut self, key: KeyEvent) -> Outcome {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc => return Outcome::Cancel,
+            KeyCode::Char('c' | 'g') if ctrl => return Outcome::Cancel,
+            KeyCode::Enter => {
+                return self
+                    .matches
+                    .get(self.selected)
+                    .map_or(Outcome::Cancel, |&index| {
+                        Outcome::Choose(self.entries[index].target.clone())
+                    });
+            }
+            KeyCode::Up | KeyCode::BackTab => self.select_by(-1),
+            KeyCode::Char('p' | 'k') if ctrl => self.select_by(-1),
+            KeyCode::Down | KeyCode::Tab => self.select_by(1),
+            KeyCode::Char('n' | 'j') if ctrl => self.select_by(1),
+            KeyCode::Char('u') if ctrl => {
+                self.query.clear();
+                self.refilter();
+            }
+            KeyCode::Backspace => {
+                self.query.pop();
+                self.refilter();
+            }
+            KeyCode::Char(c) if !ctrl => {
+                self.query.push(c);
+                self.refilter();
+            }
+            _ => {}
+        }
+        Outcome::None
+    }
+
+    /// Move the highlight, wrapping around at both ends
+    /// 強調表示を移動（両端で折り返す）
+    fn select_by(&mut self, delta: isize) {
+        let count = self.matches.len();
+        if count > 0 {
+            let offset = if delta < 0 { count - 1 } else { 1 };
+            self.selected = (self.selected + offset) % count;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn entry(target: Target, label: &str) -> Entry {
+        Entry {
+            target,
+            label: label.to_string(),
+            detail: String::new(),
+        }
+    }
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_matching_and_history_order() {
+        let entries = vec![
+            entry(Target::Action(Action::Reload), "Reload notes"),
+            entry(Target::Note(PathBuf::from("rust.md")), "Rust"),
+            entry(Target::Tag("rust".to_string()), "rust"),
+            entry(Target::Search("drafts".to_string()), "drafts"),
+        ];
+        let mut history = History::default();
+        history.record(Target::Search("drafts".to_string()));
+        history.record(Target::Tag("rust".to_string()));
+
+        let mut palette = Palette::new(entries, &history);
+        let labels = |palette: &Palette| {
+            palette
+                .matches()
+                .map(|entry| entry.label.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(labels(&palette), ["rust", "drafts", "Reload notes", "Rust"]);
+        assert_eq!(
+            palette.handle_key(key(KeyCode::Enter)),
+            Outcome::Choose(Target::Tag("rust".to_string()))
+        );
+
+        for c in "rst".chars() {
+            palette.handle_key(key(KeyCode::Char(c)));
+        }
+        assert_eq!(labels(&palette), ["rust", "Rust"]);
+        palette.handle_key(key(KeyCode::Up));
+        assert_eq!(palette.selected, 1, "moving up wraps around");
+
+        palette.handle_key(key(KeyCode::Char('z')));
+        assert_eq!(palette.matches().len(), 0);
+        assert_eq!(palette.handle_key(key(KeyCode::Enter)), Outcome::Cancel);
+    }
+
+    #[test]
+    fn test_history_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("state").join("palette_history");
+        assert_eq!(History::load(&path).unwrap(), History::default());
+
+        let mut history = History::default();
+        for index in 0..=HISTORY_LIMIT {
+            history.record(Target::Note(PathBuf::from(format!("{index}.md"))));
+        }
+        history.record(Target::Action(Action::Graph));
+        history.record(Target::Note(PathBuf::from("50.md")));
+        assert_eq!(history.targets().len(), HISTORY_LIMIT);
+        assert_eq!(history.rank(&Target::Action(Action::Graph)), Some(1));
+        assert_eq!(history.rank(&Target::Note(PathBuf::from("0.md"))), None);
+
+        history.save(&path).unwrap();
+        let mut content = std::fs::read_to_string(&path).unwrap();
+        content.push_str("action:jump\nbogus\n");
+        std::fs::write(&path, content).unwrap();
+        assert_eq!(History::load(&path).unwrap(), history);
+    }
+}
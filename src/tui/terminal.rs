@@ -0,0 +1,343 @@
+//! Terminal lifecycle of the TUI
+//! TUIのターミナルのライフサイクル
+//!
+//! [`TerminalGuard`] puts the terminal into raw mode on the alternate screen
+//! and restores it when dropped, so an error leaving the event loop still
+//! hands a usable shell back. A panic hook does the same for panics. Panics and
+//! terminal failures leave a crash report in the data directory's
+//! `crash-reports/`. On Unix, [`Signals`] passes job control, resize and
+//! termination signals to the event loop.
+//! [`TerminalGuard`]はターミナルを代替画面のrawモードにし、ドロップ時に元に
+//! 戻すため、イベントループをエラーで抜けても使えるシェルが戻ります。パニック時は
+//! パニックフックが同じ処理を行います。パニックとターミナルの障害は
+//! データディレクトリの`crash-reports/`にクラッシュレポートを残します。Unixでは
+//! [`Signals`]がジョブ制御・リサイズ・終了のシグナルをイベントループに渡します。
+
+use crate::config::Config;
+use crate::{Result, ZynapseError};
+use chrono::{DateTime, Local};
+use crossterm::cursor::Show;
+use crossterm::event::{DisableMouseCapture, EnableMouseCapture};
+use crossterm::execute;
+use crossterm::terminal::{
+    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+};
+use ratatui::backend::CrosstermBackend;
+use ratatui::Terminal;
+use std::io::Stdout;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Once;
+
+/// Terminal type the TUI draws on
+/// TUIが描画するターミナルの型
+pub type Term = Terminal<CrosstermBackend<Stdout>>;
+
+/// Whether the terminal is in TUI mode, so the panic hook knows to restore it
+/// ターミナルがTUIモードかどうか（パニックフックが復元の要否を判断する）
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Whether TUI mode captures the mouse
+/// TUIモードがマウスをキャプチャしているかどうか
+static MOUSE: AtomicBool = AtomicBool::new(false);
+
+/// Installs the panic hook once per process
+/// パニックフックをプロセスごとに1回だけインストール
+static PANIC_HOOK: Once = Once::new();
+
+/// Owner of the terminal while the TUI runs
+/// TUI実行中のターミナルの所有者
+#[derive(Debug)]
+pub struct TerminalGuard {
+    terminal: Term,
+    mouse: bool,
+}
+
+impl TerminalGuard {
+    /// Switch the terminal into TUI mode
+    /// ターミナルをTUIモードに切り替え
+    ///
+    /// # Errors
+    ///
+    /// Returns a TUI error if the terminal cannot be switched.
+    /// ターミナルを切り替えられない場合にTUIエラーを返します。
+    pub fn enter(mouse: bool) -> Result<Self> {
+        install_panic_hook();
+        let terminal = Terminal::new(CrosstermBackend::new(std::io::stdout()))
+            .map_err(|e| terminal_error(&e, "Failed to initialize terminal"))?;
+        let mut guard = Self { terminal, mouse };
+        guard.resume()?;
+        Ok(guard)
+    }
+
+    /// The terminal to draw on
+    /// 描画先のターミナル
+    pub fn terminal(&mut self) -> &mut Term {
+        &mut self.terminal
+    }
+
+    /// Take the terminal (back) into TUI mode and clear it for a full redraw
+    /// ターミナルをTUIモードにし（戻し）、全体を再描画するために消去
+    ///
+    /// # Errors
+    ///
+    /// Returns a TUI error if the terminal cannot be switched.
+    /// ターミナルを切り替えられない場合にTUIエラーを返します。
+    pub fn resume(&mut self) -> Result<()> {
+        enable_raw_mode().map_err(|e| terminal_error(&e, "Failed to enable raw mode"))?;
+        ACTIVE.store(true, Ordering::SeqCst);
+        MOUSE.store(self.mouse, Ordering::SeqCst);
+        execute!(self.terminal.backend_mut(), EnterAlternateScreen)
+            .map_err(|e| terminal_error(&e, "Failed to enter alternate screen"))?;
+        if self.mouse {
+            execute!(self.terminal.backend_mut(), EnableMouseCapture)
+                .map_err(|e| terminal_error(&e, "Failed to enable mouse capture"))?;
+        }
+        self.terminal
+            .clear()
+            .map_err(|e| terminal_error(&e, "Failed to clear terminal"))
+    }
+
+    /// Give the terminal back to the shell; does nothing when already released
+    /// ターミナルをシェルに返す（解放済みなら何もしない）
+    ///
+    /// # Errors
+    ///
+    /// Returns a TUI error if the terminal cannot be restored.
+    /// ターミナルを復元できない場合にTUIエラーを返します。
+    pub fn release(&mut self) -> Result<()> {
+        if !ACTIVE.swap(false, Ordering::SeqCst) {
+            return Ok(());
+        }
+        if self.mouse {
+            execute!(self.terminal.backend_mut(), DisableMouseCapture)
+                .map_err(|e| terminal_error(&e, "Failed to disable mouse capture"))?;
+        }
+        execute!(self.terminal.backend_mut(), LeaveAlternateScreen)
+            .map_err(|e| terminal_error(&e, "Failed to leave alternate screen"))?;
+        disable_raw_mode().map_err(|e| terminal_error(&e, "Failed to disable raw mode"))?;
+        self.terminal
+            .show_cursor()
+            .map_err(|e| terminal_error(&e, "Failed to show cursor"))
+    }
+
+    /// Stop the process as Ctrl+Z does in a shell and take the terminal back
+    /// once it is continued; does nothing without Unix job control
+    /// シェルでのCtrl+Zと同様にプロセスを停止し、再開後にターミナルを取り戻す
+    /// （Unixのジョブ制御がない環境では何もしない）
+    ///
+    /// # Errors
+    ///
+    /// Returns a TUI error if the terminal cannot be switched or the process
+    /// cannot be stopped.
+    /// ターミナルを切り替えられない場合、またはプロセスを停止できない場合に
+    /// TUIエラーを返します。
+    pub fn suspend(&mut self) -> Result<()> {
+        #[cfg(unix)]
+        {
+            self.release()?;
+            signal_hook::low_level::emulate_default_handler(signal_hook::consts::SIGTSTP)
+                .map_err(|e| terminal_error(&e, "Failed to suspend"))?;
+            self.resume()
+        }
+        #[cfg(not(unix))]
+        {
+            Ok(())
+        }
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        // Errors cannot be reported from here; the terminal is restored on a
+        // best-effort basis
+        // ここからはエラーを報告できないため、ベストエフォートで復元する
+        let _ = self.release();
+    }
+}
+
+/// Signal the event loop reacts to
+/// イベントループが反応するシグナル
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Signal {
+    /// `SIGTSTP`: stop until continued
+    /// `SIGTSTP`：再開されるまで停止
+    Suspend,
+
+    /// `SIGCONT`: continued after being stopped from outside
+    /// `SIGCONT`：外部から停止された後に再開された
+    Continue,
+
+    /// `SIGWINCH`: the terminal was resized
+    /// `SIGWINCH`：ターミナルのサイズが変更された
+    Resize,
+
+    /// `SIGTERM` or `SIGHUP`: quit cleanly
+    /// `SIGTERM`または`SIGHUP`：正常に終了
+    Terminate,
+}
+
+/// Signals received since the last check; handlers stay registered for the
+/// lifetime of the value
+/// 前回の確認以降に受信したシグナル（値の存続期間中ハンドラを登録）
+#[derive(Debug)]
+pub struct Signals {
+    #[cfg(unix)]
+    inner: signal_hook::iterator::Signals,
+}
+
+impl Signals {
+    /// Start receiving signals
+    /// シグナルの受信を開始
+    ///
+    /// # Errors
+    ///
+    /// Returns a TUI error if the handlers cannot be registered.
+    /// ハンドラを登録できない場合にTUIエラーを返します。
+    pub fn register() -> Result<Self> {
+        #[cfg(unix)]
+        {
+            use signal_hook::consts::{SIGCONT, SIGHUP, SIGTERM, SIGTSTP, SIGWINCH};
+            let inner =
+                signal_hook::iterator::Signals::new([SIGTSTP, SIGCONT, SIGWINCH, SIGTERM, SIGHUP])
+                    .map_err(|e| terminal_error(&e, "Failed to register signal handlers"))?;
+            Ok(Self { inner })
+        }
+        #[cfg(not(unix))]
+        {
+            Ok(Self {})
+        }
+    }
+
+    /// Signals that arrived since the last call, without blocking
+    /// 前回の呼び出し以降に届いたシグナル（ブロックしない）
+    pub fn pending(&mut self) -> Vec<Signal> {
+        #[cfg(unix)]
+        {
+            use signal_hook::consts::{SIGCONT, SIGTSTP, SIGWINCH};
+            self.inner
+                .pending()
+                .map(|signal| match signal {
+                    SIGTSTP => Signal::Suspend,
+                    SIGCONT => Signal::Continue,
+                    SIGWINCH => Signal::Resize,
+                    _ => Signal::Terminate,
+                })
+                .collect()
+        }
+        #[cfg(not(unix))]
+        {
+            Vec::new()
+        }
+    }
+}
+
+/// Restore the terminal and write a crash report when the TUI panics
+/// TUIがパニックした際にターミナルを復元し、クラッシュレポートを書き込む
+fn install_panic_hook() {
+    PANIC_HOOK.call_once(|| {
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            if ACTIVE.swap(false, Ordering::SeqCst) {
+                let mut stdout = std::io::stdout();
+                if MOUSE.load(Ordering::SeqCst) {
+                    let _ = execute!(stdout, DisableMouseCapture);
+                }
+                let _ = execute!(stdout, LeaveAlternateScreen, Show);
+                let _ = disable_raw_mode();
+                let backtrace = std::backtrace::Backtrace::force_capture();
+                report_crash("Panic", &format!("{info}\n\nBacktrace:\n{backtrace}"));
+            }
+            previous(info);
+        }));
+    });
+}
+
+/// Write a crash report to the data directory's `crash-reports/` and tell the
+/// user where it went
+/// データディレクトリの`crash-reports/`にクラッシュレポートを書き込み、その場所を
+/// ユーザーに伝える
+pub fn report_crash(category: &str, message: &str) {
+    let written = Config::crash_reports_dir()
+        .and_then(|dir| write_crash_report(&dir, category, message, Local::now()));
+    match written {
+        Ok(path) => eprintln!("Crash report written to {}", path.display()),
+        Err(error) => eprintln!("Could not write crash report: {error}"),
+    }
+}
+
+/// Write a crash report into a directory, named after the time of the crash
+/// クラッシュの時刻にちなんだ名前でディレクトリにクラッシュレポートを書き込む
+///
+/// # Errors
+///
+/// Returns an error if the directory or the report cannot be written.
+/// ディレクトリまたはレポートを書き込めない場合にエラーを返します。
+pub fn write_crash_report(
+    dir: &Path,
+    category: &str,
+    message: &str,
+    time: DateTime<Local>,
+) -> Result<PathBuf> {
+    std::fs::create_dir_all(dir).map_err(|e| {
+        ZynapseError::io_error(
+            e,
+            format!("Failed to create crash report directory {}", dir.display()),
+        )
+    })?;
+    let path = dir.join(format!("crash-{}.txt", time.format("%Y%m%d-%H%M%S-%3f")));
+    let report = format!(
+        "Zynapse crash report\n\
+         time: {}\n\
+         version: {}\n\
+         platform: {}/{}\n\
+         category: {category}\n\
+         \n\
+         {message}\n",
+        time.to_rfc3339(),
+        env!("CARGO_PKG_VERSION"),
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+    );
+    std::fs::write(&path, report).map_err(|e| {
+        ZynapseError::io_error(
+            e,
+            format!("Failed to write crash report {}", path.display()),
+        )
+    })?;
+    Ok(path)
+}
+
+/// Wrap a terminal I/O error as a TUI error
+/// ターミナルI/OエラーをTUIエラーとしてラップ
+#[must_use]
+pub fn terminal_error(error: &std::io::Error, context: &str) -> ZynapseError {
+    ZynapseError::tui_error(format!("{context}: {error}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_crash_report() {
+        let dir = TempDir::new().unwrap();
+        let reports = dir.path().join("crash-reports");
+        let time = Local.with_ymd_and_hms(2024, 5, 1, 9, 30, 0).unwrap();
+        let error = terminal_error(
+            &std::io::Error::new(std::io::ErrorKind::BrokenPipe, "gone"),
+            "Failed to draw",
+        );
+
+        let path =
+            write_crash_report(&reports, error.category(), &error.to_string(), time).unwrap();
+        assert_eq!(path, reports.join("crash-20240501-093000-000.txt"));
+        let report = std::fs::read_to_string(path).unwrap();
+        assert!(report.starts_with("Zynapse crash report\n"));
+        assert!(report.contains("\ncategory: TUI\n"));
+        assert!(report.contains(&format!("version: {}", env!("CARGO_PKG_VERSION"))));
+        assert!(report.ends_with("\nTUI error: Failed to draw: gone\n"));
+    }
+}
@@ -0,0 +1,945 @@
+//! Color themes for the TUI
+//! TUIのカラーテーマ
+//!
+//! A theme assigns a style to every [`Role`]: the parts of the widgets and the
+//! Markdown elements of the preview. `tui.theme` names a built-in theme
+//! (`dark`, `light`, `high-contrast`; `default` is `dark`), a user theme file
+//! `<name>.toml` in the themes directory, or the path of a theme file.
+//! テーマはすべての[`Role`]（ウィジェットの各部分とプレビューのMarkdown要素）に
+//! スタイルを割り当てます。`tui.theme`には組み込みテーマ（`dark`、`light`、
+//! `high-contrast`。`default`は`dark`）、テーマディレクトリ内のユーザーテーマファイル
+//! `<name>.toml`、またはテーマファイルのパスを指定します。
+//!
+//! ```toml
+//! # <config dir>/themes/ocean.toml
+//! extends = "dark"
+//!
+//! [styles]
+//! border_focused = { fg = "#5fafd7" }
+//! selection = { fg = "black", bg = "#5fafd7", modifiers = ["bold"] }
+//! heading1 = { fg = "lightcyan", modifiers = ["bold", "underlined"] }
+//! ```
+//!
+//! Colors are named ANSI colors, `#rrggbb` or a 256-color index. Themes are
+//! converted to what the terminal supports: true colors are approximated on
+//! 256 and 16 color terminals, and monochrome terminals (or `NO_COLOR`) keep
+//! only text modifiers.
+//! 色はANSIの色名、`#rrggbb`、または256色のインデックスで指定します。テーマは
+//! ターミナルの対応状況に合わせて変換されます。256色・16色のターミナルでは
+//! トゥルーカラーが近似され、モノクロのターミナル（または`NO_COLOR`）では
+//! テキスト修飾のみが残ります。
+
+use crate::config::Config;
+use crate::{Result, ZynapseError};
+use ratatui::style::{Color, Modifier, Style};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Part of the interface that is styled by a theme
+/// テーマによってスタイルが決まるインターフェースの部分
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Role {
+    /// Regular text
+    /// 通常のテキスト
+    Text,
+    /// Secondary text such as paths and dates
+    /// パスや日付などの補助的なテキスト
+    Muted,
+    /// Border of an unfocused pane
+    /// フォーカスされていないペインの枠線
+    Border,
+    /// Border of the focused pane
+    /// フォーカス中のペインの枠線
+    BorderFocused,
+    /// Pane titles
+    /// ペインのタイトル
+    Title,
+    /// Selected row of the focused pane
+    /// フォーカス中のペインの選択行
+    Selection,
+    /// Selected row of an unfocused pane
+    /// フォーカスされていないペインの選択行
+    SelectionUnfocused,
+    /// Tags in lists
+    /// 一覧内のタグ
+    Tag,
+    /// Hint shown in empty inputs
+    /// 空の入力欄に表示されるヒント
+    Placeholder,
+    /// Mode indicator of the status line
+    /// ステータス行のモード表示
+    StatusMode,
+    /// Informational status messages
+    /// 情報ステータスメッセージ
+    StatusInfo,
+    /// Error status messages
+    /// エラーステータスメッセージ
+    StatusError,
+    /// Keys of an incomplete chord
+    /// 未完成のキーコードのキー
+    StatusPending,
+    /// Key hints of the status line
+    /// ステータス行のキーヒント
+    StatusHint,
+    /// Level 1 headings
+    /// レベル1見出し
+    Heading1,
+    /// Level 2 headings
+    /// レベル2見出し
+    Heading2,
+    /// Level 3 headings
+    /// レベル3見出し
+    Heading3,
+    /// Level 4 to 6 headings
+    /// レベル4〜6見出し
+    Heading,
+    /// Emphasized text
+    /// 強調テキスト
+    Emphasis,
+    /// Strongly emphasized text
+    /// 強い強調テキスト
+    Strong,
+    /// Struck-through text
+    /// 取り消し線付きテキスト
+    Strikethrough,
+    /// Inline code
+    /// インラインコード
+    Code,
+    /// Code blocks
+    /// コードブロック
+    CodeBlock,
+    /// Keywords in highlighted code
+    /// ハイライトされたコード内のキーワード
+    CodeKeyword,
+    /// String literals in highlighted code
+    /// ハイライトされたコード内の文字列リテラル
+    CodeString,
+    /// Comments in highlighted code
+    /// ハイライトされたコード内のコメント
+    CodeComment,
+    /// Number literals in highlighted code
+    /// ハイライトされたコード内の数値リテラル
+    CodeNumber,
+    /// Markdown links
+    /// Markdownリンク
+    Link,
+    /// Wiki links to existing notes
+    /// 既存のノートへのWikiリンク
+    WikiLink,
+    /// Wiki links to missing notes
+    /// 存在しないノートへのWikiリンク
+    WikiLinkMissing,
+    /// Block quotes
+    /// 引用ブロック
+    Quote,
+    /// List bullets and numbers
+    /// リストの記号と番号
+    ListMarker,
+    /// Checked task boxes
+    /// チェック済みのタスクボックス
+    TaskDone,
+    /// Unchecked task boxes
+    /// 未チェックのタスクボックス
+    TaskOpen,
+    /// Table header cells
+    /// テーブルのヘッダーセル
+    TableHeader,
+    /// Table borders
+    /// テーブルの罫線
+    TableBorder,
+    /// Horizontal rules
+    /// 水平線
+    Rule,
+    /// Notes in the graph view
+    /// グラフビューのノート
+    GraphNode,
+    /// Note the graph view is centered on
+    /// グラフビューの中心のノート
+    GraphCenter,
+    /// Links in the graph view
+    /// グラフビューのリンク
+    GraphEdge,
+    /// Links made of several wiki links
+    /// 複数のWikiリンクからなるリンク
+    GraphEdgeStrong,
+    /// Calendar days with some activity
+    /// 何らかの活動があるカレンダーの日
+    CalendarActive,
+    /// Calendar days with a lot of activity
+    /// 活動の多いカレンダーの日
+    CalendarBusy,
+    /// Marker of calendar days with a daily note
+    /// デイリーノートがあるカレンダーの日の印
+    CalendarDaily,
+    /// Today in the calendar
+    /// カレンダーの今日
+    CalendarToday,
+}
+
+impl Role {
+    /// Every role, in the order of theme files
+    /// テーマファイルの順のすべてのロール
+    pub const ALL: [Self; 45] = [
+        Self::Text,
+        Self::Muted,
+        Self::Border,
+        Self::BorderFocused,
+        Self::Title,
+        Self::Selection,
+        Self::SelectionUnfocused,
+        Self::Tag,
+        Self::Placeholder,
+        Self::StatusMode,
+        Self::StatusInfo,
+        Self::StatusError,
+        Self::StatusPending,
+        Self::StatusHint,
+        Self::Heading1,
+        Self::Heading2,
+        Self::Heading3,
+        Self::Heading,
+        Self::Emphasis,
+        Self::Strong,
+        Self::Strikethrough,
+        Self::Code,
+        Self::CodeBlock,
+        Self::CodeKeyword,
+        Self::CodeString,
+        Self::CodeComment,
+        Self::CodeNumber,
+        Self::Link,
+        Self::WikiLink,
+        Self::WikiLinkMissing,
+        Self::Quote,
+        Self::ListMarker,
+        Self::TaskDone,
+        Self::TaskOpen,
+        Self::TableHeader,
+        Self::TableBorder,
+        Self::Rule,
+        Self::GraphNode,
+        Self::GraphCenter,
+        Self::GraphEdge,
+        Self::GraphEdgeStrong,
+        Self::CalendarActive,
+        Self::CalendarBusy,
+        Self::CalendarDaily,
+        Self::CalendarToday,
+    ];
+
+    /// Key of the role in theme files
+    /// テーマファイルにおけるロールのキー
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Text => "text",
+            Self::Muted => "muted",
+            Self::Border => "border",
+            Self::BorderFocused => "border_focused",
+            Self::Title => "title",
+            Self::Selection => "selection",
+            Self::SelectionUnfocused => "selection_unfocused",
+            Self::Tag => "tag",
+            Self::Placeholder => "placeholder",
+            Self::StatusMode => "status_mode",
+            Self::StatusInfo => "status_info",
+            Self::StatusError => "status_error",
+            Self::StatusPending => "status_pending",
+            Self::StatusHint => "status_hint",
+            Self::Heading1 => "heading1",
+            Self::Heading2 => "heading2",
+            Self::Heading3 => "heading3",
+            Self::Heading => "heading",
+            Self::Emphasis => "emphasis",
+            Self::Strong => "strong",
+            Self::Strikethrough => "strikethrough",
+            Self::Code => "code",
+            Self::CodeBlock => "code_block",
+            Self::CodeKeyword => "code_keyword",
+            Self::CodeString => "code_string",
+            Self::CodeComment => "code_comment",
+            Self::CodeNumber => "code_number",
+            Self::Link => "link",
+            Self::WikiLink => "wiki_link",
+            Self::WikiLinkMissing => "wiki_link_missing",
+            Self::Quote => "quote",
+            Self::ListMarker => "list_marker",
+            Self::TaskDone => "task_done",
+            Self::TaskOpen => "task_open",
+            Self::TableHeader => "table_header",
+            Self::TableBorder => "table_border",
+            Self::Rule => "rule",
+            Self::GraphNode => "graph_node",
+            Self::GraphCenter => "graph_center",
+            Self::GraphEdge => "graph_edge",
+            Self::GraphEdgeStrong => "graph_edge_strong",
+            Self::CalendarActive => "calendar_active",
+            Self::CalendarBusy => "calendar_busy",
+            Self::CalendarDaily => "calendar_daily",
+            Self::CalendarToday => "calendar_today",
+        }
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Role {
+    type Err = ZynapseError;
+
+    fn from_str(name: &str) -> Result<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|role| role.name() == name)
+            .ok_or_else(|| ZynapseError::config_error(format!("Unknown theme style `{name}`")))
+    }
+}
+
+/// Colors a terminal can display
+/// ターミナルが表示できる色
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ColorSupport {
+    /// No colors, only text modifiers
+    /// 色なし（テキスト修飾のみ）
+    Monochrome,
+
+    /// The 16 ANSI colors
+    /// 16色のANSIカラー
+    Ansi16,
+
+    /// The 256-color palette
+    /// 256色パレット
+    Ansi256,
+
+    /// 24-bit colors
+    /// 24ビットカラー
+    TrueColor,
+}
+
+impl ColorSupport {
+    /// Detect the color support of the terminal from the environment
+    /// 環境変数からターミナルの色対応を検出
+    ///
+    /// `NO_COLOR` and `TERM=dumb` select monochrome, `COLORTERM=truecolor`
+    /// selects true colors and a `TERM` ending in `256color` selects 256 colors.
+    /// `NO_COLOR`と`TERM=dumb`はモノクロ、`COLORTERM=truecolor`はトゥルーカラー、
+    /// `256color`で終わる`TERM`は256色を選択します。
+    #[must_use]
+    pub fn detect() -> Self {
+        let var = |name| std::env::var(name).ok().filter(|value| !value.is_empty());
+        Self::from_env(
+            var("NO_COLOR").is_some(),
+            var("COLORTERM").as_deref(),
+            var("TERM").as_deref(),
+        )
+    }
+
+    /// Decide the color support from environment values
+    /// 環境変数の値から色対応を決定
+    fn from_env(no_color: bool, colorterm: Option<&str>, term: Option<&str>) -> Self {
+        if no_color || term == Some("dumb") {
+            Self::Monochrome
+        } else if matches!(colorterm, Some("truecolor" | "24bit")) {
+            Self::TrueColor
+        } else if term.is_some_and(|term| term.ends_with("256color")) {
+            Self::Ansi256
+        } else {
+            Self::Ansi16
+        }
+    }
+}
+
+/// Style of every role
+/// すべてのロールのスタイル
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Theme {
+    /// Theme name
+    /// テーマ名
+    pub name: String,
+
+    /// Styles indexed by role
+    /// ロールでインデックス付けされたスタイル
+    styles: BTreeMap<Role, Style>,
+}
+
+/// Theme file contents
+/// テーマファイルの内容
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ThemeFile {
+    /// Display name, defaulting to the file name
+    /// 表示名（デフォルトはファイル名）
+    name: Option<String>,
+
+    /// Built-in theme providing the styles the file leaves out
+    /// ファイルで省略されたスタイルを提供する組み込みテーマ
+    extends: Option<String>,
+
+    /// Styles per role name
+    /// ロール名ごとのスタイル
+    #[serde(default)]
+    styles: BTreeMap<String, StyleSpec>,
+}
+
+/// Style of one role in a theme file
+/// テーマファイル内の1つのロールのスタイル
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct StyleSpec {
+    /// Foreground color
+    /// 前景色
+    fg: Option<String>,
+
+    /// Background color
+    /// 背景色
+    bg: Option<String>,
+
+    /// Text modifiers replacing those of the base theme
+    /// 基になるテーマの修飾を置き換えるテキスト修飾
+    modifiers: Option<Vec<String>>,
+}
+
+/// Names of the built-in themes
+/// 組み込みテーマの名前
+pub const BUILTIN_THEMES: [&str; 4] = ["default", "dark", "light", "high-contrast"];
+
+impl Theme {
+    /// Resolve `tui.theme` to a theme
+    /// `tui.theme`をテーマに解決
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the theme is neither built in nor a readable, valid
+    /// theme file.
+    /// テーマが組み込みでも、読み込み可能で有効なテーマファイルでもない場合に
+    /// エラーを返します。
+    pub fn named(name: &str) -> Result<Self> {
+        if let Some(theme) = Self::builtin(name) {
+            return Ok(theme);
+        }
+        let path = Path::new(name);
+        if path
+            .extension()
+            .is_some_and(|extension| extension == "toml")
+            || path.components().count() > 1
+        {
+            Self::from_file(path)
+        } else {
+            Self::from_file(&Config::themes_dir()?.join(format!("{name}.toml")))
+        }
+    }
+
+    /// Built-in theme by name
+    /// 名前による組み込みテーマ
+    #[must_use]
+    pub fn builtin(name: &str) -> Option<Self> {
+        let styles = match name {
+            "default" | "dark" => dark(),
+            "light" => light(),
+            "high-contrast" => high_contrast(),
+            _ => return None,
+        };
+        Some(Self {
+            name: name.to_string(),
+            styles: Role::ALL.into_iter().zip(styles).collect(),
+        })
+    }
+
+    /// Load a theme file
+    /// テーマファイルを読み込み
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or is not a valid theme.
+    /// ファイルを読み込めない場合、または有効なテーマでない場合にエラーを返します。
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            ZynapseError::io_error(e, format!("Failed to read theme {}", path.display()))
+        })?;
+        let name = path
+            .file_stem()
+            .map_or_else(String::new, |stem| stem.to_string_lossy().into_owned());
+        Self::parse(&name, &content)
+            .map_err(|e| ZynapseError::config_error(format!("{}: {e}", path.display())))
+    }
+
+    /// Parse a theme from TOML
+    /// TOMLからテーマを解析
+    ///
+    /// # Errors
+    ///
+    /// Returns an error for invalid TOML, unknown keys, style names or
+    /// modifiers, invalid colors, or an unknown base theme.
+    /// 無効なTOML、不明なキー・スタイル名・修飾、無効な色、または不明な基底テーマの
+    /// 場合にエラーを返します。
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ratatui::style::Color;
+    /// use zynapse::tui::theme::{Role, Theme};
+    ///
+    /// let theme = Theme::parse("mine", r##"
+    /// extends = "light"
+    /// [styles]
+    /// selection = { fg = "white", bg = "#005f87" }
+    /// "##)?;
+    /// assert_eq!(theme.style(Role::Selection).bg, Some(Color::Rgb(0, 0x5f, 0x87)));
+    /// # Ok::<(), zynapse::ZynapseError>(())
+    /// ```
+    pub fn parse(name: &str, content: &str) -> Result<Self> {
+        let file: ThemeFile = toml::from_str(content)
+            .map_err(|e| ZynapseError::config_error(format!("Invalid theme: {e}")))?;
+
+        let base = file.extends.as_deref().unwrap_or("default");
+        let mut theme = Self::builtin(base).ok_or_else(|| {
+            ZynapseError::config_error(format!(
+                "Unknown base theme `{base}` (expected one of: {})",
+                BUILTIN_THEMES.join(", ")
+            ))
+        })?;
+        theme.name = file.name.unwrap_or_else(|| name.to_string());
+
+        for (key, spec) in file.styles {
+            let role: Role = key.parse()?;
+            let style = theme.styles.entry(role).or_default();
+            let context = |field: &str| format!("styles.{key}.{field}");
+            if let Some(fg) = &spec.fg {
+                style.fg = Some(parse_color(fg, &context("fg"))?);
+            }
+            if let Some(bg) = &spec.bg {
+                style.bg = Some(parse_color(bg, &context("bg"))?);
+            }
+            if let Some(modifiers) = &spec.modifiers {
+                style.add_modifier = Modifier::empty();
+                style.sub_modifier = Modifier::empty();
+                for modifier in modifiers {
+                    style.add_modifier |= parse_modifier(modifier, &context("modifiers"))?;
+                }
+            }
+        }
+        Ok(theme)
+    }
+
+    /// Style of a role
+    /// ロールのスタイル
+    #[must_use]
+    pub fn style(&self, role: Role) -> Style {
+        self.styles.get(&role).copied().unwrap_or_default()
+    }
+
+    /// Convert every color to what the terminal can display
+    /// すべての色をターミナルが表示できる色に変換
+    ///
+    /// On monochrome terminals, styles that relied on a background color are
+    /// shown reversed so selections stay visible.
+    /// モノクロのターミナルでは、背景色に依存していたスタイルを反転表示にして
+    /// 選択が見えるようにします。
+    #[must_use]
+    pub fn degrade(mut self, support: ColorSupport) -> Self {
+        for style in self.styles.values_mut() {
+            if support == ColorSupport::Monochrome {
+                if style.bg.is_some_and(|bg| bg != Color::Reset) {
+                    style.add_modifier |= Modifier::REVERSED;
+                }
+                style.fg = None;
+                style.bg = None;
+            } else {
+                style.fg = style.fg.map(|color| convert(color, support));
+                style.bg = style.bg.map(|color| convert(color, support));
+            }
+        }
+        self
+    }
+}
+
+/// Parse a color, naming the theme field on error
+/// 色を解析（エラー時はテーマのフィールドを示す）
+fn parse_color(value: &str, field: &str) -> Result<Color> {
+    value.parse().map_err(|_| {
+        ZynapseError::config_error(format!(
+            "{field}: invalid color `{value}` (use a color name, #rrggbb or 0-255)"
+        ))
+    })
+}
+
+/// Parse a text modifier name
+/// テキスト修飾名を解析
+fn parse_modifier(value: &str, field: &str) -> Result<Modifier> {
+    Ok(match value {
+        "bold" => Modifier::BOLD,
+        "dim" => Modifier::DIM,
+        "italic" => Modifier::ITALIC,
+        "underlined" => Modifier::UNDERLINED,
+        "slow_blink" => Modifier::SLOW_BLINK,
+        "rapid_blink" => Modifier::RAPID_BLINK,
+        "reversed" => Modifier::REVERSED,
+        "hidden" => Modifier::HIDDEN,
+        "crossed_out" => Modifier::CROSSED_OUT,
+        _ => {
+            return Err(ZynapseError::config_error(format!(
+                "{field}: unknown modifier `{value}`"
+            )))
+        }
+    })
+}
+
+/// RGB values of the 16 ANSI colors (xterm defaults)
+/// 16色のANSIカラーのRGB値（xtermのデフォルト）
+const ANSI16: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::Red, (205, 0, 0)),
+    (Color::Green, (0, 205, 0)),
+    (Color::Yellow, (205, 205, 0)),
+    (Color::Blue, (0, 0, 238)),
+    (Color::Magenta, (205, 0, 205)),
+    (Color::Cyan, (0, 205, 205)),
+    (Color::Gray, (229, 229, 229)),
+    (Color::DarkGray, (127, 127, 127)),
+    (Color::LightRed, (255, 0, 0)),
+    (Color::LightGreen, (0, 255, 0)),
+    (Color::LightYellow, (255, 255, 0)),
+    (Color::LightBlue, (92, 92, 255)),
+    (Color::LightMagenta, (255, 0, 255)),
+    (Color::LightCyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+/// Steps of the 6x6x6 color cube of the 256-color palette
+/// 256色パレットの6x6x6カラーキューブの段階
+const CUBE: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// Approximate a color within the given color support
+/// 指定された色対応の範囲で色を近似
+fn convert(color: Color, support: ColorSupport) -> Color {
+    match (color, support) {
+        (Color::Rgb(r, g, b), ColorSupport::Ansi256) => Color::Indexed(nearest_indexed(r, g, b)),
+        (Color::Rgb(r, g, b), ColorSupport::Ansi16) => nearest_ansi16((r, g, b)),
+        (Color::Indexed(index), ColorSupport::Ansi16) => nearest_ansi16(indexed_rgb(index)),
+        (color, _) => color,
+    }
+}
+
+/// Squared distance between two RGB colors
+/// 2つのRGB色の距離の2乗
+fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+    let d = |x: u8, y: u8| u32::from(x.abs_diff(y)).pow(2);
+    d(a.0, b.0) + d(a.1, b.1) + d(a.2, b.2)
+}
+
+/// Closest of the 16 ANSI colors
+/// 16色のANSIカラーで最も近い色
+fn nearest_ansi16(rgb: (u8, u8, u8)) -> Color {
+    ANSI16
+        .iter()
+        .min_by_key(|(_, candidate)| distance(rgb, *candidate))
+        .map_or(Color::Reset, |&(color, _)| color)
+}
+
+/// Closest entry of the 256-color palette outside the 16 system colors
+/// 16色のシステムカラーを除く256色パレットで最も近い項目
+fn nearest_indexed(r: u8, g: u8, b: u8) -> u8 {
+    (16..=255)
+        .min_by_key(|&index| distance((r, g, b), indexed_rgb(index)))
+        .unwrap_or(16)
+}
+
+/// RGB value of a 256-color palette entry
+/// 256色パレットの項目のRGB値
+fn indexed_rgb(index: u8) -> (u8, u8, u8) {
+    match index {
+        0..=15 => ANSI16[usize::from(index)].1,
+        16..=231 => {
+            let cube = index - 16;
+            (
+                CUBE[usize::from(cube / 36)],
+                CUBE[usize::from(cube / 6 % 6)],
+                CUBE[usize::from(cube % 6)],
+            )
+        }
+        _ => {
+            let level = 8 + (index - 232) * 10;
+            (level, level, level)
+        }
+    }
+}
+
+/// Style with a foreground color
+/// 前景色付きのスタイル
+const fn fg(color: Color) -> Style {
+    Style::new().fg(color)
+}
+
+/// Styles of the dark theme, in [`Role::ALL`] order
+/// ダークテーマのスタイル（[`Role::ALL`]の順）
+const fn dark() -> [Style; 45] {
+    let bold = Modifier::BOLD;
+    [
+        Style::new(),
+        fg(Color::DarkGray),
+        fg(Color::DarkGray),
+        fg(Color::Cyan),
+        Style::new().add_modifier(bold),
+        Style::new()
+            .fg(Color::Black)
+            .bg(Color::Cyan)
+            .add_modifier(bold),
+        Style::new().add_modifier(bold.union(Modifier::REVERSED)),
+        fg(Color::DarkGray).add_modifier(Modifier::ITALIC),
+        fg(Color::DarkGray),
+        Style::new()
+            .fg(Color::Black)
+            .bg(Color::Cyan)
+            .add_modifier(bold),
+        fg(Color::Green),
+        fg(Color::Red),
+        fg(Color::Yellow),
+        fg(Color::DarkGray),
+        fg(Color::LightCyan).add_modifier(bold.union(Modifier::UNDERLINED)),
+        fg(Color::LightBlue).add_modifier(bold),
+        fg(Color::LightMagenta).add_modifier(bold),
+        fg(Color::Magenta),
+        Style::new().add_modifier(Modifier::ITALIC),
+        Style::new().add_modifier(bold),
+        Style::new().add_modifier(Modifier::CROSSED_OUT),
+        fg(Color::Yellow),
+        fg(Color::Gray),
+        fg(Color::LightMagenta),
+        fg(Color::LightGreen),
+        fg(Color::DarkGray).add_modifier(Modifier::ITALIC),
+        fg(Color::LightYellow),
+        fg(Color::Blue).add_modifier(Modifier::UNDERLINED),
+        fg(Color::Cyan).add_modifier(Modifier::UNDERLINED),
+        fg(Color::Red).add_modifier(Modifier::UNDERLINED),
+        fg(Color::Gray).add_modifier(Modifier::ITALIC),
+        fg(Color::Cyan),
+        fg(Color::Green),
+        fg(Color::Yellow),
+        Style::new().add_modifier(bold),
+        fg(Color::DarkGray),
+        fg(Color::DarkGray),
+        fg(Color::Cyan),
+        fg(Color::Yellow).add_modifier(bold),
+        fg(Color::DarkGray),
+        fg(Color::Magenta),
+        fg(Color::Green),
+        fg(Color::LightGreen).add_modifier(bold),
+        fg(Color::Yellow),
+        Style::new().add_modifier(Modifier::UNDERLINED),
+    ]
+}
+
+/// Styles of the light theme, in [`Role::ALL`] order
+/// ライトテーマのスタイル（[`Role::ALL`]の順）
+const fn light() -> [Style; 45] {
+    let bold = Modifier::BOLD;
+    let gray = Color::Indexed(244);
+    [
+        fg(Color::Black),
+        fg(gray),
+        fg(gray),
+        fg(Color::Blue),
+        fg(Color::Black).add_modifier(bold),
+        Style::new()
+            .fg(Color::White)
+            .bg(Color::Blue)
+            .add_modifier(bold),
+        Style::new().add_modifier(bold.union(Modifier::REVERSED)),
+        fg(gray).add_modifier(Modifier::ITALIC),
+        fg(gray),
+        Style::new()
+            .fg(Color::White)
+            .bg(Color::Blue)
+            .add_modifier(bold),
+        fg(Color::Green),
+        fg(Color::Red),
+        fg(Color::Magenta),
+        fg(gray),
+        fg(Color::Blue).add_modifier(bold.union(Modifier::UNDERLINED)),
+        fg(Color::Magenta).add_modifier(bold),
+        fg(Color::Cyan).add_modifier(bold),
+        fg(Color::Black).add_modifier(bold),
+        Style::new().add_modifier(Modifier::ITALIC),
+        Style::new().add_modifier(bold),
+        Style::new().add_modifier(Modifier::CROSSED_OUT),
+        fg(Color::Red),
+        fg(Color::Black),
+        fg(Color::Magenta),
+        fg(Color::Green),
+        fg(gray).add_modifier(Modifier::ITALIC),
+        fg(Color::Blue),
+        fg(Color::Blue).add_modifier(Modifier::UNDERLINED),
+        fg(Color::Cyan).add_modifier(Modifier::UNDERLINED),
+        fg(Color::Red).add_modifier(Modifier::UNDERLINED),
+        fg(gray).add_modifier(Modifier::ITALIC),
+        fg(Color::Blue),
+        fg(Color::Green),
+        fg(Color::Red),
+        fg(Color::Black).add_modifier(bold),
+        fg(gray),
+        fg(gray),
+        fg(Color::Blue),
+        fg(Color::Red).add_modifier(bold),
+        fg(gray),
+        fg(Color::Magenta),
+        fg(Color::Green),
+        fg(Color::Green).add_modifier(bold),
+        fg(Color::Red),
+        Style::new().add_modifier(Modifier::UNDERLINED),
+    ]
+}
+
+/// Styles of the high-contrast theme, in [`Role::ALL`] order
+/// ハイコントラストテーマのスタイル（[`Role::ALL`]の順）
+const fn high_contrast() -> [Style; 45] {
+    let bold = Modifier::BOLD;
+    let underlined = Modifier::UNDERLINED;
+    [
+        fg(Color::White),
+        fg(Color::White),
+        fg(Color::White),
+        fg(Color::LightYellow).add_modifier(bold),
+        fg(Color::White).add_modifier(bold),
+        Style::new()
+            .fg(Color::Black)
+            .bg(Color::LightYellow)
+            .add_modifier(bold),
+        Style::new()
+            .fg(Color::Black)
+            .bg(Color::White)
+            .add_modifier(bold),
+        fg(Color::LightCyan),
+        fg(Color::White).add_modifier(Modifier::ITALIC),
+        Style::new()
+            .fg(Color::Black)
+            .bg(Color::LightYellow)
+            .add_modifier(bold),
+        fg(Color::LightGreen).add_modifier(bold),
+        fg(Color::LightRed).add_modifier(bold),
+        fg(Color::LightYellow).add_modifier(bold),
+        fg(Color::White),
+        fg(Color::LightYellow).add_modifier(bold.union(underlined)),
+        fg(Color::LightYellow).add_modifier(bold),
+        fg(Color::White).add_modifier(bold.union(underlined)),
+        fg(Color::White).add_modifier(bold),
+        fg(Color::White).add_modifier(Modifier::ITALIC),
+        fg(Color::White).add_modifier(bold),
+        fg(Color::White).add_modifier(Modifier::CROSSED_OUT),
+        fg(Color::LightGreen),
+        fg(Color::White),
+        fg(Color::LightYellow).add_modifier(bold),
+        fg(Color::LightGreen),
+        fg(Color::LightCyan).add_modifier(Modifier::ITALIC),
+        fg(Color::LightMagenta),
+        fg(Color::LightCyan).add_modifier(underlined),
+        fg(Color::LightCyan).add_modifier(bold.union(underlined)),
+        fg(Color::LightRed).add_modifier(bold.union(underlined)),
+        fg(Color::White).add_modifier(Modifier::ITALIC),
+        fg(Color::LightYellow),
+        fg(Color::LightGreen).add_modifier(bold),
+        fg(Color::LightYellow).add_modifier(bold),
+        fg(Color::White).add_modifier(bold.union(underlined)),
+        fg(Color::White),
+        fg(Color::White),
+        fg(Color::LightCyan),
+        fg(Color::LightYellow).add_modifier(bold),
+        fg(Color::White),
+        fg(Color::LightMagenta).add_modifier(bold),
+        fg(Color::LightGreen),
+        fg(Color::LightGreen).add_modifier(bold),
+        fg(Color::LightYellow).add_modifier(bold),
+        Style::new().add_modifier(bold.union(underlined)),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_themes_cover_every_role() {
+        for name in BUILTIN_THEMES {
+            let theme = Theme::named(name).unwrap();
+            assert_eq!(theme.styles.len(), Role::ALL.len(), "{name}");
+        }
+        for role in Role::ALL {
+            assert_eq!(role.name().parse::<Role>().unwrap(), role);
+        }
+    }
+
+    #[test]
+    fn test_theme_files_are_validated() {
+        let theme = Theme::parse(
+            "custom",
+            r##"
+name = "Custom"
+extends = "high-contrast"
+
+[styles]
+border = { fg = "#ff8700", modifiers = ["bold", "italic"] }
+"##,
+        )
+        .unwrap();
+        assert_eq!(theme.name, "Custom");
+        let border = theme.style(Role::Border);
+        assert_eq!(border.fg, Some(Color::Rgb(0xff, 0x87, 0)));
+        assert_eq!(border.add_modifier, Modifier::BOLD | Modifier::ITALIC);
+        assert_eq!(
+            theme.style(Role::Text),
+            Theme::builtin("high-contrast").unwrap().style(Role::Text)
+        );
+
+        for invalid in [
+            "[styles]\nborders = { fg = \"red\" }",
+            "[styles]\nborder = { fg = \"reddish\" }",
+            "[styles]\nborder = { modifiers = [\"loud\"] }",
+            "[styles]\nborder = { color = \"red\" }",
+            "extends = \"solarized\"",
+        ] {
+            assert!(Theme::parse("bad", invalid).is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn test_degradation() {
+        assert_eq!(
+            convert(Color::Rgb(0xff, 0x87, 0), ColorSupport::Ansi256),
+            Color::Indexed(208)
+        );
+        assert_eq!(
+            convert(Color::Rgb(250, 10, 10), ColorSupport::Ansi16),
+            Color::LightRed
+        );
+        assert_eq!(
+            convert(Color::Indexed(21), ColorSupport::Ansi16),
+            Color::Blue
+        );
+        assert_eq!(convert(Color::Cyan, ColorSupport::Ansi16), Color::Cyan);
+
+        let mono = Theme::builtin("dark")
+            .unwrap()
+            .degrade(ColorSupport::Monochrome);
+        let selection = mono.style(Role::Selection);
+        assert_eq!((selection.fg, selection.bg), (None, None));
+        assert!(selection.add_modifier.contains(Modifier::REVERSED));
+
+        assert_eq!(
+            ColorSupport::from_env(true, Some("truecolor"), Some("xterm-256color")),
+            ColorSupport::Monochrome
+        );
+        assert_eq!(
+            ColorSupport::from_env(false, None, Some("xterm-256color")),
+            ColorSupport::Ansi256
+        );
+        assert_eq!(
+            ColorSupport::from_env(false, None, Some("linux")),
+            ColorSupport::Ansi16
+        );
+    }
+}
@@ -0,0 +1,375 @@
+//! Timeline and calendar of note activity
+//! ノートの活動のタイムラインとカレンダー
+//!
+//! Every note contributes an event on the day it was created and, when edited
+//! on a later day, another on the day of its last update. Daily notes are
+//! attached to the date they were written for, so the calendar shows both the
+//! journal and the days something changed. Dates are taken in the local time
+//! zone.
+//! 各ノートは作成日にイベントを1つ、後日編集された場合は最終更新日にもう1つの
+//! イベントを持ちます。デイリーノートは対象の日付に結び付けられるため、
+//! カレンダーには日誌と変更のあった日の両方が表示されます。日付はローカル
+//! タイムゾーンで扱います。
+
+use crate::note::Note;
+use crate::periodic::Period;
+use chrono::{DateTime, Datelike, Days, Months, NaiveDate, TimeZone, Utc};
+use std::collections::BTreeMap;
+
+/// Number of events from which a day counts as busy
+/// 忙しい日とみなすイベント数
+pub const BUSY_THRESHOLD: usize = 3;
+
+/// Columns taken by one day of the calendar
+/// カレンダーの1日が占める桁数
+pub const CELL_WIDTH: u16 = 5;
+
+/// Presentation of the timeline
+/// タイムラインの表示形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum View {
+    /// Events listed newest first
+    /// 新しい順に並べたイベント
+    List,
+
+    /// Month calendar with the notes of the selected day
+    /// 月のカレンダーと選択中の日のノート
+    Calendar,
+}
+
+/// What happened to a note
+/// ノートに起きたこと
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Change {
+    /// The note was created
+    /// ノートが作成された
+    Created,
+
+    /// The note was last edited
+    /// ノートが最後に編集された
+    Updated,
+}
+
+impl Change {
+    /// Word shown next to the note
+    /// ノートの横に表示する語
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Created => "created",
+            Self::Updated => "edited",
+        }
+    }
+}
+
+/// A dated change of a note
+/// 日付付きのノートの変更
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Event {
+    /// Local date of the change
+    /// 変更のローカル日付
+    pub date: NaiveDate,
+
+    /// Index of the note in the list the timeline was built from
+    /// タイムラインの構築元一覧でのノートのインデックス
+    pub note: usize,
+
+    /// Kind of change
+    /// 変更の種類
+    pub change: Change,
+}
+
+/// Activity of one day
+/// 1日の活動
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Day {
+    /// Number of notes created
+    /// 作成されたノート数
+    pub created: usize,
+
+    /// Number of notes last edited
+    /// 最後に編集されたノート数
+    pub updated: usize,
+
+    /// Daily note written for the day
+    /// その日のデイリーノート
+    pub daily: Option<usize>,
+}
+
+impl Day {
+    /// Number of events on the day
+    /// その日のイベント数
+    #[must_use]
+    pub const fn events(&self) -> usize {
+        self.created + self.updated
+    }
+}
+
+/// Activity of a vault over time, with the selection of both views
+/// 両ビューの選択状態を含む、ボルトの時系列の活動
+#[derive(Debug, Clone)]
+pub struct Timeline {
+    /// Shown presentation
+    /// 表示中の形式
+    pub view: View,
+
+    /// Events, newest first
+    /// イベント（新しい順）
+    pub events: Vec<Event>,
+
+    /// Selected position in `events`
+    /// `events`内の選択位置
+    pub selected: usize,
+
+    /// First event shown in the list, kept between draws
+    /// 一覧に表示する最初のイベント（描画間で保持）
+    pub offset: usize,
+
+    /// Day selected in the calendar
+    /// カレンダーで選択中の日
+    pub day: NaiveDate,
+
+    /// Today's date, highlighted in the calendar
+    /// カレンダーで強調される今日の日付
+    pub today: NaiveDate,
+
+    days: BTreeMap<NaiveDate, Day>,
+}
+
+impl Timeline {
+    /// Collect the activity of `notes`, taking dates in time zone `zone`
+    /// タイムゾーン`zone`の日付で`notes`の活動を収集
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use chrono::{NaiveDate, Utc};
+    /// use zynapse::note::Note;
+    /// use zynapse::tui::timeline::{Timeline, View};
+    ///
+    /// let note = Note::new("Today", "");
+    /// let today = note.frontmatter.created.date_naive();
+    /// let timeline = Timeline::build(&[note], &Utc, today, View::Calendar);
+    /// assert_eq!(timeline.on(today).unwrap().created, 1);
+    /// assert_eq!(timeline.notes_on(today), [0]);
+    /// ```
+    #[must_use]
+    pub fn build<Tz: TimeZone>(notes: &[Note], zone: &Tz, today: NaiveDate, view: View) -> Self {
+        let date = |time: &DateTime<Utc>| time.with_timezone(zone).date_naive();
+        let mut days: BTreeMap<NaiveDate, Day> = BTreeMap::new();
+        let mut timed = Vec::new();
+        for (index, note) in notes.iter().enumerate() {
+            let frontmatter = &note.frontmatter;
+            let created = date(&frontmatter.created);
+            days.entry(created).or_default().created += 1;
+            timed.push((frontmatter.created, index, Change::Created, created));
+
+            let updated = date(&frontmatter.updated);
+            if updated > created {
+                days.entry(updated).or_default().updated += 1;
+                timed.push((frontmatter.updated, index, Change::Updated, updated));
+            }
+
+            if let Some((Period::Daily, day)) = Period::of(note) {
+                days.entry(day).or_default().daily = Some(index);
+            }
+        }
+        timed.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+
+        Self {
+            view,
+            events: timed
+                .into_iter()
+                .map(|(_, note, change, date)| Event { date, note, change })
+                .collect(),
+            selected: 0,
+            offset: 0,
+            day: today,
+            today,
+            days,
+        }
+    }
+
+    /// Activity on a date, if there was any
+    /// 日付の活動（あれば）
+    #[must_use]
+    pub fn on(&self, date: NaiveDate) -> Option<&Day> {
+        self.days.get(&date)
+    }
+
+    /// Notes with activity on a date: the daily note first, then by event
+    /// 日付に活動のあったノート（デイリーノートが先頭、その後イベント順）
+    #[must_use]
+    pub fn notes_on(&self, date: NaiveDate) -> Vec<usize> {
+        let mut notes: Vec<usize> = self
+            .on(date)
+            .and_then(|day| day.daily)
+            .into_iter()
+            .collect();
+        for event in self.events.iter().filter(|event| event.date == date) {
+            if !notes.contains(&event.note) {
+                notes.push(event.note);
+            }
+        }
+        notes
+    }
+
+    /// Selected event of the list
+    /// 一覧で選択中のイベント
+    #[must_use]
+    pub fn selected_event(&self) -> Option<&Event> {
+        self.events.get(self.selected)
+    }
+
+    /// Move the list selection, clamping at both ends, and follow it in the calendar
+    /// 一覧の選択を移動（両端で制限）し、カレンダーも追従させる
+    pub fn select_by(&mut self, delta: isize) {
+        if self.events.is_empty() {
+            return;
+        }
+        self.selected = self
+            .selected
+            .saturating_add_signed(delta)
+            .min(self.events.len() - 1);
+        self.day = self.events[self.selected].date;
+    }
+
+    /// Move the calendar selection by a number of days
+    /// カレンダーの選択を日数単位で移動
+    pub fn move_days(&mut self, days: i64) {
+        let moved = if days < 0 {
+            self.day.checked_sub_days(Days::new(days.unsigned_abs()))
+        } else {
+            self.day.checked_add_days(Days::new(days.unsigned_abs()))
+        };
+        self.day = moved.unwrap_or(self.day);
+    }
+
+    /// Move the calendar selection by a number of months, keeping the day where possible
+    /// カレンダーの選択を月単位で移動（可能な限り日を維持）
+    pub fn move_months(&mut self, months: i32) {
+        let moved = if months < 0 {
+            self.day
+                .checked_sub_months(Months::new(months.unsigned_abs()))
+        } else {
+            self.day
+                .checked_add_months(Months::new(months.unsigned_abs()))
+        };
+        self.day = moved.unwrap_or(self.day);
+    }
+
+    /// Most recent day with activity
+    /// 活動のあった最新の日
+    #[must_use]
+    pub fn latest(&self) -> Option<NaiveDate> {
+        self.days.keys().next_back().copied()
+    }
+
+    /// Show the list at the newest event of the selected day or before it
+    /// 選択中の日またはそれ以前の最新のイベントで一覧を表示
+    pub fn show_day(&mut self) {
+        self.view = View::List;
+        if let Some(position) = self.events.iter().position(|event| event.date <= self.day) {
+            self.selected = position;
+        }
+    }
+
+    /// Weeks of the selected month, Monday first, with days of other months left out
+    /// 選択中の月の週（月曜始まり、他の月の日は空欄）
+    #[must_use]
+    pub fn weeks(&self) -> Vec<[Option<NaiveDate>; 7]> {
+        let first = self.day.with_day(1).unwrap_or(self.day);
+        let mut weeks = Vec::new();
+        let mut week = [None; 7];
+        for date in first
+            .iter_days()
+            .take_while(|date| date.month() == first.month())
+        {
+            let weekday = date.weekday().num_days_from_monday() as usize;
+            week[weekday] = Some(date);
+            if weekday == 6 {
+                weeks.push(std::mem::take(&mut week));
+            }
+        }
+        if week.iter().any(Option::is_some) {
+            weeks.push(week);
+        }
+        weeks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 5, d).unwrap()
+    }
+
+    fn note(title: &str, created: u32, updated: u32) -> Note {
+        let mut note = Note::new(title, "");
+        note.frontmatter.created = date(created).and_hms_opt(9, 0, 0).unwrap().and_utc();
+        note.frontmatter.updated = date(updated).and_hms_opt(18, 0, 0).unwrap().and_utc();
+        note
+    }
+
+    #[test]
+    fn test_events_and_days() {
+        let mut daily = note("2024-05-10", 11, 11);
+        daily.frontmatter.extra.insert(
+            "period".to_string(),
+            serde_yaml::Value::String("daily".to_string()),
+        );
+        daily.frontmatter.extra.insert(
+            "date".to_string(),
+            serde_yaml::Value::String("2024-05-10".to_string()),
+        );
+        let notes = [note("Old", 1, 16), note("Same day", 16, 16), daily];
+        let mut timeline = Timeline::build(&notes, &Utc, date(20), View::Calendar);
+
+        let summary = timeline
+            .events
+            .iter()
+            .map(|event| (event.date.day(), event.note, event.change))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            [
+                (16, 0, Change::Updated),
+                (16, 1, Change::Created),
+                (11, 2, Change::Created),
+                (1, 0, Change::Created),
+            ]
+        );
+        assert_eq!(timeline.on(date(16)).unwrap().events(), 2);
+        assert_eq!(timeline.on(date(10)).unwrap().daily, Some(2));
+        assert_eq!(timeline.notes_on(date(16)), [0, 1]);
+        assert_eq!(timeline.notes_on(date(10)), [2]);
+        assert_eq!(timeline.latest(), Some(date(16)));
+
+        timeline.move_days(-7);
+        assert_eq!(timeline.day, date(13));
+        timeline.show_day();
+        assert_eq!((timeline.view, timeline.selected), (View::List, 2));
+        timeline.select_by(5);
+        assert_eq!(timeline.day, date(1));
+
+        timeline.move_months(1);
+        assert_eq!(timeline.day, NaiveDate::from_ymd_opt(2024, 6, 1).unwrap());
+    }
+
+    #[test]
+    fn test_month_weeks() {
+        let mut timeline = Timeline::build(&[], &Utc, date(20), View::Calendar);
+        let weeks = timeline.weeks();
+        // May 2024 starts on a Wednesday and ends on a Friday
+        // 2024年5月は水曜日に始まり金曜日に終わる
+        assert_eq!(weeks.len(), 5);
+        assert_eq!(weeks[0][..3], [None, None, Some(date(1))]);
+        assert_eq!(weeks[4][4], Some(date(31)));
+        assert_eq!(weeks[4][5], None);
+
+        timeline.day = NaiveDate::from_ymd_opt(2021, 2, 14).unwrap();
+        assert_eq!(timeline.weeks().len(), 4, "February 2021 fills four weeks");
+    }
+}
@@ -0,0 +1,820 @@
+//! Rendering of the TUI layout
+//! TUIレイアウトの描画
+//!
+//! ```text
+//! ┌ Search ──────────────────────────────────────────┐
+//! └──────────────────────────────────────────────────┘
+//! ┌ Notes ───────┐┌ Preview ───────────┐┌ Backlinks ─┐
+//! │              ││                    ││            │
+//! └──────────────┘└────────────────────┘└────────────┘
+//!  NORMAL  message or path                     hints
+//! ```
+//!
+//! The tag tree, when toggled on, takes a column left of the note list.
+//! タグツリーは、表示を切り替えるとノート一覧の左に列を占めます。
+//!
+//! The built-in editor takes the place of the preview, and the graph, timeline
+//! and calendar views the place of the three panes while they are shown. The
+//! command palette pops up over everything.
+//! 表示中は、組み込みエディタがプレビューの代わりに、グラフ・タイムライン・
+//! カレンダーの各ビューが3つのペインの代わりに表示されます。コマンドパレットは
+//! すべての上にポップアップします。
+
+use super::action::Action;
+use super::app::{App, Focus, Input};
+use super::editor::{Editor, Mode};
+use super::palette::Target;
+use super::theme::{Role, Theme};
+use super::timeline::{self, Change, View, BUSY_THRESHOLD};
+use crate::note::normalize_tag;
+use chrono::Datelike;
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Style};
+use ratatui::symbols::Marker;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::canvas::{Canvas, Line as Segment};
+use ratatui::widgets::{Block, Clear, List, ListItem, ListState, Paragraph};
+use ratatui::Frame;
+
+/// Longest note title printed next to a graph node
+/// グラフノードの横に表示するノートタイトルの最大文字数
+const GRAPH_LABEL_WIDTH: usize = 24;
+
+/// Widest the command palette gets
+/// コマンドパレットの最大幅
+const PALETTE_WIDTH: u16 = 80;
+
+/// Most entries the command palette shows at once
+/// コマンドパレットが一度に表示する項目の最大数
+const PALETTE_ROWS: u16 = 12;
+
+/// Draw the whole screen and remember the pane areas for mouse handling
+/// 画面全体を描画し、マウス処理用にペイン領域を記録
+pub fn draw(frame: &mut Frame, app: &mut App) {
+    let [search, panes, status] = Layout::vertical([
+        Constraint::Length(3),
+        Constraint::Min(3),
+        Constraint::Length(1),
+    ])
+    .areas(frame.size());
+    let (tags, [list, preview, backlinks]) = if app.show_tags {
+        let [tags, list, preview, backlinks] = Layout::horizontal([
+            Constraint::Percentage(18),
+            Constraint::Percentage(25),
+            Constraint::Percentage(37),
+            Constraint::Percentage(20),
+        ])
+        .areas(panes);
+        (tags, [list, preview, backlinks])
+    } else {
+        let columns = Layout::horizontal([
+            Constraint::Percentage(30),
+            Constraint::Percentage(45),
+            Constraint::Percentage(25),
+        ])
+        .areas(panes);
+        (Rect::default(), columns)
+    };
+
+    app.areas.search = search;
+    app.areas.tags = tags;
+    app.areas.list = list;
+    app.areas.preview = preview;
+    app.areas.backlinks = backlinks;
+    app.areas.graph = panes;
+
+    draw_search(frame, app, search);
+    let view = app.timeline.as_ref().map(|timeline| timeline.view);
+    if app.graph.is_some() {
+        draw_graph(frame, app, panes);
+    } else if view == Some(View::List) {
+        draw_timeline(frame, app, panes);
+    } else if view == Some(View::Calendar) {
+        draw_calendar(frame, app, panes);
+    } else {
+        if app.show_tags {
+            draw_tags(frame, app, tags);
+        }
+        draw_list(frame, app, list);
+        if app.editor.is_some() {
+            draw_editor(frame, app, preview);
+        } else {
+            draw_preview(frame, app, preview);
+        }
+        draw_backlinks(frame, app, backlinks);
+    }
+    draw_status(frame, app, status);
+    draw_palette(frame, app);
+}
+
+/// Bordered block whose border is highlighted while focused
+/// フォーカス中は枠線が強調される枠付きブロック
+fn pane(theme: &Theme, title: String, focused: bool) -> Block<'static> {
+    let border = if focused {
+        Role::BorderFocused
+    } else {
+        Role::Border
+    };
+    Block::bordered()
+        .title(Span::styled(title, theme.style(Role::Title)))
+        .border_style(theme.style(border))
+        .style(theme.style(Role::Text))
+}
+
+/// Style of the selected row in a list
+/// 一覧で選択中の行のスタイル
+fn highlight(theme: &Theme, focused: bool) -> Style {
+    if focused {
+        theme.style(Role::Selection)
+    } else {
+        theme.style(Role::SelectionUnfocused)
+    }
+}
+
+fn draw_search(frame: &mut Frame, app: &App, area: Rect) {
+    let theme = &app.theme;
+    let editing = app.input == Input::Search;
+    let text = if app.search.is_empty() && !editing {
+        let hint = app.keymap.hint(Action::Search).unwrap_or_default();
+        Line::styled(
+            format!("Press {hint} to search (e.g. tag:rust -draft)"),
+            theme.style(Role::Placeholder),
+        )
+    } else {
+        Line::from(app.search.as_str())
+    };
+    frame.render_widget(
+        Paragraph::new(text).block(pane(theme, " Search ".to_string(), editing)),
+        area,
+    );
+    if editing {
+        let column = u16::try_from(Span::raw(app.search.as_str()).width()).unwrap_or(u16::MAX);
+        frame.set_cursor(
+            (area.x + 1).saturating_add(column).min(area.right() - 2),
+            area.y + 1,
+        );
+    }
+}
+
+fn draw_tags(frame: &mut Frame, app: &mut App, area: Rect) {
+    let theme = &app.theme;
+    let focused = app.focus == Focus::Tags && app.input == Input::None;
+    let nodes = app.tags.nodes();
+    let items = app
+        .visible_tags()
+        .into_iter()
+        .map(|index| {
+            let node = &nodes[index];
+            let marker = match (
+                node.has_children,
+                app.collapsed.contains(&node.path.to_lowercase()),
+            ) {
+                (false, _) => "  ",
+                (true, false) => "▾ ",
+                (true, true) => "▸ ",
+            };
+            ListItem::new(Line::from(vec![
+                Span::raw("  ".repeat(node.depth)),
+                Span::styled(marker, theme.style(Role::Muted)),
+                Span::styled(format!("#{}", node.name()), theme.style(Role::Tag)),
+                Span::styled(format!(" {}", node.notes), theme.style(Role::Muted)),
+            ]))
+        })
+        .collect::<Vec<_>>();
+    let title = format!(" Tags {} ", nodes.len());
+    let list = List::new(items)
+        .block(pane(theme, title, focused))
+        .highlight_style(highlight(theme, focused));
+    frame.render_stateful_widget(list, area, &mut app.tags_state);
+}
+
+fn draw_list(frame: &mut Frame, app: &mut App, area: Rect) {
+    let theme = &app.theme;
+    let focused = app.focus == Focus::List && app.input == Input::None;
+    let items = app
+        .filtered
+        .iter()
+        .map(|&index| {
+            let note = &app.notes[index];
+            let mut line = vec![Span::raw(note.title().to_string())];
+            if !note.frontmatter.tags.is_empty() {
+                line.push(Span::raw(" "));
+                line.push(Span::styled(
+                    format!("#{}", note.frontmatter.tags.join(" #")),
+                    theme.style(Role::Tag),
+                ));
+            }
+            ListItem::new(Line::from(line))
+        })
+        .collect::<Vec<_>>();
+    let title = format!(" Notes {}/{} ", app.filtered.len(), app.notes.len());
+    let list = List::new(items)
+        .block(pane(theme, title, focused))
+        .highlight_style(highlight(theme, focused));
+    frame.render_stateful_widget(list, area, &mut app.list_state);
+}
+
+fn draw_preview(frame: &mut Frame, app: &mut App, area: Rect) {
+    let focused = app.focus == Focus::Preview && app.input == Input::None;
+    let block = pane(&app.theme, " Preview ".to_string(), focused);
+    if app.selected_note().is_none() {
+        frame.render_widget(
+            Paragraph::new(Span::styled("No notes", app.theme.style(Role::Muted))).block(block),
+            area,
+        );
+        return;
+    }
+
+    app.render_preview(usize::from(area.width.saturating_sub(2)));
+    let paragraph = Paragraph::new(app.preview.lines.clone())
+        .block(block)
+        .scroll((app.preview_scroll, 0));
+    frame.render_widget(paragraph, area);
+}
+
+fn draw_editor(frame: &mut Frame, app: &mut App, area: Rect) {
+    let theme = &app.theme;
+    let Some(editor) = &mut app.editor else {
+        return;
+    };
+    let modified = if editor.is_modified() { " [+]" } else { "" };
+    let title = format!(" Edit: {}{modified} ", editor.note.title());
+    let (width, height) = (area.width.saturating_sub(2), area.height.saturating_sub(2));
+    editor.scroll_to_cursor(usize::from(width), usize::from(height));
+
+    let (top, left) = editor.offset;
+    let lines = editor
+        .lines()
+        .iter()
+        .skip(top)
+        .take(usize::from(height))
+        .map(|line| Line::from(line.as_str()))
+        .collect::<Vec<_>>();
+    let left = u16::try_from(left).unwrap_or(u16::MAX);
+    frame.render_widget(
+        Paragraph::new(lines)
+            .block(pane(theme, title, true))
+            .scroll((0, left)),
+        area,
+    );
+
+    let (row, _) = editor.cursor();
+    let x = (area.x + 1)
+        .saturating_add(u16::try_from(editor.cursor_column()).unwrap_or(u16::MAX))
+        .saturating_sub(left);
+    let y = (area.y + 1).saturating_add(u16::try_from(row - top).unwrap_or(u16::MAX));
+    frame.set_cursor(x, y);
+    draw_completion(frame, theme, editor, (x, y));
+}
+
+/// Draw the link completion list below the cursor, or above it near the
+/// bottom of the screen
+/// カーソルの下（画面下端付近では上）にリンク補完一覧を描画
+fn draw_completion(frame: &mut Frame, theme: &Theme, editor: &Editor, cursor: (u16, u16)) {
+    let Some(completion) = editor.completion() else {
+        return;
+    };
+    let screen = frame.size();
+    let widest = completion
+        .matches
+        .iter()
+        .map(|name| Span::raw(name.as_str()).width())
+        .max()
+        .unwrap_or(0);
+    let width = u16::try_from(widest + 2)
+        .unwrap_or(u16::MAX)
+        .min(screen.width);
+    let height = u16::try_from(completion.matches.len() + 2).unwrap_or(u16::MAX);
+    let x = cursor.0.min(screen.right().saturating_sub(width));
+    let y = if cursor.1 + 1 + height <= screen.bottom() {
+        cursor.1 + 1
+    } else {
+        cursor.1.saturating_sub(height)
+    };
+    let area = Rect::new(x, y, width, height).intersection(screen);
+
+    let items = completion
+        .matches
+        .iter()
+        .map(|name| ListItem::new(name.as_str()))
+        .collect::<Vec<_>>();
+    let list = List::new(items)
+        .block(pane(theme, String::new(), true))
+        .highlight_style(theme.style(Role::Selection));
+    let mut state = ListState::default().with_selected(Some(completion.selected));
+    frame.render_widget(Clear, area);
+    frame.render_stateful_widget(list, area, &mut state);
+}
+
+fn draw_backlinks(frame: &mut Frame, app: &mut App, area: Rect) {
+    let theme = &app.theme;
+    let focused = app.focus == Focus::Backlinks && app.input == Input::None;
+    let backlinks = app.backlinks();
+    let items = backlinks
+        .iter()
+        .map(|&index| ListItem::new(app.notes[index].title().to_string()))
+        .collect::<Vec<_>>();
+    let title = format!(" Backlinks {} ", backlinks.len());
+    let list = List::new(items)
+        .block(pane(theme, title, focused))
+        .highlight_style(highlight(theme, focused));
+    frame.render_stateful_widget(list, area, &mut app.backlinks_state);
+}
+
+/// Draw the command palette centered near the top of the screen
+/// コマンドパレットを画面上部の中央に描画
+///
+/// Each row shows the kind of entry, its label and, right-aligned, its key
+/// binding or other detail.
+/// 各行には項目の種類とラベル、右寄せでキーバインドなどの詳細を表示します。
+fn draw_palette(frame: &mut Frame, app: &App) {
+    let Some(palette) = &app.palette else {
+        return;
+    };
+    let theme = &app.theme;
+    let screen = frame.size();
+    let width = screen.width.saturating_sub(4).min(PALETTE_WIDTH);
+    let rows = u16::try_from(palette.matches().len()).unwrap_or(u16::MAX);
+    let height = rows
+        .clamp(1, PALETTE_ROWS)
+        .saturating_add(4)
+        .min(screen.height);
+    let area = Rect::new(
+        screen.x + (screen.width - width) / 2,
+        screen.y + screen.height / 8,
+        width,
+        height,
+    )
+    .intersection(screen);
+
+    let block = pane(
+        theme,
+        format!(" Command Palette {} ", palette.matches().len()),
+        true,
+    );
+    let inner = block.inner(area);
+    frame.render_widget(Clear, area);
+    frame.render_widget(block, area);
+    let [input, rule, list] = Layout::vertical([
+        Constraint::Length(1),
+        Constraint::Length(1),
+        Constraint::Min(0),
+    ])
+    .areas(inner);
+
+    frame.render_widget(
+        Paragraph::new(Line::from(vec![
+            Span::styled("> ", theme.style(Role::Muted)),
+            Span::raw(palette.query.as_str()),
+        ])),
+        input,
+    );
+    frame.render_widget(
+        Paragraph::new("─".repeat(usize::from(rule.width))).style(theme.style(Role::Border)),
+        rule,
+    );
+    let column = u16::try_from(Span::raw(palette.query.as_str()).width()).unwrap_or(u16::MAX);
+    frame.set_cursor(
+        (input.x + 2)
+            .saturating_add(column)
+            .min(input.right().saturating_sub(1)),
+        input.y,
+    );
+
+    let width = usize::from(list.width);
+    let items = palette
+        .matches()
+        .map(|entry| {
+            let kind = format!("{:<7}", entry.target.kind());
+            let used = kind.len() + Span::raw(entry.label.as_str()).width();
+            let detail_width = Span::raw(entry.detail.as_str()).width();
+            let gap = width.saturating_sub(used + detail_width).max(1);
+            let role = match entry.target {
+                Target::Tag(_) => Role::Tag,
+                _ => Role::Text,
+            };
+            ListItem::new(Line::from(vec![
+                Span::styled(kind, theme.style(Role::Muted)),
+                Span::styled(entry.label.clone(), theme.style(role)),
+                Span::raw(" ".repeat(gap)),
+                Span::styled(entry.detail.clone(), theme.style(Role::Muted)),
+            ]))
+        })
+        .collect::<Vec<_>>();
+    let mut state = ListState::default().with_selected(Some(palette.selected));
+    frame.render_stateful_widget(
+        List::new(items).highlight_style(theme.style(Role::Selection)),
+        list,
+        &mut state,
+    );
+}
+
+/// Draw the link graph on a Braille canvas
+/// 点字キャンバスにリンクグラフを描画
+///
+/// Edges are drawn with one parallel line per wiki link, up to three, one dot
+/// apart.
+/// エッジはWikiリンク1つにつき1本（最大3本）の平行線を1ドット間隔で描画します。
+fn draw_graph(frame: &mut Frame, app: &App, area: Rect) {
+    let Some(graph) = &app.graph else {
+        return;
+    };
+    let theme = &app.theme;
+    let center = graph
+        .nodes
+        .first()
+        .map_or("", |node| app.notes[node.note].title());
+    let title = format!(
+        " Graph: {center} ({} notes, {} hops) ",
+        graph.nodes.len(),
+        graph.hops
+    );
+
+    // Size of one Braille dot in layout coordinates
+    // レイアウト座標での点字1ドットの大きさ
+    let dot_x = 1.0 / (f64::from(area.width.saturating_sub(2).max(1)) * 2.0);
+    let dot_y = 1.0 / (f64::from(area.height.saturating_sub(2).max(1)) * 4.0);
+    let color = |role| theme.style(role).fg.unwrap_or(Color::Reset);
+
+    let canvas = Canvas::default()
+        .block(pane(theme, title, true))
+        .background_color(theme.style(Role::Text).bg.unwrap_or(Color::Reset))
+        .marker(Marker::Braille)
+        .x_bounds([0.0, 1.0])
+        .y_bounds([0.0, 1.0])
+        .paint(|ctx| {
+            for edge in &graph.edges {
+                let (a, b) = (&graph.nodes[edge.from], &graph.nodes[edge.to]);
+                let role = if edge.strength >= 3 {
+                    Role::GraphEdgeStrong
+                } else {
+                    Role::GraphEdge
+                };
+                let length = (b.x - a.x).hypot(b.y - a.y).max(f64::EPSILON);
+                // Unit normal of the edge, scaled to one dot
+                // 1ドットに拡大縮小したエッジの単位法線
+                let (nx, ny) = (-(b.y - a.y) / length * dot_x, (b.x - a.x) / length * dot_y);
+                for offset in [0.0, 1.0, -1.0].into_iter().take(edge.strength.min(3)) {
+                    ctx.draw(&Segment::new(
+                        nx.mul_add(offset, a.x),
+                        ny.mul_add(offset, a.y),
+                        nx.mul_add(offset, b.x),
+                        ny.mul_add(offset, b.y),
+                        color(role),
+                    ));
+                }
+            }
+            for (position, node) in graph.nodes.iter().enumerate() {
+                let style = if position == graph.selected {
+                    theme.style(Role::Selection)
+                } else if position == 0 {
+                    theme.style(Role::GraphCenter)
+                } else {
+                    theme.style(Role::GraphNode)
+                };
+                let mut label: String = app.notes[node.note]
+                    .title()
+                    .chars()
+                    .take(GRAPH_LABEL_WIDTH)
+                    .collect();
+                if label.chars().count() < app.notes[node.note].title().chars().count() {
+                    label.push('…');
+                }
+                ctx.print(node.x, node.y, Span::styled(format!("● {label}"), style));
+            }
+        });
+    frame.render_widget(canvas, area);
+}
+
+/// Draw the timeline, one row per event with the date shown once per day
+/// タイムラインを描画（イベントごとに1行、日付は1日に1回表示）
+fn draw_timeline(frame: &mut Frame, app: &mut App, area: Rect) {
+    app.areas.timeline = area;
+    let theme = &app.theme;
+    let Some(timeline) = &mut app.timeline else {
+        return;
+    };
+    let items = timeline
+        .events
+        .iter()
+        .enumerate()
+        .map(|(position, event)| {
+            let first = position == 0 || timeline.events[position - 1].date != event.date;
+            let date = if first {
+                Span::styled(
+                    event.date.format("%a %Y-%m-%d").to_string(),
+                    theme.style(Role::Title),
+                )
+            } else {
+                Span::raw(" ".repeat(14))
+            };
+            let note = &app.notes[event.note];
+            let mut line = vec![
+                date,
+                Span::styled(
+                    format!("  {:<9}", event.change.label()),
+                    theme.style(Role::Muted),
+                ),
+                Span::raw(note.title().to_string()),
+            ];
+            if !note.frontmatter.tags.is_empty() {
+                line.push(Span::styled(
+                    format!(" #{}", note.frontmatter.tags.join(" #")),
+                    theme.style(Role::Tag),
+                ));
+            }
+            ListItem::new(Line::from(line))
+        })
+        .collect::<Vec<_>>();
+    let list = List::new(items)
+        .block(pane(
+            theme,
+            format!(" Timeline {} events ", timeline.events.len()),
+            true,
+        ))
+        .highlight_style(theme.style(Role::Selection));
+    let mut state = ListState::default()
+        .with_selected(Some(timeline.selected))
+        .with_offset(timeline.offset);
+    frame.render_stateful_widget(list, area, &mut state);
+    timeline.offset = state.offset();
+}
+
+/// Draw the month calendar next to the notes of the selected day
+/// 月のカレンダーと選択中の日のノートを並べて描画
+///
+/// Days are colored by their number of events and marked with `•` when they
+/// have a daily note.
+/// 日はイベント数に応じて色分けされ、デイリーノートがある場合は`•`で示されます。
+fn draw_calendar(frame: &mut Frame, app: &mut App, area: Rect) {
+    let [calendar, notes] = Layout::horizontal([
+        Constraint::Length(7 * timeline::CELL_WIDTH + 2),
+        Constraint::Min(0),
+    ])
+    .areas(area);
+    app.areas.timeline = calendar;
+    let theme = &app.theme;
+    let Some(timeline) = &app.timeline else {
+        return;
+    };
+
+    let cell = |text: &str| format!(" {text:>2}  ");
+    let header = ["Mo", "Tu", "We", "Th", "Fr", "Sa", "Su"]
+        .iter()
+        .map(|name| Span::styled(cell(name), theme.style(Role::Muted)))
+        .collect::<Vec<_>>();
+    let mut lines = vec![Line::from(header)];
+    let (mut created, mut updated) = (0, 0);
+    for week in timeline.weeks() {
+        let mut spans = Vec::new();
+        for date in week {
+            let Some(date) = date else {
+                spans.push(Span::raw(cell("")));
+                continue;
+            };
+            let day = timeline.on(date).copied().unwrap_or_default();
+            created += day.created;
+            updated += day.updated;
+            let mut style = match day.events() {
+                0 => theme.style(Role::Text),
+                events if events >= BUSY_THRESHOLD => theme.style(Role::CalendarBusy),
+                _ => theme.style(Role::CalendarActive),
+            };
+            if date == timeline.today {
+                style = style.patch(theme.style(Role::CalendarToday));
+            }
+            let (style, marker) = if date == timeline.day {
+                (theme.style(Role::Selection), theme.style(Role::Selection))
+            } else {
+                (style, theme.style(Role::CalendarDaily))
+            };
+            let daily = if day.daily.is_some() { "•" } else { " " };
+            spans.extend([
+                Span::raw(" "),
+                Span::styled(format!("{:>2}", date.day()), style),
+                Span::styled(daily, marker),
+                Span::raw(" "),
+            ]);
+        }
+        lines.push(Line::from(spans));
+    }
+    lines.push(Line::default());
+    lines.push(Line::from(vec![
+        Span::styled(" •", theme.style(Role::CalendarDaily)),
+        Span::styled(" daily note", theme.style(Role::Muted)),
+    ]));
+    lines.push(Line::styled(
+        format!(" {created} created, {updated} edited"),
+        theme.style(Role::Muted),
+    ));
+    frame.render_widget(
+        Paragraph::new(lines).block(pane(
+            theme,
+            timeline.day.format(" %B %Y ").to_string(),
+            true,
+        )),
+        calendar,
+    );
+
+    let daily = timeline.on(timeline.day).and_then(|day| day.daily);
+    let day_notes = timeline.notes_on(timeline.day);
+    let items = if day_notes.is_empty() {
+        vec![ListItem::new(Line::styled(
+            "No activity",
+            theme.style(Role::Placeholder),
+        ))]
+    } else {
+        day_notes
+            .iter()
+            .map(|&index| {
+                let (label, role) = if daily == Some(index) {
+                    ("daily", Role::CalendarDaily)
+                } else {
+                    let change = timeline
+                        .events
+                        .iter()
+                        .find(|event| event.date == timeline.day && event.note == index)
+                        .map_or(Change::Created, |event| event.change);
+                    (change.label(), Role::Muted)
+                };
+                ListItem::new(Line::from(vec![
+                    Span::styled(format!("{label:<9}"), theme.style(role)),
+                    Span::raw(app.notes[index].title().to_string()),
+                ]))
+            })
+            .collect()
+    };
+    let title = format!(
+        " {} · {} notes ",
+        timeline.day.format("%a %Y-%m-%d"),
+        day_notes.len()
+    );
+    frame.render_widget(List::new(items).block(pane(theme, title, false)), notes);
+}
+
+fn draw_status(frame: &mut Frame, app: &App, area: Rect) {
+    let theme = &app.theme;
+    let (mode, hints) = match (app.input, app.editor.as_ref().map(|editor| editor.mode)) {
+        (Input::None, Some(Mode::Insert)) => {
+            (" INSERT ", "[[ link  Esc normal  ctrl+s save".to_string())
+        }
+        (Input::None, Some(Mode::Normal)) => (
+            " EDIT ",
+            "i insert  u undo  ctrl+r redo  ctrl+s save  q close".to_string(),
+        ),
+        (Input::None, None) if app.graph.is_some() => (" GRAPH ", graph_hints(app)),
+        (Input::None, None) if app.timeline.is_some() => timeline_hints(app),
+        (Input::None, None) if app.show_tags && app.focus == Focus::Tags => {
+            (" TAGS ", tag_hints(app))
+        }
+        (Input::None, None) => (" NORMAL ", normal_hints(app)),
+        (Input::Search, _) => (" SEARCH ", "Enter apply  Esc clear".to_string()),
+        (Input::NewNote, _) => (" NEW ", "Enter create  Esc cancel".to_string()),
+        (Input::Palette, _) => (" PALETTE ", "Enter run  ↑↓ select  Esc close".to_string()),
+        (Input::RenameTag, _) => (" RENAME ", "Enter rename  Esc cancel".to_string()),
+        (Input::ConfirmRenameTag, _) => (" RENAME ", "y apply  n cancel".to_string()),
+    };
+
+    let message = match (&app.status, app.input) {
+        (_, Input::NewNote) => Span::raw(format!("Title: {}", app.new_title)),
+        (_, Input::RenameTag) => Span::raw(format!(
+            "Rename #{} to: #{}",
+            app.selected_tag().map_or("", |node| node.path.as_str()),
+            app.tag_name
+        )),
+        (_, Input::ConfirmRenameTag) => Span::raw(format!(
+            "Rename #{} to #{} in {} notes?",
+            app.selected_tag().map_or("", |node| node.path.as_str()),
+            normalize_tag(&app.tag_name),
+            app.rename_plan
+                .as_ref()
+                .map_or(0, |plan| plan.changes.len())
+        )),
+        (Some((message, true, _)), _) => {
+            Span::styled(message.clone(), theme.style(Role::StatusError))
+        }
+        (Some((message, false, _)), _) => {
+            Span::styled(message.clone(), theme.style(Role::StatusInfo))
+        }
+        (None, _) if !app.keymap.pending().is_empty() => Span::styled(
+            app.keymap
+                .pending()
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(" "),
+            theme.style(Role::StatusPending),
+        ),
+        (None, _) => app
+            .selected_note()
+            .map(|note| Span::styled(note.path.display().to_string(), theme.style(Role::Muted)))
+            .unwrap_or_default(),
+    };
+
+    let [left, right] = Layout::horizontal([
+        Constraint::Min(0),
+        Constraint::Length(u16::try_from(hints.len() + 1).unwrap_or(u16::MAX)),
+    ])
+    .areas(area);
+    frame.render_widget(
+        Paragraph::new(Line::from(vec![
+            Span::styled(mode, theme.style(Role::StatusMode)),
+            Span::raw(" "),
+            message,
+        ])),
+        left,
+    );
+    frame.render_widget(
+        Paragraph::new(Span::styled(hints, theme.style(Role::StatusHint))),
+        right,
+    );
+}
+
+/// Key hints of the most common actions under the current key bindings
+/// 現在のキーバインドにおける主なアクションのキーヒント
+fn normal_hints(app: &App) -> String {
+    hints(
+        app,
+        &[
+            (Action::Search, "search"),
+            (Action::NewNote, "new"),
+            (Action::Edit, "edit"),
+            (Action::FocusNext, "focus"),
+            (Action::Graph, "graph"),
+            (Action::Palette, "commands"),
+            (Action::Quit, "quit"),
+        ],
+    )
+}
+
+/// Key hints of the tag tree
+/// タグツリーのキーヒント
+fn tag_hints(app: &App) -> String {
+    hints(
+        app,
+        &[
+            (Action::Open, "fold"),
+            (Action::RenameTag, "rename"),
+            (Action::FocusNext, "focus"),
+            (Action::Tags, "close"),
+        ],
+    )
+}
+
+/// Key hints of the graph view
+/// グラフビューのキーヒント
+fn graph_hints(app: &App) -> String {
+    hints(
+        app,
+        &[
+            (Action::FocusNext, "next"),
+            (Action::Open, "center"),
+            (Action::Edit, "edit"),
+            (Action::Graph, "close"),
+        ],
+    )
+}
+
+/// Mode and key hints of the timeline and calendar views
+/// タイムラインとカレンダーのビューのモードとキーヒント
+fn timeline_hints(app: &App) -> (&'static str, String) {
+    match app.timeline.as_ref().map(|timeline| timeline.view) {
+        Some(View::Calendar) => (
+            " CALENDAR ",
+            hints(
+                app,
+                &[
+                    (Action::Open, "notes"),
+                    (Action::PageDown, "month"),
+                    (Action::Top, "today"),
+                    (Action::Calendar, "close"),
+                ],
+            ),
+        ),
+        _ => (
+            " TIMELINE ",
+            hints(
+                app,
+                &[
+                    (Action::Open, "open"),
+                    (Action::Calendar, "calendar"),
+                    (Action::Timeline, "close"),
+                ],
+            ),
+        ),
+    }
+}
+
+/// Join the first chord of each action with its label
+/// 各アクションの最初のキーコードとラベルを連結
+fn hints(app: &App, actions: &[(Action, &str)]) -> String {
+    actions
+        .iter()
+        .filter_map(|&(action, label)| {
+            app.keymap
+                .hint(action)
+                .map(|chord| format!("{chord} {label}"))
+        })
+        .collect::<Vec<_>>()
+        .join("  ")
+}
@@ -506,16 +506,37 @@ pub fn create_backup_filename(original_path: &Path) -> std::path::PathBuf {
 pub fn ensure_directory_exists(path: &Path) -> Result<()> {
     if !path.exists() {
         std::fs::create_dir_all(path).map_err(|e| {
-            ZynapseError::io_error(e, format!("Failed to create directory: {path:?}"))
+            ZynapseError::io_error(e, format!("Failed to create directory: {}", path.display()))
         })?;
     } else if !path.is_dir() {
         return Err(ZynapseError::invalid_content(format!(
-            "Path exists but is not a directory: {path:?}"
+            "Path exists but is not a directory: {}",
+            path.display()
         )));
     }
     Ok(())
 }
 
+/// A count followed by its noun, in the plural unless the count is one
+/// 件数とその名詞（件数が1でなければ複数形）
+///
+/// # Examples
+///
+/// ```rust
+/// use zynapse::utils::plural;
+///
+/// assert_eq!(plural(1, "note"), "1 note");
+/// assert_eq!(plural(3, "link"), "3 links");
+/// ```
+#[must_use]
+pub fn plural(count: usize, noun: &str) -> String {
+    if count == 1 {
+        format!("1 {noun}")
+    } else {
+        format!("{count} {noun}s")
+    }
+}
+
 /// Calculate the relative path from one directory to another
 /// あるディレクトリから別のディレクトリへの相対パスを計算
 ///
@@ -548,6 +569,40 @@ pub fn relative_path(from: &Path, to: &Path) -> std::path::PathBuf {
         .map_or_else(|_| to.to_path_buf(), Path::to_path_buf)
 }
 
+/// Open a file in an editor command and wait for it to exit
+/// エディタコマンドでファイルを開き、終了を待つ
+///
+/// The command is split on whitespace, so it may carry arguments such as
+/// `code --wait`.
+/// コマンドは空白で分割されるため、`code --wait`のような引数を含められます。
+///
+/// # Errors
+///
+/// Returns an error if the command is empty, cannot be started, or exits
+/// unsuccessfully.
+/// コマンドが空、起動できない、または異常終了した場合にエラーを返します。
+pub fn launch_editor(command: &str, path: &Path) -> Result<()> {
+    let mut parts = command.split_whitespace();
+    let program = parts
+        .next()
+        .ok_or_else(|| ZynapseError::config_error("Editor command cannot be empty"))?;
+
+    let status = std::process::Command::new(program)
+        .args(parts)
+        .arg(path)
+        .status()
+        .map_err(|e| ZynapseError::io_error(e, format!("Failed to launch editor `{program}`")))?;
+
+    if status.success() {
+        Ok(())
+    } else {
+        Err(ZynapseError::io_error(
+            std::io::Error::new(std::io::ErrorKind::Other, format!("exited with {status}")),
+            format!("Editor `{program}` failed"),
+        ))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -633,8 +688,8 @@ mod tests {
         assert_eq!(format_file_size(512), "512 B");
         assert_eq!(format_file_size(1024), "1.0 KB");
         assert_eq!(format_file_size(1536), "1.5 KB");
-        assert_eq!(format_file_size(1048576), "1.0 MB");
-        assert_eq!(format_file_size(1073741824), "1.0 GB");
+        assert_eq!(format_file_size(1_048_576), "1.0 MB");
+        assert_eq!(format_file_size(1_073_741_824), "1.0 GB");
     }
 
     #[test]